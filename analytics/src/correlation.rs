@@ -0,0 +1,139 @@
+use silicon_core::SpikeRecorder;
+
+/// Counts the spikes of `b` at every lag from the spikes of `a`, for lags up to `lag_range` ms in
+/// both directions. Returns `(lag, count)` pairs for bins of `bin_size` ms centered on multiples of
+/// `bin_size`, the bin at lag 0 is in the middle. A positive lag means `b` fired after `a`.
+pub fn spike_cross_correlation<A, B>(a: &A, b: &B, lag_range: f64, bin_size: f64) -> Vec<(f64, f64)>
+where
+    A: SpikeRecorder + ?Sized,
+    B: SpikeRecorder + ?Sized,
+{
+    correlogram(&a.get_spikes(), &b.get_spikes(), lag_range, bin_size, false)
+}
+
+/// The cross-correlation of a spike train with itself, without pairing a spike with itself. The
+/// autocorrelogram of a Poisson spike train is flat, regular spiking shows up as peaks at
+/// multiples of the interval.
+pub fn spike_autocorrelation<R>(recorder: &R, lag_range: f64, bin_size: f64) -> Vec<(f64, f64)>
+where
+    R: SpikeRecorder + ?Sized,
+{
+    let spikes = recorder.get_spikes();
+    correlogram(&spikes, &spikes, lag_range, bin_size, true)
+}
+
+fn correlogram(
+    a: &[f64],
+    b: &[f64],
+    lag_range: f64,
+    bin_size: f64,
+    skip_self: bool,
+) -> Vec<(f64, f64)> {
+    if bin_size <= 0.0 || lag_range < 0.0 {
+        return vec![];
+    }
+
+    let half_bins = (lag_range / bin_size).floor() as i64;
+    let mut counts = vec![0.0; (2 * half_bins + 1) as usize];
+
+    let mut b = b.iter().copied().enumerate().collect::<Vec<_>>();
+    b.sort_by(|(_, x), (_, y)| x.total_cmp(y));
+    // the outer bins reach half a bin past the last bin center
+    let reach = (half_bins as f64 + 0.5) * bin_size;
+
+    for (i, spike_a) in a.iter().enumerate() {
+        let start = b.partition_point(|(_, spike_b)| *spike_b < spike_a - reach);
+        for (j, spike_b) in b[start..].iter() {
+            if *spike_b > spike_a + reach {
+                break;
+            }
+
+            if skip_self && i == *j {
+                continue;
+            }
+
+            let bin = ((spike_b - spike_a) / bin_size).round() as i64;
+            if bin.abs() <= half_bins {
+                counts[(bin + half_bins) as usize] += 1.0;
+            }
+        }
+    }
+
+    counts
+        .into_iter()
+        .enumerate()
+        .map(|(bin, count)| ((bin as i64 - half_bins) as f64 * bin_size, count))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+    use crate::test_utils::Spikes;
+
+    /// Poisson spike train at `rate` Hz for `duration` ms.
+    fn poisson(rng: &mut StdRng, rate: f64, duration: f64) -> Spikes {
+        let mut spikes = vec![];
+        let mut time = 0.0;
+        loop {
+            time += -(1.0 - rng.gen::<f64>()).ln() * 1000.0 / rate;
+            if time > duration {
+                return Spikes(spikes);
+            }
+            spikes.push(time);
+        }
+    }
+
+    #[test]
+    fn test_identical_trains_peak_at_zero_lag() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let a = poisson(&mut rng, 20.0, 10000.0);
+        let b = Spikes(a.0.clone());
+
+        let correlogram = spike_cross_correlation(&a, &b, 50.0, 1.0);
+        assert_eq!(correlogram.len(), 101);
+
+        let (lag, count) = correlogram
+            .iter()
+            .copied()
+            .max_by(|(_, x), (_, y)| x.total_cmp(y))
+            .unwrap();
+        assert_eq!(lag, 0.0);
+        assert!(count >= a.0.len() as f64);
+    }
+
+    #[test]
+    fn test_shifted_train_peaks_at_shift() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let a = poisson(&mut rng, 20.0, 10000.0);
+        let b = Spikes(a.0.iter().map(|time| time + 10.0).collect());
+
+        let correlogram = spike_cross_correlation(&a, &b, 50.0, 2.0);
+        let (lag, _) = correlogram
+            .iter()
+            .copied()
+            .max_by(|(_, x), (_, y)| x.total_cmp(y))
+            .unwrap();
+        assert_eq!(lag, 10.0);
+    }
+
+    #[test]
+    fn test_poisson_autocorrelogram_is_flat() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let spikes = poisson(&mut rng, 20.0, 100000.0);
+
+        let correlogram = spike_autocorrelation(&spikes, 50.0, 5.0);
+        assert_eq!(correlogram.len(), 21);
+
+        // every bin expects the number of spikes times the chance of a spike in the bin
+        let expected = spikes.0.len() as f64 * 20.0 / 1000.0 * 5.0;
+        for (lag, count) in correlogram {
+            assert!(
+                (count - expected).abs() < expected * 0.25,
+                "{count} spikes at lag {lag}, expected {expected}"
+            );
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use bevy::{
+    prelude::{Component, Query, Res},
+    reflect::Reflect,
+};
+use bevy_trait_query::One;
+use silicon_core::{Clock, SpikeRecorder};
+
+/// Tracks the firing rate of the neuron on the same entity, the entity needs a `SpikeRecorder`.
+///
+/// The rate is the number of spikes in the last `window_seconds` divided by the window. Recorders
+/// that only keep a limited number of spikes underestimate the rate once the window holds more
+/// spikes than they keep.
+#[derive(Debug, Clone, Component, Reflect)]
+pub struct FiringRateRecorder {
+    /// the length of the window the spikes are counted in, in seconds
+    pub window_seconds: f64,
+    /// the firing rate in Hz, updated every tick
+    pub rate: f64,
+}
+
+impl FiringRateRecorder {
+    pub fn new(window_seconds: f64) -> Self {
+        FiringRateRecorder {
+            window_seconds,
+            rate: 0.0,
+        }
+    }
+}
+
+impl Default for FiringRateRecorder {
+    fn default() -> Self {
+        FiringRateRecorder::new(1.0)
+    }
+}
+
+pub fn update_firing_rates(
+    mut neurons: Query<(One<&dyn SpikeRecorder>, &mut FiringRateRecorder)>,
+    clock: Res<Clock>,
+) {
+    for (spike_recorder, mut firing_rate) in neurons.iter_mut() {
+        // the clock runs in ms
+        let window_start = clock.time - firing_rate.window_seconds * 1000.0;
+        let spikes = spike_recorder
+            .get_spikes()
+            .iter()
+            .filter(|time| **time > window_start)
+            .count();
+
+        firing_rate.rate = spikes as f64 / firing_rate.window_seconds;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        app::{App, Update},
+        prelude::{IntoSystemConfigs, ResMut},
+    };
+    use bevy_trait_query::RegisterExt;
+    use neurons::leaky::LifNeuron;
+    use silicon_core::Neuron;
+
+    use super::*;
+    use crate::test_utils::Spikes;
+
+    /// Advances the clock and drives the neuron with a constant current.
+    fn drive(mut clock: ResMut<Clock>, mut neurons: Query<(&mut LifNeuron, &mut Spikes)>) {
+        clock.time += clock.tau;
+        for (mut neuron, mut recorder) in neurons.iter_mut() {
+            neuron.insert_current(2.0);
+            if neuron.update(clock.tau) {
+                recorder.record_spike(clock.time);
+            }
+        }
+    }
+
+    #[test]
+    fn test_constant_input_rate() {
+        let mut app = App::new();
+        app.insert_resource(Clock::default())
+            .register_component_as::<dyn SpikeRecorder, Spikes>()
+            .add_systems(Update, (drive, update_firing_rates).chain());
+
+        let neuron = app
+            .world_mut()
+            .spawn((
+                LifNeuron::default(),
+                Spikes::default(),
+                FiringRateRecorder::new(1.0),
+            ))
+            .id();
+
+        // 5 seconds
+        for _ in 0..200000 {
+            app.update();
+        }
+
+        // the membrane reaches the threshold after 10ms * ln(4), followed by 2ms refractory period
+        let expected = 1000.0 / (10.0 * 4.0f64.ln() + 2.0);
+        let rate = app.world().get::<FiringRateRecorder>(neuron).unwrap().rate;
+        assert!(
+            (rate - expected).abs() < expected * 0.1,
+            "expected {expected}Hz, got {rate}Hz"
+        );
+    }
+}
@@ -0,0 +1,162 @@
+use bevy::{
+    prelude::{Component, Query, Res},
+    reflect::Reflect,
+};
+use bevy_trait_query::One;
+use silicon_core::{Clock, SpikeRecorder};
+
+/// Spike statistics of the neuron on the same entity, the entity needs a `SpikeRecorder`.
+///
+/// Unlike `FiringRateRecorder` the rate is an exponentially weighted average: every spike adds
+/// `1 / window_seconds` Hz and the rate decays with a time constant of `window_seconds`, so it
+/// falls towards zero while the neuron is silent. The inter-spike interval statistics cover
+/// every spike the recorder kept.
+#[derive(Debug, Clone, Component, Reflect)]
+pub struct FiringStats {
+    /// the time constant of the firing rate, in seconds
+    pub window_seconds: f64,
+    /// mean inter-spike interval in ms, `None` with fewer than two spikes
+    pub mean_isi: Option<f64>,
+    /// coefficient of variation of the inter-spike intervals, `None` with fewer than two spikes
+    pub cv_isi: Option<f64>,
+    rate: f64,
+    /// the time of the last spike that was counted towards the rate
+    last_spike: Option<f64>,
+    /// the time up to which the rate has been decayed
+    last_update: f64,
+}
+
+impl FiringStats {
+    pub fn new(window_seconds: f64) -> Self {
+        FiringStats {
+            window_seconds,
+            mean_isi: None,
+            cv_isi: None,
+            rate: 0.0,
+            last_spike: None,
+            last_update: 0.0,
+        }
+    }
+
+    /// Forget every spike and start over at time zero, for when the simulation is reset.
+    pub fn reset(&mut self) {
+        *self = FiringStats::new(self.window_seconds);
+    }
+
+    /// The exponentially weighted firing rate in Hz.
+    pub fn rate(&self) -> f64 {
+        self.rate
+    }
+
+    /// Update the statistics at `time` in ms from everything `recorder` holds.
+    pub fn update<R: SpikeRecorder + ?Sized>(&mut self, recorder: &R, time: f64) {
+        // the clock runs in ms
+        let window = self.window_seconds * 1000.0;
+        self.rate *= (-(time - self.last_update).max(0.0) / window).exp();
+        self.last_update = time;
+
+        let spikes = recorder.get_spikes();
+        for spike in spikes
+            .iter()
+            .filter(|spike| self.last_spike.is_none_or(|last| **spike > last))
+        {
+            // a spike is worth less the longer ago it happened
+            self.rate += (-(time - spike) / window).exp() / self.window_seconds;
+        }
+        if let Some(last) = spikes.last() {
+            self.last_spike = Some(*last);
+        }
+
+        self.mean_isi = recorder.mean_isi();
+        self.cv_isi = recorder.cv_isi();
+    }
+}
+
+impl Default for FiringStats {
+    fn default() -> Self {
+        FiringStats::new(1.0)
+    }
+}
+
+pub fn update_firing_stats(
+    mut neurons: Query<(One<&dyn SpikeRecorder>, &mut FiringStats)>,
+    clock: Res<Clock>,
+) {
+    for (spike_recorder, mut firing_stats) in neurons.iter_mut() {
+        firing_stats.update(&*spike_recorder, clock.time);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::Spikes;
+
+    /// Spikes every 10ms for 5 seconds while updating every ms.
+    fn periodic(stats: &mut FiringStats) -> Spikes {
+        let mut spikes = Spikes::default();
+        for time in 1..=5000 {
+            if time % 10 == 0 {
+                spikes.record_spike(time as f64);
+            }
+            stats.update(&spikes, time as f64);
+        }
+        spikes
+    }
+
+    #[test]
+    fn test_periodic_spike_train() {
+        let mut stats = FiringStats::new(0.5);
+        periodic(&mut stats);
+
+        assert_eq!(stats.mean_isi, Some(10.0));
+        assert!(stats.cv_isi.unwrap() < 1e-9);
+        assert!(
+            (stats.rate() - 100.0).abs() < 5.0,
+            "rate was {}",
+            stats.rate()
+        );
+    }
+
+    #[test]
+    fn test_rate_decays_during_silence() {
+        let mut stats = FiringStats::new(0.5);
+        let spikes = periodic(&mut stats);
+
+        let rate = stats.rate();
+        stats.update(&spikes, 5500.0);
+        assert!((stats.rate() - rate * (-1.0f64).exp()).abs() < 1e-9);
+        stats.update(&spikes, 10000.0);
+        assert!(stats.rate() < 0.01);
+        // the intervals don't change without new spikes
+        assert_eq!(stats.mean_isi, Some(10.0));
+    }
+
+    #[test]
+    fn test_fewer_than_two_spikes() {
+        let mut stats = FiringStats::default();
+        let mut spikes = Spikes::default();
+        stats.update(&spikes, 1.0);
+        assert_eq!(stats.mean_isi, None);
+        assert_eq!(stats.rate(), 0.0);
+
+        spikes.record_spike(2.0);
+        stats.update(&spikes, 2.0);
+        assert_eq!(stats.cv_isi, None);
+        assert_eq!(stats.rate(), 1.0);
+    }
+
+    #[test]
+    fn test_reset_counts_spikes_of_the_restarted_clock() {
+        let mut stats = FiringStats::new(0.5);
+        let mut spikes = periodic(&mut stats);
+
+        // the recorder is cleared and the clock starts over
+        spikes.clear();
+        stats.reset();
+        spikes.record_spike(10.0);
+        stats.update(&spikes, 10.0);
+        assert_eq!(stats.rate(), 2.0);
+        assert_eq!(stats.mean_isi, None);
+    }
+}
@@ -1 +1,21 @@
+use bevy::app::{App, Plugin, Update};
+use firing_rate::{update_firing_rates, FiringRateRecorder};
+use firing_stats::{update_firing_stats, FiringStats};
 
+pub mod correlation;
+pub mod firing_rate;
+pub mod firing_stats;
+pub mod receptive_field;
+#[cfg(test)]
+mod test_utils;
+
+/// Registers the analytics components and the systems that keep them up to date.
+pub struct SiliconAnalyticsPlugin;
+
+impl Plugin for SiliconAnalyticsPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<FiringRateRecorder>()
+            .register_type::<FiringStats>()
+            .add_systems(Update, (update_firing_rates, update_firing_stats));
+    }
+}
@@ -0,0 +1,175 @@
+use std::collections::HashMap;
+
+use bevy::{
+    prelude::{Entity, Resource, World},
+    reflect::Reflect,
+    transform::components::Transform,
+};
+use bevy_trait_query::One;
+use synapses::{Synapse, SynapseType};
+
+/// Configures how far back `receptive_field` traces through the network.
+#[derive(Debug, Clone, Reflect, Resource)]
+pub struct ReceptiveFieldConfig {
+    /// The maximum number of synapses a path from an input neuron to the output neuron may contain.
+    pub max_hops: usize,
+    /// Multiplier applied to the contribution of a path for every hop after the first.
+    pub hop_decay: f64,
+}
+
+impl Default for ReceptiveFieldConfig {
+    fn default() -> Self {
+        ReceptiveFieldConfig {
+            max_hops: 3,
+            hop_decay: 0.5,
+        }
+    }
+}
+
+/// Compute the effective receptive field of `output_neuron` on the neurons of `input_layer`.
+///
+/// Every path from an input neuron to the output neuron contributes the product of its synapse
+/// weights, inhibitory synapses count negatively and every hop after the first is multiplied by
+/// `ReceptiveFieldConfig::hop_decay`. Paths stop at the first input neuron they reach.
+///
+/// The result is indexed as `field[x][y]` using the position of the input neurons, neurons without
+/// a `Transform` are left out.
+pub fn receptive_field(
+    world: &mut World,
+    output_neuron: Entity,
+    input_layer: &[Entity],
+) -> Vec<Vec<f64>> {
+    let config = world
+        .get_resource::<ReceptiveFieldConfig>()
+        .cloned()
+        .unwrap_or_default();
+
+    let synapses = world
+        .query::<One<&dyn Synapse>>()
+        .iter(world)
+        .map(|synapse| {
+            let sign = match synapse.get_type() {
+                SynapseType::Excitatory | SynapseType::Electrical => 1.0,
+                SynapseType::Inhibitory => -1.0,
+            };
+
+            (
+                synapse.get_presynaptic(),
+                synapse.get_postsynaptic(),
+                sign * synapse.get_weight(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let mut strengths: HashMap<Entity, f64> = HashMap::new();
+    let mut frontier: HashMap<Entity, f64> = HashMap::from([(output_neuron, 1.0)]);
+    let mut decay = 1.0;
+
+    for _ in 0..config.max_hops {
+        let mut next: HashMap<Entity, f64> = HashMap::new();
+        for (pre, post, weight) in synapses.iter() {
+            if let Some(strength) = frontier.get(post) {
+                *next.entry(*pre).or_default() += strength * weight;
+            }
+        }
+
+        for (neuron, strength) in next.iter() {
+            if input_layer.contains(neuron) {
+                *strengths.entry(*neuron).or_default() += strength * decay;
+            }
+        }
+
+        next.retain(|neuron, _| !input_layer.contains(neuron));
+        if next.is_empty() {
+            break;
+        }
+
+        frontier = next;
+        decay *= config.hop_decay;
+    }
+
+    let positions = input_layer
+        .iter()
+        .filter_map(|neuron| {
+            world.get::<Transform>(*neuron).map(|transform| {
+                (
+                    *neuron,
+                    transform.translation.x.round() as i32,
+                    transform.translation.y.round() as i32,
+                )
+            })
+        })
+        .collect::<Vec<_>>();
+
+    if positions.is_empty() {
+        return vec![];
+    }
+
+    let min_x = positions.iter().map(|(_, x, _)| *x).min().unwrap();
+    let max_x = positions.iter().map(|(_, x, _)| *x).max().unwrap();
+    let min_y = positions.iter().map(|(_, _, y)| *y).min().unwrap();
+    let max_y = positions.iter().map(|(_, _, y)| *y).max().unwrap();
+
+    let mut field = vec![vec![0.0; (max_y - min_y + 1) as usize]; (max_x - min_x + 1) as usize];
+    for (neuron, x, y) in positions {
+        field[(x - min_x) as usize][(y - min_y) as usize] +=
+            strengths.get(&neuron).copied().unwrap_or(0.0);
+    }
+
+    field
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_trait_query::RegisterExt;
+    use synapses::simple::SimpleSynapse;
+
+    use super::*;
+
+    fn spawn_synapse(
+        world: &mut World,
+        source: Entity,
+        target: Entity,
+        weight: f64,
+        synapse_type: SynapseType,
+    ) {
+        world.spawn(SimpleSynapse {
+            weight,
+            delay: 1,
+            source,
+            target,
+            synapse_type,
+        });
+    }
+
+    #[test]
+    fn test_receptive_field_multi_hop() {
+        let mut world = World::new();
+        world.register_component_as::<dyn Synapse, SimpleSynapse>();
+
+        let input_00 = world.spawn(Transform::from_xyz(0.0, 0.0, 0.0)).id();
+        let input_10 = world.spawn(Transform::from_xyz(1.0, 0.0, 0.0)).id();
+        let input_01 = world.spawn(Transform::from_xyz(0.0, 1.0, 0.0)).id();
+        let input_11 = world.spawn(Transform::from_xyz(1.0, 1.0, 0.0)).id();
+        let hidden = world.spawn(Transform::from_xyz(0.0, 0.0, -5.0)).id();
+        let output = world.spawn(Transform::from_xyz(0.0, 0.0, -10.0)).id();
+
+        spawn_synapse(&mut world, input_00, output, 0.5, SynapseType::Excitatory);
+        spawn_synapse(&mut world, input_10, hidden, 1.0, SynapseType::Excitatory);
+        spawn_synapse(&mut world, hidden, output, 0.8, SynapseType::Excitatory);
+        spawn_synapse(&mut world, input_01, output, 0.4, SynapseType::Inhibitory);
+
+        let field = receptive_field(
+            &mut world,
+            output,
+            &[input_00, input_10, input_01, input_11],
+        );
+
+        assert_eq!(field.len(), 2);
+        assert_eq!(field[0].len(), 2);
+        assert!((field[0][0] - 0.5).abs() < 1e-9);
+        assert!((field[1][0] - 0.4).abs() < 1e-9);
+        assert!((field[0][1] + 0.4).abs() < 1e-9);
+        assert!(field[1][1].abs() < 1e-9);
+    }
+}
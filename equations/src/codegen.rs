@@ -0,0 +1,576 @@
+//! Turns parsed equations into Rust source, so hot loops can run compiled expressions instead of
+//! looking every variable up in a `HashMap` like the `ExpressionEvaluator`.
+
+use std::collections::BTreeSet;
+
+use proc_macro2::{Ident, Literal, Span, TokenStream};
+use quote::{format_ident, quote};
+
+use crate::{
+    equation::{reset_statement, Equation},
+    s::S,
+    tokenize::Token,
+};
+
+/// The variables an equation reads, in order of their first appearance. These are the parameters
+/// of the closure `generate_rust_closure` emits. `pi` and `e` are constants, not variables.
+pub fn closure_parameters(eq: &Equation) -> Vec<String> {
+    let mut parameters = vec![];
+    for s in expressions(eq) {
+        collect_variables(s, &mut parameters);
+    }
+    parameters
+}
+
+/// Emits a closure that evaluates `eq` with the expression inlined, taking every variable it
+/// reads as an `f64` in the order of `closure_parameters`:
+///
+/// `dv/dt = (v_rest - v) / tau` becomes `|v_rest: f64, v: f64, tau: f64| -> f64 { ... }`.
+///
+/// Assignments and differential equations return their right hand side, a threshold returns 1.0
+/// when it holds and 0.0 otherwise and a reset returns the tuple of its new values. Unknown
+/// functions become a `compile_error!`.
+pub fn generate_rust_closure(eq: &Equation) -> TokenStream {
+    let parameters = closure_parameters(eq)
+        .iter()
+        .map(|name| ident(name))
+        .collect::<Vec<_>>();
+    let variable = |name: &str| {
+        let name = ident(name);
+        quote!(#name)
+    };
+
+    match eq {
+        Equation::Reset(_) => {
+            let values = expressions(eq)
+                .into_iter()
+                .map(|s| expression(s, &variable))
+                .collect::<Vec<_>>();
+            let types = values.iter().map(|_| quote!(f64));
+            quote! {
+                |#(#parameters: f64),*| -> (#(#types,)*) { (#(#values,)*) }
+            }
+        }
+        _ => {
+            let body = expressions(eq)
+                .into_iter()
+                .map(|s| expression(s, &variable));
+            quote! {
+                |#(#parameters: f64),*| -> f64 { #(#body)* }
+            }
+        }
+    }
+}
+
+/// Emits a neuron struct with an `f64` field for every variable of the equations and an
+/// `impl Neuron` that runs them the way `EquationNeuron` does: the assignments in order, then
+/// every differential equation with forward Euler, `t` advances by the time step and `I_in` holds
+/// the input current of the update.
+///
+/// ```ignore
+/// let tokens = NeuronModelBuilder::new(parse_equations("dv/dt = -v + I_in")?)
+///     .with_name("DecayNeuron")
+///     .with_threshold(1.0, 0.0)
+///     .build();
+/// ```
+pub struct NeuronModelBuilder {
+    equations: Vec<Equation>,
+    name: String,
+    membrane_variable: String,
+    /// the threshold and reset of the membrane variable, used without threshold or reset equations
+    threshold: Option<(f64, f64)>,
+}
+
+impl NeuronModelBuilder {
+    pub fn new(equations: Vec<Equation>) -> Self {
+        NeuronModelBuilder {
+            equations,
+            name: "GeneratedNeuron".to_string(),
+            membrane_variable: "v".to_string(),
+            threshold: None,
+        }
+    }
+
+    /// The name of the generated struct, `GeneratedNeuron` by default.
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = name.to_string();
+        self
+    }
+
+    /// The variable that is the membrane potential, `v` by default.
+    pub fn with_membrane_variable(mut self, variable: &str) -> Self {
+        self.membrane_variable = variable.to_string();
+        self
+    }
+
+    /// Fire when the membrane variable reaches `threshold` and set it to `reset`. Threshold and
+    /// reset equations take precedence, without a threshold of either kind the neuron never
+    /// fires.
+    pub fn with_threshold(mut self, threshold: f64, reset: f64) -> Self {
+        self.threshold = Some((threshold, reset));
+        self
+    }
+
+    /// The names of the fields of the generated struct besides `input_current` and
+    /// `conductance`, sorted.
+    pub fn variables(&self) -> Vec<String> {
+        let mut variables = BTreeSet::new();
+        variables.insert(self.membrane_variable.clone());
+        variables.insert(INPUT_CURRENT_VARIABLE.to_string());
+        variables.insert(TIME_VARIABLE.to_string());
+        for equation in &self.equations {
+            if let Some(variable) = equation.variable() {
+                variables.insert(variable.to_string());
+            }
+            variables.extend(closure_parameters(equation));
+        }
+        variables.into_iter().collect()
+    }
+
+    pub fn build(&self) -> TokenStream {
+        let name = ident(&self.name);
+        let fields = self
+            .variables()
+            .iter()
+            .map(|name| ident(name))
+            .collect::<Vec<_>>();
+        let membrane = ident(&self.membrane_variable);
+        let input_current = ident(INPUT_CURRENT_VARIABLE);
+        let time = ident(TIME_VARIABLE);
+        let field = |name: &str| {
+            let name = ident(name);
+            quote!(self.#name)
+        };
+
+        let assignments = self.equations.iter().filter_map(|equation| match equation {
+            Equation::Assignment(_, rhs, _) => {
+                let variable = ident(equation.variable()?);
+                let value = expression(rhs, &field);
+                Some(quote!(self.#variable = #value;))
+            }
+            _ => None,
+        });
+
+        // all derivatives are evaluated on the same state before any variable is integrated
+        let (derivatives, integration): (Vec<_>, Vec<_>) = self
+            .equations
+            .iter()
+            .filter_map(|equation| match equation {
+                Equation::Differential(_, rhs, _) => {
+                    let variable = ident(equation.variable()?);
+                    let derivative = format_ident!("d_{}", variable);
+                    let value = expression(rhs, &field);
+                    Some((
+                        quote!(let #derivative = #value;),
+                        quote!(self.#variable += #derivative * tau;),
+                    ))
+                }
+                _ => None,
+            })
+            .unzip();
+
+        let threshold = self.equations.iter().find_map(|equation| match equation {
+            Equation::Threshold(condition) => Some(expression(condition, &field)),
+            _ => None,
+        });
+        let fired = match (threshold, self.threshold) {
+            (Some(condition), _) => quote!(#condition != 0.0),
+            (None, Some((threshold, _))) => {
+                let threshold = number(threshold);
+                quote!(self.#membrane >= #threshold)
+            }
+            (None, None) => quote!(false),
+        };
+
+        let reset = self.equations.iter().find_map(|equation| match equation {
+            Equation::Reset(statements) => Some(
+                statements
+                    .iter()
+                    .filter_map(reset_statement)
+                    .map(|(variable, value)| {
+                        let variable = ident(variable);
+                        let value = expression(value, &field);
+                        quote!(self.#variable = #value;)
+                    })
+                    .collect::<Vec<_>>(),
+            ),
+            _ => None,
+        });
+        let reset = match (reset, self.threshold) {
+            (Some(statements), _) => quote!(#(#statements)*),
+            (None, Some((_, reset))) => {
+                let reset = number(reset);
+                quote!(self.#membrane = #reset;)
+            }
+            (None, None) => quote!(),
+        };
+
+        quote! {
+            #[derive(Debug, Clone, Default)]
+            #[allow(non_snake_case)]
+            pub struct #name {
+                #(pub #fields: f64,)*
+                pub input_current: f64,
+                pub conductance: silicon_core::SynapticConductance,
+            }
+
+            #[allow(non_snake_case, unused_parens)]
+            impl silicon_core::Neuron for #name {
+                fn update(&mut self, tau: f64) -> bool {
+                    self.#input_current =
+                        self.input_current + self.conductance.current(self.#membrane);
+                    self.input_current = 0.0;
+                    self.conductance.clear();
+
+                    #(#assignments)*
+                    #(#derivatives)*
+                    #(#integration)*
+                    self.#time += tau;
+
+                    if !(#fired) {
+                        return false;
+                    }
+                    #reset
+                    true
+                }
+
+                fn get_membrane_potential(&self) -> f64 {
+                    self.#membrane
+                }
+
+                fn insert_current(&mut self, current: f64) -> f64 {
+                    self.input_current += current;
+                    self.#membrane
+                }
+
+                fn add_conductance(&mut self, g: f64, reversal_potential: f64) {
+                    self.conductance.add(g, reversal_potential);
+                }
+            }
+        }
+    }
+}
+
+/// The input current and time variables of `EquationNeuron`.
+const INPUT_CURRENT_VARIABLE: &str = "I_in";
+const TIME_VARIABLE: &str = "t";
+
+/// The expressions an equation evaluates, the values of its statements for a reset.
+fn expressions(eq: &Equation) -> Vec<&S> {
+    match eq {
+        Equation::Assignment(_, rhs, _) | Equation::Differential(_, rhs, _) => vec![rhs],
+        Equation::Threshold(condition) => vec![condition],
+        Equation::Reset(statements) => statements
+            .iter()
+            .filter_map(reset_statement)
+            .map(|(_, value)| value)
+            .collect(),
+    }
+}
+
+fn collect_variables(s: &S, variables: &mut Vec<String>) {
+    match s {
+        S::Atom(Token::Identifier(name)) => {
+            if constant(name).is_none() && !variables.contains(name) {
+                variables.push(name.clone());
+            }
+        }
+        S::Atom(_) => {}
+        S::Cons(_, children) => {
+            for child in children {
+                collect_variables(child, variables);
+            }
+        }
+    }
+}
+
+fn ident(name: &str) -> Ident {
+    Ident::new(name, Span::call_site())
+}
+
+fn number(n: f64) -> TokenStream {
+    let literal = Literal::f64_suffixed(n.abs());
+    if n < 0.0 {
+        quote!((-#literal))
+    } else {
+        quote!(#literal)
+    }
+}
+
+fn constant(name: &str) -> Option<TokenStream> {
+    match name {
+        "pi" => Some(quote!(::std::f64::consts::PI)),
+        "e" => Some(quote!(::std::f64::consts::E)),
+        _ => None,
+    }
+}
+
+/// The Rust expression of `s`, `variable` turns the name of a variable into the expression that
+/// reads it. Every operation is parenthesized, so the precedence is the one of the parse tree.
+fn expression(s: &S, variable: &dyn Fn(&str) -> TokenStream) -> TokenStream {
+    match s {
+        S::Atom(Token::Number(n)) => number(*n),
+        S::Atom(Token::Identifier(name)) => constant(name).unwrap_or_else(|| variable(name)),
+        S::Cons(Token::Operator('-'), children) if children.len() == 1 => {
+            let operand = expression(&children[0], variable);
+            quote!((-#operand))
+        }
+        S::Cons(Token::Operator(op @ ('+' | '-' | '*' | '/')), children) => {
+            let op = match op {
+                '+' => quote!(+),
+                '-' => quote!(-),
+                '*' => quote!(*),
+                _ => quote!(/),
+            };
+            let mut operands = children.iter().map(|child| expression(child, variable));
+            let first = operands.next().unwrap_or_default();
+            let operation = operands.fold(first, |lhs, rhs| quote!(#lhs #op #rhs));
+            quote!((#operation))
+        }
+        S::Cons(Token::Operator('^'), children) if children.len() == 2 => {
+            let base = expression(&children[0], variable);
+            let exponent = expression(&children[1], variable);
+            quote!((#base).powf(#exponent))
+        }
+        S::Cons(Token::Operator(op @ ('>' | '<' | '≥' | '≤')), children) if children.len() == 2 =>
+        {
+            let lhs = expression(&children[0], variable);
+            let rhs = expression(&children[1], variable);
+            let op = match op {
+                '>' => quote!(>),
+                '<' => quote!(<),
+                '≥' => quote!(>=),
+                _ => quote!(<=),
+            };
+            quote!((if #lhs #op #rhs { 1.0f64 } else { 0.0f64 }))
+        }
+        S::Cons(Token::Function(function), children) if children.len() == 1 => {
+            let argument = expression(&children[0], variable);
+            match function_method(function) {
+                Some(method) => {
+                    let method = ident(method);
+                    quote!((#argument).#method())
+                }
+                None => unsupported(s),
+            }
+        }
+        _ => unsupported(s),
+    }
+}
+
+/// The `f64` method of a function the `ExpressionEvaluator` knows.
+fn function_method(function: &str) -> Option<&'static str> {
+    match function {
+        "sin" => Some("sin"),
+        "cos" => Some("cos"),
+        "tan" => Some("tan"),
+        "tanh" => Some("tanh"),
+        "exp" => Some("exp"),
+        "log" | "ln" => Some("ln"),
+        "sqrt" => Some("sqrt"),
+        "abs" => Some("abs"),
+        _ => None,
+    }
+}
+
+fn unsupported(s: &S) -> TokenStream {
+    let message = format!("can't generate code for `{}`", s.to_standard_string());
+    quote!(compile_error!(#message))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, fs, path::PathBuf};
+
+    use super::*;
+    use crate::{equation::parse_equations, evaluator::ExpressionEvaluator};
+
+    fn equation(input: &str) -> Equation {
+        parse_equations(input).unwrap().remove(0)
+    }
+
+    /// Writes `source` to a file trybuild can compile, named after the test.
+    fn source_file(name: &str, source: String) -> PathBuf {
+        let directory = std::env::temp_dir().join("equations-codegen");
+        fs::create_dir_all(&directory).unwrap();
+        let path = directory.join(format!("{name}.rs"));
+        fs::write(&path, source).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_closure_parameters() {
+        let eq = equation("dv/dt = -(v - v_rest) / tau + sin(2 * pi * t) * v : volt");
+        assert_eq!(closure_parameters(&eq), ["v", "v_rest", "tau", "t"]);
+
+        let closure = generate_rust_closure(&eq).to_string();
+        assert!(closure.starts_with("| v : f64 , v_rest : f64 , tau : f64 , t : f64 | -> f64"));
+
+        let unknown = generate_rust_closure(&equation("x = atan(1)")).to_string();
+        assert!(unknown.contains("compile_error"));
+    }
+
+    #[test]
+    fn test_generated_code_matches_interpreter() {
+        let inputs = [
+            "dv/dt = -(v - v_rest) / tau + sin(2 * pi * t) * v : volt",
+            "x = a^2 - -3 * b / (a + 1) + exp(-b) - log(a) + tanh(b) : 1",
+            "y = (a > b) + (a <= b) * 2 + sqrt(abs(-a)) + e : 1",
+            "v >= v_thresh : 1",
+        ];
+        let values = [
+            ("v", -60.0),
+            ("v_rest", -65.0),
+            ("tau", 10.0),
+            ("t", 0.3),
+            ("a", 1.5),
+            ("b", 0.25),
+            ("v_thresh", -60.0),
+        ];
+        let variables = values
+            .iter()
+            .map(|(name, value)| (name.to_string(), *value))
+            .collect::<HashMap<_, _>>();
+
+        let checks = inputs.iter().map(|input| {
+            let eq = equation(input);
+            let expected = match &eq {
+                Equation::Threshold(condition) => condition.evaluate(&variables),
+                _ => eq.rhs().unwrap().evaluate(&variables),
+            }
+            .unwrap();
+            let closure = generate_rust_closure(&eq);
+            let arguments = closure_parameters(&eq)
+                .iter()
+                .map(|name| number(variables[name]))
+                .collect::<Vec<_>>();
+            quote! {
+                let f = #closure;
+                let value: f64 = f(#(#arguments),*);
+                assert!((value - #expected).abs() < 1e-12, "{} != {}", value, #expected);
+            }
+        });
+        let reset = equation("reset: v = v_reset, w = w + b");
+        let reset_closure = generate_rust_closure(&reset);
+        let main = quote! {
+            #![allow(unused_parens)]
+
+            fn main() {
+                #(#checks)*
+                let reset = #reset_closure;
+                assert_eq!(reset(-70.0, 1.0, 0.5), (-70.0, 1.5));
+            }
+        };
+
+        let tests = trybuild::TestCases::new();
+        tests.pass(source_file("closures", main.to_string()));
+    }
+
+    /// Runs the equations with the `ExpressionEvaluator` the way `EquationNeuron` does, returns
+    /// the spike ticks and the final membrane potential.
+    fn interpret(
+        equations: &[Equation],
+        variables: &mut HashMap<String, f64>,
+    ) -> (Vec<usize>, f64) {
+        let mut spikes = vec![];
+        for tick in 0..4000 {
+            variables.insert("I_in".to_string(), 3.0);
+            for equation in equations {
+                if let Equation::Assignment(_, rhs, _) = equation {
+                    let value = rhs.evaluate(variables).unwrap();
+                    variables.insert(equation.variable().unwrap().to_string(), value);
+                }
+            }
+            let derivatives = equations
+                .iter()
+                .filter_map(|equation| match equation {
+                    Equation::Differential(_, rhs, _) => Some((
+                        equation.variable().unwrap().to_string(),
+                        rhs.evaluate(variables).unwrap(),
+                    )),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            for (variable, derivative) in derivatives {
+                *variables.get_mut(&variable).unwrap() += derivative * 0.025;
+            }
+            *variables.get_mut("t").unwrap() += 0.025;
+
+            let fired = equations.iter().any(|equation| match equation {
+                Equation::Threshold(condition) => condition.evaluate(variables) != Some(0.0),
+                _ => false,
+            });
+            if fired {
+                spikes.push(tick);
+                for equation in equations {
+                    if let Equation::Reset(statements) = equation {
+                        for (variable, value) in statements.iter().filter_map(reset_statement) {
+                            let value = value.evaluate(variables).unwrap();
+                            variables.insert(variable.to_string(), value);
+                        }
+                    }
+                }
+            }
+        }
+        (spikes, variables["v"])
+    }
+
+    #[test]
+    fn test_generated_neuron_matches_interpreter() {
+        let equations = parse_equations(
+            "I_leak = (v_rest - v) / tau_m : volt/second
+            dv/dt = I_leak + I_in - w : volt
+            dw/dt = -w / 100 : volt
+            v >= -50 : 1
+            reset: v = -70, w = w + 0.5",
+        )
+        .unwrap();
+        let builder = NeuronModelBuilder::new(equations.clone()).with_name("AdaptiveNeuron");
+        assert_eq!(
+            builder.variables(),
+            ["I_in", "I_leak", "t", "tau_m", "v", "v_rest", "w"]
+        );
+
+        let mut variables = builder
+            .variables()
+            .into_iter()
+            .map(|name| (name, 0.0))
+            .collect::<HashMap<_, _>>();
+        variables.insert("v".to_string(), -65.0);
+        variables.insert("v_rest".to_string(), -65.0);
+        variables.insert("tau_m".to_string(), 10.0);
+        let (spikes, v) = interpret(&equations, &mut variables);
+        assert!(spikes.len() > 2);
+        let spike_count = spikes.len();
+        let expected_spikes = spikes.iter().map(|tick| *tick as u64);
+
+        let neuron = builder.build();
+        let main = quote! {
+            use silicon_core::Neuron;
+
+            #neuron
+
+            fn main() {
+                let mut neuron = AdaptiveNeuron {
+                    v: -65.0,
+                    v_rest: -65.0,
+                    tau_m: 10.0,
+                    ..Default::default()
+                };
+                let mut spikes = vec![];
+                for tick in 0..4000u64 {
+                    neuron.insert_current(3.0);
+                    if neuron.update(0.025) {
+                        spikes.push(tick);
+                    }
+                }
+                assert_eq!(spikes.len(), #spike_count);
+                assert_eq!(spikes, [#(#expected_spikes),*]);
+                assert!((neuron.get_membrane_potential() - #v).abs() < 1e-9);
+            }
+        };
+
+        let tests = trybuild::TestCases::new();
+        tests.pass(source_file("neuron", main.to_string()));
+    }
+}
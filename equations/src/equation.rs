@@ -3,10 +3,17 @@ use crate::{
     tokenize::Token,
 };
 
+/// Marks a line of reset statements, `reset: v = v_reset, w = w + b`.
+pub const RESET_KEYWORD: &str = "reset:";
+
 #[derive(Debug, Clone)]
 pub enum Equation {
     Assignment(S, S, String),
     Differential(S, S, String),
+    /// a comparison like `v > v_thresh`, the neuron fires when it evaluates to anything but 0
+    Threshold(S),
+    /// the assignments applied in order after the neuron fired, every statement is an `=` node
+    Reset(Vec<S>),
 }
 
 impl Equation {
@@ -17,7 +24,9 @@ impl Equation {
         while let Some(s) = queue.pop() {
             match s {
                 S::Cons(Token::Operator(token), children) => {
-                    if token == '=' {
+                    if matches!(token, '>' | '<' | '≥' | '≤') {
+                        return Equation::Threshold(S::Cons(Token::Operator(token), children));
+                    } else if token == '=' {
                         let left_node = children.first().unwrap();
                         let right_node = children.last().unwrap();
 
@@ -50,33 +59,111 @@ impl Equation {
         panic!("Invalid expression");
     }
 
-    pub fn lhs(&self) -> &S {
+    /// The left hand side, of the comparison for a threshold. `None` for a reset, which consists
+    /// of several statements.
+    pub fn lhs(&self) -> Option<&S> {
+        match self {
+            Equation::Assignment(lhs, _, _) => Some(lhs),
+            Equation::Differential(lhs, _, _) => Some(lhs),
+            Equation::Threshold(S::Cons(_, children)) => children.first(),
+            Equation::Threshold(_) | Equation::Reset(_) => None,
+        }
+    }
+
+    /// The right hand side, of the comparison for a threshold. `None` for a reset, which consists
+    /// of several statements.
+    pub fn rhs(&self) -> Option<&S> {
         match self {
-            Equation::Assignment(lhs, _, _) => lhs,
-            Equation::Differential(lhs, _, _) => lhs,
+            Equation::Assignment(_, rhs, _) => Some(rhs),
+            Equation::Differential(_, rhs, _) => Some(rhs),
+            Equation::Threshold(S::Cons(_, children)) => children.get(1),
+            Equation::Threshold(_) | Equation::Reset(_) => None,
         }
     }
 
-    pub fn rhs(&self) -> &S {
+    /// The name of the variable this equation defines, `v` for both `v = ...` and `dv/dt = ...`.
+    pub fn variable(&self) -> Option<&str> {
         match self {
-            Equation::Assignment(_, rhs, _) => rhs,
-            Equation::Differential(_, rhs, _) => rhs,
+            Equation::Assignment(S::Atom(Token::Identifier(name)), _, _) => Some(name),
+            Equation::Differential(S::Cons(Token::Operator('/'), children), _, _) => {
+                match children.first() {
+                    Some(S::Atom(Token::Identifier(name))) => name.strip_prefix('d'),
+                    _ => None,
+                }
+            }
+            _ => None,
         }
     }
 
+    /// Thresholds and resets are dimensionless, their unit is `1`.
     pub fn unit(&self) -> &str {
         match self {
             Equation::Assignment(_, _, unit) => unit,
             Equation::Differential(_, _, unit) => unit,
+            Equation::Threshold(_) | Equation::Reset(_) => "1",
         }
     }
 }
 
+/// The variable a reset statement assigns to and the expression of its new value.
+pub fn reset_statement(statement: &S) -> Option<(&str, &S)> {
+    match statement {
+        S::Cons(Token::Operator('='), children) => match children.as_slice() {
+            [S::Atom(Token::Identifier(variable)), value] => Some((variable, value)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Splits `input` at the commas outside of parentheses, the ones inside separate the arguments
+/// of a function.
+fn split_top_level_commas(input: &str) -> Vec<&str> {
+    let mut parts = vec![];
+    let (mut depth, mut start) = (0, 0);
+    for (i, c) in input.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(&input[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&input[start..]);
+    parts
+}
+
+/// Parses the statements after the reset keyword, separated by commas. Units are dropped.
+fn parse_reset(statements: &str) -> Result<Equation, ParseError> {
+    let mut parsed = vec![];
+    for statement in split_top_level_commas(statements) {
+        let mut s = expr(statement)?;
+        if let S::Cons(Token::Operator(':'), children) = &s {
+            s = children.first().cloned().unwrap_or(s);
+        }
+
+        if reset_statement(&s).is_none() {
+            return Err(ParseError::ExpectedAssignment(statement.trim().to_string()));
+        }
+        parsed.push(s);
+    }
+
+    Ok(Equation::Reset(parsed))
+}
+
 pub fn parse_equations(input: &str) -> Result<Vec<Equation>, ParseError> {
     let mut parsed_expressions = vec![];
 
     let expressions = input.trim().split('\n');
     for expression in expressions {
+        if let Some(statements) = expression.trim().strip_prefix(RESET_KEYWORD) {
+            parsed_expressions.push(parse_reset(statements)?);
+            continue;
+        }
+
         parsed_expressions.push(Equation::new(expr(expression)?));
     }
 
@@ -85,7 +172,10 @@ pub fn parse_equations(input: &str) -> Result<Vec<Equation>, ParseError> {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
+
     use super::*;
+    use crate::evaluator::ExpressionEvaluator;
 
     #[test]
     fn test_parse_expressions() {
@@ -102,8 +192,8 @@ mod tests {
         for expression in &expressions {
             println!(
                 "{} = {} : {}",
-                expression.lhs().to_standard_string(),
-                expression.rhs().to_standard_string(),
+                expression.lhs().unwrap().to_standard_string(),
+                expression.rhs().unwrap().to_standard_string(),
                 expression.unit(),
             );
         }
@@ -114,7 +204,7 @@ mod tests {
         assert_eq!(expressions[1].unit(), "volt");
         assert_eq!(expressions[2].unit(), "amp");
 
-        assert_eq!(expressions[0].lhs().to_standard_string(), "x");
+        assert_eq!(expressions[0].lhs().unwrap().to_standard_string(), "x");
 
         assert!(match expressions[0] {
             Equation::Assignment(_, _, _) => true,
@@ -127,10 +217,64 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_variable() {
+        let expressions =
+            parse_equations("I_leak = (v_rest - v) / R\ndv/dt = I_leak : volt").unwrap();
+        assert_eq!(expressions[0].variable(), Some("I_leak"));
+        assert_eq!(expressions[1].variable(), Some("v"));
+    }
+
     #[test]
     fn test_parse_expressions_error() {
         let input = "1 + 2 3\n4 * 5 = 20\n";
         let result = parse_equations(input);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_threshold() {
+        let expressions = parse_equations("v > -50 : 1").unwrap();
+        let Equation::Threshold(condition) = &expressions[0] else {
+            panic!("expected a threshold, got {:?}", expressions[0]);
+        };
+        assert_eq!(expressions[0].lhs().unwrap().to_standard_string(), "v");
+        assert_eq!(expressions[0].variable(), None);
+
+        let mut variables = HashMap::new();
+        variables.insert("v".to_string(), -40.0);
+        assert_eq!(condition.evaluate(&variables), Some(1.0));
+        variables.insert("v".to_string(), -60.0);
+        assert_eq!(condition.evaluate(&variables), Some(0.0));
+    }
+
+    #[test]
+    fn test_reset() {
+        let expressions =
+            parse_equations("dv/dt = -v : volt\nreset: v = v_reset : volt, w = max(w, b) + b")
+                .unwrap();
+        let Equation::Reset(statements) = &expressions[1] else {
+            panic!("expected a reset, got {:?}", expressions[1]);
+        };
+
+        let statements = statements
+            .iter()
+            .map(|statement| {
+                let (variable, value) = reset_statement(statement).unwrap();
+                (variable, value.to_string())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            statements,
+            [
+                ("v", "v_reset".to_string()),
+                ("w", "(+ (max w b) b)".to_string())
+            ]
+        );
+
+        assert!(matches!(
+            parse_equations("reset: v + 1"),
+            Err(ParseError::ExpectedAssignment(statement)) if statement == "v + 1"
+        ));
+    }
 }
@@ -10,7 +10,7 @@ impl ExpressionEvaluator for S {
     fn evaluate(&self, variables: &HashMap<String, f64>) -> Option<f64> {
         match self {
             S::Atom(Token::Number(n)) => Some(*n),
-            S::Atom(Token::Identifier(s)) => variables.get(s).cloned(),
+            S::Atom(Token::Identifier(s)) => variables.get(s).cloned().or_else(|| constant(s)),
             S::Cons(Token::Operator('+'), children) => {
                 let mut sum = 0.0;
                 for child in children {
@@ -18,6 +18,9 @@ impl ExpressionEvaluator for S {
                 }
                 Some(sum)
             }
+            S::Cons(Token::Operator('-'), children) if children.len() == 1 => {
+                Some(-children.first().unwrap().evaluate(variables)?)
+            }
             S::Cons(Token::Operator('-'), children) => {
                 let mut sum = children.first().unwrap().evaluate(variables)?;
                 for child in children.iter().skip(1) {
@@ -44,11 +47,53 @@ impl ExpressionEvaluator for S {
                 let exponent = children.last().unwrap().evaluate(variables)?;
                 Some(base.powf(exponent))
             }
+            // comparisons are 1.0 when they hold and 0.0 otherwise
+            S::Cons(Token::Operator(op @ ('>' | '<' | '≥' | '≤')), children)
+                if children.len() == 2 =>
+            {
+                let lhs = children[0].evaluate(variables)?;
+                let rhs = children[1].evaluate(variables)?;
+                let holds = match op {
+                    '>' => lhs > rhs,
+                    '<' => lhs < rhs,
+                    '≥' => lhs >= rhs,
+                    _ => lhs <= rhs,
+                };
+                Some(if holds { 1.0 } else { 0.0 })
+            }
+            S::Cons(Token::Function(function), children) if children.len() == 1 => {
+                let argument = children.first().unwrap().evaluate(variables)?;
+                apply_function(function, argument)
+            }
             _ => None,
         }
     }
 }
 
+/// Built-in constants, these can be shadowed by variables with the same name.
+fn constant(name: &str) -> Option<f64> {
+    match name {
+        "pi" => Some(std::f64::consts::PI),
+        "e" => Some(std::f64::consts::E),
+        _ => None,
+    }
+}
+
+fn apply_function(function: &str, argument: f64) -> Option<f64> {
+    match function {
+        "sin" => Some(argument.sin()),
+        "cos" => Some(argument.cos()),
+        "tan" => Some(argument.tan()),
+        "tanh" => Some(argument.tanh()),
+        "exp" => Some(argument.exp()),
+        // the natural logarithm, `log` as in Brian2
+        "log" | "ln" => Some(argument.ln()),
+        "sqrt" => Some(argument.sqrt()),
+        "abs" => Some(argument.abs()),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::equation::parse_equations;
@@ -63,7 +108,7 @@ mod tests {
         variables.insert("c".to_string(), 3.0);
 
         let expressions = parse_equations("x = (a + b) * c").unwrap();
-        let equation = expressions.first().unwrap().rhs();
+        let equation = expressions.first().unwrap().rhs().unwrap();
         let result = equation.evaluate(&variables);
 
         assert_eq!(
@@ -81,7 +126,7 @@ mod tests {
         variables.insert("e".to_string(), 5.0);
 
         let expressions = parse_equations("x = a^b + (a * c) / e").unwrap();
-        let equation = expressions.first().unwrap().rhs();
+        let equation = expressions.first().unwrap().rhs().unwrap();
         let result = equation.evaluate(&variables);
 
         assert_eq!(
@@ -92,4 +137,86 @@ mod tests {
             )
         );
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_negation() {
+        let mut variables = HashMap::new();
+        variables.insert("v".to_string(), 2.0);
+        variables.insert("I".to_string(), 1.0);
+
+        let expressions = parse_equations("dv/dt = -(v + I) / 3").unwrap();
+        let result = expressions
+            .first()
+            .unwrap()
+            .rhs()
+            .unwrap()
+            .evaluate(&variables);
+        assert_eq!(result, Some(-1.0));
+    }
+
+    fn evaluate_rhs(input: &str) -> Option<f64> {
+        let expressions = parse_equations(input).unwrap();
+        expressions
+            .first()
+            .unwrap()
+            .rhs()
+            .unwrap()
+            .evaluate(&HashMap::new())
+    }
+
+    #[test]
+    fn test_functions() {
+        assert_eq!(evaluate_rhs("x = sin(0)"), Some(0.0));
+        assert_eq!(evaluate_rhs("x = exp(0)"), Some(1.0));
+        assert_eq!(evaluate_rhs("x = sqrt(16) + abs(0 - 2)"), Some(6.0));
+        assert_eq!(evaluate_rhs("x = unknown(1)"), None);
+        assert!(evaluate_rhs("x = sin(pi)").unwrap().abs() < 1e-12);
+        assert!((evaluate_rhs("x = exp(1)").unwrap() - 2.718).abs() < 1e-3);
+        assert_eq!(evaluate_rhs("x = log(exp(2))"), Some(2.0));
+        assert_eq!(evaluate_rhs("x = tanh(0) + cos(0)"), Some(1.0));
+        // functions take a single argument
+        assert_eq!(evaluate_rhs("x = sin(1, 2)"), None);
+    }
+
+    #[test]
+    fn test_sinusoidal_drive() {
+        let expressions = parse_equations("dv/dt = -v + sin(2*pi*freq*t) : volt").unwrap();
+        let equation = expressions.first().unwrap();
+        assert_eq!(equation.unit(), "volt");
+
+        let mut variables = HashMap::new();
+        variables.insert("v".to_string(), 0.5);
+        variables.insert("freq".to_string(), 10.0);
+        variables.insert("t".to_string(), 0.025);
+        let result = equation.rhs().unwrap().evaluate(&variables).unwrap();
+        let expected = -0.5 + (2.0 * std::f64::consts::PI * 10.0 * 0.025).sin();
+        assert!((result - expected).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_constants() {
+        assert_eq!(evaluate_rhs("x = 2 * pi"), Some(2.0 * std::f64::consts::PI));
+        assert_eq!(evaluate_rhs("x = ln(e)"), Some(1.0));
+
+        // variables shadow the built-in constants
+        let mut variables = HashMap::new();
+        variables.insert("pi".to_string(), 3.0);
+        let expressions = parse_equations("x = 2 * pi").unwrap();
+        let result = expressions
+            .first()
+            .unwrap()
+            .rhs()
+            .unwrap()
+            .evaluate(&variables);
+        assert_eq!(result, Some(6.0));
+    }
+
+    #[test]
+    fn test_comparisons() {
+        assert_eq!(evaluate_rhs("x = 2 > 1"), Some(1.0));
+        assert_eq!(evaluate_rhs("x = 2 < 1"), Some(0.0));
+        assert_eq!(evaluate_rhs("x = 1 >= 1"), Some(1.0));
+        assert_eq!(evaluate_rhs("x = 2 <= 1"), Some(0.0));
+        assert_eq!(evaluate_rhs("x = (1 > 0) + (2 > 0)"), Some(2.0));
+    }
+}
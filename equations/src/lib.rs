@@ -1,3 +1,4 @@
+pub mod codegen;
 pub mod equation;
 pub mod evaluator;
 pub mod s;
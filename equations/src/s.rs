@@ -5,6 +5,8 @@ use crate::tokenize::{Lexer, Token};
 #[derive(Debug)]
 pub enum ParseError {
     UnexpectedToken(Token),
+    /// a reset statement that doesn't assign to a variable
+    ExpectedAssignment(String),
 }
 
 #[derive(Debug, Clone)]
@@ -19,6 +21,16 @@ impl S {
     pub fn to_standard_string(&self) -> String {
         match self {
             S::Atom(t) => t.to_string(),
+            S::Cons(Token::Function(function), args) => {
+                format!(
+                    "{}({})",
+                    function,
+                    args.iter()
+                        .map(|s| s.to_standard_string())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            }
             S::Cons(t, rest) => {
                 format!(
                     "{} {} {}",
@@ -60,10 +72,21 @@ pub fn expr(input: &str) -> Result<S, ParseError> {
 pub(crate) fn expr_bp(lexer: &mut Lexer, min_bp: u8) -> Result<S, ParseError> {
     let mut lhs = match lexer.next() {
         Token::Number(n) => S::Atom(Token::Number(n)),
+        Token::Function(function) => {
+            // the arguments are the comma separated expressions in the parentheses
+            expect_operator(lexer, '(')?;
+            let mut arguments = vec![expr_bp(lexer, 0)?];
+            while lexer.peek() == Token::Operator(',') {
+                lexer.next();
+                arguments.push(expr_bp(lexer, 0)?);
+            }
+            expect_operator(lexer, ')')?;
+            S::Cons(Token::Function(function), arguments)
+        }
         Token::Identifier(s) => S::Atom(Token::Identifier(s)),
         Token::Operator('(') => {
             let lhs = expr_bp(lexer, 0)?;
-            assert_eq!(lexer.next(), Token::Operator(')'));
+            expect_operator(lexer, ')')?;
             lhs
         }
         Token::Operator(op) => {
@@ -110,9 +133,16 @@ pub(crate) fn expr_bp(lexer: &mut Lexer, min_bp: u8) -> Result<S, ParseError> {
     Ok(lhs)
 }
 
+fn expect_operator(lexer: &mut Lexer, op: char) -> Result<(), ParseError> {
+    match lexer.next() {
+        Token::Operator(next) if next == op => Ok(()),
+        t => Err(ParseError::UnexpectedToken(t)),
+    }
+}
+
 fn prefix_binding_power(op: char) -> ((), u8) {
     match op {
-        '+' | '-' => ((), 5),
+        '+' | '-' => ((), 7),
         _ => panic!("bad op: {:?}", op),
     }
 }
@@ -120,9 +150,10 @@ fn prefix_binding_power(op: char) -> ((), u8) {
 fn infix_binding_power(op: char) -> Option<(u8, u8)> {
     let res = match op {
         '=' | ':' => (0, 1),
-        '+' | '-' => (1, 2),
-        '*' | '/' => (3, 4),
-        '^' => (5, 6),
+        '>' | '<' | '≥' | '≤' => (1, 2),
+        '+' | '-' => (3, 4),
+        '*' | '/' => (5, 6),
+        '^' => (7, 8),
         _ => return None,
     };
     Some(res)
@@ -130,7 +161,7 @@ fn infix_binding_power(op: char) -> Option<(u8, u8)> {
 
 fn postfix_binding_power(op: char) -> Option<(u8, ())> {
     let res = match op {
-        '!' => (7, ()),
+        '!' => (9, ()),
         _ => return None,
     };
     Some(res)
@@ -146,10 +177,13 @@ mod tests {
         assert_eq!(s.to_string(), "1");
 
         let s = expr("-1").unwrap();
-        assert_eq!(s.to_string(), "(- 1)");
+        assert_eq!(s.to_string(), "-1");
 
         let s = expr("--1").unwrap();
-        assert_eq!(s.to_string(), "(- (- 1))");
+        assert_eq!(s.to_string(), "(- -1)");
+
+        let s = expr("-x").unwrap();
+        assert_eq!(s.to_string(), "(- x)");
 
         let s = expr("((0))").unwrap();
         assert_eq!(s.to_string(), "0");
@@ -187,6 +221,23 @@ mod tests {
         assert_eq!(format!("{}", output), "(* (+ 1 2) 3)");
     }
 
+    #[test]
+    fn test_function_application() {
+        let input = "sin(2 * pi * freq * t) + 1";
+        let output = expr(input).unwrap();
+        assert_eq!(format!("{}", output), "(+ (sin (* (* (* 2 pi) freq) t)) 1)");
+
+        let output = expr("max(a + 1, b) * 2").unwrap();
+        assert_eq!(format!("{}", output), "(* (max (+ a 1) b) 2)");
+        assert_eq!(output.to_standard_string(), "max(a + 1, b) * 2");
+
+        assert!(matches!(
+            expr("sin(1"),
+            Err(ParseError::UnexpectedToken(Token::Eof))
+        ));
+        assert!(expr("sin 1").is_err());
+    }
+
     #[test]
     fn test_equation() {
         let input = "dv/dt = -(v + I)/ tau : volt";
@@ -196,4 +247,14 @@ mod tests {
             "(: (= (/ dv dt) (/ (- (+ v I)) tau)) volt)"
         );
     }
+
+    #[test]
+    fn test_comparison() {
+        let output = expr("v > -50 : 1").unwrap();
+        assert_eq!(format!("{}", output), "(: (> v -50) 1)");
+
+        // comparisons bind looser than arithmetic and tighter than assignments
+        let output = expr("x = a + 1 >= b * 2").unwrap();
+        assert_eq!(format!("{}", output), "(= x (≥ (+ a 1) (* b 2)))");
+    }
 }
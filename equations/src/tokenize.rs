@@ -4,9 +4,9 @@ use nom::{
     branch::alt,
     bytes::complete::{tag, take_while1},
     character::complete::{digit1, multispace0, one_of},
-    combinator::{map, opt, recognize},
+    combinator::{map, opt, peek, recognize},
     multi::many0,
-    sequence::{delimited, preceded, tuple},
+    sequence::{delimited, preceded, terminated, tuple},
     IResult,
 };
 
@@ -15,6 +15,8 @@ pub enum Token {
     Number(f64),
     Operator(char),
     Identifier(String),
+    /// the name of a function, an identifier that is directly followed by its arguments
+    Function(String),
     Eof,
 }
 
@@ -23,7 +25,7 @@ impl fmt::Display for Token {
         let s = match self {
             Token::Number(n) => n.to_string(),
             Token::Operator(c) => c.to_string(),
-            Token::Identifier(s) => s.to_string(),
+            Token::Identifier(s) | Token::Function(s) => s.to_string(),
             Token::Eof => "EOF".to_string(),
         };
         write!(f, "{}", s)
@@ -35,7 +37,7 @@ impl Into<String> for Token {
         match self {
             Token::Number(n) => n.to_string(),
             Token::Operator(c) => c.to_string(),
-            Token::Identifier(s) => s,
+            Token::Identifier(s) | Token::Function(s) => s,
             Token::Eof => "EOF".to_string(),
         }
     }
@@ -59,11 +61,16 @@ impl Lexer {
     fn tokenize(input: &str) -> IResult<&str, Vec<Token>> {
         let (input, tokens) = many0(delimited(
             multispace0,
-            alt((parse_number, parse_operator, parse_identifier)),
+            alt((
+                parse_number,
+                parse_operator,
+                parse_function,
+                parse_identifier,
+            )),
             multispace0,
         ))(input)?;
 
-        Ok((input, tokens))
+        Ok((input, merge_negative_numbers(tokens)))
     }
 
     /// Check if the lexer contains an assignment operator
@@ -120,14 +127,59 @@ fn parse_number(input: &str) -> IResult<&str, Token> {
     )(input)
 }
 
+/// A `-` in front of a number becomes part of the literal, unless it follows an operand and is a
+/// subtraction. A number raised to a power keeps its `-` as an operator, `-2^2` is `-(2^2)`.
+fn merge_negative_numbers(tokens: Vec<Token>) -> Vec<Token> {
+    let mut merged: Vec<Token> = Vec::with_capacity(tokens.len());
+    let mut i = 0;
+    while i < tokens.len() {
+        let follows_operand = matches!(
+            merged.last(),
+            Some(Token::Number(_) | Token::Identifier(_) | Token::Operator(')'))
+        );
+        let negative_number = match (&tokens[i], tokens.get(i + 1), tokens.get(i + 2)) {
+            (Token::Operator('-'), Some(Token::Number(n)), next) if !follows_operand => {
+                (next != Some(&Token::Operator('^'))).then_some(-n)
+            }
+            _ => None,
+        };
+
+        match negative_number {
+            Some(n) => {
+                merged.push(Token::Number(n));
+                i += 2;
+            }
+            None => {
+                merged.push(tokens[i].clone());
+                i += 1;
+            }
+        }
+    }
+    merged
+}
+
+/// `>=` and `<=` become the single character operators `≥` and `≤`.
 fn parse_operator(input: &str) -> IResult<&str, Token> {
-    map(one_of("+-*/^()=:"), Token::Operator)(input)
+    alt((
+        map(tag(">="), |_| Token::Operator('≥')),
+        map(tag("<="), |_| Token::Operator('≤')),
+        map(one_of("+-*/^()=:<>,"), Token::Operator),
+    ))(input)
+}
+
+fn identifier(input: &str) -> IResult<&str, &str> {
+    take_while1(|c: char| c.is_alphabetic() || c == '_')(input)
 }
 
 fn parse_identifier(input: &str) -> IResult<&str, Token> {
+    map(identifier, |s: &str| Token::Identifier(s.to_string()))(input)
+}
+
+/// An identifier followed by an opening parenthesis, the parenthesis is left for the parser.
+fn parse_function(input: &str) -> IResult<&str, Token> {
     map(
-        take_while1(|c: char| c.is_alphabetic() || c == '_'),
-        |s: &str| Token::Identifier(s.to_string()),
+        terminated(identifier, peek(preceded(multispace0, tag("(")))),
+        |s: &str| Token::Function(s.to_string()),
     )(input)
 }
 
@@ -189,7 +241,7 @@ mod tests {
             Token::Operator('*'),
             Token::Number(2.0),
             Token::Operator('('),
-            Token::Identifier("sin".to_string()),
+            Token::Function("sin".to_string()),
             Token::Operator('='),
             Token::Identifier("I".to_string()),
         ];
@@ -250,4 +302,82 @@ mod tests {
         ];
         assert_eq!(Lexer::new(input).tokens, expected);
     }
+
+    #[test]
+    fn test_comparison() {
+        let input = "v >= -50 : 1";
+        let expected = vec![
+            Token::Number(1.0),
+            Token::Operator(':'),
+            Token::Number(-50.0),
+            Token::Operator('≥'),
+            Token::Identifier("v".to_string()),
+        ];
+        assert_eq!(Lexer::new(input).tokens, expected);
+
+        let input = "a<b<=c>d";
+        let expected = vec![
+            Token::Identifier("d".to_string()),
+            Token::Operator('>'),
+            Token::Identifier("c".to_string()),
+            Token::Operator('≤'),
+            Token::Identifier("b".to_string()),
+            Token::Operator('<'),
+            Token::Identifier("a".to_string()),
+        ];
+        assert_eq!(Lexer::new(input).tokens, expected);
+    }
+
+    #[test]
+    fn test_function() {
+        let input = "max (y, x) - x";
+        let expected = vec![
+            Token::Identifier("x".to_string()),
+            Token::Operator('-'),
+            Token::Operator(')'),
+            Token::Identifier("x".to_string()),
+            Token::Operator(','),
+            Token::Identifier("y".to_string()),
+            Token::Operator('('),
+            Token::Function("max".to_string()),
+        ];
+        assert_eq!(Lexer::new(input).tokens, expected);
+    }
+
+    #[test]
+    fn test_negative_number() {
+        assert_eq!(Lexer::new("-70").tokens, vec![Token::Number(-70.0)]);
+        assert_eq!(Lexer::new("-0.001").tokens, vec![Token::Number(-0.001)]);
+
+        // subtractions stay operators
+        let input = "a - -b";
+        let expected = vec![
+            Token::Identifier("b".to_string()),
+            Token::Operator('-'),
+            Token::Operator('-'),
+            Token::Identifier("a".to_string()),
+        ];
+        assert_eq!(Lexer::new(input).tokens, expected);
+
+        let input = "(1) - 2 * -3";
+        let expected = vec![
+            Token::Number(-3.0),
+            Token::Operator('*'),
+            Token::Number(2.0),
+            Token::Operator('-'),
+            Token::Operator(')'),
+            Token::Number(1.0),
+            Token::Operator('('),
+        ];
+        assert_eq!(Lexer::new(input).tokens, expected);
+
+        let input = "-2^2";
+        let expected = vec![
+            Token::Number(2.0),
+            Token::Operator('^'),
+            Token::Number(2.0),
+            Token::Operator('-'),
+        ];
+        assert_eq!(Lexer::new(input).tokens, expected);
+    }
 }
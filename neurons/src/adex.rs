@@ -0,0 +1,196 @@
+use bevy::{prelude::Component, reflect::Reflect};
+use silicon_core::SynapticConductance;
+
+use super::{Neuron, NeuronVisualizer};
+
+/// Adaptive exponential integrate-and-fire neuron (Brette & Gerstner, 2005).
+/// Time is in ms, voltages in mV, conductances in nS, currents in pA and capacitance in pF.
+#[derive(Component, Debug, Reflect)]
+pub struct AdExNeuron {
+    pub c_m: f64,
+    pub g_l: f64,
+    pub e_l: f64,
+    /// threshold potential
+    pub v_t: f64,
+    /// slope factor, controls the sharpness of the spike initiation
+    pub delta_t: f64,
+    /// time constant of the adaptation variable
+    pub tau_w: f64,
+    /// subthreshold adaptation
+    pub a: f64,
+    /// spike-triggered adaptation
+    pub b: f64,
+    /// reset potential
+    pub v_r: f64,
+    /// a spike is registered once the membrane potential reaches this value
+    pub v_peak: f64,
+    /// adaptation current
+    pub w: f64,
+    pub v: f64,
+    /// constant external current
+    pub i_ext: f64,
+    /// current accumulated through `insert_current` since the last update
+    pub input_current: f64,
+    pub conductance: SynapticConductance,
+}
+
+impl Default for AdExNeuron {
+    fn default() -> Self {
+        AdExNeuron {
+            c_m: 281.0,
+            g_l: 30.0,
+            e_l: -70.6,
+            v_t: -50.4,
+            delta_t: 2.0,
+            tau_w: 144.0,
+            a: 4.0,
+            b: 80.5,
+            v_r: -70.6,
+            v_peak: 20.0,
+            w: 0.0,
+            v: -70.6,
+            i_ext: 0.0,
+            input_current: 0.0,
+            conductance: SynapticConductance::default(),
+        }
+    }
+}
+
+impl Neuron for AdExNeuron {
+    fn update(&mut self, tau: f64) -> bool {
+        let input_current = self.input_current + self.conductance.current(self.v);
+        self.input_current = 0.0;
+        self.conductance.clear();
+
+        let spike_current = self.g_l * self.delta_t * ((self.v - self.v_t) / self.delta_t).exp();
+        let dv =
+            (-self.g_l * (self.v - self.e_l) + spike_current - self.w + self.i_ext + input_current)
+                / self.c_m;
+        let dw = (self.a * (self.v - self.e_l) - self.w) / self.tau_w;
+
+        self.v += tau * dv;
+        self.w += tau * dw;
+
+        if self.v >= self.v_peak {
+            self.v = self.v_r;
+            self.w += self.b;
+            return true;
+        }
+
+        false
+    }
+
+    fn get_membrane_potential(&self) -> f64 {
+        self.v
+    }
+
+    fn insert_current(&mut self, current: f64) -> f64 {
+        self.input_current += current;
+        self.v
+    }
+
+    fn add_conductance(&mut self, g: f64, reversal_potential: f64) {
+        self.conductance.add(g, reversal_potential);
+    }
+
+    fn reset_state(&mut self) {
+        self.v = self.e_l;
+        self.w = 0.0;
+        self.input_current = 0.0;
+        self.conductance.clear();
+    }
+}
+
+impl NeuronVisualizer for AdExNeuron {
+    fn activation_percent(&self) -> f64 {
+        ((self.v - self.e_l) / (self.v_t - self.e_l)).clamp(0.0, 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spike_times(neuron: &mut AdExNeuron, duration: f64, tau: f64) -> Vec<f64> {
+        (0..(duration / tau) as usize)
+            .filter(|_| neuron.update(tau))
+            .map(|step| step as f64 * tau)
+            .collect()
+    }
+
+    fn intervals(spikes: &[f64]) -> Vec<f64> {
+        spikes.windows(2).map(|pair| pair[1] - pair[0]).collect()
+    }
+
+    // parameter sets from Naud et al. (2008), firing patterns of the AdEx model
+    fn naud_neuron(g_l: f64, e_l: f64, tau_w: f64, a: f64, b: f64, v_r: f64, i: f64) -> AdExNeuron {
+        AdExNeuron {
+            c_m: 200.0,
+            g_l,
+            e_l,
+            v_t: -50.0,
+            delta_t: 2.0,
+            tau_w,
+            a,
+            b,
+            v_r,
+            v_peak: 0.0,
+            w: 0.0,
+            v: e_l,
+            i_ext: i,
+            input_current: 0.0,
+            conductance: SynapticConductance::default(),
+        }
+    }
+
+    #[test]
+    fn test_regular_spiking() {
+        let mut neuron = naud_neuron(10.0, -70.0, 30.0, 2.0, 0.0, -58.0, 500.0);
+        let intervals = intervals(&spike_times(&mut neuron, 500.0, 0.025));
+
+        assert!(intervals.len() > 10);
+        let last = &intervals[intervals.len() - 5..];
+        let max = last.iter().cloned().fold(f64::MIN, f64::max);
+        let min = last.iter().cloned().fold(f64::MAX, f64::min);
+        assert!((max - min) / min < 0.05, "tonic firing should be regular");
+    }
+
+    #[test]
+    fn test_adaptation() {
+        let mut neuron = naud_neuron(12.0, -70.0, 300.0, 2.0, 60.0, -58.0, 500.0);
+        let intervals = intervals(&spike_times(&mut neuron, 500.0, 0.025));
+
+        assert!(intervals.len() > 3);
+        assert!(
+            intervals.last().unwrap() > &(intervals[0] * 2.0),
+            "inter-spike intervals should grow as the adaptation current builds up"
+        );
+    }
+
+    #[test]
+    fn test_bursting() {
+        let mut neuron = naud_neuron(10.0, -58.0, 120.0, 2.0, 100.0, -46.0, 210.0);
+        let intervals = intervals(&spike_times(&mut neuron, 1000.0, 0.025));
+
+        assert!(intervals.len() > 3);
+        let max = intervals.iter().cloned().fold(f64::MIN, f64::max);
+        let min = intervals.iter().cloned().fold(f64::MAX, f64::min);
+        assert!(
+            max > min * 3.0,
+            "bursts should be separated by long pauses, got {intervals:?}"
+        );
+    }
+
+    #[test]
+    fn test_injected_current_is_integrated() {
+        let mut external = naud_neuron(10.0, -70.0, 30.0, 2.0, 0.0, -58.0, 500.0);
+        let mut injected = naud_neuron(10.0, -70.0, 30.0, 2.0, 0.0, -58.0, 0.0);
+
+        // injecting the current every tick is the same as a constant external current
+        for _ in 0..20000 {
+            injected.insert_current(500.0);
+            assert_eq!(external.update(0.025), injected.update(0.025));
+            assert!((external.v - injected.v).abs() < 1e-9);
+        }
+    }
+}
@@ -0,0 +1,204 @@
+use bevy::{prelude::Component, reflect::Reflect};
+
+use super::{Neuron, NeuronVisualizer};
+
+/// Conductance based leaky integrate-and-fire neuron. Excitatory and inhibitory synapses open
+/// separate conductances that decay exponentially, the resulting current depends on the distance
+/// between the membrane potential and the reversal potential of each conductance. This makes
+/// inhibition shunting instead of a fixed subtraction.
+#[derive(Component, Debug, Reflect)]
+pub struct CobaLifNeuron {
+    pub v: f64,
+    pub e_l: f64,
+    pub tau_m: f64,
+    pub threshold_potential: f64,
+    pub reset_potential: f64,
+    pub refractory_period: f64,
+    pub refractory_counter: f64,
+    /// excitatory conductance relative to the leak conductance
+    pub g_exc: f64,
+    /// inhibitory conductance relative to the leak conductance
+    pub g_inh: f64,
+    pub e_exc: f64,
+    pub e_inh: f64,
+    /// decay time constant of the excitatory conductance
+    pub tau_exc: f64,
+    /// decay time constant of the inhibitory conductance
+    pub tau_inh: f64,
+    /// current accumulated through `insert_current` since the last update, relative to the leak
+    /// conductance like the synaptic conductances, so it is in mV
+    pub input_current: f64,
+}
+
+impl Default for CobaLifNeuron {
+    fn default() -> Self {
+        CobaLifNeuron {
+            v: -70.0,
+            e_l: -70.0,
+            tau_m: 20.0,
+            threshold_potential: -50.0,
+            reset_potential: -70.0,
+            refractory_period: 2.0,
+            refractory_counter: 0.0,
+            g_exc: 0.0,
+            g_inh: 0.0,
+            e_exc: 0.0,
+            e_inh: -80.0,
+            tau_exc: 5.0,
+            tau_inh: 10.0,
+            input_current: 0.0,
+        }
+    }
+}
+
+impl CobaLifNeuron {
+    fn decay_conductances(&mut self, tau: f64) {
+        self.g_exc *= (-tau / self.tau_exc).exp();
+        self.g_inh *= (-tau / self.tau_inh).exp();
+    }
+}
+
+impl Neuron for CobaLifNeuron {
+    fn update(&mut self, tau: f64) -> bool {
+        let input_current = self.input_current;
+        self.input_current = 0.0;
+
+        if self.refractory_counter > 0.0 {
+            self.refractory_counter -= tau;
+            self.decay_conductances(tau);
+            return false;
+        }
+
+        let synaptic_current =
+            self.g_exc * (self.e_exc - self.v) + self.g_inh * (self.e_inh - self.v);
+        self.v += tau * (-(self.v - self.e_l) + synaptic_current + input_current) / self.tau_m;
+        self.decay_conductances(tau);
+
+        if self.v >= self.threshold_potential {
+            self.v = self.reset_potential;
+            self.refractory_counter = self.refractory_period;
+            return true;
+        }
+
+        false
+    }
+
+    fn get_membrane_potential(&self) -> f64 {
+        self.v
+    }
+
+    fn insert_current(&mut self, current: f64) -> f64 {
+        self.input_current += current;
+        self.v
+    }
+
+    fn add_conductance(&mut self, g: f64, reversal_potential: f64) {
+        if reversal_potential > self.e_l {
+            self.add_excitatory_conductance(g);
+        } else {
+            self.add_inhibitory_conductance(g);
+        }
+    }
+
+    fn add_excitatory_conductance(&mut self, g: f64) {
+        self.g_exc += g;
+    }
+
+    fn add_inhibitory_conductance(&mut self, g: f64) {
+        self.g_inh += g;
+    }
+
+    fn reset_state(&mut self) {
+        self.v = self.e_l;
+        self.refractory_counter = 0.0;
+        self.g_exc = 0.0;
+        self.g_inh = 0.0;
+        self.input_current = 0.0;
+    }
+}
+
+impl NeuronVisualizer for CobaLifNeuron {
+    fn activation_percent(&self) -> f64 {
+        ((self.v - self.e_l) / (self.threshold_potential - self.e_l)).clamp(0.0, 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_excitation_causes_spikes() {
+        let mut neuron = CobaLifNeuron::default();
+
+        let spikes = (0..4000)
+            .filter(|_| {
+                neuron.add_excitatory_conductance(0.05);
+                neuron.update(0.025)
+            })
+            .count();
+
+        assert!(spikes > 0);
+    }
+
+    #[test]
+    fn test_inhibition_is_shunting() {
+        // at the inhibitory reversal potential an inhibitory conductance has no effect
+        let mut neuron = CobaLifNeuron {
+            v: -80.0,
+            e_l: -80.0,
+            ..Default::default()
+        };
+        neuron.add_inhibitory_conductance(5.0);
+        neuron.update(0.025);
+        assert!((neuron.v - -80.0).abs() < 1e-9);
+
+        // but it does reduce the depolarization caused by excitation
+        let depolarization = |g_inh: f64| {
+            let mut neuron = CobaLifNeuron {
+                v: -65.0,
+                ..Default::default()
+            };
+            neuron.add_excitatory_conductance(1.0);
+            neuron.add_inhibitory_conductance(g_inh);
+            neuron.update(0.025);
+            neuron.v + 65.0
+        };
+        assert!(depolarization(1.0) < depolarization(0.0));
+    }
+
+    #[test]
+    fn test_excitation_diminishes_near_reversal_potential() {
+        let excitatory_effect = |v: f64| {
+            let update = |g_exc: f64| {
+                let mut neuron = CobaLifNeuron {
+                    v,
+                    threshold_potential: 100.0,
+                    ..Default::default()
+                };
+                neuron.add_excitatory_conductance(g_exc);
+                neuron.update(0.025);
+                neuron.v
+            };
+
+            update(1.0) - update(0.0)
+        };
+
+        let far = excitatory_effect(-65.0);
+        let near = excitatory_effect(-5.0);
+        assert!(near > 0.0);
+        assert!(near < far / 10.0);
+        assert!(excitatory_effect(0.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_injected_current_settles_above_rest() {
+        let mut neuron = CobaLifNeuron::default();
+        for _ in 0..40000 {
+            neuron.insert_current(10.0);
+            assert!(!neuron.update(0.025));
+        }
+
+        assert!((neuron.v - (neuron.e_l + 10.0)).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,268 @@
+use std::collections::HashMap;
+
+use bevy::{prelude::Component, reflect::Reflect};
+use equations::{
+    equation::{parse_equations, reset_statement, Equation},
+    evaluator::ExpressionEvaluator,
+    s::ParseError,
+};
+use silicon_core::SynapticConductance;
+
+use super::{Neuron, NeuronVisualizer};
+
+/// The variable holding the current inserted into the neuron since the last update, including
+/// the synaptic current.
+pub const INPUT_CURRENT_VARIABLE: &str = "I_in";
+/// The variable holding the simulated time in ms.
+pub const TIME_VARIABLE: &str = "t";
+
+/// When `variable` reaches `threshold` the neuron fires and the variable is set to `reset`.
+/// `variable` is the membrane potential of the neuron. A threshold equation like `v > v_thresh`
+/// replaces `threshold` and a `reset:` line replaces `reset`.
+#[derive(Debug, Clone, Reflect)]
+pub struct ThresholdReset {
+    pub variable: String,
+    pub threshold: f64,
+    pub reset: f64,
+}
+
+/// Neuron whose dynamics are defined at runtime by Brian-style equations. Every update the
+/// assignments are evaluated first, in order, after which every differential equation is
+/// integrated with forward Euler. Equations can read the inserted current through `I_in` and the
+/// simulated time through `t`.
+#[derive(Component, Debug, Reflect)]
+pub struct EquationNeuron {
+    #[reflect(ignore)]
+    pub equations: Vec<Equation>,
+    pub variables: HashMap<String, f64>,
+    /// the values `variables` started with, restored by `reset_state`
+    pub initial_variables: HashMap<String, f64>,
+    pub threshold: ThresholdReset,
+    pub input_current: f64,
+    pub conductance: SynapticConductance,
+}
+
+impl EquationNeuron {
+    pub fn new(
+        equations: &str,
+        variables: HashMap<String, f64>,
+        threshold: ThresholdReset,
+    ) -> Result<Self, ParseError> {
+        Ok(EquationNeuron {
+            equations: parse_equations(equations)?,
+            initial_variables: variables.clone(),
+            variables,
+            threshold,
+            input_current: 0.0,
+            conductance: SynapticConductance::default(),
+        })
+    }
+
+    pub fn variable(&self, name: &str) -> Option<f64> {
+        self.variables.get(name).cloned()
+    }
+
+    /// Whether the threshold equation holds, or the membrane potential reached
+    /// `threshold.threshold` when there is none.
+    fn fired(&self) -> bool {
+        let condition = self.equations.iter().find_map(|equation| match equation {
+            Equation::Threshold(condition) => Some(condition),
+            _ => None,
+        });
+
+        match condition {
+            Some(condition) => condition
+                .evaluate(&self.variables)
+                .is_some_and(|value| value != 0.0),
+            None => self.get_membrane_potential() >= self.threshold.threshold,
+        }
+    }
+}
+
+impl Neuron for EquationNeuron {
+    fn update(&mut self, tau: f64) -> bool {
+        let input_current =
+            self.input_current + self.conductance.current(self.get_membrane_potential());
+        self.input_current = 0.0;
+        self.conductance.clear();
+        self.variables
+            .insert(INPUT_CURRENT_VARIABLE.to_string(), input_current);
+
+        for equation in self.equations.iter() {
+            if let Equation::Assignment(_, rhs, _) = equation {
+                let variable = equation.variable();
+                let value = rhs.evaluate(&self.variables);
+                if let (Some(variable), Some(value)) = (variable, value) {
+                    self.variables.insert(variable.to_string(), value);
+                }
+            }
+        }
+
+        // all derivatives are evaluated on the same state before any variable is integrated
+        let derivatives = self
+            .equations
+            .iter()
+            .filter_map(|equation| match equation {
+                Equation::Differential(_, rhs, _) => Some((
+                    equation.variable()?.to_string(),
+                    rhs.evaluate(&self.variables)?,
+                )),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        for (variable, derivative) in derivatives {
+            *self.variables.entry(variable).or_insert(0.0) += derivative * tau;
+        }
+
+        *self
+            .variables
+            .entry(TIME_VARIABLE.to_string())
+            .or_insert(0.0) += tau;
+
+        if !self.fired() {
+            return false;
+        }
+
+        let resets = self.equations.iter().find_map(|equation| match equation {
+            Equation::Reset(statements) => Some(statements),
+            _ => None,
+        });
+        match resets {
+            Some(statements) => {
+                for statement in statements {
+                    let Some((variable, value)) = reset_statement(statement) else {
+                        continue;
+                    };
+                    if let Some(value) = value.evaluate(&self.variables) {
+                        self.variables.insert(variable.to_string(), value);
+                    }
+                }
+            }
+            None => {
+                self.variables
+                    .insert(self.threshold.variable.clone(), self.threshold.reset);
+            }
+        }
+
+        true
+    }
+
+    fn get_membrane_potential(&self) -> f64 {
+        self.variable(&self.threshold.variable).unwrap_or_default()
+    }
+
+    /// The current is made available to the equations as `I_in` during the next update.
+    fn insert_current(&mut self, current: f64) -> f64 {
+        self.input_current += current;
+        self.get_membrane_potential()
+    }
+
+    fn add_conductance(&mut self, g: f64, reversal_potential: f64) {
+        self.conductance.add(g, reversal_potential);
+    }
+
+    fn reset_state(&mut self) {
+        self.variables = self.initial_variables.clone();
+        self.input_current = 0.0;
+        self.conductance.clear();
+    }
+}
+
+impl NeuronVisualizer for EquationNeuron {
+    fn activation_percent(&self) -> f64 {
+        ((self.get_membrane_potential() - self.threshold.reset)
+            / (self.threshold.threshold - self.threshold.reset))
+            .clamp(0.0, 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rc_neuron(v: f64, threshold: f64) -> EquationNeuron {
+        let variables = [("v", v), ("v_rest", -65.0), ("tau_m", 10.0)]
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value))
+            .collect();
+
+        EquationNeuron::new(
+            "I_leak = (v_rest - v) / tau_m : volt/second
+            dv/dt = I_leak + I_in : volt",
+            variables,
+            ThresholdReset {
+                variable: "v".to_string(),
+                threshold,
+                reset: -65.0,
+            },
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_rc_decay_relaxes_to_steady_state() {
+        let mut neuron = rc_neuron(-40.0, 0.0);
+
+        for _ in 0..(100.0 / 0.025) as usize {
+            assert!(!neuron.update(0.025));
+        }
+
+        // after ten time constants the distance to rest has shrunk by e^-10
+        assert!((neuron.get_membrane_potential() - -65.0).abs() < 0.01);
+        assert!((neuron.variable("t").unwrap() - 100.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_input_current_causes_spikes() {
+        let mut neuron = rc_neuron(-65.0, -50.0);
+
+        let spikes = (0..(100.0 / 0.025) as usize)
+            .filter(|_| {
+                neuron.insert_current(2.0);
+                neuron.update(0.025)
+            })
+            .count();
+
+        assert!(spikes > 0);
+        assert!(neuron.get_membrane_potential() < -50.0);
+    }
+
+    #[test]
+    fn test_threshold_and_reset_equations() {
+        let variables = [("v", -65.0), ("w", 0.0)]
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value))
+            .collect();
+        // the threshold and reset of `ThresholdReset` are replaced by the equations
+        let mut neuron = EquationNeuron::new(
+            "dv/dt = I_in - w : volt
+            dw/dt = -w / 100 : volt
+            v >= -50 : 1
+            reset: v = -70, w = w + 0.5",
+            variables,
+            ThresholdReset {
+                variable: "v".to_string(),
+                threshold: 100.0,
+                reset: 0.0,
+            },
+        )
+        .unwrap();
+
+        let mut spikes = vec![];
+        for step in 0..(100.0 / 0.025) as usize {
+            neuron.insert_current(2.0);
+            if neuron.update(0.025) {
+                spikes.push(step);
+                assert_eq!(neuron.get_membrane_potential(), -70.0);
+            }
+        }
+
+        assert!(spikes.len() > 2);
+        // every spike adds adaptation, so the intervals grow
+        let first = spikes[1] - spikes[0];
+        let last = spikes[spikes.len() - 1] - spikes[spikes.len() - 2];
+        assert!(last > first);
+        assert!(neuron.variable("w").unwrap() > 0.0);
+    }
+}
@@ -0,0 +1,242 @@
+use bevy::{prelude::Component, reflect::Reflect};
+use silicon_core::SynapticConductance;
+
+use super::{Neuron, NeuronVisualizer};
+
+/// Hodgkin-Huxley neuron using the squid giant axon parameters, with the membrane potential
+/// shifted so that the resting potential sits around -65mV. Time is in ms, voltages in mV,
+/// conductances in mS/cm² and currents in µA/cm².
+#[derive(Component, Debug, Reflect)]
+pub struct HodgkinHuxleyNeuron {
+    pub v: f64,
+    /// sodium activation
+    pub m: f64,
+    /// sodium inactivation
+    pub h: f64,
+    /// potassium activation
+    pub n: f64,
+    pub g_na: f64,
+    pub g_k: f64,
+    pub g_l: f64,
+    pub e_na: f64,
+    pub e_k: f64,
+    pub e_l: f64,
+    pub c_m: f64,
+    /// constant external current
+    pub i_ext: f64,
+    /// current accumulated through `insert_current` since the last update
+    pub input_current: f64,
+    /// A spike is reported when `v` crosses this value on the rising edge.
+    pub spike_threshold: f64,
+    /// The largest integration step used inside `update`, the time step is split into sub-steps
+    /// of at most this size to keep the gating equations stable.
+    pub max_step: f64,
+    pub above_threshold: bool,
+    pub conductance: SynapticConductance,
+}
+
+/// The resting membrane potential of the squid giant axon with the default parameters.
+const RESTING_POTENTIAL: f64 = -65.0;
+
+impl Default for HodgkinHuxleyNeuron {
+    fn default() -> Self {
+        let v = RESTING_POTENTIAL;
+        HodgkinHuxleyNeuron {
+            v,
+            m: steady_state(alpha_m(v), beta_m(v)),
+            h: steady_state(alpha_h(v), beta_h(v)),
+            n: steady_state(alpha_n(v), beta_n(v)),
+            g_na: 120.0,
+            g_k: 36.0,
+            g_l: 0.3,
+            e_na: 50.0,
+            e_k: -77.0,
+            e_l: -54.387,
+            c_m: 1.0,
+            i_ext: 0.0,
+            input_current: 0.0,
+            spike_threshold: 0.0,
+            max_step: 0.01,
+            above_threshold: false,
+            conductance: SynapticConductance::default(),
+        }
+    }
+}
+
+impl HodgkinHuxleyNeuron {
+    fn step(&mut self, dt: f64, input_current: f64) {
+        let v = self.v;
+
+        let i_na = self.g_na * self.m.powi(3) * self.h * (v - self.e_na);
+        let i_k = self.g_k * self.n.powi(4) * (v - self.e_k);
+        let i_l = self.g_l * (v - self.e_l);
+
+        self.v += dt * (self.i_ext + input_current - i_na - i_k - i_l) / self.c_m;
+        self.m += dt * (alpha_m(v) * (1.0 - self.m) - beta_m(v) * self.m);
+        self.h += dt * (alpha_h(v) * (1.0 - self.h) - beta_h(v) * self.h);
+        self.n += dt * (alpha_n(v) * (1.0 - self.n) - beta_n(v) * self.n);
+    }
+}
+
+impl Neuron for HodgkinHuxleyNeuron {
+    fn update(&mut self, tau: f64) -> bool {
+        let steps = (tau / self.max_step).ceil().max(1.0) as usize;
+        let dt = tau / steps as f64;
+
+        let input_current = self.input_current + self.conductance.current(self.v);
+        self.input_current = 0.0;
+        self.conductance.clear();
+
+        let mut fired = false;
+        for _ in 0..steps {
+            self.step(dt, input_current);
+
+            if self.v >= self.spike_threshold {
+                if !self.above_threshold {
+                    fired = true;
+                }
+                self.above_threshold = true;
+            } else {
+                self.above_threshold = false;
+            }
+        }
+
+        fired
+    }
+
+    fn get_membrane_potential(&self) -> f64 {
+        self.v
+    }
+
+    fn insert_current(&mut self, current: f64) -> f64 {
+        self.input_current += current;
+        self.v
+    }
+
+    fn add_conductance(&mut self, g: f64, reversal_potential: f64) {
+        self.conductance.add(g, reversal_potential);
+    }
+
+    /// The gating variables are set to their steady state at rest.
+    fn reset_state(&mut self) {
+        let v = RESTING_POTENTIAL;
+        self.v = v;
+        self.m = steady_state(alpha_m(v), beta_m(v));
+        self.h = steady_state(alpha_h(v), beta_h(v));
+        self.n = steady_state(alpha_n(v), beta_n(v));
+        self.above_threshold = false;
+        self.input_current = 0.0;
+        self.conductance.clear();
+    }
+}
+
+impl NeuronVisualizer for HodgkinHuxleyNeuron {
+    fn activation_percent(&self) -> f64 {
+        ((self.v - self.e_l) / (self.spike_threshold - self.e_l)).clamp(0.0, 1.0)
+    }
+}
+
+/// The value a gating variable settles at for the given rates.
+fn steady_state(alpha: f64, beta: f64) -> f64 {
+    alpha / (alpha + beta)
+}
+
+/// `x / (exp(x / y) - 1)` with the removable singularity at `x = 0` handled.
+fn vtrap(x: f64, y: f64) -> f64 {
+    if (x / y).abs() < 1e-6 {
+        y * (1.0 - x / y / 2.0)
+    } else {
+        x / ((x / y).exp() - 1.0)
+    }
+}
+
+fn alpha_m(v: f64) -> f64 {
+    0.1 * vtrap(-(v + 40.0), 10.0)
+}
+
+fn beta_m(v: f64) -> f64 {
+    4.0 * (-(v + 65.0) / 18.0).exp()
+}
+
+fn alpha_h(v: f64) -> f64 {
+    0.07 * (-(v + 65.0) / 20.0).exp()
+}
+
+fn beta_h(v: f64) -> f64 {
+    1.0 / (1.0 + (-(v + 35.0) / 10.0).exp())
+}
+
+fn alpha_n(v: f64) -> f64 {
+    0.01 * vtrap(-(v + 55.0), 10.0)
+}
+
+fn beta_n(v: f64) -> f64 {
+    0.125 * (-(v + 65.0) / 80.0).exp()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn count_spikes(neuron: &mut HodgkinHuxleyNeuron, duration: f64, tau: f64) -> usize {
+        (0..(duration / tau) as usize)
+            .filter(|_| neuron.update(tau))
+            .count()
+    }
+
+    #[test]
+    fn test_repetitive_spiking_above_rheobase() {
+        let mut neuron = HodgkinHuxleyNeuron {
+            i_ext: 10.0,
+            ..Default::default()
+        };
+
+        let spikes = count_spikes(&mut neuron, 200.0, 0.025);
+        assert!(
+            spikes >= 10,
+            "expected repetitive spiking, got {spikes} spikes"
+        );
+    }
+
+    #[test]
+    fn test_zero_current_stays_at_rest() {
+        let mut neuron = HodgkinHuxleyNeuron::default();
+
+        let spikes = count_spikes(&mut neuron, 200.0, 0.025);
+        assert_eq!(spikes, 0);
+        assert!((neuron.v + 65.0).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_single_crossing_per_spike() {
+        let mut neuron = HodgkinHuxleyNeuron {
+            i_ext: 10.0,
+            ..Default::default()
+        };
+
+        // the membrane stays above threshold for several coarse updates, each spike should still only count once
+        let coarse = count_spikes(&mut neuron, 200.0, 0.1);
+        let mut neuron = HodgkinHuxleyNeuron {
+            i_ext: 10.0,
+            ..Default::default()
+        };
+        let fine = count_spikes(&mut neuron, 200.0, 0.01);
+        assert!((coarse as i64 - fine as i64).abs() <= 1);
+    }
+
+    #[test]
+    fn test_injected_current_is_integrated() {
+        let mut external = HodgkinHuxleyNeuron {
+            i_ext: 10.0,
+            ..Default::default()
+        };
+        let mut injected = HodgkinHuxleyNeuron::default();
+
+        // injecting the current every tick is the same as a constant external current
+        for _ in 0..8000 {
+            injected.insert_current(10.0);
+            assert_eq!(external.update(0.025), injected.update(0.025));
+            assert!((external.v - injected.v).abs() < 1e-9);
+        }
+    }
+}
@@ -1,8 +1,10 @@
 use bevy::{prelude::Component, reflect::Reflect};
+use silicon_core::{integrator::Rk4Integrator, SynapticConductance, UnknownParameter};
 
-use super::{Neuron, NeuronVisualizer};
+use super::{leaky::IntegrationMethod, Neuron, NeuronVisualizer};
 
-#[derive(Component, Debug, Reflect)]
+#[derive(Component, Debug, Clone, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct IzhikevichNeuron {
     pub a: f64,
     pub b: f64,
@@ -10,32 +12,276 @@ pub struct IzhikevichNeuron {
     pub d: f64,
     pub v: f64,
     pub u: f64,
+    /// the membrane potential restored by `reset_state`
+    pub v_init: f64,
+    /// the recovery variable restored by `reset_state`
+    pub u_init: f64,
+    /// scales the synaptic conductances the neuron receives
     pub synapse_weight_multiplier: f64,
+    /// current accumulated through `insert_current` since the last update
+    pub input_current: f64,
+    pub conductance: SynapticConductance,
+    /// time after a spike during which the neuron ignores its dynamics and all input, 0.0 disables it
+    pub refractory_period: f64,
+    pub refractory_counter: f64,
+    /// how `v` and `u` are integrated, `ExponentialEuler` is forward Euler for this model
+    pub integration: IntegrationMethod,
+}
+
+/// The published parameter sets from Izhikevich (2003), "Simple model of spiking neurons".
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+pub enum IzhikevichPreset {
+    /// Tonic spiking with spike frequency adaptation, the most common excitatory cortical neuron.
+    #[default]
+    RegularSpiking,
+    /// An initial burst followed by tonic spiking.
+    IntrinsicallyBursting,
+    /// Repetitive bursts of closely spaced spikes.
+    Chattering,
+    /// High frequency tonic spiking without adaptation, typical for inhibitory interneurons.
+    FastSpiking,
+    /// High frequency spiking with adaptation and a low firing threshold.
+    LowThresholdSpiking,
+}
+
+impl IzhikevichPreset {
+    /// The `a`, `b`, `c` and `d` parameters of the preset.
+    pub fn parameters(&self) -> (f64, f64, f64, f64) {
+        match self {
+            IzhikevichPreset::RegularSpiking => (0.02, 0.2, -65.0, 8.0),
+            IzhikevichPreset::IntrinsicallyBursting => (0.02, 0.2, -55.0, 4.0),
+            IzhikevichPreset::Chattering => (0.02, 0.2, -50.0, 2.0),
+            IzhikevichPreset::FastSpiking => (0.1, 0.2, -65.0, 2.0),
+            IzhikevichPreset::LowThresholdSpiking => (0.02, 0.25, -65.0, 2.0),
+        }
+    }
+
+    pub fn neuron(&self) -> IzhikevichNeuron {
+        let (a, b, c, d) = self.parameters();
+        let v = -65.0;
+
+        IzhikevichNeuron {
+            a,
+            b,
+            c,
+            d,
+            v,
+            u: b * v,
+            v_init: v,
+            u_init: b * v,
+            synapse_weight_multiplier: 1.0,
+            input_current: 0.0,
+            conductance: SynapticConductance::default(),
+            refractory_period: 0.0,
+            refractory_counter: 0.0,
+            integration: IntegrationMethod::ForwardEuler,
+        }
+    }
+}
+
+impl IzhikevichNeuron {
+    pub fn regular_spiking() -> Self {
+        IzhikevichPreset::RegularSpiking.neuron()
+    }
+
+    pub fn intrinsically_bursting() -> Self {
+        IzhikevichPreset::IntrinsicallyBursting.neuron()
+    }
+
+    pub fn chattering() -> Self {
+        IzhikevichPreset::Chattering.neuron()
+    }
+
+    pub fn fast_spiking() -> Self {
+        IzhikevichPreset::FastSpiking.neuron()
+    }
+
+    pub fn low_threshold_spiking() -> Self {
+        IzhikevichPreset::LowThresholdSpiking.neuron()
+    }
+
+    /// The time derivatives of `v` and `u` for the given state and input `current`.
+    pub fn derivatives(&self, v: f64, u: f64, current: f64) -> (f64, f64) {
+        derivatives(self.a, self.b, v, u, current)
+    }
+
+    /// Start building a neuron from a preset, the remaining state can be overridden before calling `build`.
+    pub fn builder(preset: IzhikevichPreset) -> IzhikevichBuilder {
+        IzhikevichBuilder {
+            neuron: preset.neuron(),
+        }
+    }
+}
+
+fn derivatives(a: f64, b: f64, v: f64, u: f64, current: f64) -> (f64, f64) {
+    (
+        0.04 * v * v + 5.0 * v + 140.0 - u + current,
+        a * (b * v - u),
+    )
+}
+
+/// Advances `[v, u]` by `tau` with the given `integration`. Shared by `IzhikevichNeuron` and
+/// `IzhikevichNeuronF32`, which converts its state to `f64` for the step.
+pub(crate) fn integrate(
+    a: f64,
+    b: f64,
+    [v, u]: [f64; 2],
+    current: f64,
+    tau: f64,
+    integration: IntegrationMethod,
+) -> [f64; 2] {
+    match integration {
+        IntegrationMethod::ForwardEuler | IntegrationMethod::ExponentialEuler => {
+            let (dv, du) = derivatives(a, b, v, u, current);
+            [v + tau * dv, u + tau * du]
+        }
+        IntegrationMethod::RungeKutta4 => Rk4Integrator::integrate_system(
+            |_, [v, u]| {
+                let (dv, du) = derivatives(a, b, v, u, current);
+                [dv, du]
+            },
+            [v, u],
+            tau,
+        ),
+    }
+}
+
+pub struct IzhikevichBuilder {
+    neuron: IzhikevichNeuron,
+}
+
+impl IzhikevichBuilder {
+    pub fn synapse_weight_multiplier(mut self, synapse_weight_multiplier: f64) -> Self {
+        self.neuron.synapse_weight_multiplier = synapse_weight_multiplier;
+        self
+    }
+
+    /// The initial membrane potential.
+    pub fn v(mut self, v: f64) -> Self {
+        self.neuron.v = v;
+        self.neuron.v_init = v;
+        self
+    }
+
+    /// The initial recovery variable.
+    pub fn u(mut self, u: f64) -> Self {
+        self.neuron.u = u;
+        self.neuron.u_init = u;
+        self
+    }
+
+    pub fn refractory_period(mut self, refractory_period: f64) -> Self {
+        self.neuron.refractory_period = refractory_period;
+        self
+    }
+
+    pub fn integration(mut self, integration: IntegrationMethod) -> Self {
+        self.neuron.integration = integration;
+        self
+    }
+
+    pub fn build(self) -> IzhikevichNeuron {
+        self.neuron
+    }
 }
 
 impl Neuron for IzhikevichNeuron {
     fn update(&mut self, tau: f64) -> bool {
-        let v = self.v + tau * (0.04 * self.v * self.v + 5.0 * self.v + 140.0 - self.u) + 0.0;
-        let u = self.u + tau * self.a * (self.b * self.v - self.u);
-        self.v = v;
-        self.u = u;
+        let input_current = self.input_current + self.conductance.current(self.v);
+        self.input_current = 0.0;
+        self.conductance.clear();
+
+        if self.refractory_counter > 0.0 {
+            self.refractory_counter -= tau;
+            return false;
+        }
+
+        [self.v, self.u] = integrate(
+            self.a,
+            self.b,
+            [self.v, self.u],
+            input_current,
+            tau,
+            self.integration,
+        );
         if self.v >= 30.0 {
-            self.v = self.c;
-            self.u += self.d;
-            return true;
+            return self.force_spike();
         }
 
         false
     }
 
+    fn force_spike(&mut self) -> bool {
+        self.v = self.c;
+        self.u += self.d;
+        self.refractory_counter = self.refractory_period;
+        true
+    }
+
     fn get_membrane_potential(&self) -> f64 {
         self.v
     }
 
-    fn insert_current(&mut self, delta_v: f64) -> f64 {
-        self.v += delta_v * self.synapse_weight_multiplier;
+    /// The current is integrated during the next update, it is ignored during the refractory
+    /// period like all other input.
+    fn insert_current(&mut self, current: f64) -> f64 {
+        self.input_current += current;
         self.v
     }
+
+    fn add_conductance(&mut self, g: f64, reversal_potential: f64) {
+        if self.refractory_counter > 0.0 {
+            return;
+        }
+
+        self.conductance
+            .add(g * self.synapse_weight_multiplier, reversal_potential);
+    }
+
+    fn reset_state(&mut self) {
+        self.v = self.v_init;
+        self.u = self.u_init;
+        self.refractory_counter = 0.0;
+        self.input_current = 0.0;
+        self.conductance.clear();
+    }
+
+    fn parameter_names(&self) -> &'static [&'static str] {
+        &[
+            "a",
+            "b",
+            "c",
+            "d",
+            "synapse_weight_multiplier",
+            "refractory_period",
+        ]
+    }
+
+    fn get_parameter(&self, name: &str) -> Option<f64> {
+        match name {
+            "a" => Some(self.a),
+            "b" => Some(self.b),
+            "c" => Some(self.c),
+            "d" => Some(self.d),
+            "synapse_weight_multiplier" => Some(self.synapse_weight_multiplier),
+            "refractory_period" => Some(self.refractory_period),
+            _ => None,
+        }
+    }
+
+    fn set_parameter(&mut self, name: &str, value: f64) -> Result<(), UnknownParameter> {
+        let parameter = match name {
+            "a" => &mut self.a,
+            "b" => &mut self.b,
+            "c" => &mut self.c,
+            "d" => &mut self.d,
+            "synapse_weight_multiplier" => &mut self.synapse_weight_multiplier,
+            "refractory_period" => &mut self.refractory_period,
+            _ => return Err(UnknownParameter(name.to_string())),
+        };
+        *parameter = value;
+        Ok(())
+    }
 }
 
 impl NeuronVisualizer for IzhikevichNeuron {
@@ -47,3 +293,231 @@ impl NeuronVisualizer for IzhikevichNeuron {
         (self.v + 65.0) / 30.0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn izhikevich_neuron(refractory_period: f64) -> IzhikevichNeuron {
+        IzhikevichNeuron {
+            v: -70.0,
+            u: -14.0,
+            v_init: -70.0,
+            u_init: -14.0,
+            a: 0.02,
+            b: 0.2,
+            c: -65.0,
+            d: 8.0,
+            synapse_weight_multiplier: 80.0,
+            input_current: 0.0,
+            conductance: SynapticConductance::default(),
+            refractory_period,
+            refractory_counter: 0.0,
+            integration: IntegrationMethod::ForwardEuler,
+        }
+    }
+
+    fn bombard(neuron: &mut IzhikevichNeuron, duration: f64, tau: f64) -> Vec<f64> {
+        (0..(duration / tau) as usize)
+            .filter(|_| {
+                neuron.insert_current(500.0);
+                neuron.update(tau)
+            })
+            .map(|step| step as f64 * tau)
+            .collect()
+    }
+
+    /// Spike times in ms under a constant input current of 10.
+    fn constant_current_spikes(mut neuron: IzhikevichNeuron, duration: f64) -> Vec<f64> {
+        let tau = 0.025;
+        (0..(duration / tau) as usize)
+            .filter(|_| {
+                neuron.insert_current(10.0);
+                neuron.update(tau)
+            })
+            .map(|step| step as f64 * tau)
+            .collect()
+    }
+
+    fn intervals(spikes: &[f64]) -> Vec<f64> {
+        spikes.windows(2).map(|pair| pair[1] - pair[0]).collect()
+    }
+
+    #[test]
+    fn test_regular_spiking_adapts() {
+        let isi = intervals(&constant_current_spikes(
+            IzhikevichNeuron::regular_spiking(),
+            500.0,
+        ));
+
+        assert!(isi.len() > 5);
+        assert!(isi.last().unwrap() > &(isi[0] * 1.5));
+    }
+
+    #[test]
+    fn test_intrinsically_bursting_starts_with_burst() {
+        let isi = intervals(&constant_current_spikes(
+            IzhikevichNeuron::intrinsically_bursting(),
+            500.0,
+        ));
+
+        // the initial burst is much faster than the tonic spiking that follows
+        assert!(isi.len() > 5);
+        assert!(isi[0] * 3.0 < *isi.last().unwrap());
+    }
+
+    #[test]
+    fn test_chattering_fires_bursts() {
+        let isi = intervals(&constant_current_spikes(
+            IzhikevichNeuron::chattering(),
+            500.0,
+        ));
+
+        // spikes within a burst are closely spaced, bursts are separated by long pauses
+        let within_burst = isi.iter().filter(|isi| **isi < 10.0).count();
+        let between_bursts = isi.iter().filter(|isi| **isi > 20.0).count();
+        assert!(within_burst > between_bursts);
+        assert!(between_bursts > 3);
+    }
+
+    #[test]
+    fn test_fast_spiking_does_not_adapt() {
+        let fast_spikes = constant_current_spikes(IzhikevichNeuron::fast_spiking(), 500.0);
+        let regular_spikes = constant_current_spikes(IzhikevichNeuron::regular_spiking(), 500.0);
+        assert!(fast_spikes.len() > regular_spikes.len() * 2);
+
+        // the firing rate settles within a few spikes
+        let isi = intervals(&fast_spikes);
+        let last = *isi.last().unwrap();
+        assert!((last - isi[4]).abs() < last * 0.05);
+    }
+
+    #[test]
+    fn test_low_threshold_spiking_adapts() {
+        let low_threshold_spikes =
+            constant_current_spikes(IzhikevichNeuron::low_threshold_spiking(), 500.0);
+        let regular_spikes = constant_current_spikes(IzhikevichNeuron::regular_spiking(), 500.0);
+        assert!(low_threshold_spikes.len() > regular_spikes.len());
+
+        let isi = intervals(&low_threshold_spikes);
+        assert!(isi.last().unwrap() > &isi[0]);
+    }
+
+    #[test]
+    fn test_builder_overrides() {
+        let neuron = IzhikevichNeuron::builder(IzhikevichPreset::Chattering)
+            .synapse_weight_multiplier(80.0)
+            .v(-70.0)
+            .u(-14.0)
+            .build();
+
+        assert_eq!(neuron.c, -50.0);
+        assert_eq!(neuron.synapse_weight_multiplier, 80.0);
+        assert_eq!(neuron.v, -70.0);
+        assert_eq!(neuron.u, -14.0);
+    }
+
+    #[test]
+    fn test_refractory_period_limits_firing() {
+        let mut neuron = izhikevich_neuron(2.0);
+        let spikes = bombard(&mut neuron, 100.0, 0.025);
+
+        assert!(spikes.len() > 10);
+        for pair in spikes.windows(2) {
+            assert!(pair[1] - pair[0] >= 2.0 - 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_no_refractory_period_by_default() {
+        let mut neuron = izhikevich_neuron(0.0);
+        let spikes = bombard(&mut neuron, 100.0, 0.025);
+
+        assert!(spikes.windows(2).any(|pair| pair[1] - pair[0] < 2.0));
+    }
+
+    #[test]
+    fn test_reset_state_restores_initial_state() {
+        let mut neuron = IzhikevichNeuron::builder(IzhikevichPreset::RegularSpiking)
+            .refractory_period(2.0)
+            .v(-70.0)
+            .u(-14.0)
+            .build();
+        let first = bombard(&mut neuron, 50.0, 0.025);
+        assert_ne!(neuron.u, -14.0);
+
+        neuron.reset_state();
+        assert_eq!(neuron.v, -70.0);
+        assert_eq!(neuron.u, -14.0);
+        assert_eq!(bombard(&mut neuron, 50.0, 0.025), first);
+    }
+
+    #[test]
+    fn test_parameter_round_trip() {
+        let mut neuron = IzhikevichNeuron::regular_spiking();
+        for (i, name) in neuron.parameter_names().iter().enumerate() {
+            let value = 100.0 + i as f64;
+            assert!(neuron.get_parameter(name).is_some());
+            neuron.set_parameter(name, value).unwrap();
+            assert_eq!(neuron.get_parameter(name), Some(value));
+        }
+
+        assert_eq!(neuron.get_parameter("unknown"), None);
+        assert_eq!(
+            neuron.set_parameter("unknown", 1.0),
+            Err(UnknownParameter("unknown".to_string()))
+        );
+    }
+
+    /// The membrane potential after every step of `tau` ms over 50ms without input.
+    fn trajectory(integration: IntegrationMethod, tau: f64) -> Vec<f64> {
+        let mut neuron = IzhikevichNeuron::builder(IzhikevichPreset::RegularSpiking)
+            .integration(integration)
+            .build();
+        (0..(50.0 / tau).round() as usize)
+            .map(|_| {
+                assert!(!neuron.update(tau));
+                neuron.v
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_runge_kutta_is_more_accurate() {
+        let reference = trajectory(IntegrationMethod::RungeKutta4, 0.001);
+        let tau = 0.5;
+        let error = |integration| {
+            trajectory(integration, tau)
+                .iter()
+                .enumerate()
+                .map(|(step, v)| (v - reference[(step + 1) * 500 - 1]).abs())
+                .fold(0.0, f64::max)
+        };
+
+        let euler = error(IntegrationMethod::ForwardEuler);
+        let runge_kutta = error(IntegrationMethod::RungeKutta4);
+        assert!(euler > 0.1, "euler error {euler}");
+        assert!(
+            runge_kutta < euler / 100.0,
+            "runge-kutta error {runge_kutta}"
+        );
+    }
+
+    #[test]
+    fn test_injected_current_does_not_depend_on_time_step() {
+        let spike_count = |tau: f64| {
+            let mut neuron = IzhikevichNeuron::regular_spiking();
+            (0..(500.0 / tau) as usize)
+                .filter(|_| {
+                    neuron.insert_current(10.0);
+                    neuron.update(tau)
+                })
+                .count()
+        };
+
+        let coarse = spike_count(0.05);
+        let fine = spike_count(0.0125);
+        assert!(coarse > 5);
+        assert!((coarse as i64 - fine as i64).abs() <= 1);
+    }
+}
@@ -0,0 +1,145 @@
+use rand::RngCore;
+use silicon_core::sample_normal;
+
+use crate::{izhikevich::IzhikevichNeuron, leaky::LifNeuron};
+
+/// The standard deviation in mV of the jitter of a membrane potential parameter at a fraction of
+/// 1.0, see `ParameterJitter`.
+pub const VOLTAGE_JITTER: f64 = 10.0;
+
+/// Neurons whose parameters can be perturbed, identical neurons driven by the same input tend to
+/// synchronize, a little heterogeneity breaks that up.
+pub trait ParameterJitter {
+    /// Multiply every strictly positive parameter by `1 + fraction * N(0, 1)`, so across a
+    /// population it has a coefficient of variation of `fraction`. Membrane potentials are shifted
+    /// by `fraction * VOLTAGE_JITTER * N(0, 1)` mV instead, scaling a potential of -65 mV would
+    /// move it by several mV even for a small fraction. A fraction of 0.0 leaves the neuron
+    /// untouched and doesn't draw from `rng`.
+    fn randomize_parameters(&mut self, rng: &mut dyn RngCore, fraction: f64);
+}
+
+fn jitter(value: f64, rng: &mut dyn RngCore, fraction: f64) -> f64 {
+    value * sample_normal(rng, 1.0, fraction)
+}
+
+fn jitter_voltage(value: f64, rng: &mut dyn RngCore, fraction: f64) -> f64 {
+    sample_normal(rng, value, fraction * VOLTAGE_JITTER)
+}
+
+impl ParameterJitter for IzhikevichNeuron {
+    fn randomize_parameters(&mut self, rng: &mut dyn RngCore, fraction: f64) {
+        if fraction == 0.0 {
+            return;
+        }
+
+        self.a = jitter(self.a, rng, fraction);
+        self.b = jitter(self.b, rng, fraction);
+        self.c = jitter_voltage(self.c, rng, fraction);
+        self.d = jitter(self.d, rng, fraction);
+    }
+}
+
+impl ParameterJitter for LifNeuron {
+    fn randomize_parameters(&mut self, rng: &mut dyn RngCore, fraction: f64) {
+        if fraction == 0.0 {
+            return;
+        }
+
+        self.threshold_potential = jitter_voltage(self.threshold_potential, rng, fraction);
+        self.resistance = jitter(self.resistance, rng, fraction);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use silicon_core::SimulationRng;
+
+    use super::*;
+    use crate::izhikevich::IzhikevichPreset;
+
+    /// The mean and standard deviation of `values`.
+    fn mean_and_deviation(values: &[f64]) -> (f64, f64) {
+        let mean = values.iter().sum::<f64>() / values.len() as f64;
+        let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64;
+        (mean, variance.sqrt())
+    }
+
+    fn coefficient_of_variation(values: &[f64]) -> f64 {
+        let (mean, deviation) = mean_and_deviation(values);
+        deviation / mean.abs()
+    }
+
+    #[test]
+    fn test_coefficient_of_variation() {
+        let mut rng = SimulationRng::from_seed(3);
+        let neurons = (0..5000)
+            .map(|_| {
+                let mut neuron = IzhikevichNeuron::regular_spiking();
+                neuron.randomize_parameters(&mut rng, 0.1);
+                neuron
+            })
+            .collect::<Vec<_>>();
+
+        for parameter in [
+            |n: &IzhikevichNeuron| n.a,
+            |n: &IzhikevichNeuron| n.b,
+            |n: &IzhikevichNeuron| n.d,
+        ] {
+            let values = neurons.iter().map(parameter).collect::<Vec<_>>();
+            let cv = coefficient_of_variation(&values);
+            assert!(
+                (cv - 0.1).abs() < 0.005,
+                "coefficient of variation was {cv}"
+            );
+        }
+
+        // the reset potential is shifted by about 1mV, not by 10% of -65mV
+        let resets = neurons.iter().map(|n| n.c).collect::<Vec<_>>();
+        let (mean, deviation) = mean_and_deviation(&resets);
+        assert!((mean - -65.0).abs() < 0.05, "mean was {mean}");
+        assert!((deviation - 1.0).abs() < 0.05, "deviation was {deviation}");
+    }
+
+    #[test]
+    fn test_lif_threshold_stays_near_rest() {
+        let mut rng = SimulationRng::from_seed(3);
+        let neurons = (0..5000)
+            .map(|_| {
+                let mut neuron = LifNeuron::default();
+                neuron.randomize_parameters(&mut rng, 0.1);
+                neuron
+            })
+            .collect::<Vec<_>>();
+
+        let thresholds = neurons
+            .iter()
+            .map(|n| n.threshold_potential)
+            .collect::<Vec<_>>();
+        let (mean, deviation) = mean_and_deviation(&thresholds);
+        assert!((mean - -55.0).abs() < 0.05, "mean was {mean}");
+        assert!((deviation - 1.0).abs() < 0.05, "deviation was {deviation}");
+        // no neuron ends up with its threshold below its resting potential
+        assert!(neurons
+            .iter()
+            .all(|n| n.threshold_potential > n.resting_potential));
+
+        let resistances = neurons.iter().map(|n| n.resistance).collect::<Vec<_>>();
+        let cv = coefficient_of_variation(&resistances);
+        assert!(
+            (cv - 0.1).abs() < 0.005,
+            "coefficient of variation was {cv}"
+        );
+    }
+
+    #[test]
+    fn test_zero_jitter_is_identity() {
+        let mut rng = SimulationRng::from_seed(3);
+        let mut neuron = IzhikevichNeuron::builder(IzhikevichPreset::Chattering).build();
+        neuron.randomize_parameters(&mut rng, 0.0);
+
+        let expected = IzhikevichPreset::Chattering.parameters();
+        assert_eq!((neuron.a, neuron.b, neuron.c, neuron.d), expected);
+        // the random stream is left untouched, so everything drawn afterwards is unchanged too
+        assert_eq!(rng.next_u64(), SimulationRng::from_seed(3).next_u64());
+    }
+}
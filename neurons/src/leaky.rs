@@ -1,8 +1,25 @@
 use bevy::prelude::*;
+use silicon_core::{integrator::Rk4Integrator, SynapticConductance, UnknownParameter};
 
 use super::{Neuron, NeuronVisualizer};
 
-#[derive(Component, Debug, Reflect)]
+/// How the membrane equation is integrated over a time step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum IntegrationMethod {
+    /// Cheap, but overshoots the steady state once the time step approaches the membrane time constant.
+    #[default]
+    ForwardEuler,
+    /// Exact for input that is constant during the time step, stable for any time step. Only the
+    /// linear membrane of `LifNeuron` has this solution, other models fall back to forward Euler.
+    ExponentialEuler,
+    /// Fourth order Runge-Kutta, four evaluations of the derivatives per time step but far more
+    /// accurate than forward Euler at the same step.
+    RungeKutta4,
+}
+
+#[derive(Component, Debug, Clone, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LifNeuron {
     pub membrane_potential: f64,
     pub reset_potential: f64,
@@ -11,36 +28,134 @@ pub struct LifNeuron {
     pub resting_potential: f64,
     pub refactory_period: f64,
     pub refactory_counter: f64,
+    /// membrane time constant
+    pub tau_m: f64,
+    /// current accumulated through `insert_current` since the last update
+    pub input_current: f64,
+    pub conductance: SynapticConductance,
+    pub integration: IntegrationMethod,
+}
+
+impl Default for LifNeuron {
+    fn default() -> Self {
+        LifNeuron {
+            membrane_potential: -70.0,
+            reset_potential: -70.0,
+            threshold_potential: -55.0,
+            resistance: 10.0,
+            resting_potential: -70.0,
+            refactory_period: 2.0,
+            refactory_counter: 0.0,
+            tau_m: 10.0,
+            input_current: 0.0,
+            conductance: SynapticConductance::default(),
+            integration: IntegrationMethod::ForwardEuler,
+        }
+    }
 }
 
 impl Neuron for LifNeuron {
     fn update(&mut self, tau: f64) -> bool {
+        let current = self.input_current + self.conductance.current(self.membrane_potential);
+        self.input_current = 0.0;
+        self.conductance.clear();
+
         if self.refactory_counter > 0.0 {
             self.refactory_counter -= tau;
             return false;
         }
 
-        let delta_v = (self.resting_potential - self.membrane_potential) * tau;
+        let delta_v = match self.integration {
+            IntegrationMethod::ForwardEuler => {
+                (-(self.membrane_potential - self.resting_potential) + self.resistance * current)
+                    / self.tau_m
+                    * tau
+            }
+            IntegrationMethod::ExponentialEuler => {
+                // the potential relaxes towards its steady state for the current input
+                let steady_state = self.resting_potential + self.resistance * current;
+                (steady_state - self.membrane_potential) * (1.0 - (-tau / self.tau_m).exp())
+            }
+            IntegrationMethod::RungeKutta4 => {
+                let dv = |_, v: f64| {
+                    (-(v - self.resting_potential) + self.resistance * current) / self.tau_m
+                };
+                Rk4Integrator::integrate(dv, self.membrane_potential, tau) - self.membrane_potential
+            }
+        };
 
         self.membrane_potential += delta_v;
 
         if self.membrane_potential > self.threshold_potential {
-            self.membrane_potential = self.reset_potential;
-            self.refactory_counter = self.refactory_period;
-            return true;
+            return self.force_spike();
         }
 
         false
     }
 
+    fn force_spike(&mut self) -> bool {
+        self.membrane_potential = self.reset_potential;
+        self.refactory_counter = self.refactory_period;
+        true
+    }
+
     fn get_membrane_potential(&self) -> f64 {
         self.membrane_potential
     }
 
-    fn insert_current(&mut self, delta_v: f64) -> f64 {
-        self.membrane_potential += delta_v;
+    /// The current is integrated during the next update instead of being added to the membrane potential directly.
+    fn insert_current(&mut self, current: f64) -> f64 {
+        self.input_current += current;
         self.membrane_potential
     }
+
+    fn add_conductance(&mut self, g: f64, reversal_potential: f64) {
+        self.conductance.add(g, reversal_potential);
+    }
+
+    fn reset_state(&mut self) {
+        self.membrane_potential = self.resting_potential;
+        self.refactory_counter = 0.0;
+        self.input_current = 0.0;
+        self.conductance.clear();
+    }
+
+    fn parameter_names(&self) -> &'static [&'static str] {
+        &[
+            "reset_potential",
+            "threshold_potential",
+            "resistance",
+            "resting_potential",
+            "refactory_period",
+            "tau_m",
+        ]
+    }
+
+    fn get_parameter(&self, name: &str) -> Option<f64> {
+        match name {
+            "reset_potential" => Some(self.reset_potential),
+            "threshold_potential" => Some(self.threshold_potential),
+            "resistance" => Some(self.resistance),
+            "resting_potential" => Some(self.resting_potential),
+            "refactory_period" => Some(self.refactory_period),
+            "tau_m" => Some(self.tau_m),
+            _ => None,
+        }
+    }
+
+    fn set_parameter(&mut self, name: &str, value: f64) -> Result<(), UnknownParameter> {
+        let parameter = match name {
+            "reset_potential" => &mut self.reset_potential,
+            "threshold_potential" => &mut self.threshold_potential,
+            "resistance" => &mut self.resistance,
+            "resting_potential" => &mut self.resting_potential,
+            "refactory_period" => &mut self.refactory_period,
+            "tau_m" => &mut self.tau_m,
+            _ => return Err(UnknownParameter(name.to_string())),
+        };
+        *parameter = value;
+        Ok(())
+    }
 }
 
 impl NeuronVisualizer for LifNeuron {
@@ -62,3 +177,99 @@ impl NeuronVisualizer for LifNeuron {
 fn refit_to_range(n: f32, start1: f32, stop1: f32, start2: f32, stop2: f32) -> f32 {
     ((n - start1) / (stop1 - start1)) * (stop2 - start2) + start2
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spike_times(neuron: &mut LifNeuron, current: f64, duration: f64, tau: f64) -> Vec<f64> {
+        (0..(duration / tau) as usize)
+            .filter(|_| {
+                neuron.insert_current(current);
+                neuron.update(tau)
+            })
+            .map(|step| step as f64 * tau)
+            .collect()
+    }
+
+    #[test]
+    fn test_constant_current_above_rheobase_spikes_periodically() {
+        let mut neuron = LifNeuron::default();
+        let spikes = spike_times(&mut neuron, 2.0, 200.0, 0.025);
+
+        assert!(spikes.len() > 5);
+        let intervals = spikes
+            .windows(2)
+            .map(|pair| pair[1] - pair[0])
+            .collect::<Vec<_>>();
+        for interval in intervals.iter() {
+            assert!((interval - intervals[0]).abs() <= 0.025 + 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_current_below_rheobase_does_not_spike() {
+        let mut neuron = LifNeuron::default();
+        let spikes = spike_times(&mut neuron, 1.4, 200.0, 0.025);
+
+        assert!(spikes.is_empty());
+        assert!((neuron.membrane_potential - -56.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_reset_state_repeats_trial() {
+        let mut neuron = LifNeuron::default();
+        let first = spike_times(&mut neuron, 2.0, 31.0, 0.025);
+        // stop in the middle of the refractory period with pending input
+        neuron.insert_current(5.0);
+        assert!(neuron.refactory_counter > 0.0);
+
+        neuron.reset_state();
+        assert_eq!(neuron.membrane_potential, neuron.resting_potential);
+        assert_eq!(spike_times(&mut neuron, 2.0, 31.0, 0.025), first);
+    }
+
+    #[test]
+    fn test_parameter_round_trip() {
+        let mut neuron = LifNeuron::default();
+        for (i, name) in neuron.parameter_names().iter().enumerate() {
+            let value = 100.0 + i as f64;
+            assert!(neuron.get_parameter(name).is_some());
+            neuron.set_parameter(name, value).unwrap();
+            assert_eq!(neuron.get_parameter(name), Some(value));
+        }
+
+        assert_eq!(neuron.get_parameter("unknown"), None);
+        assert_eq!(
+            neuron.set_parameter("unknown", 1.0),
+            Err(UnknownParameter("unknown".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_exponential_euler_approaches_rest_monotonically() {
+        for tau in [0.025, 1.0, 10.0, 25.0, 100.0] {
+            let mut neuron = LifNeuron {
+                membrane_potential: -60.0,
+                integration: IntegrationMethod::ExponentialEuler,
+                ..Default::default()
+            };
+
+            let mut previous = neuron.membrane_potential;
+            for _ in 0..100 {
+                neuron.update(tau);
+                assert!(neuron.membrane_potential <= previous);
+                assert!(neuron.membrane_potential >= neuron.resting_potential);
+                previous = neuron.membrane_potential;
+            }
+        }
+
+        // forward euler overshoots the resting potential once tau exceeds tau_m
+        let mut neuron = LifNeuron {
+            membrane_potential: -60.0,
+            ..Default::default()
+        };
+        neuron.update(25.0);
+        assert!(neuron.membrane_potential < neuron.resting_potential);
+    }
+}
@@ -1,11 +1,30 @@
+use adex::AdExNeuron;
 use bevy::app::{App, Plugin};
 use bevy_trait_query::RegisterExt;
+use conductance_lif::CobaLifNeuron;
+use equation::EquationNeuron;
+use hodgkin_huxley::HodgkinHuxleyNeuron;
 use izhikevich::IzhikevichNeuron;
 use leaky::LifNeuron;
+use morris_lecar::MorrisLecarNeuron;
+use poisson::PoissonNeuron;
+#[cfg(feature = "serde")]
+use silicon_core::checkpoint::CheckpointExt;
 use silicon_core::{Neuron, NeuronVisualizer};
+use single_precision::{IzhikevichNeuronF32, LifNeuronF32};
+use two_compartment::TwoCompartmentNeuron;
 
+pub mod adex;
+pub mod conductance_lif;
+pub mod equation;
+pub mod hodgkin_huxley;
 pub mod izhikevich;
+pub mod jitter;
 pub mod leaky;
+pub mod morris_lecar;
+pub mod poisson;
+pub mod single_precision;
+pub mod two_compartment;
 
 pub struct NeuronPlugin;
 
@@ -13,9 +32,42 @@ impl Plugin for NeuronPlugin {
     fn build(&self, app: &mut App) {
         app.register_component_as::<dyn Neuron, LifNeuron>()
             .register_component_as::<dyn Neuron, IzhikevichNeuron>()
+            .register_component_as::<dyn Neuron, HodgkinHuxleyNeuron>()
+            .register_component_as::<dyn Neuron, AdExNeuron>()
+            .register_component_as::<dyn Neuron, PoissonNeuron>()
+            .register_component_as::<dyn Neuron, MorrisLecarNeuron>()
+            .register_component_as::<dyn Neuron, CobaLifNeuron>()
+            .register_component_as::<dyn Neuron, EquationNeuron>()
+            .register_component_as::<dyn Neuron, TwoCompartmentNeuron>()
+            .register_component_as::<dyn Neuron, LifNeuronF32>()
+            .register_component_as::<dyn Neuron, IzhikevichNeuronF32>()
             .register_component_as::<dyn NeuronVisualizer, LifNeuron>()
             .register_component_as::<dyn NeuronVisualizer, IzhikevichNeuron>()
+            .register_component_as::<dyn NeuronVisualizer, HodgkinHuxleyNeuron>()
+            .register_component_as::<dyn NeuronVisualizer, AdExNeuron>()
+            .register_component_as::<dyn NeuronVisualizer, PoissonNeuron>()
+            .register_component_as::<dyn NeuronVisualizer, MorrisLecarNeuron>()
+            .register_component_as::<dyn NeuronVisualizer, CobaLifNeuron>()
+            .register_component_as::<dyn NeuronVisualizer, EquationNeuron>()
+            .register_component_as::<dyn NeuronVisualizer, TwoCompartmentNeuron>()
+            .register_component_as::<dyn NeuronVisualizer, LifNeuronF32>()
+            .register_component_as::<dyn NeuronVisualizer, IzhikevichNeuronF32>()
             .register_type::<IzhikevichNeuron>()
-            .register_type::<LifNeuron>();
+            .register_type::<LifNeuron>()
+            .register_type::<HodgkinHuxleyNeuron>()
+            .register_type::<AdExNeuron>()
+            .register_type::<PoissonNeuron>()
+            .register_type::<MorrisLecarNeuron>()
+            .register_type::<CobaLifNeuron>()
+            .register_type::<EquationNeuron>()
+            .register_type::<TwoCompartmentNeuron>()
+            .register_type::<LifNeuronF32>()
+            .register_type::<IzhikevichNeuronF32>();
+
+        #[cfg(feature = "serde")]
+        app.register_checkpoint::<LifNeuron>()
+            .register_checkpoint::<IzhikevichNeuron>()
+            .register_checkpoint::<LifNeuronF32>()
+            .register_checkpoint::<IzhikevichNeuronF32>();
     }
 }
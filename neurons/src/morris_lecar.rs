@@ -0,0 +1,210 @@
+use bevy::{prelude::Component, reflect::Reflect};
+use silicon_core::SynapticConductance;
+
+use super::{Neuron, NeuronVisualizer};
+
+/// Morris-Lecar neuron with a calcium and a potassium current.
+/// Time is in ms, voltages in mV, conductances in mS/cm² and currents in µA/cm².
+#[derive(Component, Debug, Reflect)]
+pub struct MorrisLecarNeuron {
+    pub v: f64,
+    /// potassium recovery variable
+    pub w: f64,
+    pub c_m: f64,
+    pub g_ca: f64,
+    pub g_k: f64,
+    pub g_l: f64,
+    pub v1: f64,
+    pub v2: f64,
+    pub v3: f64,
+    pub v4: f64,
+    pub phi: f64,
+    pub e_ca: f64,
+    pub e_k: f64,
+    pub e_l: f64,
+    /// constant external current
+    pub i_ext: f64,
+    /// current accumulated through `insert_current` since the last update
+    pub input_current: f64,
+    /// A spike is reported when `v` crosses this value on the rising edge.
+    pub spike_threshold: f64,
+    pub above_threshold: bool,
+    pub conductance: SynapticConductance,
+}
+
+impl MorrisLecarNeuron {
+    /// Parameters with class I excitability, firing starts at an arbitrarily low frequency (saddle-node on invariant circle).
+    pub fn type_one() -> Self {
+        MorrisLecarNeuron {
+            g_ca: 4.0,
+            v3: 12.0,
+            v4: 17.4,
+            phi: 1.0 / 15.0,
+            ..Default::default()
+        }
+    }
+
+    /// Parameters with class II excitability, firing starts at a non-zero frequency (Hopf bifurcation).
+    pub fn type_two() -> Self {
+        MorrisLecarNeuron::default()
+    }
+
+    fn m_inf(&self, v: f64) -> f64 {
+        0.5 * (1.0 + ((v - self.v1) / self.v2).tanh())
+    }
+
+    fn w_inf(&self, v: f64) -> f64 {
+        0.5 * (1.0 + ((v - self.v3) / self.v4).tanh())
+    }
+
+    fn tau_w(&self, v: f64) -> f64 {
+        1.0 / ((v - self.v3) / (2.0 * self.v4)).cosh()
+    }
+}
+
+impl Default for MorrisLecarNeuron {
+    fn default() -> Self {
+        MorrisLecarNeuron {
+            v: -60.0,
+            w: 0.0,
+            c_m: 20.0,
+            g_ca: 4.4,
+            g_k: 8.0,
+            g_l: 2.0,
+            v1: -1.2,
+            v2: 18.0,
+            v3: 2.0,
+            v4: 30.0,
+            phi: 0.04,
+            e_ca: 120.0,
+            e_k: -84.0,
+            e_l: -60.0,
+            i_ext: 0.0,
+            input_current: 0.0,
+            spike_threshold: 0.0,
+            above_threshold: false,
+            conductance: SynapticConductance::default(),
+        }
+    }
+}
+
+impl Neuron for MorrisLecarNeuron {
+    fn update(&mut self, tau: f64) -> bool {
+        let input_current = self.input_current + self.conductance.current(self.v);
+        self.input_current = 0.0;
+        self.conductance.clear();
+
+        let v = self.v;
+        let i_ca = self.g_ca * self.m_inf(v) * (v - self.e_ca);
+        let i_k = self.g_k * self.w * (v - self.e_k);
+        let i_l = self.g_l * (v - self.e_l);
+
+        self.v += tau * (self.i_ext + input_current - i_ca - i_k - i_l) / self.c_m;
+        self.w += tau * self.phi * (self.w_inf(v) - self.w) / self.tau_w(v);
+
+        let above_threshold = self.v >= self.spike_threshold;
+        let fired = above_threshold && !self.above_threshold;
+        self.above_threshold = above_threshold;
+
+        fired
+    }
+
+    fn get_membrane_potential(&self) -> f64 {
+        self.v
+    }
+
+    fn insert_current(&mut self, current: f64) -> f64 {
+        self.input_current += current;
+        self.v
+    }
+
+    fn add_conductance(&mut self, g: f64, reversal_potential: f64) {
+        self.conductance.add(g, reversal_potential);
+    }
+
+    fn reset_state(&mut self) {
+        self.v = self.e_l;
+        self.w = 0.0;
+        self.above_threshold = false;
+        self.input_current = 0.0;
+        self.conductance.clear();
+    }
+}
+
+impl NeuronVisualizer for MorrisLecarNeuron {
+    fn activation_percent(&self) -> f64 {
+        ((self.v - self.e_l) / (self.spike_threshold - self.e_l)).clamp(0.0, 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Steady state firing rate in Hz, the first second is skipped to let transients settle.
+    fn firing_rate(mut neuron: MorrisLecarNeuron) -> f64 {
+        let tau = 0.025;
+        let spikes = (0..(4000.0 / tau) as usize)
+            .filter(|_| neuron.update(tau))
+            .map(|step| step as f64 * tau)
+            .filter(|time| *time > 1000.0)
+            .collect::<Vec<_>>();
+
+        if spikes.len() < 2 {
+            return 0.0;
+        }
+
+        1000.0 * (spikes.len() - 1) as f64 / (spikes.last().unwrap() - spikes[0])
+    }
+
+    /// Scans the applied current upwards and returns the firing rate at the onset of firing.
+    fn onset_rate(create: fn() -> MorrisLecarNeuron, from: f64, to: f64) -> f64 {
+        let mut i_ext = from;
+        while i_ext <= to {
+            let rate = firing_rate(MorrisLecarNeuron { i_ext, ..create() });
+            if rate > 0.0 {
+                return rate;
+            }
+
+            i_ext += 0.25;
+        }
+
+        panic!("neuron did not start firing between {from} and {to}");
+    }
+
+    #[test]
+    fn test_rest_without_current() {
+        assert_eq!(firing_rate(MorrisLecarNeuron::type_one()), 0.0);
+        assert_eq!(firing_rate(MorrisLecarNeuron::type_two()), 0.0);
+    }
+
+    #[test]
+    fn test_type_one_excitability() {
+        // class I neurons can fire at arbitrarily low rates close to the threshold current
+        let rate = onset_rate(MorrisLecarNeuron::type_one, 35.0, 45.0);
+        assert!(rate < 3.0, "onset rate was {rate}Hz");
+    }
+
+    #[test]
+    fn test_type_two_excitability() {
+        // class II neurons jump from rest to a non-zero firing rate
+        let rate = onset_rate(MorrisLecarNeuron::type_two, 85.0, 95.0);
+        assert!(rate > 7.0, "onset rate was {rate}Hz");
+    }
+
+    #[test]
+    fn test_injected_current_is_integrated() {
+        let mut external = MorrisLecarNeuron {
+            i_ext: 100.0,
+            ..MorrisLecarNeuron::type_one()
+        };
+        let mut injected = MorrisLecarNeuron::type_one();
+
+        // injecting the current every tick is the same as a constant external current
+        for _ in 0..40000 {
+            injected.insert_current(100.0);
+            assert_eq!(external.update(0.025), injected.update(0.025));
+            assert!((external.v - injected.v).abs() < 1e-9);
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use bevy::{prelude::Component, reflect::Reflect};
+use rand::{Rng, RngCore};
+use silicon_core::SimulationRng;
+
+use super::{Neuron, NeuronVisualizer};
+
+/// Spike generator that fires as a Poisson process with the given rate, useful as stochastic input.
+/// Every update fires with probability `rate_hz * tau / 1000.0`, `tau` is in ms like the rest of
+/// the simulation.
+#[derive(Component, Debug, Reflect)]
+pub struct PoissonNeuron {
+    pub rate_hz: f64,
+    pub fired: bool,
+    #[reflect(ignore)]
+    pub rng: SimulationRng,
+}
+
+impl PoissonNeuron {
+    /// The neuron gets a generator of its own, seeded from the shared `SimulationRng` so seeded
+    /// runs stay reproducible.
+    pub fn new(rate_hz: f64, rng: &mut SimulationRng) -> Self {
+        PoissonNeuron::from_seed(rate_hz, rng.next_u64())
+    }
+
+    pub fn from_seed(rate_hz: f64, seed: u64) -> Self {
+        PoissonNeuron {
+            rate_hz,
+            fired: false,
+            rng: SimulationRng::from_seed(seed),
+        }
+    }
+
+    pub fn set_rate(&mut self, rate_hz: f64) {
+        self.rate_hz = rate_hz.max(0.0);
+    }
+}
+
+impl Neuron for PoissonNeuron {
+    fn update(&mut self, tau: f64) -> bool {
+        // the rate is per second, the time step in ms
+        let probability = (self.rate_hz * tau / 1000.0).clamp(0.0, 1.0);
+        self.fired = self.rng.gen_bool(probability);
+        self.fired
+    }
+
+    /// There is no membrane to speak of, the instantaneous firing rate is reported instead.
+    fn get_membrane_potential(&self) -> f64 {
+        self.rate_hz
+    }
+
+    fn insert_current(&mut self, _current: f64) -> f64 {
+        self.rate_hz
+    }
+
+    fn reset_state(&mut self) {
+        self.fired = false;
+    }
+}
+
+impl NeuronVisualizer for PoissonNeuron {
+    fn activation_percent(&self) -> f64 {
+        if self.fired {
+            1.0
+        } else {
+            0.0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mean_interval(neuron: &mut PoissonNeuron, steps: usize, tau: f64) -> f64 {
+        let spikes = (0..steps)
+            .filter(|_| neuron.update(tau))
+            .map(|step| step as f64 * tau)
+            .collect::<Vec<_>>();
+
+        (spikes.last().unwrap() - spikes.first().unwrap()) / (spikes.len() - 1) as f64
+    }
+
+    #[test]
+    fn test_interval_matches_rate() {
+        let mut neuron = PoissonNeuron::from_seed(50.0, 7);
+        // 100 s in steps of 0.1 ms
+        let interval = mean_interval(&mut neuron, 1_000_000, 0.1);
+
+        // 50 Hz is a spike every 20 ms
+        assert!(
+            (interval - 20.0).abs() < 20.0 * 0.05,
+            "mean interval {interval} does not match the rate"
+        );
+    }
+
+    #[test]
+    fn test_set_rate() {
+        let mut neuron = PoissonNeuron::from_seed(10.0, 7);
+        neuron.set_rate(200.0);
+        let interval = mean_interval(&mut neuron, 1_000_000, 0.025);
+
+        assert!((interval - 5.0).abs() < 5.0 * 0.05);
+
+        neuron.set_rate(-5.0);
+        assert_eq!(neuron.rate_hz, 0.0);
+        assert!(!(0..1000).any(|_| neuron.update(0.025)));
+    }
+
+    #[test]
+    fn test_default_time_step_does_not_saturate() {
+        // a 50 Hz neuron fires on about one in 800 ticks of 0.025 ms, not on every tick
+        let mut neuron = PoissonNeuron::from_seed(50.0, 7);
+        let spikes = (0..40_000).filter(|_| neuron.update(0.025)).count();
+        assert!((30..=70).contains(&spikes), "{spikes} spikes in 1 s");
+    }
+
+    #[test]
+    fn test_seeded_from_simulation_rng() {
+        let spikes = |seed: u64| {
+            let mut rng = SimulationRng::from_seed(seed);
+            let mut neuron = PoissonNeuron::new(200.0, &mut rng);
+            (0..40_000)
+                .filter(|_| neuron.update(0.025))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(spikes(3), spikes(3));
+        assert_ne!(spikes(3), spikes(4));
+    }
+}
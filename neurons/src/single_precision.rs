@@ -0,0 +1,410 @@
+//! Single precision versions of the neurons used for large networks.
+//!
+//! At the usual time step of 0.025ms the extra precision of `f64` doesn't change the spike times
+//! in any meaningful way, but it doubles the memory the update loop has to stream through. These
+//! neurons store their state as `f32` and behave like their `f64` counterparts otherwise. The
+//! `Neuron` trait still works with `f64`, values are converted at the boundary. Run
+//! `cargo bench -p simulator --bench precision` to compare the update throughput of both.
+
+use bevy::{prelude::Component, reflect::Reflect};
+use silicon_core::UnknownParameter;
+
+use crate::{
+    izhikevich::{self, IzhikevichNeuron},
+    leaky::{IntegrationMethod, LifNeuron},
+};
+
+use super::{Neuron, NeuronVisualizer};
+
+/// The precision builders create neurons with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+pub enum Precision {
+    /// `f64` state, the default.
+    #[default]
+    Double,
+    /// `f32` state, see `LifNeuronF32` and `IzhikevichNeuronF32`.
+    Single,
+}
+
+/// The synaptic conductances of a single time step, like `SynapticConductance` but in `f32`.
+#[derive(Debug, Default, Clone, Copy, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ConductanceF32 {
+    pub g: f32,
+    pub g_reversal: f32,
+}
+
+impl ConductanceF32 {
+    pub fn add(&mut self, g: f64, reversal_potential: f64) {
+        self.g += g as f32;
+        self.g_reversal += (g * reversal_potential) as f32;
+    }
+
+    pub fn current(&self, v: f32) -> f32 {
+        self.g_reversal - self.g * v
+    }
+
+    pub fn clear(&mut self) {
+        *self = ConductanceF32::default();
+    }
+}
+
+/// `LifNeuron` with `f32` state, always integrated with forward Euler.
+#[derive(Component, Debug, Clone, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LifNeuronF32 {
+    pub membrane_potential: f32,
+    pub reset_potential: f32,
+    pub threshold_potential: f32,
+    pub resistance: f32,
+    pub resting_potential: f32,
+    pub refactory_period: f32,
+    pub refactory_counter: f32,
+    /// membrane time constant
+    pub tau_m: f32,
+    /// current accumulated through `insert_current` since the last update
+    pub input_current: f32,
+    pub conductance: ConductanceF32,
+}
+
+impl From<&LifNeuron> for LifNeuronF32 {
+    fn from(neuron: &LifNeuron) -> Self {
+        LifNeuronF32 {
+            membrane_potential: neuron.membrane_potential as f32,
+            reset_potential: neuron.reset_potential as f32,
+            threshold_potential: neuron.threshold_potential as f32,
+            resistance: neuron.resistance as f32,
+            resting_potential: neuron.resting_potential as f32,
+            refactory_period: neuron.refactory_period as f32,
+            refactory_counter: neuron.refactory_counter as f32,
+            tau_m: neuron.tau_m as f32,
+            input_current: neuron.input_current as f32,
+            conductance: ConductanceF32::default(),
+        }
+    }
+}
+
+impl Neuron for LifNeuronF32 {
+    fn update(&mut self, tau: f64) -> bool {
+        let tau = tau as f32;
+        let current = self.input_current + self.conductance.current(self.membrane_potential);
+        self.input_current = 0.0;
+        self.conductance.clear();
+
+        if self.refactory_counter > 0.0 {
+            self.refactory_counter -= tau;
+            return false;
+        }
+
+        self.membrane_potential += (-(self.membrane_potential - self.resting_potential)
+            + self.resistance * current)
+            / self.tau_m
+            * tau;
+
+        if self.membrane_potential > self.threshold_potential {
+            return self.force_spike();
+        }
+
+        false
+    }
+
+    fn force_spike(&mut self) -> bool {
+        self.membrane_potential = self.reset_potential;
+        self.refactory_counter = self.refactory_period;
+        true
+    }
+
+    fn get_membrane_potential(&self) -> f64 {
+        self.membrane_potential as f64
+    }
+
+    /// The current is integrated during the next update instead of being added to the membrane potential directly.
+    fn insert_current(&mut self, current: f64) -> f64 {
+        self.input_current += current as f32;
+        self.membrane_potential as f64
+    }
+
+    fn add_conductance(&mut self, g: f64, reversal_potential: f64) {
+        self.conductance.add(g, reversal_potential);
+    }
+
+    fn reset_state(&mut self) {
+        self.membrane_potential = self.resting_potential;
+        self.refactory_counter = 0.0;
+        self.input_current = 0.0;
+        self.conductance.clear();
+    }
+
+    fn parameter_names(&self) -> &'static [&'static str] {
+        &[
+            "reset_potential",
+            "threshold_potential",
+            "resistance",
+            "resting_potential",
+            "refactory_period",
+            "tau_m",
+        ]
+    }
+
+    fn get_parameter(&self, name: &str) -> Option<f64> {
+        match name {
+            "reset_potential" => Some(self.reset_potential as f64),
+            "threshold_potential" => Some(self.threshold_potential as f64),
+            "resistance" => Some(self.resistance as f64),
+            "resting_potential" => Some(self.resting_potential as f64),
+            "refactory_period" => Some(self.refactory_period as f64),
+            "tau_m" => Some(self.tau_m as f64),
+            _ => None,
+        }
+    }
+
+    fn set_parameter(&mut self, name: &str, value: f64) -> Result<(), UnknownParameter> {
+        let parameter = match name {
+            "reset_potential" => &mut self.reset_potential,
+            "threshold_potential" => &mut self.threshold_potential,
+            "resistance" => &mut self.resistance,
+            "resting_potential" => &mut self.resting_potential,
+            "refactory_period" => &mut self.refactory_period,
+            "tau_m" => &mut self.tau_m,
+            _ => return Err(UnknownParameter(name.to_string())),
+        };
+        *parameter = value as f32;
+        Ok(())
+    }
+}
+
+impl NeuronVisualizer for LifNeuronF32 {
+    fn activation_percent(&self) -> f64 {
+        if self.membrane_potential < self.resting_potential {
+            return 1.0;
+        }
+
+        ((self.membrane_potential - self.resting_potential)
+            / (self.threshold_potential - self.resting_potential)) as f64
+    }
+}
+
+/// `IzhikevichNeuron` with `f32` state. The dynamics are shared with the `f64` neuron, the state
+/// is converted for each step.
+#[derive(Component, Debug, Clone, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IzhikevichNeuronF32 {
+    pub a: f32,
+    pub b: f32,
+    pub c: f32,
+    pub d: f32,
+    pub v: f32,
+    pub u: f32,
+    /// the membrane potential restored by `reset_state`
+    pub v_init: f32,
+    /// the recovery variable restored by `reset_state`
+    pub u_init: f32,
+    /// scales the synaptic conductances the neuron receives
+    pub synapse_weight_multiplier: f32,
+    /// current accumulated through `insert_current` since the last update
+    pub input_current: f32,
+    pub conductance: ConductanceF32,
+    /// time after a spike during which the neuron ignores its dynamics and all input, 0.0 disables it
+    pub refractory_period: f32,
+    pub refractory_counter: f32,
+    /// how `v` and `u` are integrated, see `IzhikevichNeuron::integration`
+    pub integration: IntegrationMethod,
+}
+
+impl From<&IzhikevichNeuron> for IzhikevichNeuronF32 {
+    fn from(neuron: &IzhikevichNeuron) -> Self {
+        IzhikevichNeuronF32 {
+            a: neuron.a as f32,
+            b: neuron.b as f32,
+            c: neuron.c as f32,
+            d: neuron.d as f32,
+            v: neuron.v as f32,
+            u: neuron.u as f32,
+            v_init: neuron.v_init as f32,
+            u_init: neuron.u_init as f32,
+            synapse_weight_multiplier: neuron.synapse_weight_multiplier as f32,
+            input_current: neuron.input_current as f32,
+            conductance: ConductanceF32::default(),
+            refractory_period: neuron.refractory_period as f32,
+            refractory_counter: neuron.refractory_counter as f32,
+            integration: neuron.integration,
+        }
+    }
+}
+
+impl Neuron for IzhikevichNeuronF32 {
+    fn update(&mut self, tau: f64) -> bool {
+        let tau = tau as f32;
+        let input_current = self.input_current + self.conductance.current(self.v);
+        self.input_current = 0.0;
+        self.conductance.clear();
+
+        if self.refractory_counter > 0.0 {
+            self.refractory_counter -= tau;
+            return false;
+        }
+
+        let [v, u] = izhikevich::integrate(
+            self.a as f64,
+            self.b as f64,
+            [self.v as f64, self.u as f64],
+            input_current as f64,
+            tau as f64,
+            self.integration,
+        );
+        self.v = v as f32;
+        self.u = u as f32;
+        if self.v >= 30.0 {
+            return self.force_spike();
+        }
+
+        false
+    }
+
+    fn force_spike(&mut self) -> bool {
+        self.v = self.c;
+        self.u += self.d;
+        self.refractory_counter = self.refractory_period;
+        true
+    }
+
+    fn get_membrane_potential(&self) -> f64 {
+        self.v as f64
+    }
+
+    fn insert_current(&mut self, current: f64) -> f64 {
+        self.input_current += current as f32;
+        self.v as f64
+    }
+
+    fn add_conductance(&mut self, g: f64, reversal_potential: f64) {
+        if self.refractory_counter > 0.0 {
+            return;
+        }
+
+        self.conductance.add(
+            g * self.synapse_weight_multiplier as f64,
+            reversal_potential,
+        );
+    }
+
+    fn reset_state(&mut self) {
+        self.v = self.v_init;
+        self.u = self.u_init;
+        self.refractory_counter = 0.0;
+        self.input_current = 0.0;
+        self.conductance.clear();
+    }
+
+    fn parameter_names(&self) -> &'static [&'static str] {
+        &[
+            "a",
+            "b",
+            "c",
+            "d",
+            "synapse_weight_multiplier",
+            "refractory_period",
+        ]
+    }
+
+    fn get_parameter(&self, name: &str) -> Option<f64> {
+        match name {
+            "a" => Some(self.a as f64),
+            "b" => Some(self.b as f64),
+            "c" => Some(self.c as f64),
+            "d" => Some(self.d as f64),
+            "synapse_weight_multiplier" => Some(self.synapse_weight_multiplier as f64),
+            "refractory_period" => Some(self.refractory_period as f64),
+            _ => None,
+        }
+    }
+
+    fn set_parameter(&mut self, name: &str, value: f64) -> Result<(), UnknownParameter> {
+        let parameter = match name {
+            "a" => &mut self.a,
+            "b" => &mut self.b,
+            "c" => &mut self.c,
+            "d" => &mut self.d,
+            "synapse_weight_multiplier" => &mut self.synapse_weight_multiplier,
+            "refractory_period" => &mut self.refractory_period,
+            _ => return Err(UnknownParameter(name.to_string())),
+        };
+        *parameter = value as f32;
+        Ok(())
+    }
+}
+
+impl NeuronVisualizer for IzhikevichNeuronF32 {
+    fn activation_percent(&self) -> f64 {
+        if self.v < -65.0 {
+            return 1.0;
+        }
+
+        ((self.v + 65.0) / 30.0) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn spike_times(neuron: &mut impl Neuron, current: f64, duration: f64) -> Vec<f64> {
+        let tau = 0.025;
+        (0..(duration / tau) as usize)
+            .filter(|_| {
+                neuron.insert_current(current);
+                neuron.update(tau)
+            })
+            .map(|step| step as f64 * tau)
+            .collect()
+    }
+
+    fn assert_spikes_match(double: &[f64], single: &[f64]) {
+        assert!(!double.is_empty());
+        assert_eq!(double.len(), single.len());
+        for (double, single) in double.iter().zip(single) {
+            // a tick of difference where the membrane crosses the threshold just in between
+            assert!(
+                (double - single).abs() <= 0.025 + 1e-9,
+                "{double} != {single}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_lif_matches_double_precision() {
+        let mut neuron = LifNeuron::default();
+        let mut single = LifNeuronF32::from(&neuron);
+
+        assert_spikes_match(
+            &spike_times(&mut neuron, 2.0, 200.0),
+            &spike_times(&mut single, 2.0, 200.0),
+        );
+    }
+
+    #[test]
+    fn test_izhikevich_matches_double_precision() {
+        let mut neuron = IzhikevichNeuron::regular_spiking();
+        let mut single = IzhikevichNeuronF32::from(&neuron);
+
+        assert_spikes_match(
+            &spike_times(&mut neuron, 10.0, 200.0),
+            &spike_times(&mut single, 10.0, 200.0),
+        );
+    }
+
+    #[test]
+    fn test_izhikevich_keeps_integration_method() {
+        let mut neuron = IzhikevichNeuron::builder(izhikevich::IzhikevichPreset::RegularSpiking)
+            .integration(IntegrationMethod::RungeKutta4)
+            .build();
+        let mut single = IzhikevichNeuronF32::from(&neuron);
+        assert_eq!(single.integration, IntegrationMethod::RungeKutta4);
+
+        assert_spikes_match(
+            &spike_times(&mut neuron, 10.0, 200.0),
+            &spike_times(&mut single, 10.0, 200.0),
+        );
+    }
+}
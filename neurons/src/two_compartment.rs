@@ -0,0 +1,171 @@
+use bevy::{prelude::Component, reflect::Reflect};
+use silicon_core::SynapticConductance;
+
+use super::{Neuron, NeuronVisualizer};
+
+/// Leaky integrate-and-fire neuron with a passive dendrite coupled to the soma by the conductance
+/// `g_c`. Spikes are detected and reset on the soma, input that arrives on the dendrite reaches
+/// the soma low-pass filtered by the dendritic membrane.
+#[derive(Component, Debug, Reflect)]
+pub struct TwoCompartmentNeuron {
+    /// somatic membrane potential
+    pub v_s: f64,
+    /// dendritic membrane potential
+    pub v_d: f64,
+    pub e_l: f64,
+    /// somatic membrane time constant
+    pub tau_s: f64,
+    /// dendritic membrane time constant
+    pub tau_d: f64,
+    /// coupling conductance between the compartments relative to the leak conductance
+    pub g_c: f64,
+    pub threshold_potential: f64,
+    pub reset_potential: f64,
+    pub refractory_period: f64,
+    pub refractory_counter: f64,
+    /// current accumulated through `insert_current` since the last update, injected into the soma
+    pub input_current: f64,
+    pub somatic_conductance: SynapticConductance,
+    pub dendritic_conductance: SynapticConductance,
+}
+
+impl Default for TwoCompartmentNeuron {
+    fn default() -> Self {
+        TwoCompartmentNeuron {
+            v_s: -70.0,
+            v_d: -70.0,
+            e_l: -70.0,
+            tau_s: 10.0,
+            tau_d: 30.0,
+            g_c: 1.0,
+            threshold_potential: -50.0,
+            reset_potential: -70.0,
+            refractory_period: 2.0,
+            refractory_counter: 0.0,
+            input_current: 0.0,
+            somatic_conductance: SynapticConductance::default(),
+            dendritic_conductance: SynapticConductance::default(),
+        }
+    }
+}
+
+impl Neuron for TwoCompartmentNeuron {
+    fn update(&mut self, tau: f64) -> bool {
+        let somatic_current = self.input_current + self.somatic_conductance.current(self.v_s);
+        let dendritic_current = self.dendritic_conductance.current(self.v_d);
+        self.input_current = 0.0;
+        self.somatic_conductance.clear();
+        self.dendritic_conductance.clear();
+
+        let coupling = self.g_c * (self.v_d - self.v_s);
+        self.v_d += tau * (-(self.v_d - self.e_l) - coupling + dendritic_current) / self.tau_d;
+
+        if self.refractory_counter > 0.0 {
+            self.refractory_counter -= tau;
+            return false;
+        }
+
+        self.v_s += tau * (-(self.v_s - self.e_l) + coupling + somatic_current) / self.tau_s;
+
+        if self.v_s >= self.threshold_potential {
+            self.v_s = self.reset_potential;
+            self.refractory_counter = self.refractory_period;
+            return true;
+        }
+
+        false
+    }
+
+    fn get_membrane_potential(&self) -> f64 {
+        self.v_s
+    }
+
+    /// The current is injected into the soma during the next update.
+    fn insert_current(&mut self, current: f64) -> f64 {
+        self.input_current += current;
+        self.v_s
+    }
+
+    fn add_conductance(&mut self, g: f64, reversal_potential: f64) {
+        self.somatic_conductance.add(g, reversal_potential);
+    }
+
+    fn add_dendritic_conductance(&mut self, g: f64, reversal_potential: f64) {
+        self.dendritic_conductance.add(g, reversal_potential);
+    }
+
+    fn reset_state(&mut self) {
+        self.v_s = self.e_l;
+        self.v_d = self.e_l;
+        self.refractory_counter = 0.0;
+        self.input_current = 0.0;
+        self.somatic_conductance.clear();
+        self.dendritic_conductance.clear();
+    }
+}
+
+impl NeuronVisualizer for TwoCompartmentNeuron {
+    fn activation_percent(&self) -> f64 {
+        ((self.v_s - self.e_l) / (self.threshold_potential - self.e_l)).clamp(0.0, 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use silicon_core::EXCITATORY_REVERSAL_POTENTIAL;
+
+    use super::*;
+
+    /// Somatic response to a single pulse of excitatory conductance, returns the peak
+    /// depolarization and the time of the peak.
+    fn somatic_response(dendritic: bool) -> (f64, f64) {
+        let mut neuron = TwoCompartmentNeuron {
+            threshold_potential: 0.0,
+            ..Default::default()
+        };
+
+        if dendritic {
+            neuron.add_dendritic_conductance(20.0, EXCITATORY_REVERSAL_POTENTIAL);
+        } else {
+            neuron.add_conductance(20.0, EXCITATORY_REVERSAL_POTENTIAL);
+        }
+
+        let tau = 0.025;
+        (1..=(100.0 / tau) as usize)
+            .map(|step| {
+                neuron.update(tau);
+                (neuron.v_s - neuron.e_l, step as f64 * tau)
+            })
+            .fold((f64::MIN, 0.0), |peak, sample| {
+                if sample.0 > peak.0 {
+                    sample
+                } else {
+                    peak
+                }
+            })
+    }
+
+    #[test]
+    fn test_dendritic_input_is_low_pass_filtered() {
+        let (somatic_peak, somatic_peak_time) = somatic_response(false);
+        let (dendritic_peak, dendritic_peak_time) = somatic_response(true);
+
+        assert!(dendritic_peak > 0.0);
+        assert!(dendritic_peak < somatic_peak);
+        assert!(dendritic_peak_time > somatic_peak_time);
+    }
+
+    #[test]
+    fn test_somatic_input_causes_spikes() {
+        let mut neuron = TwoCompartmentNeuron::default();
+
+        let spikes = (0..4000)
+            .filter(|_| {
+                neuron.insert_current(40.0);
+                neuron.update(0.025)
+            })
+            .count();
+
+        assert!(spikes > 0);
+    }
+}
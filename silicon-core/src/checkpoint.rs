@@ -0,0 +1,166 @@
+//! Save the state of a running simulation and restore it later.
+//!
+//! Only the clock and the components registered with [`CheckpointExt::register_checkpoint`] are
+//! part of a checkpoint. Entities keep their ids, so a checkpoint can be restored into the app it
+//! was taken from or into a fresh app, in which case the entities are spawned with the same ids.
+//! Spikes that are still in transit and the state of the random number generator are not saved.
+
+use bevy::{
+    app::App,
+    prelude::{Component, Entity, Resource, World},
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::Clock;
+
+/// The errors that can occur while saving or restoring a checkpoint.
+#[derive(Debug)]
+pub enum CheckpointError {
+    /// The checkpoint could not be encoded or decoded.
+    Serialization(bincode::Error),
+    /// The checkpoint contains a component type that isn't registered in the app it's restored into.
+    UnregisteredComponent(String),
+}
+
+impl From<bincode::Error> for CheckpointError {
+    fn from(error: bincode::Error) -> Self {
+        CheckpointError::Serialization(error)
+    }
+}
+
+impl std::fmt::Display for CheckpointError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CheckpointError::Serialization(error) => write!(f, "invalid checkpoint: {}", error),
+            CheckpointError::UnregisteredComponent(name) => {
+                write!(f, "component {} is not registered for checkpoints", name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CheckpointError {}
+
+type SaveFn = fn(&World) -> Result<Vec<(Entity, Vec<u8>)>, bincode::Error>;
+type RestoreFn = fn(&mut World, &[(Entity, Vec<u8>)]) -> Result<(), bincode::Error>;
+
+/// The component types that are saved in a checkpoint.
+#[derive(Resource, Default)]
+pub struct CheckpointRegistry {
+    components: Vec<(&'static str, SaveFn, RestoreFn)>,
+}
+
+impl CheckpointRegistry {
+    /// Include the component `C` in checkpoints, registering a type twice has no effect.
+    pub fn register<C: Component + Serialize + DeserializeOwned>(&mut self) {
+        let name = std::any::type_name::<C>();
+        if self
+            .components
+            .iter()
+            .any(|(registered, _, _)| *registered == name)
+        {
+            return;
+        }
+
+        self.components
+            .push((name, save_components::<C>, restore_components::<C>));
+    }
+}
+
+/// Registers components for checkpoints on an [`App`].
+pub trait CheckpointExt {
+    /// Include the component `C` in checkpoints.
+    fn register_checkpoint<C: Component + Serialize + DeserializeOwned>(&mut self) -> &mut Self;
+}
+
+impl CheckpointExt for App {
+    fn register_checkpoint<C: Component + Serialize + DeserializeOwned>(&mut self) -> &mut Self {
+        self.world_mut()
+            .get_resource_or_insert_with(CheckpointRegistry::default)
+            .register::<C>();
+        self
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Checkpoint {
+    clock: Option<Clock>,
+    components: Vec<(String, Vec<(Entity, Vec<u8>)>)>,
+}
+
+fn save_components<C: Component + Serialize>(
+    world: &World,
+) -> Result<Vec<(Entity, Vec<u8>)>, bincode::Error> {
+    world
+        .iter_entities()
+        .filter_map(|entity| Some((entity.id(), entity.get::<C>()?)))
+        .map(|(entity, component)| Ok((entity, bincode::serialize(component)?)))
+        .collect()
+}
+
+fn restore_components<C: Component + DeserializeOwned>(
+    world: &mut World,
+    components: &[(Entity, Vec<u8>)],
+) -> Result<(), bincode::Error> {
+    for (entity, data) in components {
+        let component = bincode::deserialize::<C>(data)?;
+        if let Some(mut entity) = world.get_or_spawn(*entity) {
+            entity.insert(component);
+        }
+    }
+
+    Ok(())
+}
+
+/// Serialize the clock and every registered component in the world.
+pub fn save_checkpoint(world: &World) -> Result<Vec<u8>, CheckpointError> {
+    let components = match world.get_resource::<CheckpointRegistry>() {
+        Some(registry) => registry
+            .components
+            .iter()
+            .map(|(name, save, _)| Ok((name.to_string(), save(world)?)))
+            .collect::<Result<Vec<_>, bincode::Error>>()?,
+        None => vec![],
+    };
+
+    let checkpoint = Checkpoint {
+        clock: world.get_resource::<Clock>().cloned(),
+        components,
+    };
+
+    Ok(bincode::serialize(&checkpoint)?)
+}
+
+/// Restore a checkpoint created with [`save_checkpoint`]. The saved components replace the
+/// components of the entities with the same id, entities that don't exist are spawned.
+pub fn restore_checkpoint(app: &mut App, data: &[u8]) -> Result<(), CheckpointError> {
+    let checkpoint = bincode::deserialize::<Checkpoint>(data)?;
+    let world = app.world_mut();
+
+    let restore_fns = checkpoint
+        .components
+        .iter()
+        .map(|(name, _)| {
+            world
+                .get_resource::<CheckpointRegistry>()
+                .and_then(|registry| {
+                    registry
+                        .components
+                        .iter()
+                        .find(|(registered, _, _)| *registered == name.as_str())
+                })
+                .map(|(_, _, restore)| *restore)
+                .ok_or_else(|| CheckpointError::UnregisteredComponent(name.clone()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    for (restore, (_, components)) in restore_fns.into_iter().zip(checkpoint.components.iter()) {
+        restore(world, components)?;
+    }
+
+    if let Some(clock) = checkpoint.clock {
+        world.insert_resource(clock);
+    }
+
+    Ok(())
+}
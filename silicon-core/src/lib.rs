@@ -4,9 +4,15 @@
 //! Silicon core is a library for building spiking neural networks in bevy.
 
 use bevy::{
+    ecs::schedule::ScheduleLabel,
     prelude::{Component, Resource},
     reflect::Reflect,
 };
+use rand::{rngs::StdRng, Rng, RngCore, SeedableRng};
+
+#[cfg(feature = "serde")]
+pub mod checkpoint;
+pub mod integrator;
 
 #[bevy_trait_query::queryable]
 /// Core trait for neurons. Simulator queries for this trait and calls update for every simulation time tick.
@@ -15,8 +21,97 @@ pub trait Neuron {
     fn update(&mut self, tau: f64) -> bool;
     /// Get the membrane potential of the neuron.
     fn get_membrane_potential(&self) -> f64;
-    /// Add to the membrane potential of the neuron, subtract by providing a negative value.
-    fn insert_current(&mut self, delta_v: f64) -> f64;
+    /// Inject a current into the neuron, subtract by providing a negative value. Currents add up
+    /// until the next `update`, which integrates them over the time step like any other input of
+    /// the model and clears them. Injecting the same current every tick is a constant current,
+    /// independent of the time step. Returns the membrane potential.
+    fn insert_current(&mut self, current: f64) -> f64;
+    /// Open a synaptic conductance `g` with the given reversal potential for the next time step.
+    /// The resulting current is `g * (reversal_potential - V)`, so the input weakens as the
+    /// membrane potential approaches the reversal potential.
+    fn add_conductance(&mut self, _g: f64, _reversal_potential: f64) {}
+    /// Open an excitatory synaptic conductance, by default this is `add_conductance` with [`EXCITATORY_REVERSAL_POTENTIAL`].
+    /// Neurons that track excitatory conductances separately should override this.
+    fn add_excitatory_conductance(&mut self, g: f64) {
+        self.add_conductance(g, EXCITATORY_REVERSAL_POTENTIAL);
+    }
+    /// Open an inhibitory synaptic conductance, by default this is `add_conductance` with [`INHIBITORY_REVERSAL_POTENTIAL`].
+    /// Neurons that track inhibitory conductances separately should override this.
+    fn add_inhibitory_conductance(&mut self, g: f64) {
+        self.add_conductance(g, INHIBITORY_REVERSAL_POTENTIAL);
+    }
+    /// Open a synaptic conductance on the dendrite of the neuron.
+    /// Point neurons don't have a separate dendrite, so by default this is `add_conductance`.
+    fn add_dendritic_conductance(&mut self, g: f64, reversal_potential: f64) {
+        self.add_conductance(g, reversal_potential);
+    }
+    /// Return the state of the neuron to its resting values, so a new trial doesn't inherit the
+    /// membrane potential, refractory period or pending input of the previous one.
+    /// Parameters are left untouched. Neurons without state can keep the default, which does nothing.
+    fn reset_state(&mut self) {}
+    /// Make the neuron fire now, as if its membrane crossed the threshold: the model applies its
+    /// own reset and refractory period. Returns whether the neuron fired, neurons that can't be
+    /// forced keep the default, which does nothing and returns `false`.
+    fn force_spike(&mut self) -> bool {
+        false
+    }
+    /// The names of the parameters that can be read and changed with `get_parameter` and `set_parameter`.
+    fn parameter_names(&self) -> &'static [&'static str] {
+        &[]
+    }
+    /// Get the value of a parameter by name, `None` if the neuron has no such parameter.
+    fn get_parameter(&self, _name: &str) -> Option<f64> {
+        None
+    }
+    /// Set the value of a parameter by name.
+    fn set_parameter(&mut self, name: &str, _value: f64) -> Result<(), UnknownParameter> {
+        Err(UnknownParameter(name.to_string()))
+    }
+}
+
+/// The neuron has no parameter with this name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownParameter(pub String);
+
+impl std::fmt::Display for UnknownParameter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "unknown parameter {}", self.0)
+    }
+}
+
+impl std::error::Error for UnknownParameter {}
+
+/// The reversal potential in mV of excitatory synapses.
+pub const EXCITATORY_REVERSAL_POTENTIAL: f64 = 0.0;
+/// The reversal potential in mV of inhibitory synapses.
+pub const INHIBITORY_REVERSAL_POTENTIAL: f64 = -80.0;
+
+/// Accumulates the synaptic conductances a neuron receives during a single time step.
+#[derive(Debug, Default, Clone, Copy, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SynapticConductance {
+    /// The summed conductance.
+    pub g: f64,
+    /// The summed product of every conductance and its reversal potential.
+    pub g_reversal: f64,
+}
+
+impl SynapticConductance {
+    /// Add a conductance with the given reversal potential.
+    pub fn add(&mut self, g: f64, reversal_potential: f64) {
+        self.g += g;
+        self.g_reversal += g * reversal_potential;
+    }
+
+    /// The total synaptic current at membrane potential `v`.
+    pub fn current(&self, v: f64) -> f64 {
+        self.g_reversal - self.g * v
+    }
+
+    /// Close all conductances, call this after the current has been applied.
+    pub fn clear(&mut self) {
+        *self = SynapticConductance::default();
+    }
 }
 
 /// Allows a neuron to be visualized in 3D.
@@ -28,7 +123,7 @@ pub trait NeuronVisualizer {
 
 /// This trait allows for implementations like STDP, where the synapse needs to know when a neuron spiked.
 /// Your neuron implementation should call this method when it spikes.
-/// We recommend clearing the spikes after reading them.
+/// We recommend clearing the spikes after reading them with `clear` or `clear_before`.
 /// This is not enforced by the trait. But prevents memory from getting out of hand during long simulation times.
 #[bevy_trait_query::queryable]
 pub trait SpikeRecorder {
@@ -36,10 +131,86 @@ pub trait SpikeRecorder {
     fn record_spike(&mut self, time: f64);
     /// Get the spikes that have been recorded.
     fn get_spikes(&self) -> Vec<f64>;
+    /// Forget all recorded spikes.
+    fn clear(&mut self);
+    /// Forget the spikes recorded before `time`.
+    fn clear_before(&mut self, time: f64);
+
+    /// The intervals between consecutive recorded spikes.
+    fn isi_list(&self) -> Vec<f64> {
+        self.get_spikes()
+            .windows(2)
+            .map(|pair| pair[1] - pair[0])
+            .collect()
+    }
+
+    /// The mean inter-spike interval, `None` when fewer than two spikes were recorded.
+    fn mean_isi(&self) -> Option<f64> {
+        let isis = self.isi_list();
+        if isis.is_empty() {
+            return None;
+        }
+
+        Some(isis.iter().sum::<f64>() / isis.len() as f64)
+    }
+
+    /// The coefficient of variation of the inter-spike intervals, `std(ISI) / mean(ISI)`.
+    /// Regular spiking gives a CV close to 0 and Poisson spiking a CV close to 1.
+    fn cv_isi(&self) -> Option<f64> {
+        let isis = self.isi_list();
+        let (mean, variance) = mean_and_variance(&isis)?;
+        if mean <= 0.0 {
+            return None;
+        }
+
+        Some(variance.sqrt() / mean)
+    }
+
+    /// The Fano factor of the spike counts, `var(count) / mean(count)`, with the spikes counted in
+    /// bins of `bin_size` from the first recorded spike onwards. `None` when the spikes don't span
+    /// at least two full bins.
+    fn fano_factor(&self, bin_size: f64) -> Option<f64> {
+        let spikes = self.get_spikes();
+        let (first, last) = (*spikes.first()?, *spikes.last()?);
+        if bin_size <= 0.0 {
+            return None;
+        }
+
+        let bins = ((last - first) / bin_size).floor() as usize;
+        if bins < 2 {
+            return None;
+        }
+
+        let mut counts = vec![0.0; bins];
+        for spike in spikes {
+            let bin = ((spike - first) / bin_size) as usize;
+            if bin < bins {
+                counts[bin] += 1.0;
+            }
+        }
+
+        let (mean, variance) = mean_and_variance(&counts)?;
+        if mean <= 0.0 {
+            return None;
+        }
+
+        Some(variance / mean)
+    }
+}
+
+fn mean_and_variance(values: &[f64]) -> Option<(f64, f64)> {
+    if values.len() < 2 {
+        return None;
+    }
+
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64;
+    Some((mean, variance))
 }
 
 /// Clock is a high level resource that tracks the simulation time.
-#[derive(Resource, Reflect)]
+#[derive(Clone, Resource, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Clock {
     /// The total time that has been simulated in seconds.
     pub time: f64,
@@ -47,12 +218,34 @@ pub struct Clock {
     pub time_to_simulate: f64,
     /// If true, the simulation will run indefinitely.
     pub run_indefinitely: bool,
+    /// If true, every frame adds its real duration scaled by the `SimulationSpeed` to
+    /// `time_to_simulate`, and the simulation runs as many steps of `tau` as fit in it.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub real_time: bool,
     /// The time step of the simulation.
     pub tau: f64,
 }
 
+impl Default for Clock {
+    fn default() -> Self {
+        Clock {
+            time: 0.0,
+            time_to_simulate: 0.0,
+            run_indefinitely: false,
+            real_time: false,
+            tau: 0.025,
+        }
+    }
+}
+
+/// The schedule that advances the simulation by a single time step of the [`Clock`]. It runs once
+/// every frame, or as many times as the duration of the frame needs in real time mode.
+#[derive(ScheduleLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SimulationStep;
+
 /// A component that records the membrane potential of a neuron or the weight of a synapse.
 #[derive(Debug, Component, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ValueRecorder {
     /// A time & value tuple that represents the membrane potential or weight.
     pub values: Vec<(f64, f64)>,
@@ -72,6 +265,57 @@ impl ValueRecorder {
 
         self.values.push((time, value));
     }
+
+    /// The recorded values reduced to at most `max_points`, see `decimate`.
+    pub fn decimated(&self, max_points: usize) -> Vec<(f64, f64)> {
+        decimate(&self.values, max_points)
+    }
+}
+
+/// Reduce a time series to at most `max_points` (at least two) by splitting its time range into
+/// buckets of equal duration and keeping the lowest and highest value of every bucket. Unlike
+/// averaging or keeping every nth point this preserves spikes and other short transients.
+/// The values have to be sorted by time, a series that is short enough is returned as is.
+pub fn decimate(values: &[(f64, f64)], max_points: usize) -> Vec<(f64, f64)> {
+    if values.len() <= max_points.max(2) {
+        return values.to_vec();
+    }
+
+    let buckets = (max_points / 2).max(1);
+    let start = values[0].0;
+    let duration = values[values.len() - 1].0 - start;
+    let bucket_of = |time: f64| {
+        if duration > 0.0 {
+            (((time - start) / duration * buckets as f64) as usize).min(buckets - 1)
+        } else {
+            0
+        }
+    };
+
+    let mut decimated = Vec::with_capacity(buckets * 2);
+    let mut bucket_start = 0;
+    while bucket_start < values.len() {
+        let bucket = bucket_of(values[bucket_start].0);
+        let (mut min, mut max) = (bucket_start, bucket_start);
+        let mut end = bucket_start + 1;
+        while end < values.len() && bucket_of(values[end].0) == bucket {
+            if values[end].1 < values[min].1 {
+                min = end;
+            }
+            if values[end].1 > values[max].1 {
+                max = end;
+            }
+            end += 1;
+        }
+
+        decimated.push(values[min.min(max)]);
+        if min != max {
+            decimated.push(values[min.max(max)]);
+        }
+        bucket_start = end;
+    }
+
+    decimated
 }
 
 impl Default for ValueRecorder {
@@ -85,4 +329,92 @@ impl Default for ValueRecorder {
 pub struct ValueRecorderConfig {
     /// The size of the window that the value recorder will keep track of.
     pub window_size: usize,
+    /// The number of values a recorder's history is decimated to, see `decimate`. It only happens
+    /// once a recorder holds twice as many, so a history isn't decimated again every frame and
+    /// a recorder keeps up to `2 * max_points` values. `None` keeps every value within the window.
+    pub max_points: Option<usize>,
+}
+
+/// A seedable random number generator resource.
+/// Builders and systems should draw from this resource instead of `rand::thread_rng` so runs can be reproduced.
+#[derive(Debug, Clone, Resource)]
+pub struct SimulationRng {
+    rng: StdRng,
+}
+
+impl SimulationRng {
+    /// Create a new random number generator from the given seed.
+    pub fn from_seed(seed: u64) -> Self {
+        SimulationRng {
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// Draw a sample from a normal distribution using the Box-Muller transform.
+    pub fn sample_normal(&mut self, mean: f64, std_dev: f64) -> f64 {
+        sample_normal(&mut self.rng, mean, std_dev)
+    }
+}
+
+/// Draw a sample from a normal distribution using the Box-Muller transform.
+pub fn sample_normal<R: Rng + ?Sized>(rng: &mut R, mean: f64, std_dev: f64) -> f64 {
+    let u1 = 1.0 - rng.gen::<f64>();
+    let u2 = rng.gen::<f64>();
+    mean + std_dev * (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+}
+
+impl Default for SimulationRng {
+    fn default() -> Self {
+        SimulationRng {
+            rng: StdRng::from_entropy(),
+        }
+    }
+}
+
+impl RngCore for SimulationRng {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.rng.try_fill_bytes(dest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decimation_preserves_extrema() {
+        let mut recorder = ValueRecorder::new();
+        for step in 0..100_000 {
+            let time = step as f64 * 0.1;
+            let value = match step {
+                31_415 => 30.0,
+                77_777 => -90.0,
+                _ => -65.0 + (time / 50.0).sin(),
+            };
+            recorder.push(time, value);
+        }
+        assert_eq!(recorder.values.len(), 100_000);
+
+        let decimated = recorder.decimated(1000);
+        assert!(decimated.len() <= 1000);
+        assert!(decimated.len() > 900);
+        // the transients of a single sample survive
+        assert!(decimated.contains(&recorder.values[31_415]));
+        assert!(decimated.contains(&recorder.values[77_777]));
+        assert!(decimated.windows(2).all(|pair| pair[0].0 < pair[1].0));
+
+        assert_eq!(recorder.decimated(200_000), recorder.values);
+    }
 }
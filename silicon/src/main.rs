@@ -2,6 +2,7 @@
 
 use std::{ops::Deref, time::Duration};
 
+use analytics::SiliconAnalyticsPlugin;
 use bevy::{
     core::TaskPoolThreadAssignmentPolicy,
     core_pipeline::{
@@ -14,24 +15,40 @@ use bevy::{
     tasks::available_parallelism,
     window::WindowResolution,
 };
-use bevy_mod_outline::OutlineVolume;
+use bevy_mod_outline::{OutlinePlugin, OutlineVolume};
 use bevy_panorbit_camera::{PanOrbitCamera, PanOrbitCameraPlugin};
 use bevy_rapier3d::{
     pipeline::QueryFilter,
     plugin::{NoUserData, RapierContext, RapierPhysicsPlugin},
 };
 use bevy_trait_query::One;
-use neurons::NeuronPlugin;
+use neurons::{izhikevich::IzhikevichPreset, NeuronPlugin};
 use rand::Rng;
-use silicon_core::{Clock, Neuron, NeuronVisualizer, SpikeRecorder, ValueRecorderConfig};
-use simulator::SimulationPlugin;
+use silicon_core::{
+    Clock, Neuron, NeuronVisualizer, SimulationRng, SimulationStep, SpikeRecorder,
+    ValueRecorderConfig,
+};
+use simulator::{
+    current::CurrentSource,
+    dopamine::{Dopamine, DopamineReleaseEvent, RewardModel},
+    reset::{reset_network, ResetNetworkEvent},
+    SimulationPlugin,
+};
 use structure::{feed_forward::FeedForwardNetwork, layer::ColumnLayer};
 use synapses::{
+    bcm::BcmSynapse,
+    exponential::{ConductanceExpSynapse, ExponentialSynapse},
+    gap_junction::GapJunctionSynapse,
     simple::SimpleSynapse,
     stdp::{StdpSettings, StdpSynapse},
-    DeferredStdpEvent, Synapse, SynapsePlugin,
+    stp::StpSynapse,
+    triplet_stdp::TripletStdpSynapse,
+    Synapse, SynapsePlugin,
+};
+use transcoder::{
+    nlp::string_to_spike_train,
+    population::{PopulationDecoder, PopulationEncoder},
 };
-use transcoder::{nlp::string_to_spike_train, population::PopulationEncoder};
 use ui::{
     state::{PlotterConfig, UiState},
     SiliconUiPlugin,
@@ -87,12 +104,14 @@ impl Plugin for SiliconPlugin {
                 }),
         )
         .add_plugins(PanOrbitCameraPlugin)
+        .add_plugins(OutlinePlugin)
         .add_plugins(RapierPhysicsPlugin::<NoUserData>::default())
         .add_plugins((
-            SimulationPlugin,
+            SimulationPlugin::default(),
             NeuronPlugin,
             SynapsePlugin,
             SiliconUiPlugin,
+            SiliconAnalyticsPlugin,
         ))
         // .add_plugins(RapierDebugRenderPlugin::default())
         .insert_resource(Msaa::Sample8)
@@ -109,7 +128,15 @@ impl Plugin for SiliconPlugin {
         //     amount: 0.0001,
         //     next_decay: 1.0,
         // })
-        .insert_resource(ValueRecorderConfig { window_size: 10000 })
+        // only the deferred changes of a presentation are rewarded, not the eligibility traces
+        .insert_resource(Dopamine {
+            model: RewardModel::Deferred,
+            ..Default::default()
+        })
+        .insert_resource(ValueRecorderConfig {
+            window_size: 10000,
+            max_points: Some(5000),
+        })
         .insert_resource(PlotterConfig {
             window_size: 300,
             weight_window_size: Some(100000),
@@ -119,10 +146,16 @@ impl Plugin for SiliconPlugin {
         .insert_resource(EncoderState::default())
         .add_systems(Startup, (create_neurons, setup_scene))
         .add_systems(PostStartup, notify_setup_done)
+        .add_systems(
+            SimulationStep,
+            (
+                insert_current.before(reset_network),
+                present_class.after(reset_network),
+            ),
+        )
         .add_systems(
             Update,
             (
-                insert_current,
                 show_select_neuron_synapses,
                 update_neuron_materials,
                 mouse_click,
@@ -143,7 +176,20 @@ fn hide_meshes(mut visibilities: Query<&mut Visibility>) {
 fn show_select_neuron_synapses(
     insights: Res<Interactions>,
     mut synapse_query: Query<(One<&dyn Synapse>, &mut Visibility, &Children)>,
-    mut child_query: Query<&mut Visibility, (Without<StdpSynapse>, Without<SimpleSynapse>)>, // https://github.com/JoJoJet/bevy-trait-query/pull/58
+    // https://github.com/JoJoJet/bevy-trait-query/pull/58
+    mut child_query: Query<
+        &mut Visibility,
+        (
+            Without<StdpSynapse>,
+            Without<SimpleSynapse>,
+            Without<StpSynapse>,
+            Without<BcmSynapse>,
+            Without<GapJunctionSynapse>,
+            Without<TripletStdpSynapse>,
+            Without<ExponentialSynapse>,
+            Without<ConductanceExpSynapse>,
+        ),
+    >,
 ) {
     if let Some(selected_entity) = insights.selected_entity {
         for (synapse, mut visibility, children) in synapse_query.iter_mut() {
@@ -191,6 +237,11 @@ struct EncoderState {
     pub time_between_classes: f64,
     pub current_class: Class,
     pub encoders: Vec<(Class, PopulationEncoder)>,
+    /// reads the presented class from the output layer
+    #[reflect(ignore)]
+    pub decoder: PopulationDecoder<Class>,
+    /// set when the current class still has to be presented to the network
+    pub pending_presentation: bool,
 }
 
 impl Default for EncoderState {
@@ -198,8 +249,10 @@ impl Default for EncoderState {
         EncoderState {
             current_class: Class::Hello,
             encoders: vec![],
+            decoder: PopulationDecoder::new(),
             time_between_classes: 5.0,
             next_presentation_time: 5.0,
+            pending_presentation: false,
         }
     }
 }
@@ -221,16 +274,12 @@ fn error(firing_rate: f64, target_rate: f64) -> f64 {
 }
 
 fn insert_current(
-    mut neurons_query: Query<(
-        Entity,
-        One<&mut dyn Neuron>,
-        &ColumnLayer,
-        One<&dyn SpikeRecorder>,
-    )>,
+    neurons_query: Query<(Entity, One<&dyn SpikeRecorder>)>,
     clock: Res<Clock>,
     mut encoder: ResMut<EncoderState>,
-    mut deferred_stdp_events: ResMut<Events<DeferredStdpEvent>>,
-    mut stdp_synapses: Query<(Entity, &mut StdpSynapse)>,
+    mut release_writer: EventWriter<DopamineReleaseEvent>,
+    mut reset_writer: EventWriter<ResetNetworkEvent>,
+    mut rng: ResMut<SimulationRng>,
 ) {
     if clock.time < encoder.next_presentation_time {
         return;
@@ -242,42 +291,26 @@ fn insert_current(
 
     // == calculate reward ==
 
-    let mut output_neurons = neurons_query
-        .iter()
-        .filter(|(_, _, layer, _)| *layer == &ColumnLayer::L6)
-        .collect::<Vec<_>>();
-    output_neurons.sort_by(|(a, _, _, _), (b, _, _, _)| {
-        let a = a.generation() as f64 + (a.index() as f64 / 10.0);
-        let b = b.generation() as f64 + (b.index() as f64 / 10.0);
-        a.partial_cmp(&b).unwrap()
-    });
+    let recorders = neurons_query.iter().collect::<Vec<_>>();
+    let window = encoder.time_between_classes;
+    trace!(
+        "Decoded class {:?}",
+        encoder
+            .decoder
+            .decode(window, &clock, recorders.iter().copied())
+    );
 
-    let mut class_for_neuron = Class::Hello;
     let mut correct_class_spikes = 0;
     let mut wrong_class_spikes = 0;
-
-    for (entity, _, _, spike_recorder) in output_neurons {
-        trace!(
-            "Calculating reward for neuron {:?} with class {:?}",
-            entity,
-            class_for_neuron
-        );
-        let spikes = spike_recorder
-            .get_spikes()
-            .iter()
-            .filter(|s| **s >= clock.time - encoder.time_between_classes)
-            .count();
-
-        if class_for_neuron == encoder.current_class {
+    for (class, spikes) in encoder
+        .decoder
+        .spike_counts(window, &clock, recorders.iter().copied())
+    {
+        if class == encoder.current_class {
             correct_class_spikes += spikes as i32;
         } else {
             wrong_class_spikes += spikes as i32;
         }
-
-        class_for_neuron = match class_for_neuron {
-            Class::Hello => Class::World,
-            Class::World => Class::Hello,
-        };
     }
 
     trace!(
@@ -299,63 +332,82 @@ fn insert_current(
 
     if reward == 0.0 {
         trace!("reward is zero, randomizing it for network exploration purposes");
-        reward = rand::thread_rng().gen_range(-2.0..=2.0);
+        reward = rng.gen_range(-2.0..=2.0);
         trace!("Randomized reward: {}", reward);
     }
 
     // == apply reward modulated STDP ==
-    for event in deferred_stdp_events.drain() {
-        let synapse = stdp_synapses
-            .iter_mut()
-            .find(|(entity, _)| *entity == event.synapse);
-
-        if let Some((_, mut synapse)) = synapse {
-            trace!("applying stdp to {:?} with\ndelta weight {}\nreward modulated delta weight: {}\nnew weight {}",
-                event.synapse,
-                event.delta_weight,
-                event.delta_weight * reward,
-                synapse.weight + event.delta_weight
-            );
-
-            synapse.weight += event.delta_weight * reward;
-            synapse.weight = synapse
-                .weight
-                .clamp(synapse.stdp_params.w_min, synapse.stdp_params.w_max);
-        }
-    }
+    // the deferred weight changes of the presentation are scaled by the released dopamine
+    release_writer.send(DopamineReleaseEvent { amount: reward });
 
     // == present the next class ==
+    // the activity of the previous class shouldn't leak into the next presentation, the learned
+    // weights and the recordings of the whole run are kept
+    reset_writer.send(ResetNetworkEvent {
+        keep_recordings: true,
+        ..Default::default()
+    });
+
     encoder.next_presentation_time = clock.time + encoder.time_between_classes;
 
     encoder.current_class = match encoder.current_class {
         Class::Hello => Class::World,
         Class::World => Class::Hello,
     };
+    encoder.pending_presentation = true;
+}
 
+/// Drives the population of the presented class with a current for the whole presentation, this
+/// runs after the network has been reset so the input isn't wiped out.
+fn present_class(
+    mut commands: Commands,
+    clock: Res<Clock>,
+    mut encoder: ResMut<EncoderState>,
+    mut rng: ResMut<SimulationRng>,
+) {
+    if !encoder.pending_presentation {
+        return;
+    }
+    encoder.pending_presentation = false;
+
+    let end = clock.time + encoder.time_between_classes;
     let encoder = encoder
         .encoders
         .iter()
         .find(|(class, _)| *class == encoder.current_class);
 
     if let Some((_, encoder)) = encoder {
-        let population = encoder.neurons.clone();
-        let neurons = neurons_query
-            .iter_mut()
-            .filter(|(entity, _, _, _)| population.contains(entity))
-            .collect::<Vec<_>>();
-
-        for (_, mut neuron, _, _) in neurons {
-            neuron.insert_current(rand::thread_rng().gen_range(1.6..=1.8));
+        for neuron in encoder.neurons.iter() {
+            // enough for a regular spiking neuron to fire once or twice per presentation
+            let amplitude = rng.gen_range(16.0..=18.0);
+            commands.entity(*neuron).insert(CurrentSource::Steps(vec![
+                (clock.time, amplitude),
+                (end, 0.0),
+            ]));
         }
     }
 }
 
 fn create_neurons(world: &mut World) {
-    // MiniColumn::create(commands, meshes, materials);
+    // MiniColumn::create(IzhikevichPreset::RegularSpiking, commands, meshes, materials);
 
     let mut ffn = FeedForwardNetwork::new();
-    ffn.add_layer(3, 3, 1, world, Some(ColumnLayer::L1));
-    ffn.add_layer(3, 3, 1, world, Some(ColumnLayer::L4));
+    ffn.add_layer(
+        3,
+        3,
+        1,
+        IzhikevichPreset::RegularSpiking,
+        world,
+        Some(ColumnLayer::L1),
+    );
+    ffn.add_layer(
+        3,
+        3,
+        1,
+        IzhikevichPreset::RegularSpiking,
+        world,
+        Some(ColumnLayer::L4),
+    );
     ffn.add_wta_layer(2, 1, 1, world, Some(ColumnLayer::L6));
 
     ffn.connect_layers(0, 1, 0.8, 0.8, world);
@@ -364,6 +416,11 @@ fn create_neurons(world: &mut World) {
     ffn.connect_layers(1, 0, 0.2, 0.8, world);
     ffn.connect_layers(2, 1, 0.8, 0.8, world);
 
+    let output = ffn.layer(2);
+    world.resource_mut::<EncoderState>().decoder = PopulationDecoder::new()
+        .with_population(Class::Hello, vec![output[0]])
+        .with_population(Class::World, vec![output[1]]);
+
     world.resource_scope(|world, mut encoder: Mut<EncoderState>| {
         let neurons = world
             .query::<(Entity, &mut dyn Neuron, &ColumnLayer)>()
@@ -372,14 +429,16 @@ fn create_neurons(world: &mut World) {
             .map(|(entity, _, _)| entity)
             .collect::<Vec<_>>();
 
+        let mut rng = world.resource_mut::<SimulationRng>();
+
         encoder.encoders.push((
             Class::Hello,
-            PopulationEncoder::from_sample_rate(&neurons, 0.5),
+            PopulationEncoder::from_sample_rate(&neurons, 0.5, &mut *rng),
         ));
 
         encoder.encoders.push((
             Class::World,
-            PopulationEncoder::from_sample_rate(&neurons, 0.5),
+            PopulationEncoder::from_sample_rate(&neurons, 0.5, &mut *rng),
         ));
     });
 }
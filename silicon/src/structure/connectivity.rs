@@ -0,0 +1,156 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use bevy::{
+    prelude::{Entity, World},
+    utils::HashMap,
+};
+use bevy_trait_query::One;
+use silicon_core::Neuron;
+use simulator::export::entity_id;
+use synapses::{Synapse, SynapseType};
+
+/// The neurons in `world` sorted by entity, and the matrix of the weights between them. Entry
+/// `[i][j]` is the summed weight of all synapses from neuron `i` to neuron `j`, inhibitory
+/// synapses count negative. Pairs without a synapse are 0.0.
+///
+/// Trait queries need mutable access to the world to initialize their state, which is why this
+/// takes `&mut World`.
+pub fn connectivity_matrix(world: &mut World) -> (Vec<Entity>, Vec<Vec<f64>>) {
+    let mut neurons = world
+        .query::<(Entity, One<&dyn Neuron>)>()
+        .iter(world)
+        .map(|(entity, _)| entity)
+        .collect::<Vec<_>>();
+    neurons.sort_by_key(|entity| (entity.index(), entity.generation()));
+
+    let indices = neurons
+        .iter()
+        .enumerate()
+        .map(|(index, entity)| (*entity, index))
+        .collect::<HashMap<_, _>>();
+
+    let mut matrix = vec![vec![0.0; neurons.len()]; neurons.len()];
+    for synapse in world.query::<One<&dyn Synapse>>().iter(world) {
+        let (Some(source), Some(target)) = (
+            indices.get(&synapse.get_presynaptic()),
+            indices.get(&synapse.get_postsynaptic()),
+        ) else {
+            continue;
+        };
+
+        matrix[*source][*target] += match synapse.get_type() {
+            SynapseType::Inhibitory => -synapse.get_weight(),
+            SynapseType::Excitatory | SynapseType::Electrical => synapse.get_weight(),
+        };
+    }
+
+    (neurons, matrix)
+}
+
+/// Writes the matrix as CSV, the first row and column hold the `generation:index` of the neurons.
+pub fn write_connectivity_csv(
+    neurons: &[Entity],
+    matrix: &[Vec<f64>],
+    writer: &mut impl Write,
+) -> io::Result<()> {
+    let ids = neurons
+        .iter()
+        .map(|entity| entity_id(*entity))
+        .collect::<Vec<_>>();
+    writeln!(writer, "source,{}", ids.join(","))?;
+
+    for (id, row) in ids.iter().zip(matrix) {
+        let weights = row
+            .iter()
+            .map(|weight| weight.to_string())
+            .collect::<Vec<_>>();
+        writeln!(writer, "{},{}", id, weights.join(","))?;
+    }
+
+    Ok(())
+}
+
+/// Writes the connectivity matrix of the network in `world` to a CSV file at `path`.
+pub fn export_connectivity_matrix(world: &mut World, path: &Path) -> io::Result<()> {
+    let (neurons, matrix) = connectivity_matrix(world);
+    let mut writer = BufWriter::new(File::create(path)?);
+    write_connectivity_csv(&neurons, &matrix, &mut writer)?;
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::Component;
+    use bevy_trait_query::RegisterExt;
+    use synapses::simple::SimpleSynapse;
+
+    use super::*;
+
+    #[derive(Component)]
+    struct TestNeuron;
+
+    impl Neuron for TestNeuron {
+        fn update(&mut self, _tau: f64) -> bool {
+            false
+        }
+
+        fn get_membrane_potential(&self) -> f64 {
+            0.0
+        }
+
+        fn insert_current(&mut self, _current: f64) -> f64 {
+            0.0
+        }
+    }
+
+    #[test]
+    fn test_connectivity_matrix() {
+        let mut world = World::new();
+        world.register_component_as::<dyn Neuron, TestNeuron>();
+        world.register_component_as::<dyn Synapse, SimpleSynapse>();
+
+        let neurons = [(); 3].map(|_| world.spawn(TestNeuron).id());
+        for (source, target, weight, synapse_type) in [
+            (0, 1, 0.5, SynapseType::Excitatory),
+            // two synapses between the same pair sum up
+            (0, 1, 0.25, SynapseType::Excitatory),
+            (1, 2, 0.4, SynapseType::Inhibitory),
+            (2, 2, 0.1, SynapseType::Excitatory),
+        ] {
+            world.spawn(SimpleSynapse {
+                weight,
+                delay: 1,
+                source: neurons[source],
+                target: neurons[target],
+                synapse_type,
+            });
+        }
+        // synapses from entities that aren't neurons are left out
+        world.spawn(SimpleSynapse {
+            weight: 1.0,
+            delay: 1,
+            source: Entity::PLACEHOLDER,
+            target: neurons[0],
+            synapse_type: SynapseType::Excitatory,
+        });
+
+        let (order, matrix) = connectivity_matrix(&mut world);
+
+        assert_eq!(order, neurons);
+        assert_eq!(
+            matrix,
+            [[0.0, 0.75, 0.0], [0.0, 0.0, -0.4], [0.0, 0.0, 0.1]]
+        );
+
+        let mut csv = vec![];
+        write_connectivity_csv(&order, &matrix, &mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let lines = csv.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[2], format!("{},0,0,-0.4", entity_id(neurons[1])));
+    }
+}
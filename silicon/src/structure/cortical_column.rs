@@ -12,7 +12,7 @@ use bevy::{
 };
 use bevy_math::primitives::Cuboid;
 use bevy_rapier3d::geometry::Collider;
-use neurons::izhikevich::IzhikevichNeuron;
+use neurons::izhikevich::{IzhikevichNeuron, IzhikevichPreset};
 use simulator::SimpleSpikeRecorder;
 use synapses::AllowSynapses;
 
@@ -32,6 +32,7 @@ struct MiniColumnBundle {
 
 impl MiniColumn {
     pub fn create(
+        preset: IzhikevichPreset,
         mut commands: Commands,
         mut meshes: ResMut<Assets<Mesh>>,
         mut materials: ResMut<Assets<StandardMaterial>>,
@@ -58,15 +59,9 @@ impl MiniColumn {
 
                     let neuron = commands
                         .spawn((
-                            IzhikevichNeuron {
-                                v: -70.0,
-                                u: -14.0,
-                                a: 0.02,
-                                b: 0.2,
-                                c: -100.0,
-                                d: 8.0,
-                                synapse_weight_multiplier: 80.0,
-                            },
+                            IzhikevichNeuron::builder(preset)
+                                .synapse_weight_multiplier(80.0)
+                                .build(),
                             PbrBundle {
                                 mesh: mesh.clone(),
                                 material: leaky_neuron_material,
@@ -101,15 +96,9 @@ impl MiniColumn {
 
                     let neuron = commands
                         .spawn((
-                            IzhikevichNeuron {
-                                v: -70.0,
-                                u: -14.0,
-                                a: 0.02,
-                                b: 0.2,
-                                c: -100.0,
-                                d: 8.0,
-                                synapse_weight_multiplier: 80.0,
-                            },
+                            IzhikevichNeuron::builder(preset)
+                                .synapse_weight_multiplier(80.0)
+                                .build(),
                             PbrBundle {
                                 mesh: mesh.clone(),
                                 material: leaky_neuron_material,
@@ -144,15 +133,9 @@ impl MiniColumn {
 
                     let neuron = commands
                         .spawn((
-                            IzhikevichNeuron {
-                                v: -70.0,
-                                u: -14.0,
-                                a: 0.02,
-                                b: 0.2,
-                                c: -100.0,
-                                d: 8.0,
-                                synapse_weight_multiplier: 80.0,
-                            },
+                            IzhikevichNeuron::builder(preset)
+                                .synapse_weight_multiplier(80.0)
+                                .build(),
                             PbrBundle {
                                 mesh: mesh.clone(),
                                 material: leaky_neuron_material,
@@ -183,15 +166,9 @@ impl MiniColumn {
 
                     let neuron = commands
                         .spawn((
-                            IzhikevichNeuron {
-                                v: -70.0,
-                                u: -14.0,
-                                a: 0.02,
-                                b: 0.2,
-                                c: -100.0,
-                                d: 8.0,
-                                synapse_weight_multiplier: 80.0,
-                            },
+                            IzhikevichNeuron::builder(preset)
+                                .synapse_weight_multiplier(80.0)
+                                .build(),
                             PbrBundle {
                                 mesh: mesh.clone(),
                                 material: oscillating_neuron_material,
@@ -221,15 +198,9 @@ impl MiniColumn {
 
                     let neuron = commands
                         .spawn((
-                            IzhikevichNeuron {
-                                v: -70.0,
-                                u: -14.0,
-                                a: 0.02,
-                                b: 0.2,
-                                c: -100.0,
-                                d: 8.0,
-                                synapse_weight_multiplier: 80.0,
-                            },
+                            IzhikevichNeuron::builder(preset)
+                                .synapse_weight_multiplier(80.0)
+                                .build(),
                             PbrBundle {
                                 mesh: mesh.clone(),
                                 material: leaky_neuron_material,
@@ -260,15 +231,9 @@ impl MiniColumn {
 
                     let neuron = commands
                         .spawn((
-                            IzhikevichNeuron {
-                                v: -70.0,
-                                u: -14.0,
-                                a: 0.02,
-                                b: 0.2,
-                                c: -100.0,
-                                d: 8.0,
-                                synapse_weight_multiplier: 80.0,
-                            },
+                            IzhikevichNeuron::builder(preset)
+                                .synapse_weight_multiplier(80.0)
+                                .build(),
                             PbrBundle {
                                 mesh: mesh.clone(),
                                 material: leaky_neuron_material,
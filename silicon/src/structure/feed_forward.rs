@@ -4,7 +4,7 @@ use bevy::{
     hierarchy::BuildWorldChildren,
     log::info,
     pbr::{PbrBundle, StandardMaterial},
-    prelude::{Entity, Mut, World},
+    prelude::{Entity, EntityWorldMut, Mut, World},
     render::{
         alpha::AlphaMode,
         mesh::{Mesh, MeshBuilder, Meshable},
@@ -18,12 +18,16 @@ use bevy_math::{
 };
 use bevy_mod_outline::{OutlineBundle, OutlineMeshExt, OutlineVolume};
 use bevy_rapier3d::geometry::Collider;
-use neurons::izhikevich::IzhikevichNeuron;
-use rand::Rng;
-use silicon_core::ValueRecorder;
+use neurons::{
+    izhikevich::{IzhikevichNeuron, IzhikevichPreset},
+    jitter::ParameterJitter,
+    single_precision::{IzhikevichNeuronF32, Precision},
+};
+use rand::{Rng, RngCore};
+use silicon_core::{SimulationRng, ValueRecorder};
 use simulator::SimpleSpikeRecorder;
 use synapses::{
-    stdp::{StdpParams, StdpSpikeType, StdpState, StdpSynapse},
+    stdp::{InhibitoryStdp, StdpParams, StdpRule, StdpSpikeType, StdpState, StdpSynapse},
     AllowSynapses, SynapseType,
 };
 
@@ -31,11 +35,43 @@ use super::layer::ColumnLayer;
 
 pub struct FeedForwardNetwork {
     layers: Vec<Vec<Entity>>,
+    precision: Precision,
+    scale_weight_with_distance: bool,
 }
 
 impl FeedForwardNetwork {
     pub fn new() -> Self {
-        FeedForwardNetwork { layers: Vec::new() }
+        FeedForwardNetwork {
+            layers: Vec::new(),
+            precision: Precision::Double,
+            scale_weight_with_distance: false,
+        }
+    }
+
+    /// The precision of the neurons in the layers added after this, `Precision::Single` trades
+    /// accuracy nobody notices for memory bandwidth in large networks.
+    pub fn with_precision(mut self, precision: Precision) -> Self {
+        self.precision = precision;
+        self
+    }
+
+    /// Let `connect_layers_by_distance` weaken the synapses between distant neurons the same way
+    /// it makes them less likely, otherwise every synapse gets the maximum weight.
+    pub fn with_distance_weight_scaling(mut self, scale_weight_with_distance: bool) -> Self {
+        self.scale_weight_with_distance = scale_weight_with_distance;
+        self
+    }
+
+    /// The neurons of the layer at `index`, in the order they were spawned.
+    pub fn layer(&self, index: usize) -> &[Entity] {
+        &self.layers[index]
+    }
+
+    fn insert_neuron(&self, entity: &mut EntityWorldMut, neuron: IzhikevichNeuron) {
+        match self.precision {
+            Precision::Double => entity.insert(neuron),
+            Precision::Single => entity.insert(IzhikevichNeuronF32::from(&neuron)),
+        };
     }
 
     pub fn add_layer(
@@ -43,6 +79,23 @@ impl FeedForwardNetwork {
         size_x: usize,
         size_y: usize,
         size_z: usize,
+        preset: IzhikevichPreset,
+        world: &mut World,
+        column_layer: Option<ColumnLayer>,
+    ) {
+        self.add_layer_with_jitter(size_x, size_y, size_z, preset, 0.0, world, column_layer);
+    }
+
+    /// Like `add_layer`, but the parameters of every neuron are perturbed by `jitter`, see
+    /// `ParameterJitter`. The perturbations are drawn from the `SimulationRng`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn add_layer_with_jitter(
+        &mut self,
+        size_x: usize,
+        size_y: usize,
+        size_z: usize,
+        preset: IzhikevichPreset,
+        jitter: f64,
         world: &mut World,
         column_layer: Option<ColumnLayer>,
     ) {
@@ -66,45 +119,40 @@ impl FeedForwardNetwork {
                 for x in 0..size_x {
                     for y in 0..size_y {
                         for z in 0..size_z {
-                            let neuron = world
-                                .spawn((
-                                    IzhikevichNeuron {
-                                        v: -70.0,
-                                        u: -14.0,
-                                        a: 0.02,
-                                        b: 0.2,
-                                        c: -100.0,
-                                        d: 8.0,
-                                        synapse_weight_multiplier: 80.0,
-                                    },
-                                    OutlineBundle {
-                                        outline: OutlineVolume {
-                                            visible: false,
-                                            colour: Color::srgb(0.0, 1.0, 0.0),
-                                            width: 5.0,
-                                        },
-                                        ..Default::default()
-                                    },
-                                    PbrBundle {
-                                        mesh: mesh.clone(),
-                                        material: leaky_neuron_material.clone(),
-                                        visibility: Visibility::Visible,
-                                        transform: Transform::from_xyz(
-                                            x as f32,
-                                            y as f32,
-                                            z as f32 + (self.layers.len() as f32 * -5.0),
-                                        ),
-                                        ..Default::default()
+                            let mut neuron = IzhikevichNeuron::builder(preset)
+                                .synapse_weight_multiplier(80.0)
+                                .build();
+                            random(world, |rng| neuron.randomize_parameters(rng, jitter));
+
+                            let mut entity = world.spawn((
+                                OutlineBundle {
+                                    outline: OutlineVolume {
+                                        visible: false,
+                                        colour: Color::srgb(0.0, 1.0, 0.0),
+                                        width: 5.0,
                                     },
-                                    ValueRecorder::default(),
-                                    Collider::cuboid(0.25, 0.25, 0.25),
-                                    column_layer.clone(),
-                                    AllowSynapses,
-                                    SimpleSpikeRecorder::default(),
-                                ))
-                                .id();
-
-                            layer.push(neuron);
+                                    ..Default::default()
+                                },
+                                PbrBundle {
+                                    mesh: mesh.clone(),
+                                    material: leaky_neuron_material.clone(),
+                                    visibility: Visibility::Visible,
+                                    transform: Transform::from_xyz(
+                                        x as f32,
+                                        y as f32,
+                                        z as f32 + (self.layers.len() as f32 * -5.0),
+                                    ),
+                                    ..Default::default()
+                                },
+                                ValueRecorder::default(),
+                                Collider::cuboid(0.25, 0.25, 0.25),
+                                column_layer.clone(),
+                                AllowSynapses,
+                                SimpleSpikeRecorder::default(),
+                            ));
+                            self.insert_neuron(&mut entity, neuron);
+
+                            layer.push(entity.id());
                         }
                     }
                 }
@@ -153,6 +201,19 @@ impl FeedForwardNetwork {
         let normalized_direction = direction.normalize();
         let rotation = Quat::from_rotation_arc(Vec3::Y, normalized_direction);
 
+        let weight = random(world, |rng| rng.gen_range(weight_range.0..=weight_range.1));
+
+        // the asymmetric rule lets inhibition run away, inhibitory synapses instead learn to hold
+        // their target at a steady rate. Strong inhibition like the lateral inhibition of a WTA
+        // layer starts above 1, which would be cut off by the first weight change
+        let (rule, w_max) = match synapse_type {
+            SynapseType::Inhibitory => (
+                StdpRule::Inhibitory(InhibitoryStdp::new(0.01, 5.0)),
+                weight_range.1.max(1.0),
+            ),
+            SynapseType::Excitatory | SynapseType::Electrical => (StdpRule::Asymmetric, 1.0),
+        };
+
         let (synapse_stalk_mesh, synapse_mesh) =
             world.resource_scope(|world, mut meshes: Mut<Assets<Mesh>>| {
                 let mut mesh = Capsule3d::new(0.05, length).mesh().build();
@@ -179,19 +240,22 @@ impl FeedForwardNetwork {
                         a_minus: -0.01,
                         tau_plus: 0.2,
                         tau_minus: 0.2,
-                        w_max: 1.0,
+                        w_max,
                         w_min: 0.0,
+                        soft_bound: false,
+                        tau_eligibility: 1000.0,
                     },
                     stdp_state: StdpState {
                         a: 0.0,
                         spike_type: StdpSpikeType::PreSpike,
+                        eligibility: 0.0,
                     },
                     source: *pre_neuron,
                     target: *post_neuron,
-                    // weight between 0 and 1
-                    weight: rand::thread_rng().gen_range(weight_range.0..=weight_range.1),
+                    weight,
                     delay: 1,
                     synapse_type,
+                    rule,
                 },
                 Visibility::Visible,
                 GlobalTransform::default(),
@@ -204,7 +268,9 @@ impl FeedForwardNetwork {
                     PbrBundle {
                         mesh: synapse_mesh.clone(),
                         material: match synapse_type {
-                            SynapseType::Excitatory => synapse_material_excitory.clone(),
+                            SynapseType::Excitatory | SynapseType::Electrical => {
+                                synapse_material_excitory.clone()
+                            }
                             SynapseType::Inhibitory => synapse_material_inhibitory.clone(),
                         },
                         transform: Transform {
@@ -229,7 +295,9 @@ impl FeedForwardNetwork {
                     PbrBundle {
                         mesh: synapse_stalk_mesh,
                         material: match synapse_type {
-                            SynapseType::Excitatory => synapse_material_excitory.clone(),
+                            SynapseType::Excitatory | SynapseType::Electrical => {
+                                synapse_material_excitory.clone()
+                            }
                             SynapseType::Inhibitory => synapse_material_inhibitory.clone(),
                         },
                         transform: Transform {
@@ -275,11 +343,11 @@ impl FeedForwardNetwork {
 
         for pre_neuron in &self.layers[source_layer] {
             for post_neuron in &self.layers[target_layer] {
-                if rand::random::<f64>() > connection_chance {
+                if random(world, |rng| rng.gen::<f64>()) > connection_chance {
                     continue;
                 }
 
-                let synapse_type = if rand::random::<f64>() < type_ratio {
+                let synapse_type = if random(world, |rng| rng.gen::<f64>()) < type_ratio {
                     SynapseType::Excitatory
                 } else {
                     SynapseType::Inhibitory
@@ -296,6 +364,55 @@ impl FeedForwardNetwork {
         }
     }
 
+    /// Connect the layers with excitatory synapses, a pair of neurons `distance` apart is connected
+    /// with probability `exp(-distance / lambda)`, so mostly nearby neurons end up connected. A
+    /// layer can be connected to itself, neurons never connect to themselves.
+    pub fn connect_layers_by_distance(
+        &mut self,
+        source_layer: usize,
+        target_layer: usize,
+        lambda: f64,
+        max_weight: f64,
+        world: &mut World,
+    ) {
+        if source_layer >= self.layers.len() || target_layer >= self.layers.len() {
+            panic!("Invalid layer index");
+        }
+        assert!(
+            lambda > 0.0,
+            "the length constant of the connection probability must be positive, got {}",
+            lambda
+        );
+
+        for pre_neuron in &self.layers[source_layer] {
+            for post_neuron in &self.layers[target_layer] {
+                if pre_neuron == post_neuron {
+                    continue;
+                }
+
+                let pre_position = world.get::<Transform>(*pre_neuron).unwrap().translation;
+                let post_position = world.get::<Transform>(*post_neuron).unwrap().translation;
+                let proximity = (-pre_position.distance(post_position) as f64 / lambda).exp();
+                if random(world, |rng| rng.gen::<f64>()) >= proximity {
+                    continue;
+                }
+
+                let weight = if self.scale_weight_with_distance {
+                    max_weight * proximity
+                } else {
+                    max_weight
+                };
+                Self::create_synapse(
+                    pre_neuron,
+                    post_neuron,
+                    SynapseType::Excitatory,
+                    (weight, weight),
+                    world,
+                );
+            }
+        }
+    }
+
     pub fn add_wta_layer(
         &mut self,
         size_x: usize,
@@ -330,45 +447,40 @@ impl FeedForwardNetwork {
         for x in 0..size_x {
             for y in 0..size_y {
                 for z in 0..size_z {
-                    let neuron = world
-                        .spawn((
-                            IzhikevichNeuron {
-                                v: -70.0,
-                                u: -14.0,
-                                a: 0.02,
-                                b: 0.2,
-                                c: -100.0,
-                                d: 8.0,
-                                synapse_weight_multiplier: 80.0,
-                            },
-                            OutlineBundle {
-                                outline: OutlineVolume {
-                                    visible: false,
-                                    colour: Color::srgb(0.0, 1.0, 0.0),
-                                    width: 5.0,
-                                },
-                                ..Default::default()
+                    let mut entity = world.spawn((
+                        OutlineBundle {
+                            outline: OutlineVolume {
+                                visible: false,
+                                colour: Color::srgb(0.0, 1.0, 0.0),
+                                width: 5.0,
                             },
-                            PbrBundle {
-                                mesh: mesh.clone(),
-                                material: leaky_neuron_material.clone(),
-                                visibility: Visibility::Visible,
-                                transform: Transform::from_xyz(
-                                    x as f32,
-                                    y as f32,
-                                    z as f32 + (self.layers.len() as f32 * -5.0),
-                                ),
-                                ..Default::default()
-                            },
-                            ValueRecorder::default(),
-                            Collider::cuboid(0.25, 0.25, 0.25),
-                            colmun_layer,
-                            AllowSynapses,
-                            SimpleSpikeRecorder::default(),
-                        ))
-                        .id();
-
-                    layer.push(neuron);
+                            ..Default::default()
+                        },
+                        PbrBundle {
+                            mesh: mesh.clone(),
+                            material: leaky_neuron_material.clone(),
+                            visibility: Visibility::Visible,
+                            transform: Transform::from_xyz(
+                                x as f32,
+                                y as f32,
+                                z as f32 + (self.layers.len() as f32 * -5.0),
+                            ),
+                            ..Default::default()
+                        },
+                        ValueRecorder::default(),
+                        Collider::cuboid(0.25, 0.25, 0.25),
+                        colmun_layer,
+                        AllowSynapses,
+                        SimpleSpikeRecorder::default(),
+                    ));
+                    self.insert_neuron(
+                        &mut entity,
+                        IzhikevichNeuron::builder(IzhikevichPreset::RegularSpiking)
+                            .synapse_weight_multiplier(80.0)
+                            .build(),
+                    );
+
+                    layer.push(entity.id());
                 }
             }
         }
@@ -394,3 +506,138 @@ impl FeedForwardNetwork {
         self.layers.push(layer);
     }
 }
+
+/// Draws from the `SimulationRng` resource when present so the network can be rebuilt from a seed.
+pub(crate) fn random<T>(world: &mut World, sample: impl FnOnce(&mut dyn RngCore) -> T) -> T {
+    match world.get_resource_mut::<SimulationRng>() {
+        Some(mut rng) => sample(&mut *rng),
+        None => sample(&mut rand::thread_rng()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_wta_weights(seed: u64) -> Vec<f64> {
+        let mut world = World::new();
+        world.init_resource::<Assets<StandardMaterial>>();
+        world.init_resource::<Assets<Mesh>>();
+        world.insert_resource(SimulationRng::from_seed(seed));
+
+        let mut ffn = FeedForwardNetwork::new();
+        ffn.add_wta_layer(2, 2, 1, &mut world, Some(ColumnLayer::L6));
+
+        world
+            .query::<&StdpSynapse>()
+            .iter(&world)
+            .map(|synapse| synapse.weight)
+            .collect()
+    }
+
+    #[test]
+    fn test_single_precision_layer() {
+        let mut world = World::new();
+        world.init_resource::<Assets<StandardMaterial>>();
+        world.init_resource::<Assets<Mesh>>();
+
+        let mut ffn = FeedForwardNetwork::new().with_precision(Precision::Single);
+        ffn.add_layer(2, 2, 1, IzhikevichPreset::RegularSpiking, &mut world, None);
+        ffn.add_wta_layer(2, 1, 1, &mut world, None);
+
+        assert_eq!(
+            world.query::<&IzhikevichNeuronF32>().iter(&world).count(),
+            6
+        );
+        assert_eq!(world.query::<&IzhikevichNeuron>().iter(&world).count(), 0);
+    }
+
+    #[test]
+    fn test_seeded_wta_weights_are_reproducible() {
+        let first = build_wta_weights(42);
+        let second = build_wta_weights(42);
+
+        assert_eq!(first.len(), 12);
+        assert_eq!(first, second);
+        assert!(first.iter().all(|w| (2.0..=4.0).contains(w)));
+    }
+
+    fn world() -> World {
+        let mut world = World::new();
+        world.init_resource::<Assets<StandardMaterial>>();
+        world.init_resource::<Assets<Mesh>>();
+        world.insert_resource(SimulationRng::from_seed(3));
+        world
+    }
+
+    #[test]
+    fn test_nearby_neurons_connect_more_often() {
+        let mut world = world();
+        // neurons 1 unit apart on a line
+        let mut ffn = FeedForwardNetwork::new().with_distance_weight_scaling(true);
+        ffn.add_layer(5, 1, 1, IzhikevichPreset::RegularSpiking, &mut world, None);
+
+        let trials = 200;
+        for _ in 0..trials {
+            ffn.connect_layers_by_distance(0, 0, 1.0, 0.5, &mut world);
+        }
+
+        let mut connections = [0; 5];
+        for synapse in world.query::<&StdpSynapse>().iter(&world) {
+            let pre = world.get::<Transform>(synapse.source).unwrap().translation;
+            let post = world.get::<Transform>(synapse.target).unwrap().translation;
+            let distance = pre.distance(post).round() as usize;
+            connections[distance] += 1;
+            assert!((synapse.weight - 0.5 * (-(distance as f64)).exp()).abs() < 1e-6);
+        }
+
+        assert_eq!(connections[0], 0);
+        // ordered pairs of neurons at distance 1, 2, 3 and 4
+        let probabilities = [8, 6, 4, 2]
+            .iter()
+            .zip(&connections[1..])
+            .map(|(pairs, connections)| *connections as f64 / (pairs * trials) as f64)
+            .collect::<Vec<_>>();
+        assert!(probabilities.windows(2).all(|pair| pair[0] > pair[1]));
+        assert!((probabilities[0] - (-1.0f64).exp()).abs() < 0.05);
+    }
+
+    #[test]
+    #[should_panic(expected = "must be positive")]
+    fn test_connect_by_distance_rejects_non_positive_lambda() {
+        let mut world = world();
+        let mut ffn = FeedForwardNetwork::new();
+        ffn.add_layer(2, 1, 1, IzhikevichPreset::RegularSpiking, &mut world, None);
+        ffn.connect_layers_by_distance(0, 0, 0.0, 0.5, &mut world);
+    }
+
+    #[test]
+    fn test_synapse_rule_follows_synapse_type() {
+        let mut world = world();
+        let mut ffn = FeedForwardNetwork::new();
+        ffn.add_layer(2, 1, 1, IzhikevichPreset::RegularSpiking, &mut world, None);
+        let (a, b) = (ffn.layers[0][0], ffn.layers[0][1]);
+
+        let excitatory = FeedForwardNetwork::create_synapse(
+            &a,
+            &b,
+            SynapseType::Excitatory,
+            (0.5, 0.5),
+            &mut world,
+        );
+        let inhibitory = FeedForwardNetwork::create_synapse(
+            &b,
+            &a,
+            SynapseType::Inhibitory,
+            (3.0, 3.0),
+            &mut world,
+        );
+
+        let excitatory = world.get::<StdpSynapse>(excitatory).unwrap();
+        assert_eq!(excitatory.rule, StdpRule::Asymmetric);
+
+        let inhibitory = world.get::<StdpSynapse>(inhibitory).unwrap();
+        assert!(matches!(inhibitory.rule, StdpRule::Inhibitory(_)));
+        assert_eq!(inhibitory.stdp_params.w_max, 3.0);
+    }
+}
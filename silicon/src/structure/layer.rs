@@ -1,28 +1,45 @@
+use std::fmt;
+
 use bevy::{
     color::{Color, LinearRgba},
     prelude::Component,
     reflect::Reflect,
 };
+use serde::{Deserialize, Serialize};
 
-#[derive(Component, Debug, PartialEq, Clone, Copy, Reflect)]
-pub enum ColumnLayer {
-    L1,
-    L2,
-    L3,
-    L4,
-    L5,
-    L6,
-}
+/// The layer a neuron belongs to. `L1` to `L6` are the layers of a cortical column, any other id
+/// can be used for structures that don't fit into a column.
+#[derive(Component, Debug, PartialEq, Eq, Hash, Clone, Copy, Reflect, Serialize, Deserialize)]
+pub struct ColumnLayer(pub u32);
 
 impl ColumnLayer {
+    pub const L1: ColumnLayer = ColumnLayer(1);
+    pub const L2: ColumnLayer = ColumnLayer(2);
+    pub const L3: ColumnLayer = ColumnLayer(3);
+    pub const L4: ColumnLayer = ColumnLayer(4);
+    pub const L5: ColumnLayer = ColumnLayer(5);
+    pub const L6: ColumnLayer = ColumnLayer(6);
+
+    pub fn custom(id: u32) -> Self {
+        ColumnLayer(id)
+    }
+
+    /// The cortical layers have fixed colors from blue to orange, every other layer gets a hue
+    /// hashed from its id so it keeps its color across runs.
     pub fn get_color(&self) -> Color {
-        match self {
-            ColumnLayer::L1 => Color::srgb(0.0, 0.0, 1.0),
-            ColumnLayer::L2 => Color::srgb(0.0, 0.5, 1.0),
-            ColumnLayer::L3 => Color::srgb(0.0, 1.0, 1.0),
-            ColumnLayer::L4 => Color::srgb(0.5, 1.0, 0.5),
-            ColumnLayer::L5 => Color::srgb(1.0, 1.0, 0.0),
-            ColumnLayer::L6 => Color::srgb(1.0, 0.5, 0.0),
+        match self.0 {
+            1 => Color::srgb(0.0, 0.0, 1.0),
+            2 => Color::srgb(0.0, 0.5, 1.0),
+            3 => Color::srgb(0.0, 1.0, 1.0),
+            4 => Color::srgb(0.5, 1.0, 0.5),
+            5 => Color::srgb(1.0, 1.0, 0.0),
+            6 => Color::srgb(1.0, 0.5, 0.0),
+            id => {
+                // Knuth's multiplicative hash spreads consecutive ids over the hue circle
+                let hash = id.wrapping_mul(2_654_435_761);
+                let hue = (hash >> 16) as f32 / (u32::MAX >> 16) as f32 * 360.0;
+                Color::hsv(hue, 0.8, 0.9)
+            }
         }
     }
 
@@ -54,6 +71,67 @@ impl ColumnLayer {
     }
 }
 
+impl fmt::Display for ColumnLayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "L{}", self.0)
+    }
+}
+
 fn refit_to_range(n: f32, start1: f32, stop1: f32, start2: f32, stop2: f32) -> f32 {
     ((n - start1) / (stop1 - start1)) * (stop2 - start2) + start2
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::{asset::Assets, pbr::StandardMaterial, prelude::World, render::mesh::Mesh};
+    use neurons::izhikevich::IzhikevichPreset;
+
+    use super::*;
+    use crate::structure::feed_forward::FeedForwardNetwork;
+
+    #[test]
+    fn test_ten_layers_have_distinct_colors() {
+        let mut world = World::new();
+        world.init_resource::<Assets<StandardMaterial>>();
+        world.init_resource::<Assets<Mesh>>();
+
+        let mut ffn = FeedForwardNetwork::new();
+        for id in 1..=10 {
+            ffn.add_layer(
+                2,
+                1,
+                1,
+                IzhikevichPreset::RegularSpiking,
+                &mut world,
+                Some(ColumnLayer::custom(id)),
+            );
+        }
+
+        let mut layers = world
+            .query::<&ColumnLayer>()
+            .iter(&world)
+            .copied()
+            .collect::<Vec<_>>();
+        layers.sort_by_key(|layer| layer.0);
+        layers.dedup();
+        assert_eq!(layers.len(), 10);
+        assert_eq!(layers[5], ColumnLayer::L6);
+
+        let colors = layers
+            .iter()
+            .map(|layer| layer.get_color().to_srgba())
+            .collect::<Vec<_>>();
+        for (i, a) in colors.iter().enumerate() {
+            for b in colors[i + 1..].iter() {
+                let distance =
+                    (a.red - b.red).abs() + (a.green - b.green).abs() + (a.blue - b.blue).abs();
+                assert!(distance > 0.1, "{a:?} and {b:?} are too similar");
+            }
+        }
+        // the hashed colors are the same every time
+        assert_eq!(
+            ColumnLayer::custom(7).get_color(),
+            ColumnLayer(7).get_color()
+        );
+    }
+}
@@ -1,4 +1,9 @@
+pub mod connectivity;
 pub mod cortical_column;
 pub mod feed_forward;
 pub mod layer;
+pub mod reservoir;
+pub mod scale_free;
+pub mod small_world;
 pub mod test_column;
+pub mod topology;
@@ -0,0 +1,339 @@
+use bevy::{
+    hierarchy::DespawnRecursiveExt,
+    prelude::{Entity, World},
+};
+use rand::{Rng, RngCore};
+use silicon_core::{Clock, SpikeRecorder};
+use simulator::SimpleSpikeRecorder;
+use synapses::{simple::SimpleSynapse, stdp::StdpSynapse, SynapseType};
+
+use super::{
+    feed_forward::{random, FeedForwardNetwork},
+    small_world::{grid_positions, spawn_neurons},
+};
+
+/// A reservoir of randomly and recurrently connected neurons for reservoir computing. The input
+/// drives the reservoir into a high dimensional state that depends on the recent history of the
+/// input, a linear readout of that state is the only part that has to be trained.
+///
+/// The recurrent weights are scaled to `spectral_radius`, below 1 the reservoir has the echo state
+/// property: the influence of the initial state fades and the state only depends on the input.
+pub struct ReservoirNetwork {
+    pub neurons: Vec<Entity>,
+    /// the recurrent weights, `[i][j]` from neuron `i` to neuron `j`, negative for inhibitory
+    /// synapses
+    pub weights: Vec<Vec<f64>>,
+    /// multiplies the weights passed to `set_input_weights`
+    pub input_scaling: f64,
+    pub input_synapses: Vec<Entity>,
+    /// one row of weights over the reservoir state per output
+    pub readout: Vec<Vec<f64>>,
+    /// time constant in ms of the spike traces that make up the reservoir state
+    pub tau_trace: f64,
+}
+
+impl ReservoirNetwork {
+    /// Spawn `n_reservoir` regular spiking neurons and connect every pair with probability
+    /// `1 - sparsity` by a `SimpleSynapse` with a random weight.
+    pub fn new(
+        n_reservoir: usize,
+        spectral_radius: f64,
+        input_scaling: f64,
+        sparsity: f64,
+        world: &mut World,
+    ) -> Self {
+        let weights = random(world, |rng| {
+            let mut weights = random_weights(n_reservoir, 1.0 - sparsity, rng);
+            let radius = estimate_spectral_radius(&weights, rng);
+            if radius > 0.0 {
+                for weight in weights.iter_mut().flatten() {
+                    *weight *= spectral_radius / radius;
+                }
+            }
+            weights
+        });
+
+        let neurons = spawn_neurons(grid_positions(n_reservoir, 2.0), world);
+        for (source, row) in weights.iter().enumerate() {
+            for (target, weight) in row.iter().enumerate() {
+                if *weight != 0.0 {
+                    spawn_synapse(neurons[source], neurons[target], *weight, world);
+                }
+            }
+        }
+
+        ReservoirNetwork {
+            neurons,
+            weights,
+            input_scaling,
+            input_synapses: vec![],
+            readout: vec![],
+            tau_trace: 20.0,
+        }
+    }
+
+    /// Connect the input neurons to the reservoir, replacing the previous input synapses.
+    /// `weights` holds a weight for every reservoir neuron per input neuron, the weights of
+    /// `entities[i]` are `weights[i * n_reservoir..(i + 1) * n_reservoir]`. The input neurons need
+    /// a `Transform` like the neurons of the reservoir.
+    pub fn set_input_weights(&mut self, entities: &[Entity], weights: &[f64], world: &mut World) {
+        assert_eq!(
+            weights.len(),
+            entities.len() * self.neurons.len(),
+            "expected a weight for every pair of input and reservoir neuron"
+        );
+
+        for synapse in self.input_synapses.drain(..) {
+            world.entity_mut(synapse).despawn_recursive();
+        }
+
+        for (input, weights) in entities.iter().zip(weights.chunks(self.neurons.len())) {
+            for (neuron, weight) in self.neurons.iter().zip(weights) {
+                let weight = weight * self.input_scaling;
+                if weight != 0.0 {
+                    let synapse = spawn_synapse(*input, *neuron, weight, world);
+                    self.input_synapses.push(synapse);
+                }
+            }
+        }
+    }
+
+    /// The spike trace of every reservoir neuron at the current time, every spike adds 1 and
+    /// decays with `tau_trace`.
+    pub fn state(&self, world: &World) -> Vec<f64> {
+        let time = world.resource::<Clock>().time;
+        self.neurons
+            .iter()
+            .map(|neuron| {
+                world
+                    .get::<SimpleSpikeRecorder>(*neuron)
+                    .map_or(0.0, |recorder| {
+                        recorder
+                            .get_spikes()
+                            .iter()
+                            .filter(|spike| **spike <= time)
+                            .map(|spike| (-(time - spike) / self.tau_trace).exp())
+                            .sum()
+                    })
+            })
+            .collect()
+    }
+
+    /// The linear readout of the current reservoir state, one value per row of `readout`.
+    pub fn read_output(&self, world: &World) -> Vec<f64> {
+        let state = self.state(world);
+        self.readout
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .zip(state.iter())
+                    .map(|(w, x)| w * x)
+                    .sum::<f64>()
+            })
+            .collect()
+    }
+}
+
+/// A `SimpleSynapse` from `source` to `target`, inhibitory for negative weights.
+fn spawn_synapse(source: Entity, target: Entity, weight: f64, world: &mut World) -> Entity {
+    let synapse_type = if weight < 0.0 {
+        SynapseType::Inhibitory
+    } else {
+        SynapseType::Excitatory
+    };
+    let weight = weight.abs();
+
+    let synapse =
+        FeedForwardNetwork::create_synapse(&source, &target, synapse_type, (weight, weight), world);
+    world
+        .entity_mut(synapse)
+        .remove::<StdpSynapse>()
+        .insert(SimpleSynapse {
+            weight,
+            delay: 1,
+            source,
+            target,
+            synapse_type,
+        });
+    synapse
+}
+
+/// An `n` x `n` matrix without self connections, every entry is drawn uniformly from [-1, 1) with
+/// probability `density` and 0 otherwise.
+fn random_weights(n: usize, density: f64, rng: &mut dyn RngCore) -> Vec<Vec<f64>> {
+    let density = density.clamp(0.0, 1.0);
+    (0..n)
+        .map(|i| {
+            (0..n)
+                .map(|j| {
+                    if i != j && rng.gen_bool(density) {
+                        rng.gen_range(-1.0..1.0)
+                    } else {
+                        0.0
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// The largest absolute eigenvalue of `weights` by power iteration. The dominant eigenvalues of a
+/// random matrix are usually a complex pair, so instead of waiting for the vector to converge
+/// this averages the growth of its norm per iteration.
+fn estimate_spectral_radius(weights: &[Vec<f64>], rng: &mut dyn RngCore) -> f64 {
+    const BURN_IN: usize = 1000;
+    const ITERATIONS: usize = 1000;
+
+    let n = weights.len();
+    let mut x = (0..n)
+        .map(|_| rng.gen_range(-1.0..1.0))
+        .collect::<Vec<f64>>();
+    let mut log_growth = 0.0;
+
+    for iteration in 0..BURN_IN + ITERATIONS {
+        let mut next = vec![0.0; n];
+        for (row, x) in weights.iter().zip(x.iter()) {
+            for (next, weight) in next.iter_mut().zip(row) {
+                *next += weight * x;
+            }
+        }
+
+        let norm = next.iter().map(|value| value * value).sum::<f64>().sqrt();
+        if norm == 0.0 {
+            return 0.0;
+        }
+        if iteration >= BURN_IN {
+            log_growth += norm.ln();
+        }
+        x = next.into_iter().map(|value| value / norm).collect();
+    }
+
+    (log_growth / ITERATIONS as f64).exp()
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{asset::Assets, pbr::StandardMaterial, render::mesh::Mesh};
+    use rand::{rngs::StdRng, SeedableRng};
+    use silicon_core::SimulationRng;
+
+    use super::*;
+
+    fn world() -> World {
+        let mut world = World::new();
+        world.init_resource::<Assets<StandardMaterial>>();
+        world.init_resource::<Assets<Mesh>>();
+        world.insert_resource(SimulationRng::from_seed(1));
+        world.insert_resource(Clock {
+            time: 100.0,
+            ..Default::default()
+        });
+        world
+    }
+
+    #[test]
+    fn test_spectral_radius() {
+        // a rotation has a complex pair of eigenvalues
+        let (cos, sin) = (0.3f64.cos() * 0.5, 0.3f64.sin() * 0.5);
+        let rotation = vec![
+            vec![cos, -sin, 0.0],
+            vec![sin, cos, 0.0],
+            vec![0.0, 0.0, 0.2],
+        ];
+        let mut rng = StdRng::seed_from_u64(1);
+        assert!((estimate_spectral_radius(&rotation, &mut rng) - 0.5).abs() < 1e-9);
+
+        let mut world = world();
+        let reservoir = ReservoirNetwork::new(100, 0.9, 1.0, 0.9, &mut world);
+        let radius = estimate_spectral_radius(&reservoir.weights, &mut rng);
+        assert!((radius - 0.9).abs() < 0.01, "spectral radius was {radius}");
+
+        let synapses = reservoir.weights.iter().flatten().filter(|w| **w != 0.0);
+        assert_eq!(
+            world.query::<&SimpleSynapse>().iter(&world).count(),
+            synapses.count()
+        );
+        assert_eq!(world.query::<&StdpSynapse>().iter(&world).count(), 0);
+    }
+
+    #[test]
+    fn test_echo_state_property() {
+        let mut world = world();
+        let reservoir = ReservoirNetwork::new(100, 0.9, 1.0, 0.9, &mut world);
+        let mut rng = StdRng::seed_from_u64(2);
+        let input_weights = (0..100)
+            .map(|_| rng.gen_range(-1.0..1.0))
+            .collect::<Vec<f64>>();
+
+        // the rate based reservoir with the same weights, from two different initial states
+        let step = |state: &[f64], input: f64| {
+            (0..state.len())
+                .map(|j| {
+                    let recurrent = (0..state.len())
+                        .map(|i| reservoir.weights[i][j] * state[i])
+                        .sum::<f64>();
+                    (recurrent + input_weights[j] * input).tanh()
+                })
+                .collect::<Vec<_>>()
+        };
+        let mut a = (0..100)
+            .map(|_| rng.gen_range(-1.0..1.0))
+            .collect::<Vec<_>>();
+        let mut b = (0..100)
+            .map(|_| rng.gen_range(-1.0..1.0))
+            .collect::<Vec<_>>();
+
+        for _ in 0..300 {
+            let input = rng.gen_range(-1.0..1.0);
+            a = step(&a, input);
+            b = step(&b, input);
+        }
+
+        let distance = a
+            .iter()
+            .zip(b.iter())
+            .map(|(a, b)| (a - b).powi(2))
+            .sum::<f64>()
+            .sqrt();
+        assert!(distance < 1e-6, "states are {distance} apart");
+    }
+
+    #[test]
+    fn test_input_and_readout() {
+        let mut world = world();
+        let mut reservoir = ReservoirNetwork::new(3, 0.9, 0.5, 0.0, &mut world);
+        let input = spawn_neurons(grid_positions(1, 2.0).map(|p| p - 4.0), &mut world)[0];
+
+        reservoir.set_input_weights(&[input], &[1.0, 0.0, -2.0], &mut world);
+        reservoir.set_input_weights(&[input], &[1.0, 0.0, -2.0], &mut world);
+        let inputs = reservoir
+            .input_synapses
+            .iter()
+            .map(|synapse| world.get::<SimpleSynapse>(*synapse).unwrap())
+            .map(|synapse| (synapse.target, synapse.weight, synapse.synapse_type))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            inputs,
+            [
+                (reservoir.neurons[0], 0.5, SynapseType::Excitatory),
+                (reservoir.neurons[2], 1.0, SynapseType::Inhibitory),
+            ]
+        );
+
+        for (neuron, spikes) in reservoir
+            .neurons
+            .iter()
+            .zip([vec![100.0], vec![], vec![80.0]])
+        {
+            let mut recorder = world.get_mut::<SimpleSpikeRecorder>(*neuron).unwrap();
+            for spike in spikes {
+                recorder.record_spike(spike);
+            }
+        }
+        reservoir.readout = vec![vec![1.0, 1.0, 0.0], vec![0.0, 0.0, 2.0]];
+
+        let output = reservoir.read_output(&world);
+        assert_eq!(output[0], 1.0);
+        assert!((output[1] - 2.0 * (-1.0f64).exp()).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,190 @@
+use std::collections::BTreeMap;
+
+use bevy::prelude::{Entity, World};
+use rand::{Rng, RngCore};
+use synapses::SynapseType;
+
+use super::{
+    feed_forward::{random, FeedForwardNetwork},
+    small_world::{grid_positions, spawn_neurons},
+};
+
+/// A Barabasi-Albert scale-free network. It starts from a few fully connected neurons, every new
+/// neuron then connects to `m` existing neurons, picked with a probability proportional to their
+/// degree. The well connected neurons keep attracting connections, so the degrees follow a power
+/// law `P(k) ~ k^-3` with a few hubs and many sparsely connected neurons.
+pub struct ScaleFreeNetwork {
+    pub neurons: Vec<Entity>,
+    /// connections as indices into `neurons`, from the newer to the older neuron
+    pub edges: Vec<(usize, usize)>,
+}
+
+impl ScaleFreeNetwork {
+    /// Spawn `total_neurons` regular spiking neurons on a grid and connect them with excitatory
+    /// STDP synapses. The first `initial_neurons` are connected to each other, every following
+    /// neuron gets `edges_per_step` synapses onto the neurons before it.
+    pub fn new(
+        initial_neurons: usize,
+        total_neurons: usize,
+        edges_per_step: usize,
+        world: &mut World,
+    ) -> Self {
+        let edges = random(world, |rng| {
+            barabasi_albert(initial_neurons, total_neurons, edges_per_step, rng)
+        });
+
+        let neurons = spawn_neurons(grid_positions(total_neurons, 2.0), world);
+
+        for (source, target) in edges.iter() {
+            FeedForwardNetwork::create_synapse(
+                &neurons[*source],
+                &neurons[*target],
+                SynapseType::Excitatory,
+                (0.1, 0.3),
+                world,
+            );
+        }
+
+        ScaleFreeNetwork { neurons, edges }
+    }
+
+    /// The number of connections of every neuron, ignoring their direction.
+    pub fn degrees(&self) -> Vec<usize> {
+        degrees(self.neurons.len(), &self.edges)
+    }
+
+    /// `(degree, number of neurons with that degree)` pairs, sorted by degree.
+    pub fn degree_distribution(&self) -> Vec<(usize, usize)> {
+        degree_distribution(&self.degrees())
+    }
+}
+
+fn degrees(n: usize, edges: &[(usize, usize)]) -> Vec<usize> {
+    let mut degrees = vec![0; n];
+    for (a, b) in edges {
+        degrees[*a] += 1;
+        degrees[*b] += 1;
+    }
+    degrees
+}
+
+fn degree_distribution(degrees: &[usize]) -> Vec<(usize, usize)> {
+    let mut distribution = BTreeMap::new();
+    for degree in degrees {
+        *distribution.entry(*degree).or_insert(0) += 1;
+    }
+    distribution.into_iter().collect()
+}
+
+/// The edges of a Barabasi-Albert graph with `n` nodes, grown from a complete graph of `initial`
+/// nodes by attaching every new node to `m` distinct existing nodes.
+fn barabasi_albert(
+    initial: usize,
+    n: usize,
+    m: usize,
+    rng: &mut dyn RngCore,
+) -> Vec<(usize, usize)> {
+    let initial = initial.clamp(1, n.max(1));
+    let mut edges = vec![];
+    // every node appears once per connection, so a uniform pick is proportional to the degree
+    let mut endpoints = vec![];
+    for a in 0..initial {
+        for b in a + 1..initial {
+            edges.push((b, a));
+            endpoints.extend([a, b]);
+        }
+    }
+
+    for node in initial..n {
+        let mut targets = Vec::with_capacity(m.min(node));
+        while targets.len() < m.min(node) {
+            // a single initial node has no connections yet to attach to
+            let target = if endpoints.is_empty() {
+                rng.gen_range(0..node)
+            } else {
+                endpoints[rng.gen_range(0..endpoints.len())]
+            };
+            if !targets.contains(&target) {
+                targets.push(target);
+            }
+        }
+
+        for target in targets {
+            edges.push((node, target));
+            endpoints.extend([node, target]);
+        }
+    }
+
+    edges
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{asset::Assets, pbr::StandardMaterial, render::mesh::Mesh};
+    use rand::{rngs::StdRng, SeedableRng};
+    use silicon_core::SimulationRng;
+    use synapses::stdp::StdpSynapse;
+
+    use super::*;
+
+    #[test]
+    fn test_network() {
+        let mut world = World::new();
+        world.init_resource::<Assets<StandardMaterial>>();
+        world.init_resource::<Assets<Mesh>>();
+        world.insert_resource(SimulationRng::from_seed(1));
+
+        let network = ScaleFreeNetwork::new(3, 50, 2, &mut world);
+
+        // 3 initial connections and 2 for each of the other 47 neurons
+        assert_eq!(network.edges.len(), 3 + 47 * 2);
+        assert_eq!(world.query::<&StdpSynapse>().iter(&world).count(), 97);
+        assert!(network.edges.iter().all(|(a, b)| a > b));
+        assert!(network.degrees().iter().all(|degree| *degree >= 2));
+
+        let distribution = network.degree_distribution();
+        assert_eq!(
+            distribution.iter().map(|(_, count)| count).sum::<usize>(),
+            50
+        );
+        assert!(distribution.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    }
+
+    #[test]
+    fn test_power_law_degree_distribution() {
+        let n = 10000;
+        let m = 3;
+        let edges = barabasi_albert(m, n, m, &mut StdRng::seed_from_u64(1));
+        let distribution = degree_distribution(&degrees(n, &edges));
+
+        // P(K >= k) ~ k^-(gamma - 1), fitted on log-log scale away from the minimum degree and
+        // the sparse tail
+        let points = distribution
+            .iter()
+            .filter(|(degree, _)| (2 * m..=20 * m).contains(degree))
+            .map(|(degree, _)| {
+                let at_least = distribution
+                    .iter()
+                    .filter(|(other, _)| other >= degree)
+                    .map(|(_, count)| count)
+                    .sum::<usize>();
+                ((*degree as f64).ln(), (at_least as f64 / n as f64).ln())
+            })
+            .collect::<Vec<_>>();
+        let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / points.len() as f64;
+        let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / points.len() as f64;
+        let slope = points
+            .iter()
+            .map(|(x, y)| (x - mean_x) * (y - mean_y))
+            .sum::<f64>()
+            / points
+                .iter()
+                .map(|(x, _)| (x - mean_x).powi(2))
+                .sum::<f64>();
+        let exponent = 1.0 - slope;
+
+        assert!((exponent - 3.0).abs() < 0.3, "exponent was {exponent}");
+        // the oldest neurons became hubs
+        assert!(distribution.last().unwrap().0 > 100);
+    }
+}
@@ -0,0 +1,287 @@
+use std::{collections::VecDeque, f32::consts::TAU};
+
+use bevy::{
+    asset::Assets,
+    color::{Color, LinearRgba},
+    pbr::{PbrBundle, StandardMaterial},
+    prelude::{Entity, Mut, World},
+    render::{
+        mesh::{Mesh, MeshBuilder, Meshable},
+        view::Visibility,
+    },
+    transform::components::Transform,
+};
+use bevy_math::{primitives::Cuboid, Vec3};
+use bevy_mod_outline::{OutlineBundle, OutlineMeshExt, OutlineVolume};
+use bevy_rapier3d::geometry::Collider;
+use neurons::izhikevich::{IzhikevichNeuron, IzhikevichPreset};
+use rand::{Rng, RngCore};
+use silicon_core::ValueRecorder;
+use simulator::SimpleSpikeRecorder;
+use synapses::{AllowSynapses, SynapseType};
+
+use super::{
+    feed_forward::{random, FeedForwardNetwork},
+    layer::ColumnLayer,
+};
+
+/// A Watts-Strogatz small-world network. The neurons sit on a ring, every neuron is connected to
+/// its `k` nearest neighbours and every connection is then rewired to a random neuron with
+/// probability `p`. A few rewired connections are enough to make the path between any two neurons
+/// short while the neighbourhoods stay clustered.
+pub struct SmallWorldNetwork {
+    pub neurons: Vec<Entity>,
+    /// connections as indices into `neurons`, from the neuron that owns the connection on the ring
+    pub edges: Vec<(usize, usize)>,
+}
+
+impl SmallWorldNetwork {
+    /// Spawn `n_neurons` regular spiking neurons on a ring and wire them. `k_neighbors` is rounded
+    /// down to an even number, half of the neighbours are on either side. Call `connect` to create
+    /// the synapses.
+    pub fn new(n_neurons: usize, k_neighbors: usize, rewire_prob: f64, world: &mut World) -> Self {
+        let edges = random(world, |rng| {
+            watts_strogatz(n_neurons, k_neighbors, rewire_prob, rng)
+        });
+
+        // one unit between neighbours on the ring
+        let radius = n_neurons as f32 / TAU;
+        let positions = (0..n_neurons).map(|i| {
+            let angle = i as f32 / n_neurons as f32 * TAU;
+            Vec3::new(radius * angle.cos(), radius * angle.sin(), 0.0)
+        });
+        let neurons = spawn_neurons(positions, world);
+
+        SmallWorldNetwork { neurons, edges }
+    }
+
+    /// Create an excitatory STDP synapse for every connection.
+    pub fn connect(&self, weight_range: (f64, f64), world: &mut World) {
+        for (source, target) in self.edges.iter() {
+            FeedForwardNetwork::create_synapse(
+                &self.neurons[*source],
+                &self.neurons[*target],
+                SynapseType::Excitatory,
+                weight_range,
+                world,
+            );
+        }
+    }
+
+    /// The neighbours of every neuron, ignoring the direction of the connections.
+    fn adjacency(&self) -> Vec<Vec<usize>> {
+        let mut adjacency = vec![vec![]; self.neurons.len()];
+        for (a, b) in self.edges.iter() {
+            adjacency[*a].push(*b);
+            adjacency[*b].push(*a);
+        }
+        adjacency
+    }
+
+    /// The average number of connections on the shortest path between two neurons, over all
+    /// pairs that are connected at all.
+    pub fn average_path_length(&self) -> f64 {
+        let adjacency = self.adjacency();
+        let (mut total, mut pairs) = (0, 0);
+
+        for start in 0..adjacency.len() {
+            let mut distance = vec![None; adjacency.len()];
+            distance[start] = Some(0);
+            let mut queue = VecDeque::from([start]);
+            while let Some(neuron) = queue.pop_front() {
+                let next = distance[neuron].unwrap() + 1;
+                for neighbour in adjacency[neuron].iter() {
+                    if distance[*neighbour].is_none() {
+                        distance[*neighbour] = Some(next);
+                        total += next;
+                        pairs += 1;
+                        queue.push_back(*neighbour);
+                    }
+                }
+            }
+        }
+
+        if pairs == 0 {
+            return 0.0;
+        }
+        total as f64 / pairs as f64
+    }
+
+    /// The fraction of the possible connections between the neighbours of a neuron that exist,
+    /// averaged over all neurons. Neurons with fewer than two neighbours count as 0.
+    pub fn clustering_coefficient(&self) -> f64 {
+        let adjacency = self.adjacency();
+        if adjacency.is_empty() {
+            return 0.0;
+        }
+
+        let total = adjacency
+            .iter()
+            .map(|neighbours| {
+                let degree = neighbours.len();
+                if degree < 2 {
+                    return 0.0;
+                }
+
+                let links = neighbours
+                    .iter()
+                    .enumerate()
+                    .flat_map(|(i, a)| neighbours[i + 1..].iter().map(move |b| (a, b)))
+                    .filter(|(a, b)| adjacency[**a].contains(b))
+                    .count();
+                2.0 * links as f64 / (degree * (degree - 1)) as f64
+            })
+            .sum::<f64>();
+        total / adjacency.len() as f64
+    }
+}
+
+/// `n` positions filling a cube, `spacing` apart.
+pub(crate) fn grid_positions(n: usize, spacing: f32) -> impl Iterator<Item = Vec3> {
+    let side = (n as f32).cbrt().ceil().max(1.0) as usize;
+    (0..n).map(move |i| {
+        Vec3::new(
+            (i % side) as f32,
+            (i / side % side) as f32,
+            (i / (side * side)) as f32,
+        ) * spacing
+    })
+}
+
+/// Spawn a regular spiking neuron at every position, with the same components as the neurons of
+/// a `FeedForwardNetwork` layer.
+pub(crate) fn spawn_neurons(
+    positions: impl IntoIterator<Item = Vec3>,
+    world: &mut World,
+) -> Vec<Entity> {
+    let (material, mesh) =
+        world.resource_scope(|world, mut materials: Mut<Assets<StandardMaterial>>| {
+            let material = materials.add(StandardMaterial {
+                emissive: LinearRgba::rgb(23.0, 9.0, 3.0),
+                ..Default::default()
+            });
+
+            let mesh = world.resource_scope(|_, mut meshes: Mut<Assets<Mesh>>| {
+                let mut mesh = Cuboid::new(0.5, 0.5, 0.5).mesh().build();
+                mesh.generate_outline_normals().unwrap();
+                meshes.add(mesh)
+            });
+
+            (material, mesh)
+        });
+
+    positions
+        .into_iter()
+        .map(|position| {
+            world
+                .spawn((
+                    OutlineBundle {
+                        outline: OutlineVolume {
+                            visible: false,
+                            colour: Color::srgb(0.0, 1.0, 0.0),
+                            width: 5.0,
+                        },
+                        ..Default::default()
+                    },
+                    PbrBundle {
+                        mesh: mesh.clone(),
+                        material: material.clone(),
+                        visibility: Visibility::Visible,
+                        transform: Transform::from_translation(position),
+                        ..Default::default()
+                    },
+                    ValueRecorder::default(),
+                    Collider::cuboid(0.25, 0.25, 0.25),
+                    ColumnLayer::L1,
+                    AllowSynapses,
+                    SimpleSpikeRecorder::default(),
+                    IzhikevichNeuron::builder(IzhikevichPreset::RegularSpiking)
+                        .synapse_weight_multiplier(80.0)
+                        .build(),
+                ))
+                .id()
+        })
+        .collect()
+}
+
+/// The edges of a ring lattice of `n` nodes with `k` neighbours each, every edge rewired with
+/// probability `p` to a random node it isn't connected to yet.
+fn watts_strogatz(n: usize, k: usize, p: f64, rng: &mut dyn RngCore) -> Vec<(usize, usize)> {
+    let half = (k / 2).min(n.saturating_sub(1) / 2);
+    let mut adjacency = vec![vec![false; n]; n];
+    let mut edges = vec![];
+    for i in 0..n {
+        for j in 1..=half {
+            let target = (i + j) % n;
+            adjacency[i][target] = true;
+            adjacency[target][i] = true;
+            edges.push((i, target));
+        }
+    }
+
+    for (source, target) in edges.iter_mut() {
+        if !rng.gen_bool(p.clamp(0.0, 1.0)) {
+            continue;
+        }
+
+        let candidates = (0..n)
+            .filter(|other| *other != *source && !adjacency[*source][*other])
+            .collect::<Vec<_>>();
+        if candidates.is_empty() {
+            continue;
+        }
+
+        let new_target = candidates[rng.gen_range(0..candidates.len())];
+        adjacency[*source][*target] = false;
+        adjacency[*target][*source] = false;
+        adjacency[*source][new_target] = true;
+        adjacency[new_target][*source] = true;
+        *target = new_target;
+    }
+
+    edges
+}
+
+#[cfg(test)]
+mod tests {
+    use silicon_core::SimulationRng;
+    use synapses::stdp::StdpSynapse;
+
+    use super::*;
+
+    fn world() -> World {
+        let mut world = World::new();
+        world.init_resource::<Assets<StandardMaterial>>();
+        world.init_resource::<Assets<Mesh>>();
+        world.insert_resource(SimulationRng::from_seed(1));
+        world
+    }
+
+    #[test]
+    fn test_ring_lattice() {
+        let mut world = world();
+        let network = SmallWorldNetwork::new(100, 6, 0.0, &mut world);
+
+        assert_eq!(network.edges.len(), 300);
+        // 3 * (k - 2) / (4 * (k - 1)) for a ring lattice
+        assert!((network.clustering_coefficient() - 0.6).abs() < 1e-9);
+        assert!(network.average_path_length() > 8.0);
+
+        network.connect((0.5, 0.5), &mut world);
+        assert_eq!(world.query::<&StdpSynapse>().iter(&world).count(), 300);
+    }
+
+    #[test]
+    fn test_rewiring_shortens_paths_and_loses_clustering() {
+        let mut world = world();
+        let small_world = SmallWorldNetwork::new(100, 6, 0.1, &mut world);
+        let random = SmallWorldNetwork::new(100, 6, 1.0, &mut world);
+
+        assert!(small_world.clustering_coefficient() > 0.3);
+        assert!(small_world.average_path_length() < 5.0);
+        // about k / n for a random graph
+        assert!(random.clustering_coefficient() < 0.12);
+        assert!(random.average_path_length() < 3.5);
+        assert_eq!(random.edges.len(), 300);
+    }
+}
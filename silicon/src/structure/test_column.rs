@@ -11,7 +11,7 @@ use bevy::{
 };
 use bevy_math::primitives::Cuboid;
 use bevy_rapier3d::geometry::Collider;
-use neurons::izhikevich::IzhikevichNeuron;
+use neurons::izhikevich::{IzhikevichNeuron, IzhikevichPreset};
 use simulator::SimpleSpikeRecorder;
 use synapses::AllowSynapses;
 
@@ -36,15 +36,9 @@ impl TestColumn {
                     });
 
                     commands.spawn((
-                        IzhikevichNeuron {
-                            v: -70.0,
-                            u: -14.0,
-                            a: 0.02,
-                            b: 0.2,
-                            c: -100.0,
-                            d: 8.0,
-                            synapse_weight_multiplier: 80.0,
-                        },
+                        IzhikevichNeuron::builder(IzhikevichPreset::RegularSpiking)
+                            .synapse_weight_multiplier(80.0)
+                            .build(),
                         PbrBundle {
                             mesh: mesh.clone(),
                             material: leaky_neuron_material,
@@ -70,15 +64,9 @@ impl TestColumn {
                     });
 
                     commands.spawn((
-                        IzhikevichNeuron {
-                            v: -70.0,
-                            u: -14.0,
-                            a: 0.02,
-                            b: 0.2,
-                            c: -100.0,
-                            d: 8.0,
-                            synapse_weight_multiplier: 80.0,
-                        },
+                        IzhikevichNeuron::builder(IzhikevichPreset::RegularSpiking)
+                            .synapse_weight_multiplier(80.0)
+                            .build(),
                         PbrBundle {
                             mesh: mesh.clone(),
                             material: leaky_neuron_material,
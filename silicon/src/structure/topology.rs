@@ -0,0 +1,427 @@
+//! Save the neurons and synapses of a network to a JSON file and build it again from the file.
+//!
+//! Unlike a checkpoint this only describes the topology: the neuron models with their
+//! parameters, where the neurons are and how they are connected. Entities are replaced by their
+//! index in the file, loading spawns fresh entities. Recorded spikes and membrane potentials
+//! aren't saved.
+
+use std::{fs, io, path::Path};
+
+use bevy::{
+    asset::Assets,
+    color::{Color, LinearRgba},
+    pbr::{PbrBundle, StandardMaterial},
+    prelude::{Entity, Mut, World},
+    render::{
+        mesh::{Mesh, MeshBuilder, Meshable},
+        view::Visibility,
+    },
+    transform::components::Transform,
+    utils::HashMap,
+};
+use bevy_math::{primitives::Cuboid, Vec3};
+use bevy_mod_outline::{OutlineBundle, OutlineMeshExt, OutlineVolume};
+use bevy_rapier3d::geometry::Collider;
+use bevy_trait_query::One;
+use neurons::{
+    izhikevich::IzhikevichNeuron,
+    leaky::LifNeuron,
+    single_precision::{IzhikevichNeuronF32, LifNeuronF32},
+};
+use serde::{Deserialize, Serialize};
+use silicon_core::{Neuron, ValueRecorder};
+use simulator::SimpleSpikeRecorder;
+use synapses::{
+    simple::SimpleSynapse,
+    stdp::{StdpParams, StdpSynapse},
+    AllowSynapses, Synapse, SynapseType,
+};
+
+use super::{feed_forward::FeedForwardNetwork, layer::ColumnLayer};
+
+/// The neuron model of a `NeuronSpec` together with its parameters.
+#[derive(Debug, Serialize, Deserialize)]
+pub enum NeuronModel {
+    Lif(LifNeuron),
+    Izhikevich(IzhikevichNeuron),
+    LifF32(LifNeuronF32),
+    IzhikevichF32(IzhikevichNeuronF32),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NeuronSpec {
+    pub model: NeuronModel,
+    pub position: [f32; 3],
+    pub layer: Option<ColumnLayer>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SynapseSpec {
+    /// index of the presynaptic neuron in `NetworkSpec::neurons`
+    pub source: usize,
+    /// index of the postsynaptic neuron in `NetworkSpec::neurons`
+    pub target: usize,
+    pub weight: f64,
+    pub delay: u32,
+    pub synapse_type: SynapseType,
+    /// the learning rule of the synapse, `None` for a synapse with a fixed weight
+    pub stdp: Option<StdpParams>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct NetworkSpec {
+    pub neurons: Vec<NeuronSpec>,
+    pub synapses: Vec<SynapseSpec>,
+}
+
+#[derive(Debug)]
+pub enum TopologyError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    /// The network contains a neuron or synapse that has no `NeuronModel` or `SynapseSpec`, the
+    /// name of its component.
+    Unsupported(String),
+}
+
+impl From<io::Error> for TopologyError {
+    fn from(error: io::Error) -> Self {
+        TopologyError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for TopologyError {
+    fn from(error: serde_json::Error) -> Self {
+        TopologyError::Json(error)
+    }
+}
+
+impl std::fmt::Display for TopologyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TopologyError::Io(error) => write!(f, "failed to access the network file: {}", error),
+            TopologyError::Json(error) => write!(f, "invalid network file: {}", error),
+            TopologyError::Unsupported(component) => {
+                write!(f, "{} can't be saved in a network file", component)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TopologyError {}
+
+impl NetworkSpec {
+    /// Describe the neurons and synapses in `world`, fails on the first neuron or synapse type
+    /// that can't be described instead of leaving it out of the network.
+    ///
+    /// Trait queries need mutable access to the world to initialize their state, which is why this
+    /// takes `&mut World`.
+    pub fn from_world(world: &mut World) -> Result<Self, TopologyError> {
+        let neurons = world
+            .query::<(Entity, One<&dyn Neuron>)>()
+            .iter(world)
+            .map(|(entity, _)| entity)
+            .collect::<Vec<_>>();
+        let synapses = world
+            .query::<(Entity, One<&dyn Synapse>)>()
+            .iter(world)
+            .map(|(entity, _)| entity)
+            .collect::<Vec<_>>();
+
+        let mut network = NetworkSpec::default();
+        let mut indices = HashMap::new();
+
+        for entity in neurons.into_iter().map(|entity| world.entity(entity)) {
+            let model = if let Some(neuron) = entity.get::<LifNeuron>() {
+                NeuronModel::Lif(neuron.clone())
+            } else if let Some(neuron) = entity.get::<IzhikevichNeuron>() {
+                NeuronModel::Izhikevich(neuron.clone())
+            } else if let Some(neuron) = entity.get::<LifNeuronF32>() {
+                NeuronModel::LifF32(neuron.clone())
+            } else if let Some(neuron) = entity.get::<IzhikevichNeuronF32>() {
+                NeuronModel::IzhikevichF32(neuron.clone())
+            } else {
+                return Err(TopologyError::Unsupported(model_name(
+                    world,
+                    entity.id(),
+                    "neurons::",
+                )));
+            };
+
+            indices.insert(entity.id(), network.neurons.len());
+            network.neurons.push(NeuronSpec {
+                model,
+                position: entity
+                    .get::<Transform>()
+                    .map_or([0.0; 3], |transform| transform.translation.to_array()),
+                layer: entity.get::<ColumnLayer>().copied(),
+            });
+        }
+
+        for entity in synapses.into_iter().map(|entity| world.entity(entity)) {
+            let (source, target, weight, delay, synapse_type, stdp) =
+                if let Some(synapse) = entity.get::<StdpSynapse>() {
+                    (
+                        synapse.source,
+                        synapse.target,
+                        synapse.weight,
+                        synapse.delay,
+                        synapse.synapse_type,
+                        Some(synapse.stdp_params.clone()),
+                    )
+                } else if let Some(synapse) = entity.get::<SimpleSynapse>() {
+                    (
+                        synapse.source,
+                        synapse.target,
+                        synapse.weight,
+                        synapse.delay,
+                        synapse.synapse_type,
+                        None,
+                    )
+                } else {
+                    return Err(TopologyError::Unsupported(model_name(
+                        world,
+                        entity.id(),
+                        "synapses::",
+                    )));
+                };
+
+            let (Some(source), Some(target)) = (indices.get(&source), indices.get(&target)) else {
+                continue;
+            };
+
+            network.synapses.push(SynapseSpec {
+                source: *source,
+                target: *target,
+                weight,
+                delay,
+                synapse_type,
+                stdp,
+            });
+        }
+
+        Ok(network)
+    }
+
+    /// Spawn the network into `world`, returns the new neuron entities in the order of `neurons`.
+    pub fn spawn(self, world: &mut World) -> Vec<Entity> {
+        let (material, mesh) =
+            world.resource_scope(|world, mut materials: Mut<Assets<StandardMaterial>>| {
+                let material = materials.add(StandardMaterial {
+                    emissive: LinearRgba::rgb(23.0, 9.0, 3.0),
+                    ..Default::default()
+                });
+
+                let mesh = world.resource_scope(|_, mut meshes: Mut<Assets<Mesh>>| {
+                    let mut mesh = Cuboid::new(0.5, 0.5, 0.5).mesh().build();
+                    mesh.generate_outline_normals().unwrap();
+                    meshes.add(mesh)
+                });
+
+                (material, mesh)
+            });
+
+        let neurons = self
+            .neurons
+            .into_iter()
+            .map(|neuron| {
+                let mut entity = world.spawn((
+                    OutlineBundle {
+                        outline: OutlineVolume {
+                            visible: false,
+                            colour: Color::srgb(0.0, 1.0, 0.0),
+                            width: 5.0,
+                        },
+                        ..Default::default()
+                    },
+                    PbrBundle {
+                        mesh: mesh.clone(),
+                        material: material.clone(),
+                        visibility: Visibility::Visible,
+                        transform: Transform::from_translation(Vec3::from_array(neuron.position)),
+                        ..Default::default()
+                    },
+                    ValueRecorder::default(),
+                    Collider::cuboid(0.25, 0.25, 0.25),
+                    neuron.layer.unwrap_or(ColumnLayer::L1),
+                    AllowSynapses,
+                    SimpleSpikeRecorder::default(),
+                ));
+
+                match neuron.model {
+                    NeuronModel::Lif(neuron) => entity.insert(neuron),
+                    NeuronModel::Izhikevich(neuron) => entity.insert(neuron),
+                    NeuronModel::LifF32(neuron) => entity.insert(neuron),
+                    NeuronModel::IzhikevichF32(neuron) => entity.insert(neuron),
+                };
+
+                entity.id()
+            })
+            .collect::<Vec<_>>();
+
+        for spec in self.synapses {
+            let (Some(source), Some(target)) = (neurons.get(spec.source), neurons.get(spec.target))
+            else {
+                continue;
+            };
+
+            let synapse = FeedForwardNetwork::create_synapse(
+                source,
+                target,
+                spec.synapse_type,
+                (spec.weight, spec.weight),
+                world,
+            );
+
+            let mut synapse = world.entity_mut(synapse);
+            match spec.stdp {
+                Some(stdp_params) => {
+                    let mut stdp_synapse = synapse.get_mut::<StdpSynapse>().unwrap();
+                    stdp_synapse.delay = spec.delay;
+                    stdp_synapse.stdp_params = stdp_params;
+                }
+                None => {
+                    synapse.remove::<StdpSynapse>().insert(SimpleSynapse {
+                        weight: spec.weight,
+                        delay: spec.delay,
+                        source: *source,
+                        target: *target,
+                        synapse_type: spec.synapse_type,
+                    });
+                }
+            }
+        }
+
+        neurons
+    }
+}
+
+/// The name of the component of `entity` from the `krate` the model was defined in, the neurons
+/// or synapses crate. Falls back to the entity for models from anywhere else.
+fn model_name(world: &World, entity: Entity, krate: &str) -> String {
+    world
+        .inspect_entity(entity)
+        .into_iter()
+        .map(|component| component.name())
+        .find(|name| name.starts_with(krate))
+        .map_or_else(|| format!("the model of {:?}", entity), ToString::to_string)
+}
+
+/// Write the topology of the network in `world` to `path` as JSON.
+pub fn save_network(world: &mut World, path: &Path) -> Result<(), TopologyError> {
+    let network = NetworkSpec::from_world(world)?;
+    fs::write(path, serde_json::to_string_pretty(&network)?)?;
+    Ok(())
+}
+
+/// Spawn the network saved with `save_network` at `path` into `world`.
+pub fn load_network(world: &mut World, path: &Path) -> Result<(), TopologyError> {
+    let network = serde_json::from_str::<NetworkSpec>(&fs::read_to_string(path)?)?;
+    network.spawn(world);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_trait_query::RegisterExt;
+    use neurons::{hodgkin_huxley::HodgkinHuxleyNeuron, izhikevich::IzhikevichPreset};
+    use silicon_core::SimulationRng;
+    use synapses::stp::StpSynapse;
+
+    use super::*;
+
+    fn world() -> World {
+        let mut world = World::new();
+        world.init_resource::<Assets<StandardMaterial>>();
+        world.init_resource::<Assets<Mesh>>();
+        world.insert_resource(SimulationRng::from_seed(7));
+        world
+            .register_component_as::<dyn Neuron, LifNeuron>()
+            .register_component_as::<dyn Neuron, IzhikevichNeuron>()
+            .register_component_as::<dyn Neuron, HodgkinHuxleyNeuron>()
+            .register_component_as::<dyn Synapse, SimpleSynapse>()
+            .register_component_as::<dyn Synapse, StdpSynapse>()
+            .register_component_as::<dyn Synapse, StpSynapse>();
+        world
+    }
+
+    fn synapses(world: &mut World) -> (usize, f64) {
+        let weights = world
+            .query::<&StdpSynapse>()
+            .iter(world)
+            .map(|synapse| synapse.weight)
+            .collect::<Vec<_>>();
+        (weights.len(), weights.iter().sum())
+    }
+
+    #[test]
+    fn test_save_and_load_round_trip() {
+        let mut original = world();
+        let mut ffn = FeedForwardNetwork::new();
+        ffn.add_layer(
+            3,
+            3,
+            1,
+            IzhikevichPreset::RegularSpiking,
+            &mut original,
+            Some(ColumnLayer::L1),
+        );
+        ffn.add_wta_layer(2, 1, 1, &mut original, Some(ColumnLayer::L6));
+        ffn.connect_layers(0, 1, 0.8, 0.8, &mut original);
+
+        let path = std::env::temp_dir().join("silicon_topology_round_trip.json");
+        save_network(&mut original, &path).unwrap();
+        let mut loaded = world();
+        load_network(&mut loaded, &path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let (count, weight_sum) = synapses(&mut original);
+        let (loaded_count, loaded_weight_sum) = synapses(&mut loaded);
+        assert!(count > 2);
+        assert_eq!(loaded_count, count);
+        assert!((loaded_weight_sum - weight_sum).abs() < 1e-9);
+        assert_eq!(
+            loaded
+                .query::<(&IzhikevichNeuron, &ColumnLayer)>()
+                .iter(&loaded)
+                .filter(|(_, layer)| **layer == ColumnLayer::L6)
+                .count(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_unsupported_neuron_is_an_error() {
+        let mut world = world();
+        world.spawn(LifNeuron::default());
+        world.spawn(HodgkinHuxleyNeuron::default());
+
+        match NetworkSpec::from_world(&mut world) {
+            Err(TopologyError::Unsupported(component)) => {
+                assert_eq!(component, "neurons::hodgkin_huxley::HodgkinHuxleyNeuron")
+            }
+            result => panic!("expected an unsupported neuron, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn test_unsupported_synapse_is_an_error() {
+        let mut world = world();
+        let [source, target] = [(); 2].map(|_| world.spawn(LifNeuron::default()).id());
+        world.spawn(StpSynapse::new(
+            source,
+            target,
+            1.0,
+            SynapseType::Excitatory,
+            0.5,
+            800.0,
+            0.0,
+        ));
+
+        match NetworkSpec::from_world(&mut world) {
+            Err(TopologyError::Unsupported(component)) => {
+                assert_eq!(component, "synapses::stp::StpSynapse")
+            }
+            result => panic!("expected an unsupported synapse, got {:?}", result),
+        }
+    }
+}
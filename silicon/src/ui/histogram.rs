@@ -0,0 +1,161 @@
+use bevy::{
+    prelude::{Query, Res, ResMut, Resource},
+    reflect::Reflect,
+};
+use bevy_trait_query::One;
+use silicon_core::Clock;
+use synapses::{Synapse, SynapseType};
+
+/// Controls the synapse weight histogram.
+#[derive(Debug, Clone, Resource, Reflect)]
+pub struct WeightHistogramConfig {
+    pub bins: usize,
+    /// lower edge of the first bin, smaller weights are counted in the first bin
+    pub min: f64,
+    /// upper edge of the last bin, larger weights are counted in the last bin
+    pub max: f64,
+    /// show the probability density instead of the number of synapses
+    pub normalize: bool,
+    /// recompute the histogram every this many simulated seconds, `None` to only update on request
+    pub update_interval: Option<f64>,
+}
+
+impl Default for WeightHistogramConfig {
+    fn default() -> Self {
+        WeightHistogramConfig {
+            bins: 20,
+            min: 0.0,
+            max: 1.0,
+            normalize: false,
+            update_interval: Some(1.0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WeightSummary {
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub std: f64,
+}
+
+impl WeightSummary {
+    /// `None` when there are no weights.
+    pub fn from_weights(weights: &[f64]) -> Option<Self> {
+        if weights.is_empty() {
+            return None;
+        }
+
+        let n = weights.len() as f64;
+        let mean = weights.iter().sum::<f64>() / n;
+        let variance = weights.iter().map(|w| (w - mean).powi(2)).sum::<f64>() / n;
+        Some(WeightSummary {
+            min: weights.iter().copied().fold(f64::INFINITY, f64::min),
+            max: weights.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+            mean,
+            std: variance.sqrt(),
+        })
+    }
+}
+
+/// The weights of all chemical synapses, split by type. Gap junctions are left out.
+#[derive(Debug, Default, Resource)]
+pub struct WeightHistogram {
+    pub excitatory: Vec<f64>,
+    pub inhibitory: Vec<f64>,
+    /// set to recompute the histogram on the next update
+    pub requested: bool,
+    last_update: Option<f64>,
+}
+
+impl WeightHistogram {
+    pub fn summary(&self) -> Option<WeightSummary> {
+        let weights = [self.excitatory.as_slice(), self.inhibitory.as_slice()].concat();
+        WeightSummary::from_weights(&weights)
+    }
+}
+
+/// Bin `weights` into `bins` equal bins between `min` and `max`. With `normalize` the bins hold
+/// the probability density, so the area of the histogram is 1.
+pub fn histogram(weights: &[f64], bins: usize, min: f64, max: f64, normalize: bool) -> Vec<f64> {
+    let mut counts = vec![0.0; bins];
+    if bins == 0 || max <= min {
+        return counts;
+    }
+
+    let bin_width = (max - min) / bins as f64;
+    for weight in weights {
+        let bin = ((weight - min) / bin_width)
+            .floor()
+            .clamp(0.0, (bins - 1) as f64);
+        counts[bin as usize] += 1.0;
+    }
+
+    if normalize && !weights.is_empty() {
+        let total = weights.len() as f64 * bin_width;
+        for count in counts.iter_mut() {
+            *count /= total;
+        }
+    }
+
+    counts
+}
+
+pub fn update_weight_histogram(
+    synapses: Query<One<&dyn Synapse>>,
+    config: Res<WeightHistogramConfig>,
+    clock: Res<Clock>,
+    mut histogram: ResMut<WeightHistogram>,
+) {
+    let due = match (config.update_interval, histogram.last_update) {
+        (_, None) => true,
+        (Some(interval), Some(last_update)) => clock.time - last_update >= interval * 1000.0,
+        (None, Some(_)) => false,
+    };
+    if !due && !histogram.requested {
+        return;
+    }
+
+    histogram.excitatory.clear();
+    histogram.inhibitory.clear();
+    for synapse in synapses.iter() {
+        match synapse.get_type() {
+            SynapseType::Excitatory => histogram.excitatory.push(synapse.get_weight()),
+            SynapseType::Inhibitory => histogram.inhibitory.push(synapse.get_weight()),
+            SynapseType::Electrical => {}
+        }
+    }
+    histogram.requested = false;
+    histogram.last_update = Some(clock.time);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_histogram() {
+        let weights = [0.0, 0.1, 0.12, 0.5, 1.0, 1.5, -0.2];
+
+        assert_eq!(
+            histogram(&weights, 4, 0.0, 1.0, false),
+            [4.0, 0.0, 1.0, 2.0]
+        );
+
+        let density = histogram(&weights, 4, 0.0, 1.0, true);
+        let area = density.iter().sum::<f64>() * 0.25;
+        assert!((area - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_summary() {
+        let summary = WeightSummary::from_weights(&[0.2, 0.4, 0.6, 0.8]).unwrap();
+
+        assert_eq!(summary.min, 0.2);
+        assert_eq!(summary.max, 0.8);
+        assert!((summary.mean - 0.5).abs() < 1e-12);
+        assert!((summary.std - 0.05f64.sqrt()).abs() < 1e-12);
+        assert_eq!(WeightSummary::from_weights(&[]), None);
+    }
+}
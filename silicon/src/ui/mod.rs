@@ -1,10 +1,14 @@
 use bevy::{prelude::*, render::camera::Viewport, window::PrimaryWindow};
 use bevy_egui::{EguiContext, EguiPlugin, EguiSet};
+use histogram::{update_weight_histogram, WeightHistogram, WeightHistogramConfig};
+use population::{record_population_activity, PopulationActivity, PopulationActivityConfig};
 use state::UiState;
 use transform_gizmo_egui::GizmoMode;
 
 pub struct SiliconUiPlugin;
 
+pub mod histogram;
+pub mod population;
 pub mod state;
 
 impl Plugin for SiliconUiPlugin {
@@ -21,9 +25,24 @@ impl Plugin for SiliconUiPlugin {
                     set_camera_viewport.after(show_ui_system),
                 ),
             )
-            .add_systems(Update, set_gizmo_mode)
+            .add_systems(
+                Update,
+                (
+                    set_gizmo_mode,
+                    record_population_activity,
+                    update_weight_histogram,
+                ),
+            )
+            .register_type::<PopulationActivityConfig>()
+            .init_resource::<PopulationActivityConfig>()
+            .init_resource::<PopulationActivity>()
+            .register_type::<WeightHistogramConfig>()
+            .init_resource::<WeightHistogramConfig>()
+            .init_resource::<WeightHistogram>()
             .insert_resource(SimulationUiState {
                 simulation_time_slider: 50.0,
+                export_path: "spikes.csv".to_string(),
+                connectivity_path: "connectivity.csv".to_string(),
             })
             .insert_resource(UiState::new());
     }
@@ -32,6 +51,8 @@ impl Plugin for SiliconUiPlugin {
 #[derive(Resource, Debug)]
 pub struct SimulationUiState {
     simulation_time_slider: f64,
+    export_path: String,
+    connectivity_path: String,
 }
 
 fn show_ui_system(world: &mut World) {
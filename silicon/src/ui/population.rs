@@ -0,0 +1,145 @@
+use bevy::{
+    prelude::{Query, Res, ResMut, Resource},
+    reflect::Reflect,
+};
+use silicon_core::{Clock, SpikeRecorder};
+use simulator::SimpleSpikeRecorder;
+
+use super::state::PlotterConfig;
+use crate::structure::layer::ColumnLayer;
+
+/// Controls the population activity plot.
+#[derive(Debug, Clone, Resource, Reflect)]
+pub struct PopulationActivityConfig {
+    /// the rate at a point in time counts the spikes of the preceding `window` ms
+    pub window: f64,
+}
+
+impl Default for PopulationActivityConfig {
+    fn default() -> Self {
+        PopulationActivityConfig { window: 20.0 }
+    }
+}
+
+/// The population firing rate of every layer over the last `PlotterConfig::window_size` ms.
+#[derive(Debug, Default, Resource)]
+pub struct PopulationActivity {
+    /// `[time, rate]` points in ms and Hz for every layer that has neurons
+    pub layers: Vec<(ColumnLayer, Vec<[f64; 2]>)>,
+    last_time: Option<f64>,
+}
+
+/// The average firing rate in Hz of the neurons with `spike_trains` over the `window` ms before
+/// `now`, 0 for an empty population.
+pub fn population_rate(spike_trains: &[Vec<f64>], now: f64, window: f64) -> f64 {
+    if spike_trains.is_empty() || window <= 0.0 {
+        return 0.0;
+    }
+
+    let spikes = spike_trains
+        .iter()
+        .flatten()
+        .filter(|time| **time > now - window && **time <= now)
+        .count();
+    spikes as f64 / spike_trains.len() as f64 / (window / 1000.0)
+}
+
+pub fn record_population_activity(
+    neurons: Query<(&SimpleSpikeRecorder, &ColumnLayer)>,
+    config: Res<PopulationActivityConfig>,
+    plotter_config: Res<PlotterConfig>,
+    clock: Res<Clock>,
+    mut activity: ResMut<PopulationActivity>,
+) {
+    // only record when the simulation advanced
+    if activity.last_time == Some(clock.time) {
+        return;
+    }
+    activity.last_time = Some(clock.time);
+
+    let mut layers: Vec<(ColumnLayer, Vec<Vec<f64>>)> = vec![];
+    for (spike_recorder, layer) in neurons.iter() {
+        let spikes = spike_recorder.get_spikes();
+        match layers.iter_mut().find(|(other, _)| other == layer) {
+            Some((_, spike_trains)) => spike_trains.push(spikes),
+            None => layers.push((*layer, vec![spikes])),
+        }
+    }
+
+    let window_start = clock.time - plotter_config.window_size as f64;
+    for (layer, spike_trains) in layers {
+        let rate = population_rate(&spike_trains, clock.time, config.window);
+        let index = match activity
+            .layers
+            .iter()
+            .position(|(other, _)| *other == layer)
+        {
+            Some(index) => index,
+            None => {
+                activity.layers.push((layer, vec![]));
+                activity.layers.len() - 1
+            }
+        };
+
+        let points = &mut activity.layers[index].1;
+        points.push([clock.time, rate]);
+        points.retain(|[time, _]| *time >= window_start);
+    }
+    activity.layers.sort_by_key(|(layer, _)| *layer as usize);
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::app::{App, Update};
+
+    use super::*;
+
+    #[test]
+    fn test_population_rate() {
+        let spike_trains = vec![vec![5.0, 15.0, 25.0], vec![18.0], vec![]];
+
+        // 3 spikes in 10ms for 3 neurons
+        assert_eq!(population_rate(&spike_trains, 20.0, 10.0), 100.0);
+        assert_eq!(population_rate(&[], 20.0, 10.0), 0.0);
+    }
+
+    #[test]
+    fn test_silent_neurons_have_zero_rate() {
+        let mut app = App::new();
+        app.insert_resource(Clock::default())
+            .insert_resource(PlotterConfig {
+                window_size: 300,
+                ..Default::default()
+            })
+            .init_resource::<PopulationActivityConfig>()
+            .init_resource::<PopulationActivity>()
+            .add_systems(Update, record_population_activity);
+
+        for layer in [ColumnLayer::L4, ColumnLayer::L1, ColumnLayer::L1] {
+            app.world_mut()
+                .spawn((SimpleSpikeRecorder::default(), layer));
+        }
+
+        for _ in 0..10 {
+            app.world_mut().resource_mut::<Clock>().time += 0.025;
+            app.update();
+        }
+        // the clock didn't move, nothing is recorded
+        app.update();
+
+        let activity = app.world().resource::<PopulationActivity>();
+        assert_eq!(
+            activity
+                .layers
+                .iter()
+                .map(|(layer, points)| (*layer, points.len()))
+                .collect::<Vec<_>>(),
+            [(ColumnLayer::L1, 10), (ColumnLayer::L4, 10)]
+        );
+        assert!(activity
+            .layers
+            .iter()
+            .flat_map(|(_, points)| points)
+            .all(|[_, rate]| *rate == 0.0));
+    }
+}
@@ -1,14 +1,15 @@
-use std::any::TypeId;
+use std::{any::TypeId, path::Path};
 
+use analytics::receptive_field::receptive_field;
 use bevy::{
     asset::{ReflectAsset, UntypedAssetId},
-    log::info,
+    log::{info, warn},
     prelude::{
         AppTypeRegistry, Entity, Mut, ReflectResource, Resource, SystemParamFunction, With, World,
     },
     reflect::TypeRegistry,
     render::camera::{Camera, Projection},
-    transform::components::GlobalTransform,
+    transform::components::{GlobalTransform, Transform},
 };
 use bevy_egui::egui::{self};
 use bevy_inspector_egui::bevy_inspector::{
@@ -19,15 +20,31 @@ use bevy_inspector_egui::bevy_inspector::{
 use bevy_math::Mat4;
 use bevy_trait_query::One;
 use egui_dock::{DockArea, DockState, NodeIndex, Style};
-use egui_plot::{Corner, Legend, Line, Plot, VLine};
-use silicon_core::{Clock, Neuron, SpikeRecorder, ValueRecorder};
-use simulator::{PruneSettings, SimpleSpikeRecorder};
-use synapses::{Synapse, SynapseType};
+use egui_plot::{Bar, BarChart, Corner, Legend, Line, MarkerShape, Plot, Points, VLine};
+use rand::Rng;
+use silicon_core::{
+    Clock, Neuron, SimulationRng, SpikeRecorder, ValueRecorder, ValueRecorderConfig,
+};
+use simulator::{
+    dopamine::Dopamine, export::export_spikes, homeostatic::SynapticScaling, time::SimulationSpeed,
+    PruneSettings, SimpleSpikeRecorder,
+};
+use synapses::{stdp::StdpSynapse, FrozenPlasticity, Synapse, SynapseType};
 use transform_gizmo_egui::{Color32, GizmoMode};
 
-use crate::{structure::feed_forward::FeedForwardNetwork, EncoderState, Interactions};
+use crate::{
+    structure::{
+        connectivity::export_connectivity_matrix, feed_forward::FeedForwardNetwork,
+        layer::ColumnLayer,
+    },
+    EncoderState, Interactions,
+};
 
-use super::SimulationUiState;
+use super::{
+    histogram::{histogram, WeightHistogram, WeightHistogramConfig},
+    population::{PopulationActivity, PopulationActivityConfig},
+    SimulationUiState,
+};
 
 #[derive(Eq, PartialEq)]
 pub enum InspectorSelection {
@@ -51,8 +68,17 @@ impl UiState {
         let tree = state.main_surface_mut();
         // let [game, _inspector] =
         //     tree.split_right(NodeIndex::root(), 0.75, vec![EguiWindow::Inspector]);
-        let [game, _bottom] =
-            tree.split_below(NodeIndex::root(), 0.8, vec![EguiWindow::GraphViewer]);
+        let [game, _bottom] = tree.split_below(
+            NodeIndex::root(),
+            0.8,
+            vec![
+                EguiWindow::GraphViewer,
+                EguiWindow::RasterPlot,
+                EguiWindow::PopulationActivity,
+                EguiWindow::WeightHistogram,
+                EguiWindow::ReceptiveField,
+            ],
+        );
         let [_game, _hierarchy] = tree.split_right(
             game,
             0.75,
@@ -94,9 +120,13 @@ pub enum EguiWindow {
     Assets,
     Inspector,
     GraphViewer,
+    RasterPlot,
+    PopulationActivity,
+    WeightHistogram,
     SimulationSettings,
     NeuronInspector,
     Training,
+    ReceptiveField,
 }
 struct TabViewer<'a> {
     world: &'a mut World,
@@ -157,6 +187,22 @@ impl egui_dock::TabViewer for TabViewer<'_> {
                 ui.label("Neuron Inspector");
                 plotter(ui, self.world);
             }
+            EguiWindow::RasterPlot => {
+                ui.label("Spike raster");
+                raster_plot(ui, self.world);
+            }
+            EguiWindow::PopulationActivity => {
+                ui.label("Population firing rate per layer");
+                population_activity_plot(ui, self.world);
+            }
+            EguiWindow::WeightHistogram => {
+                ui.label("Synapse weight distribution");
+                weight_histogram_plot(ui, self.world);
+            }
+            EguiWindow::ReceptiveField => {
+                ui.label("Receptive field");
+                receptive_field_viewer(ui, self.world);
+            }
             EguiWindow::SimulationSettings => {
                 ui.label("Simulation Settings");
                 simulation_settings(ui, self.world);
@@ -202,11 +248,13 @@ impl egui_dock::TabViewer for TabViewer<'_> {
 
                     ui.label("Outgoing synapses");
                     for entity in outgoing_synapses {
+                        stdp_synapse_editor(ui, self.world, entity);
                         bevy_inspector::ui_for_entity(self.world, entity, ui);
                     }
                     ui.separator();
                     ui.label("Incoming synapses");
                     for entity in incoming_synapses {
+                        stdp_synapse_editor(ui, self.world, entity);
                         bevy_inspector::ui_for_entity(self.world, entity, ui);
                     }
                 } else {
@@ -225,8 +273,64 @@ impl egui_dock::TabViewer for TabViewer<'_> {
     }
 }
 
+/// Drag values for the weight and the learning parameters of a `StdpSynapse`, edits apply to the
+/// live component. Freezing the synapse keeps the learning rules from undoing the edits.
+fn stdp_synapse_editor(ui: &mut egui::Ui, world: &mut World, entity: Entity) {
+    let Some(synapse) = world.get::<StdpSynapse>(entity) else {
+        return;
+    };
+    let mut weight = synapse.weight;
+    let mut params = synapse.stdp_params.clone();
+    let mut frozen = world.get::<FrozenPlasticity>(entity).is_some();
+
+    let (edited, toggled) = ui
+        .push_id(entity, |ui| {
+            let mut edited = false;
+            egui::Grid::new("stdp_parameters")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    for (label, value, speed) in [
+                        ("Weight", &mut weight, 0.01),
+                        ("A+", &mut params.a_plus, 0.001),
+                        ("A-", &mut params.a_minus, 0.001),
+                        ("Tau+ (ms)", &mut params.tau_plus, 0.1),
+                        ("Tau- (ms)", &mut params.tau_minus, 0.1),
+                        ("Minimum weight", &mut params.w_min, 0.01),
+                        ("Maximum weight", &mut params.w_max, 0.01),
+                    ] {
+                        ui.label(label);
+                        edited |= ui.add(egui::DragValue::new(value).speed(speed)).changed();
+                        ui.end_row();
+                    }
+                });
+
+            let toggled = ui
+                .checkbox(&mut frozen, "Freeze learning")
+                .on_hover_text("STDP leaves the weight of this synapse alone")
+                .changed();
+            (edited, toggled)
+        })
+        .inner;
+
+    if edited {
+        let mut synapse = world.get_mut::<StdpSynapse>(entity).unwrap();
+        synapse.weight = weight;
+        synapse.stdp_params = params;
+    }
+
+    if toggled {
+        let mut entity = world.entity_mut(entity);
+        if frozen {
+            entity.insert(FrozenPlasticity);
+        } else {
+            entity.remove::<FrozenPlasticity>();
+        }
+    }
+}
+
 fn training_settings(ui: &mut egui::Ui, world: &mut World) {
     bevy_inspector::ui_for_resource::<EncoderState>(world, ui);
+    bevy_inspector::ui_for_resource::<Dopamine>(world, ui);
 }
 
 fn simulation_settings(ui: &mut egui::Ui, world: &mut World) {
@@ -251,6 +355,9 @@ fn simulation_settings(ui: &mut egui::Ui, world: &mut World) {
             ))
             .on_hover_text("Run the simulation indefinitely");
 
+            ui.add(egui::Checkbox::new(&mut clock.real_time, "Real time"))
+                .on_hover_text("Advance the simulation by the duration of every frame");
+
             let button = ui
                 .button("Run")
                 .on_hover_text("Run the simulation for the specified time");
@@ -261,20 +368,106 @@ fn simulation_settings(ui: &mut egui::Ui, world: &mut World) {
         })
     });
 
+    let mut speed = world.resource_mut::<SimulationSpeed>();
+    ui.add(
+        egui::Slider::new(&mut speed.speed_factor, 0.01..=10.0)
+            .logarithmic(true)
+            .text("Real time speed"),
+    );
+
     ui.separator();
 
     ui.label("Pruning settings");
+    let mut prune_settings = world.get_resource_mut::<PruneSettings>().unwrap();
+    ui.checkbox(&mut prune_settings.enabled, "Prune weak synapses");
     ui.add(
-        egui::Slider::new(
-            &mut world
-                .get_resource_mut::<PruneSettings>()
-                .unwrap()
-                .min_weight,
-            0.0..=1.0,
-        )
-        .clamp_to_range(false)
-        .text("Minimum weight to prune"),
+        egui::Slider::new(&mut prune_settings.min_weight, 0.0..=1.0)
+            .clamp_to_range(false)
+            .text("Minimum weight to prune"),
+    );
+    ui.add(
+        egui::Slider::new(&mut prune_settings.min_inhibitory_weight, 0.0..=5.0)
+            .clamp_to_range(false)
+            .text("Minimum inhibitory weight to prune"),
+    );
+    ui.add(
+        egui::Slider::new(&mut prune_settings.check_interval, 0.0..=1000.0)
+            .clamp_to_range(false)
+            .text("Check interval in ms"),
     );
+    ui.add(
+        egui::Slider::new(&mut prune_settings.grace_period, 0.0..=10000.0)
+            .clamp_to_range(false)
+            .text("Grace period in ms"),
+    )
+    .on_hover_text("Synapses younger than this are never pruned");
+
+    ui.separator();
+
+    ui.label("Synaptic scaling");
+    let mut enabled = world.contains_resource::<SynapticScaling>();
+    if ui
+        .checkbox(&mut enabled, "Normalize incoming weights")
+        .on_hover_text(
+            "Periodically rescale the incoming excitatory weights of every neuron to a fixed sum",
+        )
+        .changed()
+    {
+        if enabled {
+            let next_scaling =
+                world.resource::<Clock>().time + SynapticScaling::default().scaling_interval;
+            world.insert_resource(SynapticScaling {
+                next_scaling,
+                ..Default::default()
+            });
+        } else {
+            world.remove_resource::<SynapticScaling>();
+        }
+    }
+    if let Some(mut scaling) = world.get_resource_mut::<SynapticScaling>() {
+        ui.add(
+            egui::Slider::new(&mut scaling.target_total_weight, 0.0..=20.0)
+                .clamp_to_range(false)
+                .text("Target total weight"),
+        );
+        ui.add(
+            egui::Slider::new(&mut scaling.scaling_interval, 1.0..=10000.0)
+                .logarithmic(true)
+                .text("Scaling interval in ms"),
+        );
+    }
+
+    ui.separator();
+
+    ui.label("Export");
+    world.resource_scope(|world, mut state: Mut<SimulationUiState>| {
+        ui.horizontal(|ui| {
+            ui.text_edit_singleline(&mut state.export_path)
+                .on_hover_text("Files ending in .json or .ndjson are written as newline delimited JSON, everything else as CSV");
+
+            if ui.button("Export spikes").clicked() {
+                match export_spikes(world, Path::new(&state.export_path)) {
+                    Ok(()) => info!("Exported spikes to {}", state.export_path),
+                    Err(err) => warn!("Failed to export spikes to {}: {}", state.export_path, err),
+                }
+            }
+        });
+
+        ui.horizontal(|ui| {
+            ui.text_edit_singleline(&mut state.connectivity_path)
+                .on_hover_text("CSV file with the summed weights from every neuron (rows) to every neuron (columns)");
+
+            if ui.button("Export connectivity").clicked() {
+                match export_connectivity_matrix(world, Path::new(&state.connectivity_path)) {
+                    Ok(()) => info!("Exported connectivity matrix to {}", state.connectivity_path),
+                    Err(err) => warn!(
+                        "Failed to export connectivity matrix to {}: {}",
+                        state.connectivity_path, err
+                    ),
+                }
+            }
+        });
+    });
 
     ui.separator();
 
@@ -285,6 +478,7 @@ fn simulation_settings(ui: &mut egui::Ui, world: &mut World) {
     if button.clicked() {
         info!("Reconnecting neurons");
 
+        let mut unconnected: Vec<(Entity, Entity)> = vec![];
         let mut new_synapses: Vec<(Entity, Entity, SynapseType)> = vec![];
 
         let mut neurons = world.query::<(Entity, One<&dyn Neuron>)>();
@@ -304,23 +498,28 @@ fn simulation_settings(ui: &mut egui::Ui, world: &mut World) {
                 .map(|(entity, _)| entity);
 
             if synapse.is_none() {
-                if rand::random::<f64>() < 0.8 {
-                    continue;
-                }
+                unconnected.push((pre_synaptic, post_synaptic));
+            }
+        }
 
-                info!(
-                    "Reconnecting neurons {:?} and {:?}",
-                    pre_synaptic, post_synaptic
-                );
+        let mut rng = world.resource_mut::<SimulationRng>();
+        for (pre_synaptic, post_synaptic) in unconnected {
+            if rng.gen::<f64>() < 0.8 {
+                continue;
+            }
 
-                let synapse_type = if rand::random::<f64>() < 0.8 {
-                    SynapseType::Excitatory
-                } else {
-                    SynapseType::Inhibitory
-                };
+            info!(
+                "Reconnecting neurons {:?} and {:?}",
+                pre_synaptic, post_synaptic
+            );
 
-                new_synapses.push((pre_synaptic, post_synaptic, synapse_type));
-            }
+            let synapse_type = if rng.gen::<f64>() < 0.8 {
+                SynapseType::Excitatory
+            } else {
+                SynapseType::Inhibitory
+            };
+
+            new_synapses.push((pre_synaptic, post_synaptic, synapse_type));
         }
 
         for synapse in new_synapses {
@@ -374,6 +573,14 @@ fn plotter(ui: &mut egui::Ui, world: &mut World) {
     let insights = world.get_resource::<Interactions>().unwrap();
     let clock = world.get_resource::<Clock>().unwrap();
     let config = world.get_resource::<PlotterConfig>().unwrap();
+    // egui draws every point of a line, so long traces are decimated like the recorders are
+    let max_points = world
+        .get_resource::<ValueRecorderConfig>()
+        .and_then(|config| config.max_points);
+    let decimate = |values: Vec<(f64, f64)>| match max_points {
+        Some(max_points) => silicon_core::decimate(&values, max_points),
+        None => values,
+    };
 
     let selected_membrane_plotter = membrane_plotters.iter(world).find(|(entity, _, _)| {
         insights
@@ -415,7 +622,7 @@ fn plotter(ui: &mut egui::Ui, world: &mut World) {
                 plot_ui.vline(VLine::new(spike).color(Color32::RED));
             }
 
-            let points: Vec<[f64; 2]> = plotter
+            let values = plotter
                 .values
                 .iter()
                 .filter(|(time, _)| {
@@ -423,7 +630,11 @@ fn plotter(ui: &mut egui::Ui, world: &mut World) {
                         >= clock.time
                             - config.membrane_window_size.unwrap_or(config.window_size) as f64
                 })
-                .map(|(time, value)| [*time, *value])
+                .copied()
+                .collect();
+            let points: Vec<[f64; 2]> = decimate(values)
+                .into_iter()
+                .map(|(time, value)| [time, value])
                 .collect();
 
             plot_ui.line(
@@ -439,7 +650,7 @@ fn plotter(ui: &mut egui::Ui, world: &mut World) {
         .height(200.0);
     plot.show(ui, |plot_ui| {
         for (entity, plotter, synapse) in synapse_plots.iter() {
-            let points: Vec<[f64; 2]> = plotter
+            let values = plotter
                 .values
                 .iter()
                 .filter(|(time, _)| {
@@ -447,19 +658,280 @@ fn plotter(ui: &mut egui::Ui, world: &mut World) {
                         >= clock.time
                             - config.weight_window_size.unwrap_or(config.window_size) as f64
                 })
-                .map(|(time, value)| [*time, *value])
+                .copied()
+                .collect();
+            let points: Vec<[f64; 2]> = decimate(values)
+                .into_iter()
+                .map(|(time, value)| [time, value])
                 .collect();
 
             plot_ui.line(Line::new(points).name(format!("{:?}", entity)).color(
                 match synapse.get_type() {
                     SynapseType::Excitatory => Color32::BLUE,
                     SynapseType::Inhibitory => Color32::RED,
+                    SynapseType::Electrical => Color32::GREEN,
                 },
             ));
         }
     });
 }
 
+/// Plots the spikes of every neuron in the last `PlotterConfig::window_size` ms, every neuron in
+/// its own row grouped by layer and ordered by height. Clicking a row selects the neuron.
+fn raster_plot(ui: &mut egui::Ui, world: &mut World) {
+    let clock = world.get_resource::<Clock>().unwrap();
+    let config = world.get_resource::<PlotterConfig>().unwrap();
+    let now = clock.time;
+    let window_start = now - config.window_size as f64;
+
+    let mut rows = world
+        .query::<(
+            Entity,
+            One<&dyn SpikeRecorder>,
+            Option<&ColumnLayer>,
+            Option<&Transform>,
+        )>()
+        .iter(world)
+        .map(|(entity, spike_recorder, layer, transform)| {
+            let spikes = spike_recorder
+                .get_spikes()
+                .into_iter()
+                .filter(|time| *time >= window_start)
+                .collect::<Vec<_>>();
+            let y = transform.map_or(0.0, |transform| transform.translation.y);
+            (entity, layer.copied(), y, spikes)
+        })
+        .collect::<Vec<_>>();
+    rows.sort_by(|(entity_a, layer_a, y_a, _), (entity_b, layer_b, y_b, _)| {
+        layer_a
+            .map(|layer| layer as usize)
+            .cmp(&layer_b.map(|layer| layer as usize))
+            .then(y_a.total_cmp(y_b))
+            .then(entity_a.cmp(entity_b))
+    });
+
+    // the bounds follow the clock, so dragging and zooming are disabled
+    let plot = Plot::new("Raster")
+        .height(300.0)
+        .allow_drag(false)
+        .allow_zoom(false)
+        .allow_scroll(false)
+        .include_x(window_start)
+        .include_x(now)
+        .include_y(-0.5)
+        .include_y(rows.len() as f64 - 0.5);
+    let clicked = plot
+        .show(ui, |plot_ui| {
+            for (row, (_, layer, _, spikes)) in rows.iter().enumerate() {
+                let points = spikes
+                    .iter()
+                    .map(|time| [*time, row as f64])
+                    .collect::<Vec<_>>();
+                plot_ui.points(
+                    Points::new(points)
+                        .shape(MarkerShape::Square)
+                        .radius(1.5)
+                        .color(layer_color(*layer)),
+                );
+            }
+
+            if plot_ui.response().clicked() {
+                plot_ui.pointer_coordinate()
+            } else {
+                None
+            }
+        })
+        .inner;
+
+    let Some(clicked) = clicked else {
+        return;
+    };
+
+    let row = clicked.y.round();
+    if row < 0.0 {
+        return;
+    }
+
+    if let Some((entity, ..)) = rows.get(row as usize) {
+        world.resource_mut::<Interactions>().selected_entity = Some(*entity);
+    }
+}
+
+fn population_activity_plot(ui: &mut egui::Ui, world: &mut World) {
+    world.resource_scope(|world, mut config: Mut<PopulationActivityConfig>| {
+        ui.add(
+            egui::Slider::new(&mut config.window, 1.0..=500.0)
+                .clamp_to_range(false)
+                .text("Smoothing window in ms"),
+        );
+
+        let activity = world.resource::<PopulationActivity>();
+        let plot = Plot::new("Population activity")
+            .legend(Legend::default().position(Corner::LeftTop))
+            .height(250.0)
+            .include_y(0.0);
+        plot.show(ui, |plot_ui| {
+            for (layer, points) in activity.layers.iter() {
+                plot_ui.line(
+                    Line::new(points.clone())
+                        .name(layer.to_string())
+                        .color(layer_color(Some(*layer))),
+                );
+            }
+        });
+    });
+}
+
+fn weight_histogram_plot(ui: &mut egui::Ui, world: &mut World) {
+    world.resource_scope(|world, mut config: Mut<WeightHistogramConfig>| {
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::DragValue::new(&mut config.bins)
+                    .range(1..=200)
+                    .prefix("bins: "),
+            );
+            ui.add(
+                egui::DragValue::new(&mut config.min)
+                    .speed(0.01)
+                    .prefix("min: "),
+            );
+            ui.add(
+                egui::DragValue::new(&mut config.max)
+                    .speed(0.01)
+                    .prefix("max: "),
+            );
+            ui.checkbox(&mut config.normalize, "Normalize");
+        });
+        ui.horizontal(|ui| {
+            let mut auto_update = config.update_interval.is_some();
+            if ui.checkbox(&mut auto_update, "Update every").changed() {
+                config.update_interval = auto_update.then_some(1.0);
+            }
+            if let Some(interval) = config.update_interval.as_mut() {
+                ui.add(
+                    egui::DragValue::new(interval)
+                        .range(0.01..=100.0)
+                        .speed(0.1)
+                        .suffix(" s"),
+                );
+            }
+            if ui.button("Update").clicked() {
+                world.resource_mut::<WeightHistogram>().requested = true;
+            }
+        });
+
+        let weights = world.resource::<WeightHistogram>();
+        let bin_width = (config.max - config.min) / config.bins as f64;
+        let chart = |weights: &[f64], name: &str, color: Color32| {
+            let bars = histogram(
+                weights,
+                config.bins,
+                config.min,
+                config.max,
+                config.normalize,
+            )
+            .into_iter()
+            .enumerate()
+            .map(|(bin, value)| {
+                Bar::new(config.min + (bin as f64 + 0.5) * bin_width, value).width(bin_width)
+            })
+            .collect();
+            BarChart::new(bars).name(name).color(color)
+        };
+        let excitatory = chart(&weights.excitatory, "Excitatory", Color32::LIGHT_RED);
+        let inhibitory = chart(&weights.inhibitory, "Inhibitory", Color32::LIGHT_BLUE);
+
+        Plot::new("Weight histogram")
+            .legend(Legend::default().position(Corner::RightTop))
+            .height(250.0)
+            .include_y(0.0)
+            .show(ui, |plot_ui| {
+                plot_ui.bar_chart(excitatory);
+                plot_ui.bar_chart(inhibitory);
+            });
+
+        match weights.summary() {
+            Some(summary) => ui.label(format!(
+                "min: {:.4}  max: {:.4}  mean: {:.4}  std: {:.4}",
+                summary.min, summary.max, summary.mean, summary.std
+            )),
+            None => ui.label("No synapses"),
+        };
+    });
+}
+
+fn layer_color(layer: Option<ColumnLayer>) -> Color32 {
+    let Some(layer) = layer else {
+        return Color32::GRAY;
+    };
+
+    let color = layer.get_color().to_srgba();
+    Color32::from_rgb(
+        (color.red * 255.0) as u8,
+        (color.green * 255.0) as u8,
+        (color.blue * 255.0) as u8,
+    )
+}
+
+fn receptive_field_viewer(ui: &mut egui::Ui, world: &mut World) {
+    let selected = world
+        .get_resource::<Interactions>()
+        .unwrap()
+        .selected_entity;
+
+    let Some(selected) = selected else {
+        ui.label("No neuron selected");
+        return;
+    };
+
+    let input_layer = world
+        .query::<(Entity, &ColumnLayer)>()
+        .iter(world)
+        .filter(|(_, layer)| **layer == ColumnLayer::L1)
+        .map(|(entity, _)| entity)
+        .collect::<Vec<_>>();
+
+    let field = receptive_field(world, selected, &input_layer);
+    let max = field
+        .iter()
+        .flatten()
+        .fold(0.0_f64, |max, value| max.max(value.abs()));
+
+    let cell_size = 24.0;
+    let width = field.len();
+    let height = field.first().map_or(0, |column| column.len());
+    let (rect, _) = ui.allocate_exact_size(
+        egui::vec2(cell_size * width as f32, cell_size * height as f32),
+        egui::Sense::hover(),
+    );
+    let painter = ui.painter_at(rect);
+
+    for (x, column) in field.iter().enumerate() {
+        for (y, value) in column.iter().enumerate() {
+            let intensity = if max > 0.0 {
+                (value.abs() / max * 255.0) as u8
+            } else {
+                0
+            };
+
+            // excitatory influence is drawn in red, inhibitory influence in blue
+            let color = if *value >= 0.0 {
+                Color32::from_rgb(intensity, 0, 0)
+            } else {
+                Color32::from_rgb(0, 0, intensity)
+            };
+
+            let min = rect.left_top()
+                + egui::vec2(x as f32 * cell_size, (height - 1 - y) as f32 * cell_size);
+            painter.rect_filled(
+                egui::Rect::from_min_size(min, egui::vec2(cell_size, cell_size)),
+                0.0,
+                color,
+            );
+        }
+    }
+}
+
 fn select_resource(
     ui: &mut egui::Ui,
     type_registry: &TypeRegistry,
@@ -0,0 +1,76 @@
+//! Compares the synchronous and the event-driven neuron update on a sparse network, where only
+//! 4% of the neurons are driven and each of them excites a single other neuron.
+//!
+//! Run with `cargo bench -p simulator --bench event_driven`.
+
+use std::time::{Duration, Instant};
+
+use bevy::app::App;
+use neurons::izhikevich::IzhikevichNeuron;
+use simulator::{
+    current::CurrentSource,
+    event_driven::SimulationMode,
+    headless::{headless_app, step},
+    SimpleSpikeRecorder,
+};
+use synapses::{simple::SimpleSynapse, SynapseType};
+
+const NEURONS: usize = 10000;
+const DRIVEN: usize = NEURONS / 25;
+const TICKS: usize = 1000;
+
+fn network(mode: SimulationMode) -> App {
+    let mut app = headless_app();
+    app.insert_resource(mode);
+
+    let neurons = (0..NEURONS)
+        .map(|_| {
+            app.world_mut()
+                .spawn((
+                    IzhikevichNeuron::regular_spiking(),
+                    SimpleSpikeRecorder::default(),
+                ))
+                .id()
+        })
+        .collect::<Vec<_>>();
+
+    for i in 0..DRIVEN {
+        app.world_mut()
+            .entity_mut(neurons[i])
+            .insert(CurrentSource::Constant { amplitude: 10.0 });
+        app.world_mut().spawn(SimpleSynapse {
+            weight: 1.0,
+            delay: 40,
+            source: neurons[i],
+            target: neurons[DRIVEN + i],
+            synapse_type: SynapseType::Excitatory,
+        });
+    }
+
+    app
+}
+
+fn bench(mode: SimulationMode) -> Duration {
+    let mut app = network(mode);
+    // the first update initializes the schedule
+    step(&mut app);
+
+    let start = Instant::now();
+    for _ in 0..TICKS {
+        step(&mut app);
+    }
+    start.elapsed()
+}
+
+fn main() {
+    for mode in [SimulationMode::Synchronous, SimulationMode::EventDriven] {
+        let elapsed = bench(mode);
+        println!(
+            "{:?}: {:?} per tick ({} neurons, {} driven)",
+            mode,
+            elapsed / TICKS as u32,
+            NEURONS,
+            DRIVEN
+        );
+    }
+}
@@ -0,0 +1,52 @@
+//! Compares the update throughput of Izhikevich neurons with `f64` and with `f32` state.
+//!
+//! Run with `cargo bench -p simulator --bench precision`.
+
+use std::time::{Duration, Instant};
+
+use bevy::prelude::Component;
+use neurons::{izhikevich::IzhikevichNeuron, single_precision::IzhikevichNeuronF32};
+use silicon_core::Neuron;
+use simulator::headless::{headless_app, step};
+
+const NEURONS: usize = 100_000;
+const TICKS: usize = 1000;
+
+fn bench<N: Neuron + Component>(neuron: impl Fn(usize) -> N) -> Duration {
+    let mut app = headless_app();
+
+    for i in 0..NEURONS {
+        app.world_mut().spawn(neuron(i));
+    }
+
+    // the first update initializes the schedule
+    step(&mut app);
+
+    let start = Instant::now();
+    for _ in 0..TICKS {
+        step(&mut app);
+    }
+    start.elapsed()
+}
+
+fn izhikevich_neuron(i: usize) -> IzhikevichNeuron {
+    let mut neuron = IzhikevichNeuron::regular_spiking();
+    // spread the neurons over the phase of their firing cycle
+    neuron.v += (i % 30) as f64;
+    neuron
+}
+
+fn main() {
+    let double = bench(izhikevich_neuron);
+    let single = bench(|i| IzhikevichNeuronF32::from(&izhikevich_neuron(i)));
+
+    for (precision, elapsed) in [("f64", double), ("f32", single)] {
+        println!(
+            "{} neurons, {}: {:?} per tick ({} ticks)",
+            NEURONS,
+            precision,
+            elapsed / TICKS as u32,
+            TICKS
+        );
+    }
+}
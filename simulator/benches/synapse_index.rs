@@ -0,0 +1,165 @@
+//! Compares the spike propagation through the `SynapseIndex` with a scan over all synapses for
+//! every spike, for 1000 neurons with 50 outgoing STDP synapses each.
+//!
+//! Run with `cargo bench -p simulator --bench synapse_index`.
+
+use std::time::{Duration, Instant};
+
+use bevy::{
+    app::App,
+    ecs::schedule::ScheduleLabel,
+    prelude::{Entity, EventReader, EventWriter, IntoSystemConfigs, Query, ResMut, Resource},
+};
+use bevy_trait_query::One;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use simulator::{
+    delay::DelayBuffer, emit_spikes, headless::headless_app, synapse_index::index_synapses,
+    update_synapses_for_spikes, FiredNeurons, SpikeEvent,
+};
+use synapses::{
+    stdp::{StdpParams, StdpRule, StdpSpikeType, StdpState, StdpSynapse},
+    DeferredStdpEvent, Synapse, SynapseType,
+};
+
+const NEURONS: usize = 1000;
+const SYNAPSES: usize = 50_000;
+/// 2% of the neurons fire every tick
+const SPIKES_PER_TICK: usize = 20;
+const TICKS: usize = 100;
+
+/// `emit_spikes` as it was before the index, every spike visits every STDP synapse.
+fn scan_stdp_synapses(
+    mut fired_neurons: ResMut<FiredNeurons>,
+    mut stdp_synapses: Query<(Entity, &mut StdpSynapse)>,
+    mut spike_writer: EventWriter<SpikeEvent>,
+    mut stdp_writer: EventWriter<DeferredStdpEvent>,
+) {
+    for (neuron, time) in fired_neurons.spikes.drain(..) {
+        spike_writer.send(SpikeEvent { time, neuron });
+
+        for (synapse_entity, mut synapse) in stdp_synapses.iter_mut() {
+            let delta_weight = if synapse.source == neuron {
+                synapse.register_pre_spike()
+            } else if synapse.target == neuron {
+                synapse.register_post_spike()
+            } else {
+                None
+            };
+            if let Some(delta_weight) = delta_weight {
+                stdp_writer.send(DeferredStdpEvent {
+                    synapse: synapse_entity,
+                    delta_weight,
+                });
+            }
+        }
+    }
+}
+
+/// `update_synapses_for_spikes` as it was before the index, every spike visits every synapse.
+fn scan_synapses(
+    mut synapses: Query<(Entity, One<&mut dyn Synapse>)>,
+    mut spike_reader: EventReader<SpikeEvent>,
+    mut delay_buffer: ResMut<DelayBuffer>,
+) {
+    for spike_event in spike_reader.read() {
+        for (synapse_entity, mut synapse) in synapses.iter_mut() {
+            if synapse.get_presynaptic() == spike_event.neuron {
+                let weight = synapse.effective_weight_on_spike();
+                delay_buffer.push(1, synapse_entity, weight);
+            }
+        }
+    }
+}
+
+/// Runs only the spike propagation being compared, the rest of the simulation step would hide the
+/// difference. The headless app provides the resources and events the systems need.
+#[derive(ScheduleLabel, Debug, Clone, PartialEq, Eq, Hash)]
+struct Propagation;
+
+fn app(indexed: bool) -> App {
+    let mut app = headless_app();
+    if indexed {
+        app.add_systems(
+            Propagation,
+            (index_synapses, emit_spikes, update_synapses_for_spikes).chain(),
+        );
+    } else {
+        app.add_systems(Propagation, (scan_stdp_synapses, scan_synapses).chain());
+    }
+
+    let neurons = (0..NEURONS)
+        .map(|_| app.world_mut().spawn_empty().id())
+        .collect::<Vec<_>>();
+    let mut rng = StdRng::seed_from_u64(1);
+    for i in 0..SYNAPSES {
+        app.world_mut().spawn(StdpSynapse {
+            stdp_params: StdpParams {
+                a_plus: 0.01,
+                a_minus: -0.01,
+                tau_plus: 20.0,
+                tau_minus: 20.0,
+                w_max: 1.0,
+                w_min: 0.0,
+                soft_bound: false,
+                tau_eligibility: 1000.0,
+            },
+            stdp_state: StdpState {
+                a: 0.0,
+                spike_type: StdpSpikeType::PreSpike,
+                eligibility: 0.0,
+            },
+            source: neurons[i % NEURONS],
+            target: neurons[rng.gen_range(0..NEURONS)],
+            weight: 0.5,
+            delay: 1,
+            synapse_type: SynapseType::Excitatory,
+            rule: StdpRule::Asymmetric,
+        });
+    }
+
+    app.insert_resource(Neurons(neurons));
+    app
+}
+
+#[derive(Resource)]
+struct Neurons(Vec<Entity>);
+
+fn fire(app: &mut App, rng: &mut StdRng) {
+    let neurons = &app.world().resource::<Neurons>().0;
+    let spikes = (0..SPIKES_PER_TICK)
+        .map(|_| (neurons[rng.gen_range(0..NEURONS)], 0.0))
+        .collect();
+    app.world_mut().resource_mut::<FiredNeurons>().spikes = spikes;
+}
+
+fn bench(indexed: bool) -> Duration {
+    let mut app = app(indexed);
+    let mut rng = StdRng::seed_from_u64(2);
+
+    // the first run initializes the schedule and builds the index
+    app.world_mut().run_schedule(Propagation);
+
+    let mut elapsed = Duration::ZERO;
+    for _ in 0..TICKS {
+        fire(&mut app, &mut rng);
+        let start = Instant::now();
+        app.world_mut().run_schedule(Propagation);
+        elapsed += start.elapsed();
+    }
+    elapsed
+}
+
+fn main() {
+    let scan = bench(false);
+    let indexed = bench(true);
+    println!(
+        "{} neurons, {} synapses, {} spikes per tick",
+        NEURONS, SYNAPSES, SPIKES_PER_TICK
+    );
+    println!("scan:    {:?} per tick", scan / TICKS as u32);
+    println!("indexed: {:?} per tick", indexed / TICKS as u32);
+    println!(
+        "speedup: {:.1}x",
+        scan.as_secs_f64() / indexed.as_secs_f64()
+    );
+}
@@ -0,0 +1,44 @@
+//! Measures the time a simulation step takes for populations of Izhikevich neurons, without
+//! synapses most of it is spent in `update_neurons`.
+//!
+//! Compare the parallel and the serial update with
+//! `cargo bench -p simulator` and `cargo bench -p simulator --no-default-features`.
+
+use std::time::{Duration, Instant};
+
+use neurons::izhikevich::IzhikevichNeuron;
+use simulator::headless::{headless_app, step};
+
+const TICKS: usize = 1000;
+
+fn bench(neurons: usize) -> Duration {
+    let mut app = headless_app();
+
+    for i in 0..neurons {
+        let mut neuron = IzhikevichNeuron::regular_spiking();
+        // spread the neurons over the phase of their firing cycle
+        neuron.v += (i % 30) as f64;
+        app.world_mut().spawn(neuron);
+    }
+
+    // the first update initializes the schedule
+    step(&mut app);
+
+    let start = Instant::now();
+    for _ in 0..TICKS {
+        step(&mut app);
+    }
+    start.elapsed()
+}
+
+fn main() {
+    for neurons in [1000, 10000] {
+        let elapsed = bench(neurons);
+        println!(
+            "{} neurons: {:?} per tick ({} ticks)",
+            neurons,
+            elapsed / TICKS as u32,
+            TICKS
+        );
+    }
+}
@@ -0,0 +1,94 @@
+use bevy::{
+    prelude::{Component, EventReader, Query, Res},
+    reflect::Reflect,
+};
+use bevy_trait_query::One;
+use silicon_core::{Clock, Neuron};
+
+use crate::SpikeEvent;
+
+/// Spike-frequency adaptation for any neuron model. Every spike of the neuron on the same entity
+/// increases the adaptation current `w` by `b`, which decays back to zero with `tau_w`. The
+/// current is subtracted from the input of the neuron every tick, so sustained firing slows down.
+#[derive(Debug, Component, Reflect)]
+pub struct Adaptation {
+    /// The adaptation current.
+    pub w: f64,
+    /// The increment of `w` per spike.
+    pub b: f64,
+    /// The time constant in ms with which `w` decays.
+    pub tau_w: f64,
+}
+
+impl Adaptation {
+    pub fn new(b: f64, tau_w: f64) -> Self {
+        Adaptation { w: 0.0, b, tau_w }
+    }
+
+    /// Let `w` decay for `tau` ms and return the current that should be inserted into the neuron.
+    pub fn step(&mut self, tau: f64) -> f64 {
+        self.w *= (-tau / self.tau_w).exp();
+        -self.w
+    }
+}
+
+pub(crate) fn apply_adaptation(
+    mut neurons: Query<(&mut Adaptation, One<&mut dyn Neuron>)>,
+    clock: Res<Clock>,
+) {
+    if clock.time_to_simulate <= 0.0 {
+        return;
+    }
+
+    for (mut adaptation, mut neuron) in neurons.iter_mut() {
+        let current = adaptation.step(clock.tau);
+        neuron.insert_current(current);
+    }
+}
+
+pub(crate) fn increment_adaptation(
+    mut spike_reader: EventReader<SpikeEvent>,
+    mut adaptations: Query<&mut Adaptation>,
+) {
+    for spike_event in spike_reader.read() {
+        if let Ok(mut adaptation) = adaptations.get_mut(spike_event.neuron) {
+            adaptation.w += adaptation.b;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use neurons::leaky::LifNeuron;
+
+    use super::*;
+    use crate::{current::CurrentSource, headless::HeadlessSimulation};
+
+    #[test]
+    fn test_firing_rate_adapts() {
+        let mut simulation = HeadlessSimulation::default();
+        let neuron = simulation.add_neuron(LifNeuron::default());
+        simulation.world_mut().entity_mut(neuron).insert((
+            Adaptation::new(0.1, 200.0),
+            CurrentSource::Constant { amplitude: 3.0 },
+        ));
+
+        simulation.run_for(1.0);
+
+        let spikes = simulation.get_spikes(neuron);
+        let count = |start: f64, end: f64| {
+            spikes
+                .iter()
+                .filter(|time| **time >= start && **time < end)
+                .count()
+        };
+
+        assert!(count(0.0, 100.0) > count(900.0, 1000.0));
+        assert!(count(900.0, 1000.0) > 0);
+
+        let first_interval = spikes[1] - spikes[0];
+        let last_interval = spikes[spikes.len() - 1] - spikes[spikes.len() - 2];
+        assert!(last_interval > first_interval * 1.3);
+        assert!(simulation.world().get::<Adaptation>(neuron).unwrap().w > 0.0);
+    }
+}
@@ -0,0 +1,258 @@
+use std::f64::consts::TAU;
+
+use bevy::{
+    prelude::{Component, Query, Res},
+    reflect::Reflect,
+};
+use bevy_trait_query::One;
+use silicon_core::{Clock, Neuron};
+
+/// Tolerance used when comparing simulation times, `Clock.time` accumulates rounding errors.
+const TIME_EPSILON: f64 = 1e-9;
+
+/// Injects current into the neuron on the same entity every simulation tick.
+#[derive(Debug, Clone, Component, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CurrentSource {
+    /// The same current on every tick.
+    Constant { amplitude: f64 },
+    /// Rectangular pulses between `start` and `stop`. Every `period` ms a pulse of
+    /// `duty_cycle * period` ms is emitted.
+    PulseTrain {
+        amplitude: f64,
+        period: f64,
+        /// fraction of the period during which the current is on, between 0.0 and 1.0
+        duty_cycle: f64,
+        start: f64,
+        stop: f64,
+    },
+    /// Piecewise constant current given as (time, amplitude) pairs. Each amplitude holds until
+    /// the next step, before the first step no current is injected.
+    Steps(Vec<(f64, f64)>),
+    /// A `waveform` that starts at `start` ms, for example to measure the f-I curve of a neuron
+    /// model.
+    Waveform { waveform: Waveform, start: f64 },
+}
+
+impl CurrentSource {
+    /// The current injected at `time`.
+    pub fn current_at(&self, time: f64) -> f64 {
+        match self {
+            CurrentSource::Constant { amplitude } => *amplitude,
+            CurrentSource::PulseTrain {
+                amplitude,
+                period,
+                duty_cycle,
+                start,
+                stop,
+            } => {
+                if time + TIME_EPSILON < *start || time >= *stop || *period <= 0.0 {
+                    return 0.0;
+                }
+
+                // the phase is derived from the absolute time instead of counting ticks, so pulses
+                // don't drift when the time step doesn't evenly divide the period
+                let elapsed = time - start + TIME_EPSILON;
+                let phase = elapsed - (elapsed / period).floor() * period;
+                if phase < duty_cycle * period {
+                    *amplitude
+                } else {
+                    0.0
+                }
+            }
+            CurrentSource::Steps(steps) => steps
+                .iter()
+                .filter(|(step_time, _)| *step_time <= time + TIME_EPSILON)
+                .max_by(|(a, _), (b, _)| a.total_cmp(b))
+                .map(|(_, amplitude)| *amplitude)
+                .unwrap_or(0.0),
+            CurrentSource::Waveform { waveform, start } => waveform.current_at(time - start),
+        }
+    }
+}
+
+pub(crate) fn apply_current_sources(
+    mut neurons: Query<(&CurrentSource, One<&mut dyn Neuron>)>,
+    clock: Res<Clock>,
+) {
+    if clock.time_to_simulate <= 0.0 {
+        return;
+    }
+
+    for (source, mut neuron) in neurons.iter_mut() {
+        let current = source.current_at(clock.time);
+        if current != 0.0 {
+            neuron.insert_current(current);
+        }
+    }
+}
+
+/// A time varying current for `CurrentSource::Waveform`, times are in ms since the start of the
+/// waveform. Constant and step currents are `CurrentSource::Constant` and `CurrentSource::Steps`.
+#[derive(Debug, Clone, PartialEq, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Waveform {
+    /// A sine of `freq` Hz around `offset`.
+    Sine {
+        amplitude: f64,
+        freq: f64,
+        offset: f64,
+    },
+    /// Rises linearly from `start` to `end` over `duration` ms, zero afterwards.
+    Ramp { start: f64, end: f64, duration: f64 },
+}
+
+impl Waveform {
+    /// The current `time` ms after the start of the waveform.
+    pub fn current_at(&self, time: f64) -> f64 {
+        if time + TIME_EPSILON < 0.0 {
+            return 0.0;
+        }
+
+        match self {
+            Waveform::Sine {
+                amplitude,
+                freq,
+                offset,
+            } => offset + amplitude * (TAU * freq * time / 1000.0).sin(),
+            Waveform::Ramp {
+                start,
+                end,
+                duration,
+            } => {
+                if time >= *duration {
+                    return 0.0;
+                }
+                start + (end - start) * time / duration
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use neurons::leaky::LifNeuron;
+
+    use super::*;
+    use crate::headless::{headless_app, run_for};
+
+    #[test]
+    fn test_constant() {
+        let source = CurrentSource::Constant { amplitude: 1.5 };
+        assert_eq!(source.current_at(0.0), 1.5);
+        assert_eq!(source.current_at(1000.0), 1.5);
+    }
+
+    #[test]
+    fn test_pulse_train_does_not_drift() {
+        let source = CurrentSource::PulseTrain {
+            amplitude: 2.0,
+            period: 1.0,
+            duty_cycle: 0.5,
+            start: 10.0,
+            stop: 1010.0,
+        };
+
+        // 0.03 doesn't evenly divide the period, accumulate the time like the clock does
+        let tau = 0.03;
+        let mut time = 0.0;
+        let mut onsets = vec![];
+        let mut was_on = false;
+        while time < 1020.0 {
+            time += tau;
+            let on = source.current_at(time) != 0.0;
+            if on && !was_on {
+                onsets.push(time);
+            }
+            was_on = on;
+        }
+
+        assert_eq!(onsets.len(), 1000);
+        for (pulse, onset) in onsets.iter().enumerate() {
+            let expected = 10.0 + pulse as f64;
+            assert!(
+                *onset >= expected - TIME_EPSILON && *onset < expected + tau,
+                "pulse {pulse} started at {onset}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_pulse_train_bounds() {
+        let source = CurrentSource::PulseTrain {
+            amplitude: 2.0,
+            period: 4.0,
+            duty_cycle: 0.25,
+            start: 2.0,
+            stop: 10.0,
+        };
+
+        assert_eq!(source.current_at(1.9), 0.0);
+        assert_eq!(source.current_at(2.0), 2.0);
+        assert_eq!(source.current_at(3.5), 0.0);
+        assert_eq!(source.current_at(6.5), 2.0);
+        assert_eq!(source.current_at(10.0), 0.0);
+    }
+
+    #[test]
+    fn test_steps() {
+        let source = CurrentSource::Steps(vec![(5.0, 1.0), (10.0, -0.5), (20.0, 0.0)]);
+
+        assert_eq!(source.current_at(0.0), 0.0);
+        assert_eq!(source.current_at(5.0), 1.0);
+        assert_eq!(source.current_at(9.99), 1.0);
+        assert_eq!(source.current_at(15.0), -0.5);
+        assert_eq!(source.current_at(25.0), 0.0);
+    }
+
+    #[test]
+    fn test_waveforms() {
+        let sine = Waveform::Sine {
+            amplitude: 1.0,
+            freq: 10.0,
+            offset: 0.5,
+        };
+        assert!((sine.current_at(25.0) - 1.5).abs() < 1e-9);
+        assert!((sine.current_at(75.0) + 0.5).abs() < 1e-9);
+
+        let ramp = Waveform::Ramp {
+            start: 0.0,
+            end: 4.0,
+            duration: 8.0,
+        };
+        assert_eq!(ramp.current_at(2.0), 1.0);
+        assert_eq!(ramp.current_at(8.0), 0.0);
+
+        // nothing before the waveform starts
+        let source = CurrentSource::Waveform {
+            waveform: ramp,
+            start: 5.0,
+        };
+        assert_eq!(source.current_at(4.0), 0.0);
+        assert_eq!(source.current_at(7.0), 1.0);
+    }
+
+    #[test]
+    fn test_step_current_steady_state() {
+        let mut app = headless_app();
+        // the first step is replaced by the second one
+        let neuron = app
+            .world_mut()
+            .spawn((
+                LifNeuron::default(),
+                CurrentSource::Steps(vec![(5.0, 2.0), (10.0, 1.0)]),
+            ))
+            .id();
+
+        // 200ms, 20 membrane time constants after the last step
+        run_for(&mut app, 0.21);
+
+        let v = app
+            .world()
+            .get::<LifNeuron>(neuron)
+            .unwrap()
+            .membrane_potential;
+        // V = V_rest + R * I
+        assert!((v - -60.0).abs() < 1e-3, "membrane potential {v}");
+    }
+}
@@ -0,0 +1,250 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::{Entity, Resource};
+
+/// Queue of synaptic events waiting for their transmission delay to pass.
+///
+/// Every entry holds the tick at which it's due, the entity it belongs to and a payload. The
+/// entries are kept sorted by due tick, so delivering only ever pops from the front. Entries that
+/// are due on the same tick are delivered in the order they were pushed.
+#[derive(Resource, Debug)]
+pub struct DelayBuffer<T = f64> {
+    queue: VecDeque<(u64, Entity, T)>,
+}
+
+impl<T> Default for DelayBuffer<T> {
+    fn default() -> Self {
+        DelayBuffer {
+            queue: VecDeque::new(),
+        }
+    }
+}
+
+impl<T> DelayBuffer<T> {
+    pub fn push(&mut self, due: u64, entity: Entity, value: T) {
+        // delays are mostly equal, so new entries usually end up at the back
+        let index = self.queue.partition_point(|(tick, _, _)| *tick <= due);
+        self.queue.insert(index, (due, entity, value));
+    }
+
+    /// Removes and returns every entry that is due at or before `tick`.
+    pub fn pop_due(&mut self, tick: u64) -> impl Iterator<Item = (Entity, T)> + '_ {
+        std::iter::from_fn(move || match self.queue.front() {
+            Some((due, _, _)) if *due <= tick => self
+                .queue
+                .pop_front()
+                .map(|(_, entity, value)| (entity, value)),
+            _ => None,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.queue.clear();
+    }
+}
+
+/// The index of the simulation step that ends at `time`.
+pub fn tick_at(time: f64, tau: f64) -> u64 {
+    (time / tau).round().max(0.0) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        app::{App, Update},
+        prelude::{Component, IntoSystemConfigs},
+    };
+    use bevy_trait_query::RegisterExt;
+    use silicon_core::{Clock, Neuron};
+    use synapses::{simple::SimpleSynapse, DeferredStdpEvent, Synapse, SynapseType};
+
+    use super::*;
+    use crate::{
+        deliver_delayed_spikes, emit_spikes,
+        event_driven::NeuronActivity,
+        synapse_index::{index_synapses, SynapseIndex},
+        time::update_clock,
+        update_neurons, update_synapses_for_spikes, FiredNeurons, SpikeEvent,
+    };
+
+    /// Fires on the given updates, counting from 1.
+    #[derive(Component)]
+    struct ScheduledNeuron {
+        updates: u64,
+        spikes: Vec<u64>,
+    }
+
+    impl Neuron for ScheduledNeuron {
+        fn update(&mut self, _tau: f64) -> bool {
+            self.updates += 1;
+            self.spikes.contains(&self.updates)
+        }
+
+        fn get_membrane_potential(&self) -> f64 {
+            0.0
+        }
+
+        fn insert_current(&mut self, _current: f64) -> f64 {
+            0.0
+        }
+    }
+
+    /// Remembers on which of its updates it received a conductance.
+    #[derive(Component, Default)]
+    struct ProbeNeuron {
+        updates: u64,
+        g: f64,
+        received: Vec<u64>,
+    }
+
+    impl Neuron for ProbeNeuron {
+        fn update(&mut self, _tau: f64) -> bool {
+            self.updates += 1;
+            if self.g > 0.0 {
+                self.received.push(self.updates);
+                self.g = 0.0;
+            }
+            false
+        }
+
+        fn get_membrane_potential(&self) -> f64 {
+            0.0
+        }
+
+        fn insert_current(&mut self, _current: f64) -> f64 {
+            0.0
+        }
+
+        fn add_conductance(&mut self, g: f64, _reversal_potential: f64) {
+            self.g += g;
+        }
+    }
+
+    #[test]
+    fn test_pop_due_in_order() {
+        let mut buffer = DelayBuffer::default();
+        let entity = Entity::from_raw(0);
+        buffer.push(5, entity, 1.0);
+        buffer.push(2, entity, 2.0);
+        buffer.push(5, entity, 3.0);
+
+        assert!(buffer.pop_due(1).next().is_none());
+        assert_eq!(buffer.pop_due(4).map(|(_, w)| w).collect::<Vec<_>>(), [2.0]);
+        assert_eq!(
+            buffer.pop_due(5).map(|(_, w)| w).collect::<Vec<_>>(),
+            [1.0, 3.0]
+        );
+        assert!(buffer.is_empty());
+    }
+
+    /// A neuron firing on the given updates connected to a probe, returns the app with the probe
+    /// and the synapse.
+    fn chain(spikes: Vec<u64>, delay: u32) -> (App, Entity, Entity) {
+        let mut app = App::new();
+        app.insert_resource(Clock {
+            time_to_simulate: 100.0,
+            ..Default::default()
+        })
+        .init_resource::<DelayBuffer>()
+        .init_resource::<FiredNeurons>()
+        .init_resource::<SynapseIndex>()
+        .init_resource::<NeuronActivity>()
+        .add_event::<SpikeEvent>()
+        .add_event::<DeferredStdpEvent>()
+        .register_component_as::<dyn Neuron, ScheduledNeuron>()
+        .register_component_as::<dyn Neuron, ProbeNeuron>()
+        .register_component_as::<dyn Synapse, SimpleSynapse>()
+        .add_systems(
+            Update,
+            (
+                update_clock,
+                deliver_delayed_spikes,
+                update_neurons,
+                index_synapses,
+                emit_spikes,
+                update_synapses_for_spikes,
+            )
+                .chain(),
+        );
+
+        let source = app
+            .world_mut()
+            .spawn(ScheduledNeuron { updates: 0, spikes })
+            .id();
+        let target = app.world_mut().spawn(ProbeNeuron::default()).id();
+        let synapse = app
+            .world_mut()
+            .spawn(SimpleSynapse {
+                weight: 1.0,
+                delay,
+                source,
+                target,
+                synapse_type: SynapseType::Excitatory,
+            })
+            .id();
+
+        (app, target, synapse)
+    }
+
+    fn received(app: &App, target: Entity) -> Vec<u64> {
+        app.world()
+            .get::<ProbeNeuron>(target)
+            .unwrap()
+            .received
+            .clone()
+    }
+
+    #[test]
+    fn test_delayed_delivery() {
+        let (mut app, target, _) = chain(vec![1], 10);
+        for _ in 0..20 {
+            app.update();
+        }
+
+        assert_eq!(received(&app, target), [11]);
+        assert!(app.world().resource::<DelayBuffer>().is_empty());
+    }
+
+    #[test]
+    fn test_pending_spikes_on_same_synapse() {
+        let (mut app, target, _) = chain(vec![1, 3, 4], 10);
+        for _ in 0..20 {
+            app.update();
+        }
+
+        assert_eq!(received(&app, target), [11, 13, 14]);
+    }
+
+    #[test]
+    fn test_zero_delay_arrives_on_next_update() {
+        let (mut app, target, _) = chain(vec![1], 0);
+        for _ in 0..5 {
+            app.update();
+        }
+
+        assert_eq!(received(&app, target), [2]);
+    }
+
+    #[test]
+    fn test_pruned_synapse_drops_pending_spikes() {
+        let (mut app, target, synapse) = chain(vec![1], 10);
+        for _ in 0..5 {
+            app.update();
+        }
+        app.world_mut().despawn(synapse);
+        for _ in 0..15 {
+            app.update();
+        }
+
+        assert!(received(&app, target).is_empty());
+        assert!(app.world().resource::<DelayBuffer>().is_empty());
+    }
+}
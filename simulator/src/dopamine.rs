@@ -0,0 +1,524 @@
+use bevy::{
+    prelude::{Event, EventReader, Events, Query, Res, ResMut, Resource, Without},
+    reflect::Reflect,
+};
+use bevy_trait_query::One;
+use silicon_core::Clock;
+use synapses::{
+    stdp::{StdpRule, StdpSynapse},
+    DeferredStdpEvent, FrozenPlasticity, Synapse,
+};
+
+/// The reward signal of reward modulated STDP, `model` decides how a reward reaches the weights.
+/// The models are exclusive, so a reward is never counted twice:
+///
+/// - [`RewardModel::EligibilityTrace`]: spike pairs only leave a mark on the eligibility trace of
+///   a `StdpSynapse`, the weight changes while dopamine and the trace overlap. The level is raised
+///   with `reward` or a `DopamineReleaseEvent`, a `RewardSignal` applies the traces at once.
+/// - [`RewardModel::Deferred`]: the weight changes of the pair and triplet rules are collected and
+///   a `DopamineReleaseEvent` applies them, scaled by the level after the release.
+///
+/// Only the deviation of `amount` from `baseline` modulates plasticity, a level below the
+/// baseline punishes.
+#[derive(Debug, Reflect, Resource)]
+pub struct Dopamine {
+    pub model: RewardModel,
+    /// the current level, raise it to deliver a reward and lower it below the baseline to punish
+    pub amount: f64,
+    /// decay time constant in ms of `amount` towards `baseline`
+    pub tau: f64,
+    /// weight change per ms for an eligibility of 1 at a dopamine level of 1, and per unit of a
+    /// `RewardSignal`, in the `EligibilityTrace` model
+    pub learning_rate: f64,
+    /// the resting level `amount` decays to
+    pub baseline: f64,
+    /// multiplies the deferred STDP weight changes applied on a `DopamineReleaseEvent` in the
+    /// `Deferred` model
+    pub gain: f64,
+}
+
+/// How a reward changes the weights of the STDP synapses, see [`Dopamine`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+pub enum RewardModel {
+    /// The dopamine level and `RewardSignal`s act on the eligibility traces of the asymmetric
+    /// `StdpSynapse`s. The deferred weight changes are dropped.
+    #[default]
+    EligibilityTrace,
+    /// A `DopamineReleaseEvent` applies the deferred weight changes collected since the last
+    /// release. The level doesn't change any weight on its own and `RewardSignal`s are ignored.
+    Deferred,
+}
+
+impl Default for Dopamine {
+    fn default() -> Self {
+        Dopamine {
+            model: RewardModel::EligibilityTrace,
+            amount: 0.0,
+            tau: 200.0,
+            learning_rate: 0.01,
+            baseline: 0.0,
+            gain: 1.0,
+        }
+    }
+}
+
+impl Dopamine {
+    /// Add `amount` to the current level.
+    pub fn reward(&mut self, amount: f64) {
+        self.amount += amount;
+    }
+
+    /// The factor a deferred STDP weight change is multiplied with at the current level.
+    pub fn modulation(&self) -> f64 {
+        self.gain * (self.amount - self.baseline)
+    }
+}
+
+/// A reward delivered at a single moment, as opposed to the dopamine level that lasts. In the
+/// `EligibilityTrace` model every asymmetric `StdpSynapse` changes its weight by
+/// `learning_rate * value * eligibility` at once.
+#[derive(Debug, Clone, Copy, Event)]
+pub struct RewardSignal {
+    pub value: f64,
+}
+
+/// Adds `amount` to the level of the `Dopamine` resource, in the `Deferred` model it applies the
+/// pending `DeferredStdpEvent`s as well, see `apply_deferred_stdp`.
+#[derive(Debug, Clone, Copy, Event)]
+pub struct DopamineReleaseEvent {
+    pub amount: f64,
+}
+
+/// Applies the `RewardSignal`s sent since the last update. The learning rate is the one of the
+/// `Dopamine` resource, without it or outside of the `EligibilityTrace` model the signals are
+/// ignored.
+pub(crate) fn apply_reward_signals(
+    mut reward_reader: EventReader<RewardSignal>,
+    mut synapses: Query<&mut StdpSynapse, Without<FrozenPlasticity>>,
+    dopamine: Option<Res<Dopamine>>,
+) {
+    let Some(dopamine) =
+        dopamine.filter(|dopamine| dopamine.model == RewardModel::EligibilityTrace)
+    else {
+        reward_reader.clear();
+        return;
+    };
+
+    for reward in reward_reader.read() {
+        let scale = dopamine.learning_rate * reward.value;
+        for mut synapse in synapses.iter_mut() {
+            if synapse.rule != StdpRule::Asymmetric {
+                continue;
+            }
+
+            let weight = synapse.weight + scale * synapse.stdp_state.eligibility;
+            synapse.weight = weight.clamp(synapse.stdp_params.w_min, synapse.stdp_params.w_max);
+        }
+    }
+}
+
+/// Applies `learning_rate * (amount - baseline) * eligibility` to the weight of every asymmetric
+/// `StdpSynapse` each tick in the `EligibilityTrace` model, the inhibitory rule isn't modulated
+/// by reward.
+pub(crate) fn dopamine_modulated_stdp(
+    mut synapses: Query<&mut StdpSynapse, Without<FrozenPlasticity>>,
+    clock: Res<Clock>,
+    dopamine: Option<Res<Dopamine>>,
+) {
+    let Some(dopamine) =
+        dopamine.filter(|dopamine| dopamine.model == RewardModel::EligibilityTrace)
+    else {
+        return;
+    };
+
+    if clock.time_to_simulate <= 0.0 {
+        return;
+    }
+
+    let level = dopamine.amount - dopamine.baseline;
+    if level != 0.0 {
+        let scale = dopamine.learning_rate * level * clock.tau;
+        for mut synapse in synapses.iter_mut() {
+            if synapse.rule != StdpRule::Asymmetric {
+                continue;
+            }
+
+            let weight = synapse.weight + scale * synapse.stdp_state.eligibility;
+            synapse.weight = weight.clamp(synapse.stdp_params.w_min, synapse.stdp_params.w_max);
+        }
+    }
+}
+
+/// Adds the released dopamine to the level, which decays towards the baseline with `tau` while
+/// the simulation runs.
+pub(crate) fn dopamine_decay(
+    mut release_reader: EventReader<DopamineReleaseEvent>,
+    clock: Res<Clock>,
+    mut dopamine: Option<ResMut<Dopamine>>,
+) {
+    let Some(dopamine) = dopamine.as_mut() else {
+        release_reader.clear();
+        return;
+    };
+
+    for release in release_reader.read() {
+        dopamine.reward(release.amount);
+    }
+
+    if clock.time_to_simulate <= 0.0 {
+        return;
+    }
+
+    let decay = (-clock.tau / dopamine.tau).exp();
+    dopamine.amount = dopamine.baseline + (dopamine.amount - dopamine.baseline) * decay;
+}
+
+/// Applies the `DeferredStdpEvent`s collected since the last release of dopamine to their
+/// synapse in the `Deferred` model, every weight change is multiplied by `Dopamine::modulation`
+/// and kept within the `Synapse::weight_bounds`. Without a release the changes keep waiting.
+/// Without the `Dopamine` resource or in the `EligibilityTrace` model nothing will ever apply
+/// them, they are dropped every tick so they don't pile up.
+pub(crate) fn apply_deferred_stdp(
+    mut release_reader: EventReader<DopamineReleaseEvent>,
+    mut deferred_stdp_events: ResMut<Events<DeferredStdpEvent>>,
+    mut synapses: Query<One<&mut dyn Synapse>, Without<FrozenPlasticity>>,
+    dopamine: Option<Res<Dopamine>>,
+) {
+    let released = release_reader.read().count() > 0;
+    let Some(dopamine) = dopamine.filter(|dopamine| dopamine.model == RewardModel::Deferred) else {
+        deferred_stdp_events.clear();
+        return;
+    };
+    if !released {
+        return;
+    }
+
+    let modulation = dopamine.modulation();
+    for event in deferred_stdp_events.drain() {
+        let Ok(mut synapse) = synapses.get_mut(event.synapse) else {
+            continue;
+        };
+
+        let mut weight = synapse.get_weight() + event.delta_weight * modulation;
+        if let Some((min, max)) = synapse.weight_bounds() {
+            weight = weight.clamp(min, max);
+        }
+        synapse.set_weight(weight);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{app::App, prelude::Entity};
+    use synapses::{
+        stdp::{StdpParams, StdpSpikeType, StdpState},
+        triplet_stdp::{TripletStdpParams, TripletStdpSynapse},
+        SynapseType,
+    };
+
+    use super::*;
+    use crate::{
+        headless::{headless_app, step},
+        FiredNeurons,
+    };
+
+    fn app() -> (App, Entity, Entity, Entity) {
+        let mut app = headless_app();
+        app.init_resource::<Dopamine>();
+        app.world_mut().resource_mut::<Clock>().tau = 0.1;
+
+        let source = app.world_mut().spawn_empty().id();
+        let target = app.world_mut().spawn_empty().id();
+        let synapse = app
+            .world_mut()
+            .spawn(StdpSynapse {
+                stdp_params: StdpParams {
+                    a_plus: 0.01,
+                    a_minus: -0.01,
+                    tau_plus: 0.2,
+                    tau_minus: 0.2,
+                    w_max: 1.0,
+                    w_min: 0.0,
+                    soft_bound: false,
+                    tau_eligibility: 1000.0,
+                },
+                stdp_state: StdpState {
+                    a: 0.0,
+                    spike_type: StdpSpikeType::PreSpike,
+                    eligibility: 0.0,
+                },
+                source,
+                target,
+                weight: 0.5,
+                delay: 1,
+                synapse_type: SynapseType::Excitatory,
+                rule: StdpRule::Asymmetric,
+            })
+            .id();
+
+        (app, source, target, synapse)
+    }
+
+    /// A pre spike followed by a post spike one tick later, then `delay` ms until `reward` and
+    /// another second to let the dopamine act. Returns the final weight.
+    fn pairing_then_reward(delay: f64, reward: f64) -> f64 {
+        let (mut app, source, target, synapse) = app();
+
+        for neuron in [source, target] {
+            app.world_mut().resource_mut::<FiredNeurons>().spikes = vec![(neuron, 0.0)];
+            step(&mut app);
+        }
+        let eligibility = app
+            .world()
+            .get::<StdpSynapse>(synapse)
+            .unwrap()
+            .stdp_state
+            .eligibility;
+        assert!(eligibility > 0.0);
+
+        for _ in 0..(delay / 0.1) as usize {
+            step(&mut app);
+        }
+        // nothing changes the weight until the reward arrives
+        assert_eq!(app.world().get::<StdpSynapse>(synapse).unwrap().weight, 0.5);
+
+        app.world_mut().resource_mut::<Dopamine>().reward(reward);
+        for _ in 0..10_000 {
+            step(&mut app);
+        }
+
+        app.world().get::<StdpSynapse>(synapse).unwrap().weight
+    }
+
+    #[test]
+    fn test_delayed_reward_potentiates_paired_synapse() {
+        let rewarded = pairing_then_reward(500.0, 1.0);
+        let late = pairing_then_reward(3000.0, 1.0);
+        let punished = pairing_then_reward(500.0, -1.0);
+
+        assert!(rewarded > 0.5 + 1e-3, "weight was {rewarded}");
+        // the trace has mostly decayed by the time a late reward arrives
+        assert!(late > 0.5 && late < rewarded);
+        assert!(punished < 0.5);
+        assert_eq!(pairing_then_reward(500.0, 0.0), 0.5);
+    }
+
+    #[test]
+    fn test_reward_signal_applies_eligibility_at_once() {
+        let (mut app, source, target, synapse) = app();
+        for neuron in [source, target] {
+            app.world_mut().resource_mut::<FiredNeurons>().spikes = vec![(neuron, 0.0)];
+            step(&mut app);
+        }
+        for _ in 0..1000 {
+            step(&mut app);
+        }
+
+        let eligibility = app
+            .world()
+            .get::<StdpSynapse>(synapse)
+            .unwrap()
+            .stdp_state
+            .eligibility;
+        app.world_mut().send_event(RewardSignal { value: 2.0 });
+        step(&mut app);
+
+        // the trace decays a single tick further before the reward is applied
+        let expected = 0.5 + 0.01 * 2.0 * eligibility * (-0.1f64 / 1000.0).exp();
+        let weight = app.world().get::<StdpSynapse>(synapse).unwrap().weight;
+        assert!((weight - expected).abs() < 1e-12, "weight was {weight}");
+
+        // a signal is only applied once
+        for _ in 0..10 {
+            step(&mut app);
+        }
+        assert_eq!(
+            app.world().get::<StdpSynapse>(synapse).unwrap().weight,
+            weight
+        );
+    }
+
+    #[test]
+    fn test_frozen_synapse_keeps_its_weight() {
+        let (mut app, source, target, synapse) = app();
+        app.world_mut().entity_mut(synapse).insert(FrozenPlasticity);
+        for neuron in [source, target] {
+            app.world_mut().resource_mut::<FiredNeurons>().spikes = vec![(neuron, 0.0)];
+            step(&mut app);
+        }
+        // a trace left over from before the synapse was frozen
+        app.world_mut()
+            .get_mut::<StdpSynapse>(synapse)
+            .unwrap()
+            .stdp_state
+            .eligibility = 1.0;
+
+        app.world_mut().resource_mut::<Dopamine>().reward(1.0);
+        app.world_mut().send_event(RewardSignal { value: 1.0 });
+        for _ in 0..1000 {
+            step(&mut app);
+        }
+
+        assert_eq!(app.world().get::<StdpSynapse>(synapse).unwrap().weight, 0.5);
+    }
+
+    /// Pairs the neurons and waits 10 ms before releasing `pulse` in the `Deferred` model, the
+    /// eligibility traces don't take part even though the learning rate is set. Returns the weight change of the pairing and the final weight.
+    fn pairing_then_release(pulse: Option<f64>) -> (f64, f64) {
+        let (mut app, source, target, synapse) = app();
+        app.world_mut().resource_mut::<Dopamine>().model = RewardModel::Deferred;
+        for neuron in [source, target] {
+            app.world_mut().resource_mut::<FiredNeurons>().spikes = vec![(neuron, 0.0)];
+            step(&mut app);
+        }
+        let delta_weight = app
+            .world()
+            .get::<StdpSynapse>(synapse)
+            .unwrap()
+            .stdp_state
+            .eligibility;
+
+        for _ in 0..100 {
+            step(&mut app);
+        }
+        assert_eq!(app.world().get::<StdpSynapse>(synapse).unwrap().weight, 0.5);
+
+        if let Some(amount) = pulse {
+            app.world_mut().send_event(DopamineReleaseEvent { amount });
+        }
+        for _ in 0..100 {
+            step(&mut app);
+        }
+
+        let weight = app.world().get::<StdpSynapse>(synapse).unwrap().weight;
+        (delta_weight, weight)
+    }
+
+    #[test]
+    fn test_dopamine_release_gates_deferred_stdp() {
+        let (delta_weight, weight) = pairing_then_release(Some(2.0));
+        assert!(delta_weight > 0.0);
+        // the level decays a single tick before the pending change is applied, only once
+        let expected = 0.5 + delta_weight * 2.0 * (-0.1f64 / 200.0).exp();
+        assert!((weight - expected).abs() < 1e-5, "weight was {weight}");
+
+        assert_eq!(pairing_then_release(None).1, 0.5);
+    }
+
+    #[test]
+    fn test_deferred_stdp_is_dropped_without_dopamine() {
+        let (mut app, source, target, _) = app();
+        app.world_mut().remove_resource::<Dopamine>();
+        for neuron in [source, target] {
+            app.world_mut().resource_mut::<FiredNeurons>().spikes = vec![(neuron, 0.0)];
+            step(&mut app);
+        }
+
+        assert!(app
+            .world()
+            .resource::<Events<DeferredStdpEvent>>()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_release_only_raises_level_with_eligibility_traces() {
+        let (mut app, source, target, synapse) = app();
+        for neuron in [source, target] {
+            app.world_mut().resource_mut::<FiredNeurons>().spikes = vec![(neuron, 0.0)];
+            step(&mut app);
+        }
+        let eligibility = app
+            .world()
+            .get::<StdpSynapse>(synapse)
+            .unwrap()
+            .stdp_state
+            .eligibility;
+
+        app.world_mut()
+            .send_event(DopamineReleaseEvent { amount: 2.0 });
+        step(&mut app);
+        // the deferred changes are dropped instead of being applied on top of the trace
+        assert_eq!(app.world().get::<StdpSynapse>(synapse).unwrap().weight, 0.5);
+        assert!(app
+            .world()
+            .resource::<Events<DeferredStdpEvent>>()
+            .is_empty());
+
+        // 10ms at a level of about 2
+        for _ in 0..100 {
+            step(&mut app);
+        }
+        let weight = app.world().get::<StdpSynapse>(synapse).unwrap().weight;
+        // the level and the trace decay a little during these 10ms
+        let expected = 0.5 + 0.01 * 2.0 * 10.0 * eligibility;
+        assert!(weight > 0.5);
+        assert!(
+            (weight - expected).abs() < 0.05 * (expected - 0.5),
+            "weight was {weight}"
+        );
+    }
+
+    #[test]
+    fn test_dopamine_release_gates_triplet_stdp() {
+        let (mut app, source, target, _) = app();
+        app.world_mut().resource_mut::<Dopamine>().model = RewardModel::Deferred;
+        let synapse = app
+            .world_mut()
+            .spawn(TripletStdpSynapse {
+                params: TripletStdpParams {
+                    a2_plus: 0.01,
+                    ..Default::default()
+                },
+                ..TripletStdpSynapse::new(source, target, 0.5, SynapseType::Excitatory)
+            })
+            .id();
+        for neuron in [source, target] {
+            app.world_mut().resource_mut::<FiredNeurons>().spikes = vec![(neuron, 0.0)];
+            step(&mut app);
+        }
+        for _ in 0..100 {
+            step(&mut app);
+        }
+        // the triplet rule defers its changes like the pair rule
+        let weight = |app: &App| {
+            app.world()
+                .get::<TripletStdpSynapse>(synapse)
+                .unwrap()
+                .weight
+        };
+        assert_eq!(weight(&app), 0.5);
+
+        app.world_mut()
+            .send_event(DopamineReleaseEvent { amount: 2.0 });
+        step(&mut app);
+
+        // the fast presynaptic trace decays a single tick before the postsynaptic spike
+        let expected = 0.5 + 0.01 * (-0.1f64 / 16.8).exp() * 2.0 * (-0.1f64 / 200.0).exp();
+        assert!(
+            (weight(&app) - expected).abs() < 1e-12,
+            "weight was {}",
+            weight(&app)
+        );
+    }
+
+    #[test]
+    fn test_dopamine_decays_to_baseline() {
+        let (mut app, ..) = app();
+        let mut dopamine = app.world_mut().resource_mut::<Dopamine>();
+        dopamine.baseline = 0.2;
+        dopamine.amount = 0.2;
+        app.world_mut()
+            .send_event(DopamineReleaseEvent { amount: 1.0 });
+        step(&mut app);
+        let amount = app.world().resource::<Dopamine>().amount;
+        assert!((amount - (0.2 + (-0.1f64 / 200.0).exp())).abs() < 1e-12);
+
+        // ten time constants
+        for _ in 0..20_000 {
+            step(&mut app);
+        }
+        let amount = app.world().resource::<Dopamine>().amount;
+        assert!((amount - 0.2).abs() < 1e-4);
+        assert!(amount > 0.2);
+    }
+}
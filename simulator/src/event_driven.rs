@@ -0,0 +1,292 @@
+//! Skip the update of neurons that have nothing to do.
+//!
+//! In sparse networks most neurons sit at rest most of the time, updating them every tick only
+//! keeps them at rest. In event-driven mode a neuron is only updated while it's active: for
+//! `NeuronActivity::window` ms after it received a synaptic input or fired. Neurons with a current
+//! source, membrane noise or adaptation receive input every tick and Poisson neurons fire on their
+//! own, they are always updated. A neuron that is updated wakes the neurons it's coupled to
+//! through gap junctions up.
+//!
+//! A neuron that falls asleep keeps its state, so the window should be long enough for the
+//! membrane to return to rest. Systems that call `insert_current` on a neuron themselves have to
+//! `mark` it as active, otherwise the current piles up until the neuron wakes up.
+
+use bevy::{
+    prelude::{Entity, Or, Query, Res, ResMut, Resource, With},
+    reflect::Reflect,
+    utils::HashMap,
+};
+use bevy_trait_query::One;
+use neurons::poisson::PoissonNeuron;
+use silicon_core::{Clock, Neuron, SpikeRecorder};
+use synapses::gap_junction::GapJunctionSynapse;
+
+use crate::{
+    adaptation::Adaptation, current::CurrentSource, noise::MembraneNoise, update_neuron,
+    FiredNeurons,
+};
+
+/// Selects the system that updates the neurons.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Resource, Reflect)]
+pub enum SimulationMode {
+    /// Every neuron is updated every tick.
+    #[default]
+    Synchronous,
+    /// Only neurons that recently received input or fired are updated.
+    EventDriven,
+}
+
+/// The last time every neuron received a synaptic input or fired.
+#[derive(Debug, Resource)]
+pub struct NeuronActivity {
+    last_active: HashMap<Entity, f64>,
+    /// How long in ms a neuron keeps being updated after its last input or spike, this should
+    /// cover the refractory period and the time the membrane needs to settle.
+    pub window: f64,
+}
+
+impl Default for NeuronActivity {
+    fn default() -> Self {
+        NeuronActivity {
+            last_active: HashMap::new(),
+            window: 20.0,
+        }
+    }
+}
+
+impl NeuronActivity {
+    pub fn mark(&mut self, neuron: Entity, time: f64) {
+        self.last_active.insert(neuron, time);
+    }
+
+    pub fn is_active(&self, neuron: Entity, time: f64) -> bool {
+        self.last_active
+            .get(&neuron)
+            .is_some_and(|last_active| time - last_active <= self.window)
+    }
+
+    pub fn clear(&mut self) {
+        self.last_active.clear();
+    }
+}
+
+/// Replaces `update_neurons` when the `SimulationMode` is `EventDriven`.
+pub fn update_neurons_event_driven(
+    clock: Res<Clock>,
+    mut neuron_query: Query<(
+        Entity,
+        One<&mut dyn Neuron>,
+        Option<One<&mut dyn SpikeRecorder>>,
+    )>,
+    always_updated: Query<
+        (),
+        Or<(
+            With<CurrentSource>,
+            With<MembraneNoise>,
+            With<Adaptation>,
+            With<PoissonNeuron>,
+        )>,
+    >,
+    gap_junctions: Query<&GapJunctionSynapse>,
+    mut activity: ResMut<NeuronActivity>,
+    mut fired_neurons: ResMut<FiredNeurons>,
+) {
+    if clock.time_to_simulate <= 0.0 {
+        return;
+    }
+
+    let is_awake =
+        |neuron: Entity| always_updated.contains(neuron) || activity.is_active(neuron, clock.time);
+    // the gap junction currents were applied already, an awake neuron drives its partners
+    let woken = gap_junctions
+        .iter()
+        .filter_map(
+            |junction| match (is_awake(junction.source), is_awake(junction.target)) {
+                (true, false) => Some(junction.target),
+                (false, true) => Some(junction.source),
+                _ => None,
+            },
+        )
+        .collect::<Vec<_>>();
+    for neuron in woken {
+        activity.mark(neuron, clock.time);
+    }
+
+    for (entity, mut neuron, mut spike_recorder) in neuron_query.iter_mut() {
+        if !(always_updated.contains(entity) || activity.is_active(entity, clock.time)) {
+            continue;
+        }
+
+        if update_neuron(&mut *neuron, spike_recorder.as_deref_mut(), &clock) {
+            fired_neurons.spikes.push((entity, clock.time));
+            activity.mark(entity, clock.time);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::Component;
+    use neurons::leaky::LifNeuron;
+    use synapses::{simple::SimpleSynapse, SynapseType};
+
+    use super::*;
+    use crate::{current::Waveform, headless::HeadlessSimulation, SimulationPlugin};
+
+    /// Counts how often it's updated.
+    #[derive(Component, Default)]
+    struct CountingNeuron {
+        updates: usize,
+    }
+
+    impl Neuron for CountingNeuron {
+        fn update(&mut self, _tau: f64) -> bool {
+            self.updates += 1;
+            false
+        }
+
+        fn get_membrane_potential(&self) -> f64 {
+            0.0
+        }
+
+        fn insert_current(&mut self, _current: f64) -> f64 {
+            0.0
+        }
+    }
+
+    /// A driven neuron connected to a second neuron and to a counting neuron, returns the spike
+    /// times of the second neuron and the number of updates of the counting neurons.
+    fn run(mode: SimulationMode) -> (Vec<f64>, [usize; 2]) {
+        let mut simulation = HeadlessSimulation::new(SimulationPlugin::with_seed(1));
+        simulation.world_mut().insert_resource(mode);
+
+        let source = simulation.add_neuron(LifNeuron::default());
+        simulation
+            .world_mut()
+            .entity_mut(source)
+            .insert(CurrentSource::Constant { amplitude: 2.0 });
+        let target = simulation.add_neuron(LifNeuron::default());
+        let connected = simulation.add_neuron(CountingNeuron::default());
+        let idle = simulation.add_neuron(CountingNeuron::default());
+        for postsynaptic in [target, connected] {
+            simulation.add_synapse(SimpleSynapse {
+                weight: 10.0,
+                delay: 40,
+                source,
+                target: postsynaptic,
+                synapse_type: SynapseType::Excitatory,
+            });
+        }
+
+        simulation.run_for(0.2);
+
+        let updates = [connected, idle].map(|neuron| {
+            simulation
+                .world()
+                .get::<CountingNeuron>(neuron)
+                .unwrap()
+                .updates
+        });
+        (simulation.get_spikes(target), updates)
+    }
+
+    #[test]
+    fn test_event_driven_matches_synchronous() {
+        let (synchronous_spikes, synchronous_updates) = run(SimulationMode::Synchronous);
+        let (event_driven_spikes, event_driven_updates) = run(SimulationMode::EventDriven);
+
+        assert!(!synchronous_spikes.is_empty());
+        assert_eq!(event_driven_spikes, synchronous_spikes);
+        assert_eq!(synchronous_updates, [8000, 8000]);
+        // the connected neuron is only updated in the window after every input
+        assert!(event_driven_updates[0] > 0);
+        assert!(event_driven_updates[0] < 8000);
+        assert_eq!(event_driven_updates[1], 0);
+    }
+
+    /// Runs the network `setup` builds for 200ms in both modes and returns the spike times and
+    /// the final membrane potential of the LIF neuron it returns, synchronous first.
+    fn run_both(setup: impl Fn(&mut HeadlessSimulation) -> Entity) -> [(Vec<f64>, Option<f64>); 2] {
+        [SimulationMode::Synchronous, SimulationMode::EventDriven].map(|mode| {
+            let mut simulation = HeadlessSimulation::new(SimulationPlugin::with_seed(1));
+            simulation.world_mut().insert_resource(mode);
+            let neuron = setup(&mut simulation);
+
+            simulation.run_for(0.2);
+
+            let potential = simulation
+                .world()
+                .get::<LifNeuron>(neuron)
+                .map(|neuron| neuron.membrane_potential);
+            (simulation.get_spikes(neuron), potential)
+        })
+    }
+
+    #[test]
+    fn test_waveform_current_source_is_always_updated() {
+        let [synchronous, event_driven] = run_both(|simulation| {
+            let neuron = simulation.add_neuron(LifNeuron::default());
+            simulation
+                .world_mut()
+                .entity_mut(neuron)
+                .insert(CurrentSource::Waveform {
+                    waveform: Waveform::Ramp {
+                        start: 0.0,
+                        end: 4.0,
+                        duration: 100.0,
+                    },
+                    start: 50.0,
+                });
+            neuron
+        });
+
+        assert!(!synchronous.0.is_empty());
+        assert_eq!(event_driven, synchronous);
+    }
+
+    #[test]
+    fn test_step_current_source_is_always_updated() {
+        let [synchronous, event_driven] = run_both(|simulation| {
+            let neuron = simulation.add_neuron(LifNeuron::default());
+            simulation
+                .world_mut()
+                .entity_mut(neuron)
+                .insert(CurrentSource::Steps(vec![(50.0, 2.0), (150.0, 0.0)]));
+            neuron
+        });
+
+        assert!(!synchronous.0.is_empty());
+        assert_eq!(event_driven, synchronous);
+    }
+
+    #[test]
+    fn test_poisson_neuron_fires_on_its_own() {
+        let [synchronous, event_driven] =
+            run_both(|simulation| simulation.add_neuron(PoissonNeuron::from_seed(100.0, 7)));
+
+        assert!(!synchronous.0.is_empty());
+        assert_eq!(event_driven, synchronous);
+    }
+
+    #[test]
+    fn test_gap_junction_wakes_partner() {
+        let [synchronous, event_driven] = run_both(|simulation| {
+            let source = simulation.add_neuron(LifNeuron::default());
+            simulation
+                .world_mut()
+                .entity_mut(source)
+                .insert(CurrentSource::Constant { amplitude: 2.0 });
+            let partner = simulation.add_neuron(LifNeuron::default());
+            simulation.add_synapse(GapJunctionSynapse {
+                weight: 0.05,
+                source,
+                target: partner,
+            });
+            partner
+        });
+
+        // the partner is only moved by the current through the gap junction
+        assert_ne!(synchronous.1, Some(LifNeuron::default().resting_potential));
+        assert_eq!(event_driven, synchronous);
+    }
+}
@@ -0,0 +1,146 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use bevy::prelude::{Entity, World};
+use bevy_trait_query::One;
+use silicon_core::SpikeRecorder;
+
+/// Stable string representation of an entity used in exported files, `generation:index`.
+pub fn entity_id(entity: Entity) -> String {
+    format!("{}:{}", entity.generation(), entity.index())
+}
+
+/// Collects the spikes of every entity with a `SpikeRecorder`, sorted by entity.
+/// Entities without spikes are included with an empty spike train.
+pub fn collect_spikes(world: &mut World) -> Vec<(Entity, Vec<f64>)> {
+    let mut recorders = world
+        .query::<(Entity, One<&dyn SpikeRecorder>)>()
+        .iter(world)
+        .map(|(entity, recorder)| (entity, recorder.get_spikes()))
+        .collect::<Vec<_>>();
+    recorders.sort_by_key(|(entity, _)| (entity.index(), entity.generation()));
+    recorders
+}
+
+/// Writes the spike raster of the simulation to `path`.
+///
+/// Files ending in `.json`, `.jsonl` or `.ndjson` are written as newline delimited JSON, the first
+/// line lists the population and every following line is a `{"neuron": .., "time": ..}` object.
+/// Everything else is written as CSV with a `neuron,time` row per spike, preceded by a
+/// `# population:` comment listing every recorded neuron.
+///
+/// Trait queries need mutable access to the world to initialize their state, which is why this
+/// takes `&mut World`.
+pub fn export_spikes(world: &mut World, path: &Path) -> io::Result<()> {
+    let spikes = collect_spikes(world);
+    let mut writer = BufWriter::new(File::create(path)?);
+
+    let extension = path.extension().and_then(|extension| extension.to_str());
+    match extension {
+        Some("json" | "jsonl" | "ndjson") => write_ndjson(&spikes, &mut writer)?,
+        _ => write_csv(&spikes, &mut writer)?,
+    }
+
+    writer.flush()
+}
+
+fn write_csv(spikes: &[(Entity, Vec<f64>)], writer: &mut impl Write) -> io::Result<()> {
+    let population = spikes
+        .iter()
+        .map(|(entity, _)| entity_id(*entity))
+        .collect::<Vec<_>>();
+    writeln!(writer, "# population: {}", population.join(","))?;
+    writeln!(writer, "neuron,time")?;
+
+    for (entity, times) in spikes {
+        let id = entity_id(*entity);
+        for time in times {
+            writeln!(writer, "{},{}", id, time)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn write_ndjson(spikes: &[(Entity, Vec<f64>)], writer: &mut impl Write) -> io::Result<()> {
+    let population = spikes
+        .iter()
+        .map(|(entity, _)| format!("\"{}\"", entity_id(*entity)))
+        .collect::<Vec<_>>();
+    writeln!(writer, "{{\"population\":[{}]}}", population.join(","))?;
+
+    for (entity, times) in spikes {
+        let id = entity_id(*entity);
+        for time in times {
+            writeln!(writer, "{{\"neuron\":\"{}\",\"time\":{}}}", id, time)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_trait_query::RegisterExt;
+
+    use super::*;
+    use crate::SimpleSpikeRecorder;
+
+    fn world_with_spikes() -> (World, Entity, Entity) {
+        let mut world = World::new();
+        world.register_component_as::<dyn SpikeRecorder, SimpleSpikeRecorder>();
+
+        let mut recorder = SimpleSpikeRecorder::default();
+        recorder.record_spike(1.5);
+        recorder.record_spike(3.0);
+        let active = world.spawn(recorder).id();
+        let silent = world.spawn(SimpleSpikeRecorder::default()).id();
+
+        (world, active, silent)
+    }
+
+    #[test]
+    fn test_csv_export() {
+        let (mut world, active, silent) = world_with_spikes();
+        let path = std::env::temp_dir().join("silicon_spike_export.csv");
+
+        export_spikes(&mut world, &path).unwrap();
+        let contents = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let active = entity_id(active);
+        let silent = entity_id(silent);
+        assert_eq!(
+            contents,
+            format!("# population: {active},{silent}\nneuron,time\n{active},1.5\n{active},3\n")
+        );
+    }
+
+    #[test]
+    fn test_ndjson_export() {
+        let (mut world, active, silent) = world_with_spikes();
+        let path = std::env::temp_dir().join("silicon_spike_export.ndjson");
+
+        export_spikes(&mut world, &path).unwrap();
+        let contents = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let lines = contents.lines().collect::<Vec<_>>();
+        assert_eq!(
+            lines[0],
+            format!(
+                "{{\"population\":[\"{}\",\"{}\"]}}",
+                entity_id(active),
+                entity_id(silent)
+            )
+        );
+        assert_eq!(lines.len(), 3);
+        assert_eq!(
+            lines[1],
+            format!("{{\"neuron\":\"{}\",\"time\":1.5}}", entity_id(active))
+        );
+    }
+}
@@ -0,0 +1,131 @@
+use bevy::prelude::{Entity, Event, EventReader, Query, Res, ResMut};
+use bevy_trait_query::One;
+use silicon_core::{Clock, Neuron, SpikeRecorder};
+
+use crate::FiredNeurons;
+
+/// Make a neuron fire during the current tick, for teacher forcing or arbitration between
+/// neurons. The neuron goes through its own reset, see `Neuron::force_spike`, and the spike is
+/// sent to its synapses and the plasticity rules like any other spike.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct ForceSpikeEvent {
+    pub neuron: Entity,
+}
+
+pub(crate) fn force_spikes(
+    mut force_spike_events: EventReader<ForceSpikeEvent>,
+    mut neurons: Query<(One<&mut dyn Neuron>, Option<One<&mut dyn SpikeRecorder>>)>,
+    mut fired_neurons: ResMut<FiredNeurons>,
+    clock: Res<Clock>,
+) {
+    for event in force_spike_events.read() {
+        // the neuron may have fired on its own during this tick
+        if fired_neurons
+            .spikes
+            .iter()
+            .any(|(neuron, _)| *neuron == event.neuron)
+        {
+            continue;
+        }
+
+        let Ok((mut neuron, spike_recorder)) = neurons.get_mut(event.neuron) else {
+            continue;
+        };
+
+        if !neuron.force_spike() {
+            continue;
+        }
+
+        fired_neurons.spikes.push((event.neuron, clock.time));
+        if let Some(mut spike_recorder) = spike_recorder {
+            spike_recorder.record_spike(clock.time);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::Events;
+    use neurons::leaky::LifNeuron;
+    use synapses::{
+        stdp::{StdpParams, StdpRule, StdpSpikeType, StdpState, StdpSynapse},
+        SynapseType,
+    };
+
+    use super::*;
+    use crate::{headless::HeadlessSimulation, SpikeEvent};
+
+    #[test]
+    fn test_forced_spikes_register_stdp() {
+        let mut simulation = HeadlessSimulation::default();
+        let source = simulation.add_neuron(LifNeuron::default());
+        let target = simulation.add_neuron(LifNeuron::default());
+        let synapse = simulation.add_synapse(StdpSynapse {
+            stdp_params: StdpParams {
+                a_plus: 0.01,
+                a_minus: -0.01,
+                tau_plus: 0.2,
+                tau_minus: 0.2,
+                w_max: 1.0,
+                w_min: 0.0,
+                soft_bound: false,
+                tau_eligibility: 1000.0,
+            },
+            stdp_state: StdpState {
+                a: 0.0,
+                spike_type: StdpSpikeType::PostSpike,
+                eligibility: 0.0,
+            },
+            source,
+            target,
+            weight: 0.5,
+            delay: 1,
+            synapse_type: SynapseType::Excitatory,
+            rule: StdpRule::Asymmetric,
+        });
+        simulation.run_for(0.0125);
+
+        simulation
+            .world_mut()
+            .send_event(ForceSpikeEvent { neuron: source });
+        simulation.step();
+
+        let time = simulation.time();
+        let spikes = simulation
+            .world()
+            .resource::<Events<SpikeEvent>>()
+            .iter_current_update_events()
+            .map(|event| (event.neuron, event.time))
+            .collect::<Vec<_>>();
+        assert_eq!(spikes, [(source, time)]);
+        assert_eq!(simulation.get_spikes(source), [time]);
+        let neuron = simulation.world().get::<LifNeuron>(source).unwrap();
+        assert_eq!(neuron.refactory_counter, 2.0);
+        let stdp_state = &simulation
+            .world()
+            .get::<StdpSynapse>(synapse)
+            .unwrap()
+            .stdp_state;
+        assert_eq!(stdp_state.spike_type, StdpSpikeType::PreSpike);
+
+        // the forced postsynaptic spike pairs with the presynaptic one
+        simulation
+            .world_mut()
+            .send_event(ForceSpikeEvent { neuron: target });
+        simulation.step();
+
+        let stdp_state = &simulation
+            .world()
+            .get::<StdpSynapse>(synapse)
+            .unwrap()
+            .stdp_state;
+        assert_eq!(stdp_state.spike_type, StdpSpikeType::PostSpike);
+        // the trace of the presynaptic spike decays for a single tick before the pairing
+        let expected = 0.01 * (1.0 - 0.025);
+        assert!(
+            (stdp_state.eligibility - expected).abs() < 1e-6,
+            "eligibility {}",
+            stdp_state.eligibility
+        );
+    }
+}
@@ -0,0 +1,277 @@
+//! Run simulations without a window, for parameter sweeps and batch experiments.
+//!
+//! This lives in the simulator crate rather than in silicon-core because it drives the simulator
+//! systems, which silicon-core can't depend on.
+
+use bevy::{
+    app::{App, MinimalPlugins},
+    prelude::{Component, Entity, World},
+};
+use bevy_trait_query::RegisterExt;
+use neurons::NeuronPlugin;
+use silicon_core::{Clock, Neuron, SpikeRecorder, ValueRecorderConfig};
+use synapses::{Synapse, SynapsePlugin};
+
+use crate::{SimpleSpikeRecorder, SimulationPlugin};
+
+/// An app with the simulation, neuron and synapse plugins and nothing that needs a window or a
+/// GPU. Use [`run_for`] or [`step`] to advance it, the clock doesn't run on its own.
+///
+/// ```
+/// use neurons::leaky::LifNeuron;
+/// use silicon_core::SpikeRecorder;
+/// use simulator::{
+///     current::CurrentSource,
+///     headless::{headless_app, run_for},
+///     SimpleSpikeRecorder,
+/// };
+/// use synapses::{simple::SimpleSynapse, SynapseType};
+///
+/// let mut app = headless_app();
+/// let source = app
+///     .world_mut()
+///     .spawn((
+///         LifNeuron::default(),
+///         SimpleSpikeRecorder::default(),
+///         CurrentSource::Constant { amplitude: 2.0 },
+///     ))
+///     .id();
+/// let target = app
+///     .world_mut()
+///     .spawn((LifNeuron::default(), SimpleSpikeRecorder::default()))
+///     .id();
+/// app.world_mut().spawn(SimpleSynapse {
+///     weight: 10.0,
+///     delay: 40,
+///     source,
+///     target,
+///     synapse_type: SynapseType::Excitatory,
+/// });
+///
+/// run_for(&mut app, 0.1);
+///
+/// let recorder = app.world().get::<SimpleSpikeRecorder>(target).unwrap();
+/// assert!(!recorder.get_spikes().is_empty());
+/// ```
+pub fn headless_app() -> App {
+    headless_app_with(SimulationPlugin::default())
+}
+
+fn headless_app_with(plugin: SimulationPlugin) -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, plugin, NeuronPlugin, SynapsePlugin))
+        .insert_resource(ValueRecorderConfig {
+            window_size: 10000,
+            max_points: None,
+        });
+    app
+}
+
+/// Advance the app by a single tick.
+pub fn step(app: &mut App) {
+    // systems that run after the clock check the remaining time, half a tick of slack keeps
+    // them running for this tick
+    let mut clock = app.world_mut().resource_mut::<Clock>();
+    clock.time_to_simulate = clock.tau * 1.5;
+    clock.run_indefinitely = false;
+
+    app.update();
+
+    app.world_mut().resource_mut::<Clock>().time_to_simulate = 0.0;
+}
+
+/// Advance the app by `duration_s` seconds, rounded to a whole number of ticks.
+pub fn run_for(app: &mut App, duration_s: f64) {
+    let tau = app.world().resource::<Clock>().tau;
+    let ticks = (duration_s * 1000.0 / tau).round() as usize;
+    for _ in 0..ticks {
+        step(app);
+    }
+}
+
+/// A simulation without any rendering, every call to `step` advances the clock by exactly one tick.
+///
+/// Neuron and synapse types from outside the neurons and synapses crates are registered when the
+/// first one of their type is added. Trait queries can't learn about new types once they have run,
+/// so every type has to be added before the first step.
+pub struct HeadlessSimulation {
+    app: App,
+}
+
+impl HeadlessSimulation {
+    pub fn new(plugin: SimulationPlugin) -> Self {
+        HeadlessSimulation {
+            app: headless_app_with(plugin),
+        }
+    }
+
+    pub fn world(&self) -> &World {
+        self.app.world()
+    }
+
+    pub fn world_mut(&mut self) -> &mut World {
+        self.app.world_mut()
+    }
+
+    pub fn app_mut(&mut self) -> &mut App {
+        &mut self.app
+    }
+
+    /// The simulated time in ms.
+    pub fn time(&self) -> f64 {
+        self.app.world().resource::<Clock>().time
+    }
+
+    /// Spawns the neuron together with a `SimpleSpikeRecorder`.
+    pub fn add_neuron<N: Neuron + Component>(&mut self, model: N) -> Entity {
+        self.app.register_component_as::<dyn Neuron, N>();
+        self.app
+            .world_mut()
+            .spawn((model, SimpleSpikeRecorder::default()))
+            .id()
+    }
+
+    pub fn add_synapse<S: Synapse + Component>(&mut self, synapse: S) -> Entity {
+        self.app.register_component_as::<dyn Synapse, S>();
+        self.app.world_mut().spawn(synapse).id()
+    }
+
+    /// Advance the simulation by a single tick.
+    pub fn step(&mut self) {
+        step(&mut self.app);
+    }
+
+    /// Advance the simulation by `seconds`, rounded to a whole number of ticks.
+    pub fn run_for(&mut self, seconds: f64) {
+        run_for(&mut self.app, seconds);
+    }
+
+    /// The spike times in ms of a neuron added with `add_neuron`.
+    pub fn get_spikes(&self, entity: Entity) -> Vec<f64> {
+        self.app
+            .world()
+            .get::<SimpleSpikeRecorder>(entity)
+            .map(|recorder| recorder.get_spikes())
+            .unwrap_or_default()
+    }
+}
+
+impl Default for HeadlessSimulation {
+    fn default() -> Self {
+        HeadlessSimulation::new(SimulationPlugin::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use neurons::leaky::LifNeuron;
+
+    use super::*;
+    use crate::{current::CurrentSource, noise::MembraneNoise};
+
+    #[test]
+    fn test_driven_lif_neuron() {
+        let mut simulation = HeadlessSimulation::new(SimulationPlugin::with_seed(1));
+        let neuron = simulation.add_neuron(LifNeuron::default());
+        simulation
+            .world_mut()
+            .entity_mut(neuron)
+            .insert(CurrentSource::Constant { amplitude: 2.0 });
+
+        simulation.run_for(1.0);
+
+        // the same neuron updated directly, once per tick
+        let tau = simulation.world().resource::<Clock>().tau;
+        let mut reference = LifNeuron::default();
+        let expected = (0..(1000.0 / tau).round() as usize)
+            .filter(|_| {
+                reference.insert_current(2.0);
+                reference.update(tau)
+            })
+            .count();
+
+        assert!((simulation.time() - 1000.0).abs() < 1e-6);
+        assert_eq!(simulation.get_spikes(neuron).len(), expected);
+        // the neuron reaches the threshold after 13.9ms and is refractory for 2ms
+        assert!((60..=66).contains(&expected));
+    }
+
+    #[test]
+    fn test_same_seed_is_bit_identical() {
+        fn noisy_run(seed: u64) -> Vec<f64> {
+            let mut simulation = HeadlessSimulation::new(SimulationPlugin::with_seed(seed));
+            let neurons = (0..3)
+                .map(|_| {
+                    let neuron = simulation.add_neuron(LifNeuron::default());
+                    simulation
+                        .world_mut()
+                        .entity_mut(neuron)
+                        .insert(MembraneNoise::new(1.8, 1.0, 5.0));
+                    neuron
+                })
+                .collect::<Vec<_>>();
+
+            simulation.run_for(0.5);
+            neurons
+                .iter()
+                .flat_map(|neuron| simulation.get_spikes(*neuron))
+                .collect()
+        }
+
+        let spikes = noisy_run(42);
+        assert!(!spikes.is_empty());
+        assert_eq!(spikes, noisy_run(42));
+        assert_ne!(spikes, noisy_run(43));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_checkpoint_round_trip() {
+        use silicon_core::checkpoint::{restore_checkpoint, save_checkpoint};
+
+        fn simulation() -> HeadlessSimulation {
+            HeadlessSimulation::new(SimulationPlugin::with_seed(1))
+        }
+
+        fn driven_neuron(simulation: &mut HeadlessSimulation) -> Entity {
+            let neuron = simulation.add_neuron(LifNeuron::default());
+            simulation
+                .world_mut()
+                .entity_mut(neuron)
+                .insert(CurrentSource::Constant { amplitude: 2.0 });
+            neuron
+        }
+
+        // 0.5 s on either side of the checkpoint instead of 10 s, the checkpoint only captures a
+        // single instant and the driven neuron goes through dozens of spikes and refractory
+        // periods before and after it. 20 s would take over a million updates in a debug build.
+        let mut reference = simulation();
+        let reference_neuron = driven_neuron(&mut reference);
+        reference.run_for(1.0);
+
+        let mut interrupted = simulation();
+        let neuron = driven_neuron(&mut interrupted);
+        interrupted.run_for(0.5);
+        let spikes_before_checkpoint = interrupted.get_spikes(neuron).len();
+        assert!(spikes_before_checkpoint > 10);
+        let checkpoint = save_checkpoint(interrupted.world()).unwrap();
+
+        let mut resumed = simulation();
+        restore_checkpoint(resumed.app_mut(), &checkpoint).unwrap();
+        resumed.run_for(0.5);
+
+        let expected = reference
+            .world()
+            .get::<LifNeuron>(reference_neuron)
+            .unwrap();
+        let restored = resumed.world().get::<LifNeuron>(neuron).unwrap();
+        assert_eq!(resumed.time(), reference.time());
+        assert_eq!(restored.membrane_potential, expected.membrane_potential);
+        assert_eq!(restored.refactory_counter, expected.refactory_counter);
+        assert_eq!(
+            resumed.get_spikes(neuron),
+            reference.get_spikes(reference_neuron)
+        );
+        assert!(resumed.get_spikes(neuron).len() > spikes_before_checkpoint + 10);
+    }
+}
@@ -0,0 +1,138 @@
+use bevy::{
+    prelude::{Entity, EventReader, Local, Query, Res, Resource},
+    reflect::Reflect,
+    utils::HashMap,
+};
+use bevy_trait_query::One;
+use synapses::{Synapse, SynapseType};
+
+use crate::{synapse_index::SynapseIndex, SpikeEvent};
+
+/// A resource that configures heterosynaptic decay.
+/// Add this resource to the App to enable the decay.
+/// Whenever a neuron spikes, the incoming synapses whose presynaptic neuron didn't fire within
+/// `active_window` ms lose `fraction` of their weight. Unlike `SynapseDecay` only the pathways
+/// onto active neurons that didn't take part in their activity weaken, and a weight approaches
+/// the lower bound of its synapse instead of crossing it.
+#[derive(Debug, Clone, Reflect, Resource)]
+pub struct HeterosynapticDecay {
+    /// the relative weight loss of an inactive synapse per postsynaptic spike
+    pub fraction: f64,
+    /// a synapse counts as active if its presynaptic neuron fired this many ms before the
+    /// postsynaptic spike
+    pub active_window: f64,
+}
+
+impl Default for HeterosynapticDecay {
+    fn default() -> Self {
+        HeterosynapticDecay {
+            fraction: 0.01,
+            active_window: 20.0,
+        }
+    }
+}
+
+pub(crate) fn heterosynaptic_decay(
+    mut synapses: Query<One<&mut dyn Synapse>>,
+    mut spike_reader: EventReader<SpikeEvent>,
+    index: Res<SynapseIndex>,
+    decay: Option<Res<HeterosynapticDecay>>,
+    mut last_spikes: Local<HashMap<Entity, f64>>,
+) {
+    let Some(decay) = decay else {
+        spike_reader.clear();
+        return;
+    };
+
+    // presynaptic spikes of the same tick count as active, so record all of them first
+    let spikes = spike_reader
+        .read()
+        .map(|spike| (spike.neuron, spike.time))
+        .collect::<Vec<_>>();
+    last_spikes.extend(spikes.iter().copied());
+
+    for (neuron, spike_time) in spikes {
+        for entity in index.incoming(neuron) {
+            let Ok(mut synapse) = synapses.get_mut(*entity) else {
+                continue;
+            };
+            if synapse.get_type() == SynapseType::Electrical {
+                continue;
+            }
+
+            let active = last_spikes
+                .get(&synapse.get_presynaptic())
+                .is_some_and(|time| spike_time - time <= decay.active_window);
+            if active {
+                continue;
+            }
+
+            let min = synapse.weight_bounds().map_or(0.0, |(min, _)| min);
+            let weight = synapse.get_weight();
+            if weight > min {
+                synapse.set_weight(min + (weight - min) * (1.0 - decay.fraction));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use silicon_core::Clock;
+    use synapses::simple::SimpleSynapse;
+
+    use super::*;
+    use crate::{
+        headless::{headless_app, step},
+        FiredNeurons,
+    };
+
+    #[test]
+    fn test_unused_pathway_onto_active_neuron_weakens() {
+        let mut app = headless_app();
+        app.insert_resource(HeterosynapticDecay {
+            fraction: 0.1,
+            active_window: 5.0,
+        });
+        app.world_mut().resource_mut::<Clock>().tau = 1.0;
+
+        let active = app.world_mut().spawn_empty().id();
+        let silent = app.world_mut().spawn_empty().id();
+        let post = app.world_mut().spawn_empty().id();
+        // fires, but never close to the spikes of `post`
+        let other = app.world_mut().spawn_empty().id();
+        let other_post = app.world_mut().spawn_empty().id();
+        let synapse = |source, target| SimpleSynapse {
+            weight: 0.5,
+            delay: 1,
+            source,
+            target,
+            synapse_type: SynapseType::Excitatory,
+        };
+        let active_synapse = app.world_mut().spawn(synapse(active, post)).id();
+        let silent_synapse = app.world_mut().spawn(synapse(silent, post)).id();
+        let early_synapse = app.world_mut().spawn(synapse(other, post)).id();
+        // the target never fires, so its synapses are left alone
+        let quiet_synapse = app.world_mut().spawn(synapse(silent, other_post)).id();
+
+        for tick in 0..500 {
+            let time = app.world().resource::<Clock>().time;
+            let spikes = match tick % 50 {
+                0 => vec![(other, time)],
+                20 => vec![(active, time)],
+                22 => vec![(post, time)],
+                _ => vec![],
+            };
+            app.world_mut().resource_mut::<FiredNeurons>().spikes = spikes;
+            step(&mut app);
+        }
+
+        let weight = |synapse| app.world().get::<SimpleSynapse>(synapse).unwrap().weight;
+        assert_eq!(weight(active_synapse), 0.5);
+        assert_eq!(weight(quiet_synapse), 0.5);
+        // ten postsynaptic spikes
+        let decayed = 0.5 * 0.9f64.powi(10);
+        assert!((weight(silent_synapse) - decayed).abs() < 1e-12);
+        assert!((weight(early_synapse) - decayed).abs() < 1e-12);
+    }
+}
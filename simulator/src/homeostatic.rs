@@ -0,0 +1,342 @@
+use bevy::{
+    prelude::{Entity, Query, Res, ResMut, Resource},
+    reflect::Reflect,
+    utils::HashMap,
+};
+use bevy_trait_query::One;
+use silicon_core::{Clock, SpikeRecorder};
+use synapses::{Synapse, SynapseType};
+use tracing::trace;
+
+use crate::SimpleSpikeRecorder;
+
+/// A resource that configures homeostatic synaptic scaling.
+/// Add this resource to the App to enable scaling.
+/// Every `scaling_interval` ms the firing rate of each neuron over the last interval is compared to
+/// `target_rate` and all incoming excitatory weights are multiplied by the same factor, which keeps
+/// the relative weight structure learned by STDP intact. Inhibitory weights are left alone, growing
+/// them along with the excitatory ones would work against the rate correction. The weights are
+/// clamped to the bounds of their synapse after scaling.
+#[derive(Debug, Clone, Reflect, Resource)]
+pub struct HomeostaticScaling {
+    /// target firing rate in Hz
+    pub target_rate: f64,
+    pub scaling_interval: f64,
+    /// relative weight change per interval when the neuron is completely silent
+    pub scaling_strength: f64,
+    pub next_scaling: f64,
+}
+
+impl Default for HomeostaticScaling {
+    fn default() -> Self {
+        HomeostaticScaling {
+            target_rate: 5.0,
+            scaling_interval: 1000.0,
+            scaling_strength: 0.1,
+            next_scaling: 1000.0,
+        }
+    }
+}
+
+impl HomeostaticScaling {
+    /// The factor the incoming weights of a neuron firing at `rate` Hz are multiplied with.
+    pub fn scaling_factor(&self, rate: f64) -> f64 {
+        (1.0 + self.scaling_strength * (self.target_rate - rate) / self.target_rate).max(0.0)
+    }
+}
+
+pub(crate) fn homeostatic_scaling(
+    neurons: Query<(Entity, &SimpleSpikeRecorder)>,
+    mut synapses: Query<One<&mut dyn Synapse>>,
+    clock: Res<Clock>,
+    mut scaling: Option<ResMut<HomeostaticScaling>>,
+) {
+    let Some(scaling) = scaling.as_mut() else {
+        return;
+    };
+
+    if clock.time < scaling.next_scaling {
+        return;
+    }
+
+    scaling.next_scaling = clock.time + scaling.scaling_interval;
+    let window_start = clock.time - scaling.scaling_interval;
+
+    for (entity, recorder) in neurons.iter() {
+        let spikes = recorder
+            .get_spikes()
+            .iter()
+            .filter(|time| **time > window_start)
+            .count();
+        let rate = spikes as f64 / scaling.scaling_interval * 1000.0;
+        let factor = scaling.scaling_factor(rate);
+
+        trace!(
+            "Scaling incoming weights of {:?} firing at {}Hz by {}",
+            entity,
+            rate,
+            factor
+        );
+
+        for mut synapse in synapses.iter_mut() {
+            if synapse.get_postsynaptic() != entity || synapse.get_type() != SynapseType::Excitatory
+            {
+                continue;
+            }
+
+            let mut weight = synapse.get_weight() * factor;
+            if let Some((w_min, w_max)) = synapse.weight_bounds() {
+                weight = weight.clamp(w_min, w_max);
+            }
+            synapse.set_weight(weight);
+        }
+    }
+}
+
+/// A resource that configures synaptic scaling, the normalization of the incoming weights.
+/// Add this resource to the App to enable scaling.
+/// Every `scaling_interval` ms the incoming excitatory weights of each neuron are multiplied by
+/// the same factor, so they sum up to `target_total_weight`. This keeps STDP from driving all
+/// weights onto a neuron to their maximum while the ratios between them stay intact.
+#[derive(Debug, Clone, Reflect, Resource)]
+pub struct SynapticScaling {
+    /// the sum of the incoming excitatory weights of every neuron after scaling
+    pub target_total_weight: f64,
+    pub scaling_interval: f64,
+    pub next_scaling: f64,
+}
+
+impl Default for SynapticScaling {
+    fn default() -> Self {
+        SynapticScaling {
+            target_total_weight: 5.0,
+            scaling_interval: 1000.0,
+            next_scaling: 1000.0,
+        }
+    }
+}
+
+/// Weights are clamped to the bounds of their synapse after scaling, a neuron with clamped
+/// weights can end up off the target.
+pub(crate) fn synaptic_scaling(
+    mut synapses: Query<One<&mut dyn Synapse>>,
+    clock: Res<Clock>,
+    mut scaling: Option<ResMut<SynapticScaling>>,
+) {
+    let Some(scaling) = scaling.as_mut() else {
+        return;
+    };
+
+    if clock.time < scaling.next_scaling {
+        return;
+    }
+
+    scaling.next_scaling = clock.time + scaling.scaling_interval;
+
+    let mut total_weights = HashMap::<Entity, f64>::new();
+    for synapse in synapses.iter() {
+        if synapse.get_type() == SynapseType::Excitatory {
+            *total_weights.entry(synapse.get_postsynaptic()).or_default() += synapse.get_weight();
+        }
+    }
+
+    for mut synapse in synapses.iter_mut() {
+        if synapse.get_type() != SynapseType::Excitatory {
+            continue;
+        }
+
+        let total_weight = total_weights[&synapse.get_postsynaptic()];
+        if total_weight <= 0.0 {
+            continue;
+        }
+
+        let mut weight = synapse.get_weight() * scaling.target_total_weight / total_weight;
+        if let Some((w_min, w_max)) = synapse.weight_bounds() {
+            weight = weight.clamp(w_min, w_max);
+        }
+        synapse.set_weight(weight);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::app::{App, Update};
+    use bevy_trait_query::RegisterExt;
+    use synapses::{
+        simple::SimpleSynapse,
+        stdp::{StdpParams, StdpRule, StdpSpikeType, StdpState, StdpSynapse},
+    };
+
+    use super::*;
+
+    fn homeostatic_app() -> App {
+        let mut app = App::new();
+        app.insert_resource(Clock::default())
+            .insert_resource(HomeostaticScaling {
+                target_rate: 5.0,
+                scaling_interval: 100.0,
+                scaling_strength: 0.1,
+                next_scaling: 100.0,
+            })
+            .register_component_as::<dyn Synapse, SimpleSynapse>()
+            .register_component_as::<dyn Synapse, StdpSynapse>()
+            .add_systems(Update, homeostatic_scaling);
+        app
+    }
+
+    /// Runs ten scaling intervals of a silent network.
+    fn run_silent(app: &mut App) {
+        for step in 1..=10 {
+            app.world_mut().resource_mut::<Clock>().time = step as f64 * 100.0;
+            app.update();
+        }
+    }
+
+    #[test]
+    fn test_silenced_neuron_weights_grow() {
+        let mut app = homeostatic_app();
+        let target = app.world_mut().spawn(SimpleSpikeRecorder::default()).id();
+        let synapses =
+            [0.2, 0.4].map(|weight| synapse(&mut app, target, weight, SynapseType::Excitatory));
+
+        run_silent(&mut app);
+
+        let weights =
+            synapses.map(|synapse| app.world().get::<SimpleSynapse>(synapse).unwrap().weight);
+        assert!(weights[0] > 0.2 * 2.0);
+        // the relative weight structure is preserved
+        assert!((weights[1] / weights[0] - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_homeostatic_scaling_leaves_inhibition_and_respects_bounds() {
+        let mut app = homeostatic_app();
+        let target = app.world_mut().spawn(SimpleSpikeRecorder::default()).id();
+        let inhibitory = synapse(&mut app, target, 0.4, SynapseType::Inhibitory);
+        let bounded = app.world_mut().spawn(bounded_synapse(target, 0.4)).id();
+
+        run_silent(&mut app);
+
+        assert_eq!(
+            app.world().get::<SimpleSynapse>(inhibitory).unwrap().weight,
+            0.4
+        );
+        assert_eq!(app.world().get::<StdpSynapse>(bounded).unwrap().weight, 0.5);
+    }
+
+    #[test]
+    fn test_scaling_factor() {
+        let scaling = HomeostaticScaling::default();
+
+        assert!(scaling.scaling_factor(0.0) > 1.0);
+        assert_eq!(scaling.scaling_factor(scaling.target_rate), 1.0);
+        assert!(scaling.scaling_factor(50.0) < 1.0);
+        assert!(scaling.scaling_factor(1000.0) >= 0.0);
+    }
+
+    fn scaling_app() -> App {
+        let mut app = App::new();
+        app.insert_resource(Clock {
+            time: 100.0,
+            ..Default::default()
+        })
+        .insert_resource(SynapticScaling {
+            target_total_weight: 2.0,
+            scaling_interval: 100.0,
+            next_scaling: 100.0,
+        })
+        .register_component_as::<dyn Synapse, SimpleSynapse>()
+        .register_component_as::<dyn Synapse, StdpSynapse>()
+        .add_systems(Update, synaptic_scaling);
+        app
+    }
+
+    fn synapse(app: &mut App, target: Entity, weight: f64, synapse_type: SynapseType) -> Entity {
+        app.world_mut()
+            .spawn(SimpleSynapse {
+                weight,
+                delay: 1,
+                source: Entity::PLACEHOLDER,
+                target,
+                synapse_type,
+            })
+            .id()
+    }
+
+    #[test]
+    fn test_incoming_weights_sum_to_target() {
+        let mut app = scaling_app();
+        let neurons = [(); 2].map(|_| app.world_mut().spawn_empty().id());
+        let initial_weights = [vec![0.1, 0.2, 0.3], vec![0.5, 1.5, 4.0]];
+        let synapses = initial_weights
+            .iter()
+            .zip(neurons)
+            .map(|(weights, neuron)| {
+                weights
+                    .iter()
+                    .map(|weight| synapse(&mut app, neuron, *weight, SynapseType::Excitatory))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let inhibitory = synapse(&mut app, neurons[0], 0.7, SynapseType::Inhibitory);
+
+        app.update();
+
+        let weight =
+            |app: &App, synapse: Entity| app.world().get::<SimpleSynapse>(synapse).unwrap().weight;
+        for (synapses, initial_weights) in synapses.iter().zip(initial_weights.iter()) {
+            let weights = synapses
+                .iter()
+                .map(|synapse| weight(&app, *synapse))
+                .collect::<Vec<_>>();
+            assert!((weights.iter().sum::<f64>() - 2.0).abs() < 1e-12);
+            for (weight, initial_weight) in weights.iter().zip(initial_weights) {
+                assert!((weight / weights[0] - initial_weight / initial_weights[0]).abs() < 1e-12);
+            }
+        }
+        assert_eq!(weight(&app, inhibitory), 0.7);
+    }
+
+    /// An excitatory STDP synapse onto `target` with its weight bounded to `[0, 0.5]`.
+    fn bounded_synapse(target: Entity, weight: f64) -> StdpSynapse {
+        StdpSynapse {
+            stdp_params: StdpParams {
+                a_plus: 0.01,
+                a_minus: -0.01,
+                tau_plus: 20.0,
+                tau_minus: 20.0,
+                w_max: 0.5,
+                w_min: 0.0,
+                soft_bound: false,
+                tau_eligibility: 1000.0,
+            },
+            stdp_state: StdpState {
+                a: 0.0,
+                spike_type: StdpSpikeType::PreSpike,
+                eligibility: 0.0,
+            },
+            source: Entity::PLACEHOLDER,
+            target,
+            weight,
+            delay: 1,
+            synapse_type: SynapseType::Excitatory,
+            rule: StdpRule::Asymmetric,
+        }
+    }
+
+    #[test]
+    fn test_scaled_weights_respect_bounds() {
+        let mut app = scaling_app();
+        let neuron = app.world_mut().spawn_empty().id();
+        let bounded = app.world_mut().spawn(bounded_synapse(neuron, 0.4)).id();
+        let unbounded = synapse(&mut app, neuron, 0.4, SynapseType::Excitatory);
+
+        app.update();
+
+        assert_eq!(app.world().get::<StdpSynapse>(bounded).unwrap().weight, 0.5);
+        assert_eq!(
+            app.world().get::<SimpleSynapse>(unbounded).unwrap().weight,
+            1.0
+        );
+    }
+}
@@ -0,0 +1,107 @@
+use bevy::{
+    prelude::{Component, Entity, EventReader, Query, Res},
+    reflect::Reflect,
+    utils::HashSet,
+};
+use bevy_trait_query::One;
+use silicon_core::{Clock, Neuron};
+
+use crate::SpikeEvent;
+
+/// The parameter the threshold is read from and written to through the `Neuron` parameter API.
+pub const THRESHOLD_PARAMETER: &str = "threshold_potential";
+
+/// Intrinsic plasticity keeps the neuron on the same entity firing at `target_rate` by moving its
+/// threshold. Every tick the threshold is raised when the estimated rate is above the target and
+/// lowered when it's below, in proportion to the difference. The neuron has to expose a
+/// `threshold_potential` parameter, like `LifNeuron` does.
+#[derive(Debug, Component, Reflect)]
+pub struct IntrinsicPlasticity {
+    /// target firing rate in Hz
+    pub target_rate: f64,
+    /// threshold change in mV per ms for every Hz the rate differs from the target
+    pub learning_rate: f64,
+    /// time constant in ms of the moving average of the firing rate
+    pub tau_rate: f64,
+    /// the estimated firing rate in Hz
+    pub rate: f64,
+}
+
+impl IntrinsicPlasticity {
+    pub fn new(target_rate: f64) -> Self {
+        IntrinsicPlasticity {
+            target_rate,
+            learning_rate: 0.001,
+            tau_rate: 200.0,
+            rate: 0.0,
+        }
+    }
+
+    /// Update the rate estimate for a tick of `tau` ms and return the change of the threshold.
+    pub fn step(&mut self, tau: f64, fired: bool) -> f64 {
+        self.rate *= (-tau / self.tau_rate).exp();
+        if fired {
+            self.rate += 1000.0 / self.tau_rate;
+        }
+
+        self.learning_rate * (self.rate - self.target_rate) * tau
+    }
+}
+
+pub(crate) fn intrinsic_plasticity(
+    mut spike_reader: EventReader<SpikeEvent>,
+    mut neurons: Query<(Entity, &mut IntrinsicPlasticity, One<&mut dyn Neuron>)>,
+    clock: Res<Clock>,
+) {
+    if clock.time_to_simulate <= 0.0 {
+        return;
+    }
+
+    let fired = spike_reader
+        .read()
+        .map(|spike_event| spike_event.neuron)
+        .collect::<HashSet<_>>();
+
+    for (entity, mut plasticity, mut neuron) in neurons.iter_mut() {
+        let delta = plasticity.step(clock.tau, fired.contains(&entity));
+        // neurons without a threshold parameter are left alone
+        if let Some(threshold) = neuron.get_parameter(THRESHOLD_PARAMETER) {
+            let _ = neuron.set_parameter(THRESHOLD_PARAMETER, threshold + delta);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use neurons::leaky::LifNeuron;
+
+    use super::*;
+    use crate::{current::CurrentSource, headless::HeadlessSimulation};
+
+    #[test]
+    fn test_rate_converges_to_target() {
+        let mut simulation = HeadlessSimulation::default();
+        let neuron = simulation.add_neuron(LifNeuron::default());
+        simulation.world_mut().entity_mut(neuron).insert((
+            // without plasticity the neuron fires at about 110Hz
+            CurrentSource::Constant { amplitude: 3.0 },
+            IntrinsicPlasticity::new(40.0),
+        ));
+
+        simulation.run_for(5.0);
+
+        let spikes = simulation.get_spikes(neuron);
+        let last_second = spikes.iter().filter(|time| **time > 4000.0).count() as f64;
+        assert!(
+            (last_second - 40.0).abs() <= 4.0,
+            "fired at {last_second}Hz"
+        );
+
+        let threshold = simulation
+            .world()
+            .get::<LifNeuron>(neuron)
+            .unwrap()
+            .threshold_potential;
+        assert!(threshold > -55.0);
+    }
+}
@@ -1,27 +1,74 @@
 #![allow(clippy::type_complexity)]
 
+use std::collections::VecDeque;
+
+use adaptation::{apply_adaptation, increment_adaptation, Adaptation};
 use bevy::{
     app::{App, Plugin, Update},
+    ecs::entity::Entities,
     hierarchy::DespawnRecursiveExt,
     prelude::{
-        Commands, Component, Entity, Event, EventReader, EventWriter, Events, Query, Res, ResMut,
-        Resource,
+        resource_equals, Commands, Component, Entity, Event, EventReader, EventWriter,
+        IntoSystemConfigs, IntoSystemSetConfigs, Query, Res, ResMut, Resource, Without,
     },
     reflect::Reflect,
 };
-use bevy_mod_outline::OutlinePlugin;
+#[cfg(feature = "parallel_neurons")]
+use bevy::{prelude::Local, utils::Parallel};
 use bevy_trait_query::{One, RegisterExt};
+use current::{apply_current_sources, CurrentSource};
+use delay::{tick_at, DelayBuffer};
+use dopamine::{
+    apply_deferred_stdp, apply_reward_signals, dopamine_decay, dopamine_modulated_stdp, Dopamine,
+    DopamineReleaseEvent, RewardModel, RewardSignal,
+};
+use event_driven::{update_neurons_event_driven, NeuronActivity, SimulationMode};
+use force::{force_spikes, ForceSpikeEvent};
+use heterosynaptic::{heterosynaptic_decay, HeterosynapticDecay};
+use homeostatic::{homeostatic_scaling, synaptic_scaling, HomeostaticScaling, SynapticScaling};
+use intrinsic::{intrinsic_plasticity, IntrinsicPlasticity};
+use noise::{apply_membrane_noise, MembraneNoise};
+use pattern::{match_spike_patterns, PatternDetectedEvent, PatternMatcher};
+use player::{play_spike_trains, SpikeTrainPlayer};
 use recorder::{clean_recorder_history, record_membrane_potential, record_synapse_weight};
-use silicon_core::{Clock, Neuron, SpikeRecorder};
+use reset::{
+    record_initial_weights, reset_network, InitialWeights, ResetNetworkEvent, ResetSimulation,
+};
+#[cfg(feature = "serde")]
+use silicon_core::{checkpoint::CheckpointExt, ValueRecorder};
+use silicon_core::{Clock, Neuron, SimulationRng, SimulationStep, SpikeRecorder};
+use synapse_index::{index_synapses, unindex_despawned_synapses, SynapseIndex};
 use synapses::{
+    bcm::BcmSynapse,
     stdp::{StdpSettings, StdpSynapse},
-    DeferredStdpEvent, Synapse, SynapseType,
+    triplet_stdp::TripletStdpSynapse,
+    CompartmentTarget, DeferredStdpEvent, FrozenPlasticity, Synapse, SynapseType, SynapticInputSet,
+    TransmissionMode,
 };
-use time::update_clock;
-use tracing::{info, trace, warn};
+use time::{run_simulation_steps, update_clock, SimulationSpeed};
+use trace::record_binary_trace;
+use tracing::{info, warn};
 
+pub mod adaptation;
+pub mod current;
+pub mod delay;
+pub mod dopamine;
+pub mod event_driven;
+pub mod export;
+pub mod force;
+pub mod headless;
+pub mod heterosynaptic;
+pub mod homeostatic;
+pub mod intrinsic;
+pub mod noise;
+pub mod pattern;
+pub mod player;
 pub mod recorder;
+pub mod reset;
+pub mod structural;
+pub mod synapse_index;
 pub mod time;
+pub mod trace;
 
 #[derive(Event, Debug)]
 pub struct SpikeEvent {
@@ -35,42 +82,137 @@ pub struct Spike {
     pub neuron: Entity,
 }
 
-pub struct SimulationPlugin;
+/// Sets up the simulation clock, the neuron and synapse update systems and the recorders.
+/// When `seed` is set the `SimulationRng` is seeded with it so runs can be reproduced,
+/// otherwise it is seeded from entropy.
+#[derive(Default)]
+pub struct SimulationPlugin {
+    pub seed: Option<u64>,
+}
+
+impl SimulationPlugin {
+    pub fn with_seed(seed: u64) -> Self {
+        SimulationPlugin { seed: Some(seed) }
+    }
+}
 
 impl Plugin for SimulationPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(Clock {
-            time: 0.0,
-            tau: 0.025,
-            time_to_simulate: 0.0,
-            run_indefinitely: false,
-        })
-        .add_plugins(OutlinePlugin)
-        .register_type::<Clock>()
-        .register_type::<StdpSettings>()
-        .register_type::<SimpleSpikeRecorder>()
-        .add_event::<SpikeEvent>()
-        .insert_resource(PruneSettings::default())
-        .register_component_as::<dyn SpikeRecorder, SimpleSpikeRecorder>()
-        .add_systems(
-            Update,
-            (
-                update_clock,
-                update_neurons,
-                update_synapses_for_spikes,
-                update_synapses,
-                prune_synapses,
-                // reward_modulated_stdp,
-            ),
-        )
-        .add_systems(
-            Update,
-            (
-                record_membrane_potential,
-                record_synapse_weight,
-                clean_recorder_history,
-            ),
-        );
+        app.insert_resource(Clock::default())
+            .register_type::<Clock>()
+            .register_type::<SimulationSpeed>()
+            .init_resource::<SimulationSpeed>()
+            .register_type::<StdpSettings>()
+            .register_type::<SimpleSpikeRecorder>()
+            .register_type::<PatternMatcher>()
+            .register_type::<MembraneNoise>()
+            .register_type::<CurrentSource>()
+            .register_type::<HomeostaticScaling>()
+            .register_type::<SynapticScaling>()
+            .register_type::<HeterosynapticDecay>()
+            .register_type::<Dopamine>()
+            .register_type::<RewardModel>()
+            .register_type::<Adaptation>()
+            .register_type::<IntrinsicPlasticity>()
+            .register_type::<SpikeTrainPlayer>()
+            .register_type::<SimulationMode>()
+            .insert_resource(match self.seed {
+                Some(seed) => SimulationRng::from_seed(seed),
+                None => SimulationRng::default(),
+            })
+            .add_event::<SpikeEvent>()
+            .add_event::<PatternDetectedEvent>()
+            .add_event::<ForceSpikeEvent>()
+            .add_event::<ResetNetworkEvent>()
+            .add_event::<ResetSimulation>()
+            .add_event::<SynapsePrunedEvent>()
+            .add_event::<RewardSignal>()
+            .add_event::<DopamineReleaseEvent>()
+            .register_type::<PruneSettings>()
+            .register_type::<SynapseSpawnTime>()
+            .insert_resource(PruneSettings::default())
+            .init_resource::<DelayBuffer>()
+            .init_resource::<FiredNeurons>()
+            .init_resource::<SynapseIndex>()
+            .init_resource::<SimulationMode>()
+            .init_resource::<NeuronActivity>()
+            .init_resource::<InitialWeights>()
+            .register_component_as::<dyn SpikeRecorder, SimpleSpikeRecorder>()
+            .configure_sets(
+                SimulationStep,
+                SynapticInputSet
+                    .after(update_clock)
+                    .before(update_neurons)
+                    .before(update_neurons_event_driven),
+            )
+            .add_systems(
+                SimulationStep,
+                (
+                    reset_network.before(update_clock),
+                    update_clock.before(deliver_delayed_spikes),
+                    deliver_delayed_spikes.before(update_neurons),
+                    apply_current_sources.before(update_neurons),
+                    apply_membrane_noise.before(update_neurons),
+                    apply_adaptation.before(update_neurons),
+                    update_neurons.run_if(resource_equals(SimulationMode::Synchronous)),
+                    update_neurons_event_driven
+                        .run_if(resource_equals(SimulationMode::EventDriven))
+                        .after(update_neurons)
+                        .before(emit_spikes),
+                    play_spike_trains
+                        .after(update_neurons)
+                        .after(update_neurons_event_driven)
+                        .before(emit_spikes),
+                    force_spikes.after(play_spike_trains).before(emit_spikes),
+                    emit_spikes.after(update_neurons),
+                    index_synapses.before(emit_spikes),
+                    update_synapses_for_spikes.after(emit_spikes),
+                    increment_adaptation.after(emit_spikes),
+                    update_synapses,
+                    prune_synapses,
+                    homeostatic_scaling
+                        .after(update_neurons)
+                        .after(update_synapses),
+                    synaptic_scaling
+                        .after(update_synapses)
+                        .after(homeostatic_scaling),
+                ),
+            )
+            .add_systems(
+                SimulationStep,
+                (
+                    record_initial_weights.before(reset_network),
+                    tag_synapse_spawn_time.before(prune_synapses),
+                    unindex_despawned_synapses.before(index_synapses),
+                    intrinsic_plasticity.after(emit_spikes),
+                    dopamine_modulated_stdp.after(update_synapses),
+                    apply_reward_signals.after(update_synapses),
+                    dopamine_decay
+                        .after(dopamine_modulated_stdp)
+                        .after(apply_reward_signals),
+                    apply_deferred_stdp.after(dopamine_decay).after(emit_spikes),
+                    heterosynaptic_decay.after(emit_spikes),
+                    orphan_cleanup.before(emit_spikes),
+                    record_membrane_potential,
+                    record_synapse_weight,
+                    match_spike_patterns,
+                    record_binary_trace
+                        .after(update_neurons)
+                        .after(update_neurons_event_driven),
+                ),
+            )
+            .add_systems(
+                Update,
+                (
+                    run_simulation_steps,
+                    clean_recorder_history.after(run_simulation_steps),
+                ),
+            );
+
+        #[cfg(feature = "serde")]
+        app.register_checkpoint::<SimpleSpikeRecorder>()
+            .register_checkpoint::<ValueRecorder>()
+            .register_checkpoint::<CurrentSource>();
     }
 }
 
@@ -105,52 +247,140 @@ where
     Some(values.iter().map(|v| (v.clone()).into()).sum::<f64>() / values.len() as f64)
 }
 
-fn reward_modulated_stdp(
-    mut deferred_stdp_events: ResMut<Events<DeferredStdpEvent>>,
-    mut stdp_synapses: Query<(Entity, &mut StdpSynapse)>,
-) {
-    for event in deferred_stdp_events.drain() {
-        let synapse = stdp_synapses
-            .iter_mut()
-            .find(|(entity, _)| *entity == event.synapse);
-
-        if let Some((_, mut synapse)) = synapse {
-            trace!(
-                "applying stdp to {:?} with delta weight {} for a new weight of {}",
-                event.synapse,
-                event.delta_weight,
-                synapse.weight + event.delta_weight
-            );
-
-            synapse.weight += event.delta_weight;
-        }
-    }
-}
-
+/// Controls the removal of synapses whose weight dropped below the minimum weight of their type.
 #[derive(Debug, Reflect, Resource)]
 pub struct PruneSettings {
+    /// the minimum weight of excitatory and electrical synapses
     pub min_weight: f64,
+    /// the minimum weight of inhibitory synapses, these are usually far stronger than the
+    /// excitatory ones
+    pub min_inhibitory_weight: f64,
+    pub enabled: bool,
+    /// look for weak synapses every this many ms, 0 to check on every update
+    pub check_interval: f64,
+    /// synapses younger than this many ms are never pruned, so they get a chance to strengthen
+    pub grace_period: f64,
+    last_check: Option<f64>,
 }
 
 impl Default for PruneSettings {
     fn default() -> Self {
-        PruneSettings { min_weight: 0.1 }
+        PruneSettings {
+            min_weight: 0.1,
+            min_inhibitory_weight: 0.1,
+            enabled: true,
+            check_interval: 0.0,
+            grace_period: 0.0,
+            last_check: None,
+        }
+    }
+}
+
+impl PruneSettings {
+    /// The weight below which a synapse of `synapse_type` is pruned.
+    pub fn min_weight_for(&self, synapse_type: SynapseType) -> f64 {
+        match synapse_type {
+            SynapseType::Inhibitory => self.min_inhibitory_weight,
+            SynapseType::Excitatory | SynapseType::Electrical => self.min_weight,
+        }
+    }
+}
+
+/// The simulation time a synapse was first seen at, added to every synapse by
+/// `tag_synapse_spawn_time`.
+#[derive(Debug, Component, Reflect)]
+pub struct SynapseSpawnTime(pub f64);
+
+/// Sent for every synapse `prune_synapses` or `orphan_cleanup` removes. The synapse entity is
+/// already despawned when the event is read.
+#[derive(Debug, PartialEq, Clone, Copy, Event)]
+pub struct SynapsePrunedEvent {
+    pub synapse: Entity,
+    pub source: Entity,
+    pub target: Entity,
+}
+
+pub fn tag_synapse_spawn_time(
+    synapses: Query<(Entity, One<&dyn Synapse>), Without<SynapseSpawnTime>>,
+    clock: Res<Clock>,
+    mut commands: Commands,
+) {
+    for (entity, _) in synapses.iter() {
+        commands.entity(entity).insert(SynapseSpawnTime(clock.time));
     }
 }
 
 pub fn prune_synapses(
-    mut synapse_query: Query<(Entity, One<&dyn Synapse>)>,
+    synapse_query: Query<(Entity, One<&dyn Synapse>, Option<&SynapseSpawnTime>)>,
     mut commands: Commands,
-    prune_settings: Res<PruneSettings>,
+    mut prune_settings: ResMut<PruneSettings>,
+    clock: Res<Clock>,
+    mut index: ResMut<SynapseIndex>,
+    mut pruned_writer: EventWriter<SynapsePrunedEvent>,
 ) {
-    for (entity, synapse) in synapse_query.iter_mut() {
-        if synapse.get_weight() < prune_settings.min_weight {
+    if !prune_settings.enabled {
+        return;
+    }
+
+    // the clock runs backwards after the simulation was reset
+    let due = prune_settings.last_check.map_or(true, |last_check| {
+        clock.time < last_check || clock.time - last_check >= prune_settings.check_interval
+    });
+    if !due {
+        return;
+    }
+    prune_settings.last_check = Some(clock.time);
+
+    for (entity, synapse, spawn_time) in synapse_query.iter() {
+        // synapses without a spawn time were added during this update
+        let Some(SynapseSpawnTime(spawn_time)) = spawn_time else {
+            continue;
+        };
+        // synapses spawned before a reset of the simulation start their grace period over
+        if clock.time - spawn_time.min(clock.time) < prune_settings.grace_period {
+            continue;
+        }
+
+        if synapse.get_weight() < prune_settings.min_weight_for(synapse.get_type()) {
             info!("Pruning synapse {:?}", entity);
             commands.entity(entity).despawn_recursive();
+            // the despawn is deferred, the spikes of this frame mustn't reach the synapse anymore
+            index.remove(entity);
+            pruned_writer.send(SynapsePrunedEvent {
+                synapse: entity,
+                source: synapse.get_presynaptic(),
+                target: synapse.get_postsynaptic(),
+            });
         }
     }
 }
 
+/// Despawns the synapses whose presynaptic or postsynaptic neuron no longer exists, regardless
+/// of the `PruneSettings`.
+pub fn orphan_cleanup(
+    synapse_query: Query<(Entity, One<&dyn Synapse>)>,
+    entities: &Entities,
+    mut commands: Commands,
+    mut index: ResMut<SynapseIndex>,
+    mut pruned_writer: EventWriter<SynapsePrunedEvent>,
+) {
+    for (entity, synapse) in synapse_query.iter() {
+        let (source, target) = (synapse.get_presynaptic(), synapse.get_postsynaptic());
+        if entities.contains(source) && entities.contains(target) {
+            continue;
+        }
+
+        info!("Removing synapse {:?} of a despawned neuron", entity);
+        commands.entity(entity).despawn_recursive();
+        index.remove(entity);
+        pruned_writer.send(SynapsePrunedEvent {
+            synapse: entity,
+            source,
+            target,
+        });
+    }
+}
+
 pub fn update_synapses(
     mut synapse_query: Query<(Entity, One<&mut dyn Synapse>)>,
     clock: Res<Clock>,
@@ -160,91 +390,227 @@ pub fn update_synapses(
     }
 }
 
+/// Passes every spike to the `on_pre_spike` of the outgoing and the `on_post_spike` of the
+/// incoming synapses of the neuron. The input of synapses that transmit `OnSpike` is queued, it's
+/// delivered to the postsynaptic neuron by `deliver_delayed_spikes` once the delay of the synapse
+/// has passed.
 pub fn update_synapses_for_spikes(
-    synapse_query: Query<(Entity, One<&dyn Synapse>)>,
+    mut synapse_query: Query<One<&mut dyn Synapse>>,
     mut spike_reader: EventReader<SpikeEvent>,
-    mut neuron_query: Query<(Entity, One<&mut dyn Neuron>)>,
+    mut delay_buffer: ResMut<DelayBuffer>,
+    mut index: ResMut<SynapseIndex>,
+    mut activity: Option<ResMut<NeuronActivity>>,
+    clock: Res<Clock>,
 ) {
     for spike_event in spike_reader.read() {
-        for (_entity, synapse) in synapse_query.iter() {
-            if synapse.get_presynaptic() == spike_event.neuron {
-                let neuron = neuron_query.get_mut(synapse.get_postsynaptic());
-                if neuron.is_err() {
-                    // warn!("No target neuron found for synapse: {:?}", synapse);
-                    continue;
-                }
+        let spike_tick = tick_at(spike_event.time, clock.tau);
+        let mut removed = vec![];
 
-                let (_entity, mut target_neuron) = neuron.unwrap();
+        for entity in index.outgoing(spike_event.neuron) {
+            let Ok(mut synapse) = synapse_query.get_mut(*entity) else {
+                removed.push(*entity);
+                continue;
+            };
 
-                match synapse.get_type() {
-                    SynapseType::Excitatory => {
-                        target_neuron.insert_current(synapse.get_weight());
-                    }
-                    SynapseType::Inhibitory => {
-                        target_neuron.insert_current(-synapse.get_weight());
+            synapse.on_pre_spike(spike_event.time);
+            // gap junctions are coupled continuously by the `GapJunctionPlugin`
+            if synapse.get_type() == SynapseType::Electrical {
+                continue;
+            }
+
+            match synapse.transmission_mode() {
+                TransmissionMode::OnSpike => {
+                    // the input can't arrive before the next update of the postsynaptic neuron
+                    let delay = synapse.get_delay().max(1) as u64;
+                    let weight = synapse.effective_weight_on_spike();
+                    delay_buffer.push(spike_tick + delay, *entity, weight);
+                }
+                // nothing passes through the delay buffer, the target still has to be updated
+                // while the synapse drives it
+                TransmissionMode::Continuous => {
+                    if let Some(activity) = activity.as_mut() {
+                        activity.mark(synapse.get_postsynaptic(), clock.time);
                     }
                 }
             }
         }
+
+        for entity in index.incoming(spike_event.neuron) {
+            if let Ok(mut synapse) = synapse_query.get_mut(*entity) {
+                synapse.on_post_spike(spike_event.time);
+            }
+        }
+
+        for entity in removed {
+            index.remove(entity);
+        }
     }
 }
 
-fn update_neurons(
-    clock: ResMut<Clock>,
+/// Opens the conductances of the queued synaptic inputs that are due this tick, they are applied
+/// during the following neuron update.
+pub fn deliver_delayed_spikes(
+    synapse_query: Query<(One<&dyn Synapse>, Option<&CompartmentTarget>)>,
+    mut neuron_query: Query<One<&mut dyn Neuron>>,
+    mut delay_buffer: ResMut<DelayBuffer>,
+    mut activity: ResMut<NeuronActivity>,
+    clock: Res<Clock>,
+) {
+    for (synapse_entity, weight) in delay_buffer.pop_due(tick_at(clock.time, clock.tau)) {
+        // the synapse may have been pruned while the spike was in transit
+        let Ok((synapse, compartment)) = synapse_query.get(synapse_entity) else {
+            continue;
+        };
+
+        let Ok(mut target_neuron) = neuron_query.get_mut(synapse.get_postsynaptic()) else {
+            // warn!("No target neuron found for synapse: {:?}", synapse);
+            continue;
+        };
+        activity.mark(synapse.get_postsynaptic(), clock.time);
+
+        match (synapse.get_type(), compartment) {
+            (SynapseType::Electrical, _) => {}
+            (synapse_type, Some(CompartmentTarget::Dendrite)) => {
+                if let Some(reversal_potential) = synapse_type.reversal_potential() {
+                    target_neuron.add_dendritic_conductance(weight, reversal_potential);
+                }
+            }
+            (SynapseType::Excitatory, _) => {
+                target_neuron.add_excitatory_conductance(weight);
+            }
+            (SynapseType::Inhibitory, _) => {
+                target_neuron.add_inhibitory_conductance(weight);
+            }
+        }
+    }
+}
+
+/// The neurons that fired during the current tick with their spike time. `update_neurons` fills
+/// this and `emit_spikes` turns it into `SpikeEvent`s.
+#[derive(Debug, Default, Resource)]
+pub struct FiredNeurons {
+    pub spikes: Vec<(Entity, f64)>,
+}
+
+/// Updates a single neuron and records its spike, returns whether it fired.
+fn update_neuron(
+    neuron: &mut dyn Neuron,
+    spike_recorder: Option<&mut dyn SpikeRecorder>,
+    clock: &Clock,
+) -> bool {
+    let fired = neuron.update(clock.tau);
+    if let Some(spike_recorder) = spike_recorder {
+        if fired {
+            spike_recorder.record_spike(clock.time);
+        }
+    }
+
+    fired
+}
+
+/// Updates every neuron, in parallel when the `parallel_neurons` feature is enabled. Event
+/// writers can't be shared between threads, so the spikes are collected in `FiredNeurons`. The
+/// spikes of a tick are sorted by neuron, the order the threads finish in doesn't leak into the
+/// order the synapses learn from them.
+pub fn update_neurons(
+    clock: Res<Clock>,
     mut neuron_query: Query<(
         Entity,
         One<&mut dyn Neuron>,
         Option<One<&mut dyn SpikeRecorder>>,
     )>,
-    mut stdp_synapses: Query<(Entity, &mut StdpSynapse)>,
-    mut spike_writer: EventWriter<SpikeEvent>,
-    mut stdp_writer: EventWriter<DeferredStdpEvent>,
+    mut fired_neurons: ResMut<FiredNeurons>,
+    #[cfg(feature = "parallel_neurons")] mut thread_spikes: Local<Parallel<Vec<(Entity, f64)>>>,
 ) {
     if clock.time_to_simulate <= 0.0 {
         return;
     }
 
+    let first_spike = fired_neurons.spikes.len();
+
+    #[cfg(feature = "parallel_neurons")]
+    {
+        let spikes = &*thread_spikes;
+        neuron_query
+            .par_iter_mut()
+            .for_each(|(entity, mut neuron, mut spike_recorder)| {
+                if update_neuron(&mut *neuron, spike_recorder.as_deref_mut(), &clock) {
+                    spikes.scope(|spikes| spikes.push((entity, clock.time)));
+                }
+            });
+        thread_spikes.drain_into(&mut fired_neurons.spikes);
+    }
+
+    #[cfg(not(feature = "parallel_neurons"))]
     for (entity, mut neuron, mut spike_recorder) in neuron_query.iter_mut() {
-        let fired = neuron.update(clock.tau);
-        if let Some(spike_recorder) = spike_recorder.as_mut() {
-            if fired {
-                spike_recorder.record_spike(clock.time);
-            }
+        if update_neuron(&mut *neuron, spike_recorder.as_deref_mut(), &clock) {
+            fired_neurons.spikes.push((entity, clock.time));
         }
+    }
 
-        if fired {
-            spike_writer.send(SpikeEvent {
-                time: clock.time,
-                neuron: entity,
-            });
+    fired_neurons.spikes[first_spike..].sort_unstable_by_key(|(entity, _)| *entity);
+}
 
-            stdp_synapses
-                .iter_mut()
-                .find(|(_, s)| s.get_presynaptic() == entity)
-                .map(|(e, mut s)| {
-                    // trace!("Registering pre-spike for synapse {:?}", entity);
-                    let delta_w = s.register_pre_spike();
-                    if let Some(delta_w) = delta_w {
-                        stdp_writer.send(DeferredStdpEvent {
-                            synapse: e,
-                            delta_weight: delta_w,
-                        });
-                    }
+/// Sends a `SpikeEvent` for every neuron that fired this tick and lets the plastic synapses know
+/// about the spikes, except for the ones with `FrozenPlasticity`.
+pub fn emit_spikes(
+    mut fired_neurons: ResMut<FiredNeurons>,
+    mut stdp_synapses: Query<&mut StdpSynapse, Without<FrozenPlasticity>>,
+    mut bcm_synapses: Query<&mut BcmSynapse, Without<FrozenPlasticity>>,
+    mut triplet_synapses: Query<&mut TripletStdpSynapse, Without<FrozenPlasticity>>,
+    index: Res<SynapseIndex>,
+    mut spike_writer: EventWriter<SpikeEvent>,
+    mut stdp_writer: EventWriter<DeferredStdpEvent>,
+) {
+    for (entity, time) in fired_neurons.spikes.drain(..) {
+        spike_writer.send(SpikeEvent {
+            time,
+            neuron: entity,
+        });
+
+        // every synapse of the neuron has to learn, not just the first one. The outgoing synapses
+        // go first, so a synapse onto its own source registers the pre spike before the post spike
+        let synapses = index
+            .outgoing(entity)
+            .iter()
+            .map(|synapse| (*synapse, true))
+            .chain(
+                index
+                    .incoming(entity)
+                    .iter()
+                    .map(|synapse| (*synapse, false)),
+            );
+
+        for (synapse_entity, presynaptic) in synapses {
+            let stdp = stdp_synapses
+                .get_mut(synapse_entity)
+                .ok()
+                .and_then(|mut synapse| match presynaptic {
+                    true => synapse.register_pre_spike(),
+                    false => synapse.register_post_spike(),
                 });
 
-            stdp_synapses
-                .iter_mut()
-                .find(|(_, s)| s.get_postsynaptic() == entity)
-                .map(|(e, mut s)| {
-                    // trace!("Registering post-spike for synapse {:?}", entity);
-                    let delta_w = s.register_post_spike();
-                    if let Some(delta_w) = delta_w {
-                        stdp_writer.send(DeferredStdpEvent {
-                            synapse: e,
-                            delta_weight: delta_w,
-                        });
-                    }
+            if let Ok(mut synapse) = bcm_synapses.get_mut(synapse_entity) {
+                match presynaptic {
+                    true => synapse.register_pre_spike(),
+                    false => synapse.register_post_spike(),
+                }
+            }
+
+            let triplet = triplet_synapses
+                .get_mut(synapse_entity)
+                .ok()
+                .and_then(|mut synapse| match presynaptic {
+                    true => synapse.register_pre_spike(),
+                    false => synapse.register_post_spike(),
                 });
+
+            for delta_weight in stdp.into_iter().chain(triplet) {
+                stdp_writer.send(DeferredStdpEvent {
+                    synapse: synapse_entity,
+                    delta_weight,
+                });
+            }
         }
     }
 }
@@ -256,21 +622,34 @@ pub struct Classifier {
 }
 
 #[derive(Debug, Component, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SimpleSpikeRecorder {
     max_spikes: usize,
-    spikes: Vec<f64>,
+    /// spike times in the order they were recorded, the oldest spike is dropped once more than
+    /// `max_spikes` are recorded
+    spikes: VecDeque<f64>,
 }
 
 impl SpikeRecorder for SimpleSpikeRecorder {
     fn record_spike(&mut self, time: f64) {
-        self.spikes.push(time);
+        self.spikes.push_back(time);
         if self.spikes.len() > self.max_spikes {
-            self.spikes.remove(0);
+            self.spikes.pop_front();
         }
     }
 
     fn get_spikes(&self) -> Vec<f64> {
-        self.spikes.clone()
+        self.spikes.iter().copied().collect()
+    }
+
+    fn clear(&mut self) {
+        self.spikes.clear();
+    }
+
+    fn clear_before(&mut self, time: f64) {
+        while self.spikes.front().is_some_and(|spike| *spike < time) {
+            self.spikes.pop_front();
+        }
     }
 }
 
@@ -278,7 +657,508 @@ impl Default for SimpleSpikeRecorder {
     fn default() -> Self {
         SimpleSpikeRecorder {
             max_spikes: 1000,
-            spikes: Vec::with_capacity(1000),
+            spikes: VecDeque::with_capacity(1000),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{app::TaskPoolPlugin, prelude::Events};
+    use bevy_trait_query::RegisterExt;
+    use neurons::leaky::LifNeuron;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+    use synapses::{
+        simple::SimpleSynapse,
+        stdp::{StdpParams, StdpRule, StdpSpikeType, StdpState},
+    };
+
+    use super::*;
+
+    fn recorder(spikes: impl IntoIterator<Item = f64>) -> SimpleSpikeRecorder {
+        let mut recorder = SimpleSpikeRecorder::default();
+        for time in spikes {
+            recorder.record_spike(time);
+        }
+        recorder
+    }
+
+    /// Remembers the spikes it was told about, transmits `OnSpike` or `Continuous`.
+    #[derive(Component)]
+    struct HookSynapse {
+        source: Entity,
+        target: Entity,
+        weight: f64,
+        mode: TransmissionMode,
+        pre_spikes: Vec<f64>,
+        post_spikes: Vec<f64>,
+    }
+
+    impl HookSynapse {
+        fn new(source: Entity, target: Entity, weight: f64, mode: TransmissionMode) -> Self {
+            HookSynapse {
+                source,
+                target,
+                weight,
+                mode,
+                pre_spikes: vec![],
+                post_spikes: vec![],
+            }
+        }
+    }
+
+    impl Synapse for HookSynapse {
+        fn update(&mut self, _tau: f64) {}
+
+        fn get_weight(&self) -> f64 {
+            self.weight
         }
+
+        fn set_weight(&mut self, weight: f64) {
+            self.weight = weight;
+        }
+
+        fn get_presynaptic(&self) -> Entity {
+            self.source
+        }
+
+        fn get_postsynaptic(&self) -> Entity {
+            self.target
+        }
+
+        fn get_type(&self) -> SynapseType {
+            SynapseType::Excitatory
+        }
+
+        fn get_delay(&self) -> u32 {
+            1
+        }
+
+        fn on_pre_spike(&mut self, time: f64) {
+            self.pre_spikes.push(time);
+        }
+
+        fn on_post_spike(&mut self, time: f64) {
+            self.post_spikes.push(time);
+        }
+
+        fn transmission_mode(&self) -> TransmissionMode {
+            self.mode
+        }
+    }
+
+    #[test]
+    fn test_poisson_spiking_isi_statistics() {
+        // 20Hz, the intervals of a Poisson process are exponentially distributed
+        let mut rng = StdRng::seed_from_u64(5);
+        let mut time = 0.0;
+        let recorder = recorder((0..1000).map(|_| {
+            time += -(1.0 - rng.gen::<f64>()).ln() * 50.0;
+            time
+        }));
+
+        let mean_isi = recorder.mean_isi().unwrap();
+        assert!((mean_isi - 50.0).abs() < 5.0, "mean ISI was {mean_isi}");
+        let cv = recorder.cv_isi().unwrap();
+        assert!((cv - 1.0).abs() < 0.1, "CV was {cv}");
+        let fano_factor = recorder.fano_factor(500.0).unwrap();
+        assert!(
+            (fano_factor - 1.0).abs() < 0.4,
+            "Fano factor was {fano_factor}"
+        );
+    }
+
+    #[test]
+    fn test_regular_spiking_isi_statistics() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let recorder = recorder((0..1000).map(|i| i as f64 * 10.0 + rng.gen::<f64>() * 0.5));
+
+        assert_eq!(recorder.isi_list().len(), 999);
+        let cv = recorder.cv_isi().unwrap();
+        assert!(cv < 0.1, "CV was {cv}");
+        assert!(recorder.fano_factor(100.0).unwrap() < 0.1);
+    }
+
+    #[test]
+    fn test_too_few_spikes() {
+        assert_eq!(recorder([]).mean_isi(), None);
+        assert_eq!(recorder([1.0]).cv_isi(), None);
+        assert_eq!(recorder([1.0, 2.0]).fano_factor(10.0), None);
+    }
+
+    #[test]
+    fn test_clear_before() {
+        let mut recorder = recorder([1.0, 2.0, 3.0, 4.0]);
+
+        recorder.clear_before(3.0);
+        assert_eq!(recorder.get_spikes(), [3.0, 4.0]);
+        recorder.clear_before(0.0);
+        assert_eq!(recorder.get_spikes(), [3.0, 4.0]);
+        recorder.clear();
+        assert!(recorder.get_spikes().is_empty());
+    }
+
+    #[test]
+    fn test_capacity_drops_oldest_spikes() {
+        let recorder = recorder((0..1500).map(|i| i as f64));
+
+        let spikes = recorder.get_spikes();
+        assert_eq!(spikes.len(), 1000);
+        assert_eq!(spikes[0], 500.0);
+        assert_eq!(spikes[999], 1499.0);
+    }
+
+    #[test]
+    fn test_stdp_registers_every_synapse_of_a_neuron() {
+        let mut app = App::new();
+        app.init_resource::<FiredNeurons>()
+            .init_resource::<SynapseIndex>()
+            .add_event::<SpikeEvent>()
+            .add_event::<DeferredStdpEvent>()
+            .register_component_as::<dyn Synapse, StdpSynapse>()
+            .add_systems(Update, (index_synapses, emit_spikes).chain());
+
+        let source = app.world_mut().spawn_empty().id();
+        let targets = [(); 3].map(|_| app.world_mut().spawn_empty().id());
+        let synapses = targets.map(|target| {
+            app.world_mut()
+                .spawn(StdpSynapse {
+                    stdp_params: StdpParams {
+                        a_plus: 0.01,
+                        a_minus: -0.01,
+                        tau_plus: 0.2,
+                        tau_minus: 0.2,
+                        w_max: 1.0,
+                        w_min: 0.0,
+                        soft_bound: false,
+                        tau_eligibility: 1000.0,
+                    },
+                    stdp_state: StdpState {
+                        a: -0.01,
+                        spike_type: StdpSpikeType::PostSpike,
+                        eligibility: 0.0,
+                    },
+                    source,
+                    target,
+                    weight: 0.5,
+                    delay: 1,
+                    synapse_type: SynapseType::Excitatory,
+                    rule: StdpRule::Asymmetric,
+                })
+                .id()
+        });
+
+        let deferred = |app: &mut App, spikes: Vec<Entity>| {
+            app.world_mut().resource_mut::<FiredNeurons>().spikes =
+                spikes.into_iter().map(|neuron| (neuron, 1.0)).collect();
+            app.update();
+
+            let mut deferred = app
+                .world()
+                .resource::<Events<DeferredStdpEvent>>()
+                .iter_current_update_events()
+                .map(|event| (event.synapse, event.delta_weight))
+                .collect::<Vec<_>>();
+            deferred.sort_by_key(|(synapse, _)| *synapse);
+            deferred
+        };
+
+        // the presynaptic spike depresses all three synapses
+        let mut expected = synapses.map(|synapse| (synapse, -0.01)).to_vec();
+        expected.sort_by_key(|(synapse, _)| *synapse);
+        assert_eq!(deferred(&mut app, vec![source]), expected);
+        for synapse in synapses {
+            let stdp_state = &app.world().get::<StdpSynapse>(synapse).unwrap().stdp_state;
+            assert_eq!(stdp_state.spike_type, StdpSpikeType::PreSpike);
+        }
+
+        // and the spikes of the targets potentiate them
+        let mut expected = synapses.map(|synapse| (synapse, 0.01)).to_vec();
+        expected.sort_by_key(|(synapse, _)| *synapse);
+        assert_eq!(deferred(&mut app, targets.to_vec()), expected);
+    }
+
+    fn prune_app(prune_settings: PruneSettings) -> App {
+        let mut app = App::new();
+        app.insert_resource(Clock::default())
+            .insert_resource(prune_settings)
+            .init_resource::<SynapseIndex>()
+            .add_event::<SynapsePrunedEvent>()
+            .register_component_as::<dyn Synapse, SimpleSynapse>()
+            .add_systems(
+                Update,
+                (index_synapses, tag_synapse_spawn_time, prune_synapses).chain(),
+            );
+        app
+    }
+
+    fn spawn_synapse(app: &mut App, weight: f64) -> Entity {
+        spawn_typed_synapse(app, weight, SynapseType::Excitatory)
+    }
+
+    fn spawn_typed_synapse(app: &mut App, weight: f64, synapse_type: SynapseType) -> Entity {
+        app.world_mut()
+            .spawn(SimpleSynapse {
+                weight,
+                delay: 1,
+                source: Entity::PLACEHOLDER,
+                target: Entity::PLACEHOLDER,
+                synapse_type,
+            })
+            .id()
+    }
+
+    fn run_until(app: &mut App, time: f64) -> Vec<Entity> {
+        let mut pruned = vec![];
+        while app.world().resource::<Clock>().time < time {
+            app.world_mut().resource_mut::<Clock>().time += 1.0;
+            app.update();
+            pruned.extend(
+                app.world()
+                    .resource::<Events<SynapsePrunedEvent>>()
+                    .iter_current_update_events()
+                    .map(|event| event.synapse),
+            );
+        }
+        pruned
+    }
+
+    #[test]
+    fn test_prune_after_grace_period() {
+        let mut app = prune_app(PruneSettings {
+            min_weight: 0.1,
+            check_interval: 10.0,
+            grace_period: 50.0,
+            ..Default::default()
+        });
+        let weak = spawn_synapse(&mut app, 0.05);
+        let strong = spawn_synapse(&mut app, 0.5);
+
+        assert!(run_until(&mut app, 50.0).is_empty());
+        assert!(app.world().get_entity(weak).is_some());
+
+        // the first check after the grace period
+        assert_eq!(run_until(&mut app, 70.0), [weak]);
+        assert!(app.world().get_entity(weak).is_none());
+        assert!(app.world().get_entity(strong).is_some());
+        assert_eq!(app.world().resource::<SynapseIndex>().len(), 1);
+    }
+
+    #[test]
+    fn test_disabled_pruning() {
+        let mut app = prune_app(PruneSettings {
+            enabled: false,
+            ..Default::default()
+        });
+        let weak = spawn_synapse(&mut app, 0.05);
+
+        assert!(run_until(&mut app, 100.0).is_empty());
+        assert!(app.world().get_entity(weak).is_some());
+
+        app.world_mut().resource_mut::<PruneSettings>().enabled = true;
+        assert_eq!(run_until(&mut app, 101.0), [weak]);
+    }
+
+    #[test]
+    fn test_prune_threshold_per_synapse_type() {
+        // the inhibitory weights of a winner-take-all layer are far above the excitatory ones
+        let mut app = prune_app(PruneSettings {
+            min_weight: 2.5,
+            min_inhibitory_weight: 1.0,
+            ..Default::default()
+        });
+        let excitatory = spawn_typed_synapse(&mut app, 2.0, SynapseType::Excitatory);
+        let inhibitory = spawn_typed_synapse(&mut app, 2.0, SynapseType::Inhibitory);
+        let weak_inhibitory = spawn_typed_synapse(&mut app, 0.5, SynapseType::Inhibitory);
+
+        let mut pruned = run_until(&mut app, 10.0);
+        pruned.sort();
+        let mut expected = vec![excitatory, weak_inhibitory];
+        expected.sort();
+        assert_eq!(pruned, expected);
+        assert!(app.world().get_entity(inhibitory).is_some());
+    }
+
+    #[test]
+    fn test_synapses_of_despawned_neurons_are_removed() {
+        let mut app = App::new();
+        app.init_resource::<SynapseIndex>()
+            .add_event::<SynapsePrunedEvent>()
+            .register_component_as::<dyn Synapse, SimpleSynapse>()
+            .add_systems(Update, (index_synapses, orphan_cleanup).chain());
+
+        let [a, b, c] = [(); 3].map(|_| app.world_mut().spawn_empty().id());
+        let [a_b, b_c, c_a] = [(a, b), (b, c), (c, a)].map(|(source, target)| {
+            app.world_mut()
+                .spawn(SimpleSynapse {
+                    weight: 0.5,
+                    delay: 1,
+                    source,
+                    target,
+                    synapse_type: SynapseType::Excitatory,
+                })
+                .id()
+        });
+        app.update();
+        assert_eq!(app.world().resource::<SynapseIndex>().len(), 3);
+
+        app.world_mut().despawn(b);
+        app.update();
+
+        let mut pruned = app
+            .world()
+            .resource::<Events<SynapsePrunedEvent>>()
+            .iter_current_update_events()
+            .copied()
+            .collect::<Vec<_>>();
+        pruned.sort_by_key(|event| event.synapse);
+        let mut expected = vec![
+            SynapsePrunedEvent {
+                synapse: a_b,
+                source: a,
+                target: b,
+            },
+            SynapsePrunedEvent {
+                synapse: b_c,
+                source: b,
+                target: c,
+            },
+        ];
+        expected.sort_by_key(|event| event.synapse);
+        assert_eq!(pruned, expected);
+
+        assert!(app.world().get_entity(a_b).is_none());
+        assert!(app.world().get_entity(b_c).is_none());
+        assert!(app.world().get_entity(c_a).is_some());
+        assert_eq!(app.world().resource::<SynapseIndex>().len(), 1);
+    }
+
+    #[derive(Default, Resource)]
+    struct SpikeLog(Vec<(Entity, f64)>);
+
+    fn log_spikes(mut fired_neurons: ResMut<FiredNeurons>, mut log: ResMut<SpikeLog>) {
+        log.0.append(&mut fired_neurons.spikes);
+    }
+
+    #[test]
+    fn test_update_neurons_matches_serial_update() {
+        let mut app = App::new();
+        app.add_plugins(TaskPoolPlugin::default())
+            .insert_resource(Clock {
+                time_to_simulate: f64::MAX,
+                tau: 0.1,
+                ..Default::default()
+            })
+            .init_resource::<FiredNeurons>()
+            .init_resource::<SpikeLog>()
+            .register_component_as::<dyn Neuron, LifNeuron>()
+            .add_systems(
+                Update,
+                (
+                    update_clock,
+                    apply_current_sources,
+                    update_neurons,
+                    log_spikes,
+                )
+                    .chain(),
+            );
+
+        // groups of neurons with the same input fire in the same ticks
+        let mut reference = (0..200)
+            .map(|i| {
+                let amplitude = 1.6 + (i % 10) as f64 * 0.1;
+                let neuron = app
+                    .world_mut()
+                    .spawn((LifNeuron::default(), CurrentSource::Constant { amplitude }))
+                    .id();
+                (neuron, LifNeuron::default(), amplitude)
+            })
+            .collect::<Vec<_>>();
+        reference.sort_by_key(|(neuron, _, _)| *neuron);
+
+        // the same neurons updated one after the other outside of the app
+        let mut expected_spikes = vec![];
+        for _ in 0..1000 {
+            app.update();
+
+            let clock = app.world().resource::<Clock>();
+            for (entity, neuron, amplitude) in reference.iter_mut() {
+                neuron.insert_current(*amplitude);
+                if update_neuron(neuron, None, clock) {
+                    expected_spikes.push((*entity, clock.time));
+                }
+
+                let updated = app.world().get::<LifNeuron>(*entity).unwrap();
+                assert_eq!(updated.membrane_potential, neuron.membrane_potential);
+            }
+        }
+
+        assert!(expected_spikes.len() > 200);
+        assert_eq!(app.world().resource::<SpikeLog>().0, expected_spikes);
+    }
+
+    #[test]
+    fn test_spike_dispatch_by_transmission_mode() {
+        let mut app = App::new();
+        app.insert_resource(Clock::default())
+            .init_resource::<SynapseIndex>()
+            .init_resource::<DelayBuffer>()
+            .init_resource::<NeuronActivity>()
+            .add_event::<SpikeEvent>()
+            .register_component_as::<dyn Synapse, SimpleSynapse>()
+            .register_component_as::<dyn Synapse, HookSynapse>()
+            .add_systems(Update, (index_synapses, update_synapses_for_spikes).chain());
+
+        let [a, b, c] = [0, 1, 2].map(|_| app.world_mut().spawn_empty().id());
+        app.world_mut().spawn(SimpleSynapse {
+            weight: 1.0,
+            delay: 1,
+            source: a,
+            target: b,
+            synapse_type: SynapseType::Excitatory,
+        });
+        let on_spike = app
+            .world_mut()
+            .spawn(HookSynapse::new(a, b, 2.0, TransmissionMode::OnSpike))
+            .id();
+        let continuous = app
+            .world_mut()
+            .spawn(HookSynapse::new(a, c, 3.0, TransmissionMode::Continuous))
+            .id();
+        let incoming = app
+            .world_mut()
+            .spawn(HookSynapse::new(b, a, 4.0, TransmissionMode::OnSpike))
+            .id();
+        app.update();
+
+        app.world_mut().send_event(SpikeEvent {
+            time: 1.0,
+            neuron: a,
+        });
+        app.update();
+
+        let hook = |synapse| app.world().get::<HookSynapse>(synapse).unwrap();
+        assert_eq!(hook(on_spike).pre_spikes, [1.0]);
+        assert_eq!(hook(continuous).pre_spikes, [1.0]);
+        assert!(hook(continuous).post_spikes.is_empty());
+        assert!(hook(incoming).pre_spikes.is_empty());
+        assert_eq!(hook(incoming).post_spikes, [1.0]);
+
+        // only the synapses that transmit on spikes queue their weight, one tick after the spike
+        let mut weights = app
+            .world_mut()
+            .resource_mut::<DelayBuffer>()
+            .pop_due(41)
+            .map(|(_, weight)| weight)
+            .collect::<Vec<_>>();
+        weights.sort_by(f64::total_cmp);
+        assert_eq!(weights, [1.0, 2.0]);
+        assert!(app.world().resource::<DelayBuffer>().is_empty());
+
+        // the target of the continuous synapse is updated right away, the others once the spike
+        // arrives
+        let activity = app.world().resource::<NeuronActivity>();
+        assert!(activity.is_active(c, 0.0));
+        assert!(!activity.is_active(b, 0.0));
     }
 }
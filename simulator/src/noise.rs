@@ -0,0 +1,139 @@
+use bevy::{
+    prelude::{Component, Query, Res, ResMut},
+    reflect::Reflect,
+};
+use bevy_trait_query::One;
+use silicon_core::{Clock, Neuron, SimulationRng};
+
+/// Background fluctuations of the membrane modelled as an Ornstein-Uhlenbeck process. Every tick
+/// the current value of the process is inserted into the neuron on the same entity.
+#[derive(Debug, Component, Reflect)]
+pub struct MembraneNoise {
+    /// The value the process relaxes to.
+    pub mean: f64,
+    /// The standard deviation of the stationary process.
+    pub sigma: f64,
+    /// The correlation time of the noise in ms.
+    pub tau_noise: f64,
+    /// The current value of the process.
+    pub value: f64,
+}
+
+impl MembraneNoise {
+    pub fn new(mean: f64, sigma: f64, tau_noise: f64) -> Self {
+        MembraneNoise {
+            mean,
+            sigma,
+            tau_noise,
+            value: mean,
+        }
+    }
+
+    /// Advance the process by `tau` ms and return the new value. The exact solution of the
+    /// process is used so time steps larger than `tau_noise` stay stable.
+    pub fn step(&mut self, tau: f64, rng: &mut SimulationRng) -> f64 {
+        let decay = (-tau / self.tau_noise).exp();
+        let std_dev = self.sigma * (1.0 - decay * decay).sqrt();
+        self.value = self.mean + (self.value - self.mean) * decay + rng.sample_normal(0.0, std_dev);
+        self.value
+    }
+}
+
+pub(crate) fn apply_membrane_noise(
+    mut neurons: Query<(&mut MembraneNoise, One<&mut dyn Neuron>)>,
+    mut rng: ResMut<SimulationRng>,
+    clock: Res<Clock>,
+) {
+    if clock.time_to_simulate <= 0.0 {
+        return;
+    }
+
+    for (mut noise, mut neuron) in neurons.iter_mut() {
+        let value = noise.step(clock.tau, &mut rng);
+        neuron.insert_current(value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use neurons::leaky::LifNeuron;
+
+    use super::*;
+
+    /// Drives a LIF neuron that never fires with the noise for 25s and returns the mean and the
+    /// variance of its membrane potential relative to rest.
+    fn membrane_statistics(mean: f64, sigma: f64, tau_noise: f64) -> (f64, f64) {
+        let mut rng = SimulationRng::from_seed(11);
+        let mut noise = MembraneNoise::new(mean, sigma, tau_noise);
+        let mut neuron = LifNeuron {
+            threshold_potential: f64::INFINITY,
+            resistance: 1.0,
+            ..Default::default()
+        };
+
+        // skip the first 10 membrane time constants
+        let potentials = (0..1_000_000)
+            .map(|_| {
+                neuron.insert_current(noise.step(0.025, &mut rng));
+                neuron.update(0.025);
+                neuron.membrane_potential - neuron.resting_potential
+            })
+            .skip(4000)
+            .collect::<Vec<_>>();
+        let mean = potentials.iter().sum::<f64>() / potentials.len() as f64;
+        let variance =
+            potentials.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / potentials.len() as f64;
+        (mean, variance)
+    }
+
+    #[test]
+    fn test_stationary_statistics() {
+        let mut rng = SimulationRng::from_seed(7);
+        let mut noise = MembraneNoise::new(1.0, 0.5, 0.01);
+
+        let values = (0..100_000)
+            .map(|_| noise.step(0.025, &mut rng))
+            .collect::<Vec<_>>();
+        let mean = values.iter().sum::<f64>() / values.len() as f64;
+        let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64;
+
+        assert!((mean - 1.0).abs() < 0.01, "mean was {mean}");
+        assert!(
+            (variance.sqrt() - 0.5).abs() < 0.01,
+            "sigma was {}",
+            variance.sqrt()
+        );
+    }
+
+    #[test]
+    fn test_seeded_noise_is_reproducible() {
+        let run = || {
+            let mut rng = SimulationRng::from_seed(3);
+            let mut noise = MembraneNoise::new(0.0, 0.5, 5.0);
+            (0..100)
+                .map(|_| noise.step(0.025, &mut rng))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(run(), run());
+    }
+
+    #[test]
+    fn test_injected_noise_statistics() {
+        for (mean, sigma, tau_noise) in [(2.0, 1.0, 5.0), (2.0, 2.0, 5.0), (2.0, 1.0, 20.0)] {
+            let (v_mean, v_variance) = membrane_statistics(mean, sigma, tau_noise);
+            // the membrane low-passes the noise, an OU process with correlation time tau_noise
+            // filtered by tau_m keeps the fraction tau_noise / (tau_noise + tau_m) of its variance
+            let expected = sigma * sigma * tau_noise / (tau_noise + 10.0);
+
+            assert!(
+                (v_mean - mean).abs() < 0.05,
+                "mean was {v_mean} for sigma {sigma} and tau {tau_noise}"
+            );
+            assert!(
+                (v_variance / expected - 1.0).abs() < 0.05,
+                "variance was {v_variance} instead of {expected} for sigma {sigma} and tau {tau_noise}"
+            );
+        }
+    }
+}
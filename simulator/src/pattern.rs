@@ -0,0 +1,175 @@
+use bevy::{
+    prelude::{Component, Entity, Event, EventWriter, Query, Res},
+    reflect::Reflect,
+};
+use bevy_trait_query::One;
+use silicon_core::{Clock, SpikeRecorder};
+
+/// Scores how well the recent activity of a set of neurons matches a template spike pattern.
+/// The score is the van Rossum distance between the template and the spikes recorded during
+/// the last `duration` ms, summed over all neurons in the template.
+#[derive(Debug, Component, Reflect)]
+pub struct PatternMatcher {
+    /// Spike times per neuron, relative to the start of the pattern.
+    pub template: Vec<(Entity, Vec<f64>)>,
+    /// A detection is fired when the distance drops below this value.
+    pub threshold: f64,
+    /// The length of the sliding window in ms.
+    pub duration: f64,
+    /// The time constant of the van Rossum kernel in ms.
+    pub tau: f64,
+    /// The distance computed during the last update.
+    pub distance: f64,
+    pub matching: bool,
+}
+
+impl PatternMatcher {
+    pub fn new(template: Vec<(Entity, Vec<f64>)>, threshold: f64) -> Self {
+        let duration = template
+            .iter()
+            .flat_map(|(_, spikes)| spikes.iter())
+            .cloned()
+            .fold(0.0, f64::max);
+
+        PatternMatcher {
+            template,
+            threshold,
+            duration,
+            tau: 2.0,
+            distance: f64::INFINITY,
+            matching: false,
+        }
+    }
+}
+
+/// Sent when the activity of the network starts matching the template of a `PatternMatcher`.
+#[derive(Event, Debug)]
+pub struct PatternDetectedEvent {
+    pub matcher: Entity,
+    pub time: f64,
+    pub distance: f64,
+}
+
+/// Van Rossum distance between two spike trains using an exponential kernel with time constant `tau`.
+pub fn van_rossum_distance(a: &[f64], b: &[f64], tau: f64) -> f64 {
+    let kernel_sum = |x: &[f64], y: &[f64]| -> f64 {
+        x.iter()
+            .flat_map(|t1| y.iter().map(move |t2| (-(t1 - t2).abs() / tau).exp()))
+            .sum()
+    };
+
+    let squared = 0.5 * (kernel_sum(a, a) + kernel_sum(b, b) - 2.0 * kernel_sum(a, b));
+    squared.max(0.0).sqrt()
+}
+
+pub(crate) fn match_spike_patterns(
+    mut matchers: Query<(Entity, &mut PatternMatcher)>,
+    recorders: Query<One<&dyn SpikeRecorder>>,
+    clock: Res<Clock>,
+    mut detection_writer: EventWriter<PatternDetectedEvent>,
+) {
+    for (entity, mut matcher) in matchers.iter_mut() {
+        let window_start = clock.time - matcher.duration;
+
+        let squared_distance = matcher
+            .template
+            .iter()
+            .map(|(neuron, template_spikes)| {
+                let recent_spikes = recorders
+                    .get(*neuron)
+                    .map(|recorder| {
+                        recorder
+                            .get_spikes()
+                            .iter()
+                            .filter(|time| **time >= window_start && **time <= clock.time)
+                            .map(|time| time - window_start)
+                            .collect::<Vec<_>>()
+                    })
+                    .unwrap_or_default();
+
+                van_rossum_distance(template_spikes, &recent_spikes, matcher.tau).powi(2)
+            })
+            .sum::<f64>();
+
+        matcher.distance = squared_distance.sqrt();
+        let matching = matcher.distance <= matcher.threshold;
+
+        if matching && !matcher.matching {
+            detection_writer.send(PatternDetectedEvent {
+                matcher: entity,
+                time: clock.time,
+                distance: matcher.distance,
+            });
+        }
+
+        matcher.matching = matching;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        app::{App, Update},
+        prelude::Events,
+    };
+    use bevy_trait_query::RegisterExt;
+
+    use super::*;
+    use crate::SimpleSpikeRecorder;
+
+    fn run_matcher(spikes: [Vec<f64>; 3]) -> usize {
+        let mut app = App::new();
+        app.add_event::<PatternDetectedEvent>()
+            .insert_resource(Clock {
+                time: 20.0,
+                ..Default::default()
+            })
+            .register_component_as::<dyn SpikeRecorder, SimpleSpikeRecorder>()
+            .add_systems(Update, match_spike_patterns);
+
+        let neurons = spikes
+            .map(|spikes| {
+                let mut recorder = SimpleSpikeRecorder::default();
+                for time in spikes {
+                    recorder.record_spike(time);
+                }
+                app.world_mut().spawn(recorder).id()
+            })
+            .to_vec();
+
+        app.world_mut().spawn(PatternMatcher::new(
+            vec![
+                (neurons[0], vec![0.0]),
+                (neurons[1], vec![5.0]),
+                (neurons[2], vec![10.0, 12.0]),
+            ],
+            0.5,
+        ));
+
+        app.update();
+
+        app.world().resource::<Events<PatternDetectedEvent>>().len()
+    }
+
+    #[test]
+    fn test_template_is_detected() {
+        // the window covers the last 12ms, so the pattern starts at t = 8
+        let detections = run_matcher([vec![1.0, 8.0], vec![13.0], vec![18.0, 20.0]]);
+        assert_eq!(detections, 1);
+    }
+
+    #[test]
+    fn test_random_activity_is_not_detected() {
+        let detections = run_matcher([vec![17.5, 19.0], vec![9.0, 11.0], vec![8.5]]);
+        assert_eq!(detections, 0);
+    }
+
+    #[test]
+    fn test_van_rossum_distance() {
+        assert_eq!(van_rossum_distance(&[1.0, 2.0], &[1.0, 2.0], 1.0), 0.0);
+        assert!(van_rossum_distance(&[1.0], &[], 1.0) > 0.0);
+        assert!(
+            van_rossum_distance(&[1.0], &[1.5], 1.0) < van_rossum_distance(&[1.0], &[3.0], 1.0)
+        );
+    }
+}
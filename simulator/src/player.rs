@@ -0,0 +1,168 @@
+use bevy::{
+    prelude::{Component, Entity, Query, Res, ResMut},
+    reflect::Reflect,
+};
+use bevy_trait_query::One;
+use silicon_core::{Clock, SpikeRecorder};
+
+use crate::FiredNeurons;
+
+/// Plays a precomputed spike train, for example from the transcoder, into the network. The
+/// neuron on the same entity spikes at every time in `spikes`, on top of the spikes it fires on
+/// its own. The membrane of the neuron isn't touched, the spikes are only sent to its synapses.
+#[derive(Debug, Clone, Component, Reflect)]
+pub struct SpikeTrainPlayer {
+    /// spike times in ms relative to `start_time`, sorted
+    spikes: Vec<f64>,
+    /// index of the next spike to play
+    cursor: usize,
+    /// simulation time in ms at which playback starts
+    pub start_time: f64,
+    /// the start time the player was created with, for `rewind`
+    initial_start_time: f64,
+    /// when set the train is played again every `loop_period` ms
+    pub loop_period: Option<f64>,
+}
+
+impl SpikeTrainPlayer {
+    pub fn new(mut spikes: Vec<f64>) -> Self {
+        spikes.sort_by(|a, b| a.total_cmp(b));
+        SpikeTrainPlayer {
+            spikes,
+            cursor: 0,
+            start_time: 0.0,
+            initial_start_time: 0.0,
+            loop_period: None,
+        }
+    }
+
+    pub fn with_start_time(mut self, start_time: f64) -> Self {
+        self.start_time = start_time;
+        self.initial_start_time = start_time;
+        self
+    }
+
+    pub fn looping(mut self, loop_period: f64) -> Self {
+        self.loop_period = Some(loop_period);
+        self
+    }
+
+    pub fn spikes(&self) -> &[f64] {
+        &self.spikes
+    }
+
+    /// Play the train from the beginning, starting at `start_time`.
+    pub fn restart(&mut self, start_time: f64) {
+        self.start_time = start_time;
+        self.cursor = 0;
+    }
+
+    /// Play the train from the beginning as if the simulation had started at `time`, the start
+    /// time the player was created with is kept relative to it.
+    pub fn rewind(&mut self, time: f64) {
+        self.restart(time + self.initial_start_time);
+    }
+
+    /// Advance the playback to `time` and return whether a spike was due. Spikes closer together
+    /// than a tick are merged into a single spike.
+    pub fn advance(&mut self, time: f64) -> bool {
+        let mut due = false;
+        loop {
+            if self.cursor == self.spikes.len() {
+                match self.loop_period {
+                    Some(loop_period) if !self.spikes.is_empty() && loop_period > 0.0 => {
+                        self.start_time += loop_period;
+                        self.cursor = 0;
+                    }
+                    _ => return due,
+                }
+            }
+
+            if self.start_time + self.spikes[self.cursor] > time {
+                return due;
+            }
+
+            self.cursor += 1;
+            due = true;
+        }
+    }
+}
+
+pub(crate) fn play_spike_trains(
+    mut players: Query<(
+        Entity,
+        &mut SpikeTrainPlayer,
+        Option<One<&mut dyn SpikeRecorder>>,
+    )>,
+    mut fired_neurons: ResMut<FiredNeurons>,
+    clock: Res<Clock>,
+) {
+    if clock.time_to_simulate <= 0.0 {
+        return;
+    }
+
+    // half a tick of slack, the clock accumulates rounding errors
+    let time = clock.time + clock.tau / 2.0;
+    for (entity, mut player, spike_recorder) in players.iter_mut() {
+        if !player.advance(time) {
+            continue;
+        }
+
+        // the neuron may have fired on its own during this tick
+        if fired_neurons
+            .spikes
+            .iter()
+            .any(|(neuron, _)| *neuron == entity)
+        {
+            continue;
+        }
+
+        fired_neurons.spikes.push((entity, clock.time));
+        if let Some(mut spike_recorder) = spike_recorder {
+            spike_recorder.record_spike(clock.time);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plays_spikes_once() {
+        let mut player = SpikeTrainPlayer::new(vec![5.0, 1.0, 2.0]).with_start_time(10.0);
+        let played = (0..40)
+            .filter(|time| player.advance(*time as f64))
+            .collect::<Vec<_>>();
+
+        assert_eq!(played, [11, 12, 15]);
+    }
+
+    #[test]
+    fn test_looping_and_restart() {
+        let mut player = SpikeTrainPlayer::new(vec![1.0, 2.0, 5.0])
+            .with_start_time(10.0)
+            .looping(10.0);
+        let played = (0..40)
+            .filter(|time| player.advance(*time as f64))
+            .collect::<Vec<_>>();
+        assert_eq!(played, [11, 12, 15, 21, 22, 25, 31, 32, 35]);
+
+        player.restart(100.0);
+        assert!(!player.advance(100.5));
+        assert!(player.advance(101.0));
+
+        // the start time of the train is relative to the time it is rewound to
+        player.rewind(0.0);
+        assert_eq!(player.start_time, 10.0);
+        assert!(!player.advance(10.5));
+        assert!(player.advance(11.0));
+    }
+
+    #[test]
+    fn test_close_spikes_are_merged() {
+        let mut player = SpikeTrainPlayer::new(vec![1.0, 1.01, 1.02]);
+        assert!(player.advance(1.025));
+        assert!(!player.advance(2.0));
+    }
+}
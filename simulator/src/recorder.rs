@@ -33,5 +33,61 @@ pub(crate) fn clean_recorder_history(
             .filter(|(time, _)| clock.time - time < history_config.window_size as f64)
             .cloned()
             .collect();
+
+        // decimating an already decimated history every frame would keep blurring it, so the
+        // history has to grow back to twice the size first
+        if let Some(max_points) = history_config.max_points {
+            if recorder.values.len() > max_points.saturating_mul(2) {
+                recorder.values = recorder.decimated(max_points);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::app::{App, Update};
+
+    use super::*;
+
+    fn values(app: &App, recorder: Entity) -> Vec<(f64, f64)> {
+        app.world()
+            .get::<ValueRecorder>(recorder)
+            .unwrap()
+            .values
+            .clone()
+    }
+
+    #[test]
+    fn test_history_is_decimated_once_it_doubles() {
+        let mut app = App::new();
+        app.insert_resource(Clock::default())
+            .insert_resource(ValueRecorderConfig {
+                window_size: 1000,
+                max_points: Some(10),
+            })
+            .add_systems(Update, clean_recorder_history);
+
+        let mut recorder = ValueRecorder::new();
+        for i in 0..20 {
+            recorder.push(i as f64, (i % 3) as f64);
+        }
+        let recorder = app.world_mut().spawn(recorder).id();
+
+        // up to twice as many values are kept as they are
+        app.update();
+        assert_eq!(values(&app, recorder).len(), 20);
+
+        app.world_mut()
+            .get_mut::<ValueRecorder>(recorder)
+            .unwrap()
+            .push(20.0, 5.0);
+        app.update();
+        let decimated = values(&app, recorder);
+        assert!(decimated.len() <= 10);
+
+        // the decimated history is left alone on the next frames
+        app.update();
+        assert_eq!(values(&app, recorder), decimated);
     }
 }
@@ -0,0 +1,382 @@
+use analytics::firing_stats::FiringStats;
+use bevy::{
+    prelude::{Entity, Event, EventReader, Query, Res, ResMut, Resource},
+    utils::HashMap,
+};
+use bevy_trait_query::{One, OneAdded};
+use silicon_core::{Clock, Neuron, SpikeRecorder, ValueRecorder};
+use synapses::Synapse;
+
+use crate::{
+    adaptation::Adaptation, delay::DelayBuffer, event_driven::NeuronActivity,
+    player::SpikeTrainPlayer, trace::BinaryTraceRecorder, FiredNeurons,
+};
+
+/// Return every neuron and synapse to its resting state and set the clock back to zero, for
+/// running repeated trials on the same network.
+///
+/// The spike recorders, firing statistics and membrane potential recorders of the neurons are
+/// emptied, spikes that are still in transit are dropped and spike train players start over. The
+/// synapses lose their traces and short-term state, but unless `reset_weights` is set their
+/// weights are left untouched, so whatever the network learned carries over into the next trial.
+#[derive(Event, Debug, Default)]
+pub struct ResetNetworkEvent {
+    /// Set every synapse back to the weight it was created with and clear the weight recorders.
+    pub reset_weights: bool,
+    /// Only reset the state of the network and leave every recorder alone, for separating the
+    /// presentations of a training loop without wiping the plots of the whole run. The clock
+    /// keeps running so the recordings stay in order.
+    pub keep_recordings: bool,
+}
+
+/// Start the simulation over, this does everything [`ResetNetworkEvent`] does and clears the
+/// weight recorders of the synapses as well. Synapse weights are kept.
+#[derive(Event, Debug, Default)]
+pub struct ResetSimulation;
+
+/// The weight every synapse had when it was added to the world.
+#[derive(Resource, Debug, Default)]
+pub struct InitialWeights {
+    weights: HashMap<Entity, f64>,
+}
+
+impl InitialWeights {
+    pub fn get(&self, synapse: Entity) -> Option<f64> {
+        self.weights.get(&synapse).copied()
+    }
+}
+
+pub(crate) fn record_initial_weights(
+    synapses: Query<(Entity, One<&dyn Synapse>), OneAdded<dyn Synapse>>,
+    mut initial_weights: ResMut<InitialWeights>,
+) {
+    for (entity, synapse) in synapses.iter() {
+        initial_weights.weights.insert(entity, synapse.get_weight());
+    }
+}
+
+pub fn reset_network(
+    mut network_resets: EventReader<ResetNetworkEvent>,
+    mut simulation_resets: EventReader<ResetSimulation>,
+    mut neurons: Query<(
+        One<&mut dyn Neuron>,
+        Option<One<&mut dyn SpikeRecorder>>,
+        Option<&mut FiringStats>,
+        Option<&mut Adaptation>,
+    )>,
+    mut synapses: Query<(Entity, One<&mut dyn Synapse>)>,
+    mut players: Query<&mut SpikeTrainPlayer>,
+    mut traces: Query<&mut BinaryTraceRecorder>,
+    mut value_recorders: Query<(Entity, &mut ValueRecorder)>,
+    initial_weights: Res<InitialWeights>,
+    mut delay_buffer: ResMut<DelayBuffer>,
+    mut fired_neurons: ResMut<FiredNeurons>,
+    mut activity: ResMut<NeuronActivity>,
+    mut clock: ResMut<Clock>,
+) {
+    let reset_simulation = simulation_resets.read().count() > 0;
+    let (reset_network, reset_weights, keep_recordings) = network_resets.read().fold(
+        (false, false, true),
+        |(_, reset_weights, keep_recordings), event| {
+            (
+                true,
+                reset_weights || event.reset_weights,
+                keep_recordings && event.keep_recordings,
+            )
+        },
+    );
+    if !reset_simulation && !reset_network {
+        return;
+    }
+    // the recordings are only kept when every reset asks for it
+    let keep_recordings = keep_recordings && !reset_simulation;
+
+    if !keep_recordings {
+        clock.time = 0.0;
+        for mut trace in traces.iter_mut() {
+            trace.rewind(clock.time);
+        }
+    }
+
+    for (mut neuron, spike_recorder, firing_stats, adaptation) in neurons.iter_mut() {
+        neuron.reset_state();
+
+        if let Some(mut spike_recorder) = spike_recorder.filter(|_| !keep_recordings) {
+            spike_recorder.clear();
+        }
+
+        if let Some(mut firing_stats) = firing_stats.filter(|_| !keep_recordings) {
+            firing_stats.reset();
+        }
+
+        if let Some(mut adaptation) = adaptation {
+            adaptation.w = 0.0;
+        }
+    }
+
+    for (entity, mut synapse) in synapses.iter_mut() {
+        synapse.reset_state();
+
+        if let Some(weight) = initial_weights.get(entity).filter(|_| reset_weights) {
+            synapse.set_weight(weight);
+        }
+    }
+
+    for mut player in players.iter_mut() {
+        player.rewind(clock.time);
+    }
+
+    for (entity, mut value_recorder) in value_recorders.iter_mut() {
+        if keep_recordings {
+            break;
+        }
+
+        if reset_simulation || reset_weights || neurons.contains(entity) {
+            value_recorder.values.clear();
+        }
+    }
+
+    delay_buffer.clear();
+    fired_neurons.spikes.clear();
+    activity.clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        app::{App, Update},
+        prelude::{Component, IntoSystemConfigs},
+    };
+    use bevy_trait_query::RegisterExt;
+    use neurons::leaky::LifNeuron;
+    use silicon_core::Clock;
+    use synapses::{
+        simple::SimpleSynapse, stp::StpSynapse, DeferredStdpEvent, Synapse, SynapseType,
+    };
+
+    use super::*;
+    use crate::{
+        current::{apply_current_sources, CurrentSource},
+        deliver_delayed_spikes, emit_spikes,
+        synapse_index::{index_synapses, SynapseIndex},
+        time::update_clock,
+        update_neurons, update_synapses_for_spikes, SimpleSpikeRecorder, SpikeEvent,
+    };
+
+    /// A neuron driven by a constant current connected to a second neuron, returns the app with
+    /// the source, target and synapse.
+    fn network() -> (App, [Entity; 3]) {
+        network_with(|source, target| SimpleSynapse {
+            weight: 10.0,
+            delay: 400,
+            source,
+            target,
+            synapse_type: SynapseType::Excitatory,
+        })
+    }
+
+    /// Like `network`, with the synapse `synapse` creates from the source and target.
+    fn network_with<S: Component>(synapse: impl FnOnce(Entity, Entity) -> S) -> (App, [Entity; 3]) {
+        let mut app = App::new();
+        app.insert_resource(Clock {
+            time_to_simulate: 1000.0,
+            ..Default::default()
+        })
+        .init_resource::<DelayBuffer>()
+        .init_resource::<FiredNeurons>()
+        .init_resource::<SynapseIndex>()
+        .init_resource::<NeuronActivity>()
+        .init_resource::<InitialWeights>()
+        .add_event::<SpikeEvent>()
+        .add_event::<DeferredStdpEvent>()
+        .add_event::<ResetNetworkEvent>()
+        .add_event::<ResetSimulation>()
+        .register_component_as::<dyn Neuron, LifNeuron>()
+        .register_component_as::<dyn SpikeRecorder, SimpleSpikeRecorder>()
+        .register_component_as::<dyn Synapse, SimpleSynapse>()
+        .register_component_as::<dyn Synapse, StpSynapse>()
+        .add_systems(
+            Update,
+            (
+                record_initial_weights,
+                reset_network,
+                update_clock,
+                deliver_delayed_spikes,
+                apply_current_sources,
+                update_neurons,
+                index_synapses,
+                emit_spikes,
+                update_synapses_for_spikes,
+            )
+                .chain(),
+        );
+
+        let source = app
+            .world_mut()
+            .spawn((
+                LifNeuron::default(),
+                SimpleSpikeRecorder::default(),
+                CurrentSource::Constant { amplitude: 2.0 },
+            ))
+            .id();
+        let target = app
+            .world_mut()
+            .spawn((
+                LifNeuron::default(),
+                SimpleSpikeRecorder::default(),
+                ValueRecorder::default(),
+            ))
+            .id();
+        let synapse = app
+            .world_mut()
+            .spawn((synapse(source, target), ValueRecorder::default()))
+            .id();
+
+        (app, [source, target, synapse])
+    }
+
+    /// Run for 100ms and return the spike times of the neurons as ticks since `start`.
+    fn run_trial(app: &mut App, neurons: [Entity; 2], start: f64) -> [Vec<u64>; 2] {
+        for _ in 0..4000 {
+            app.update();
+        }
+
+        neurons.map(|neuron| {
+            app.world()
+                .get::<SimpleSpikeRecorder>(neuron)
+                .unwrap()
+                .spikes
+                .iter()
+                .map(|time| ((time - start) / 0.025).round() as u64)
+                .collect::<Vec<_>>()
+        })
+    }
+
+    #[test]
+    fn test_reset_repeats_trial() {
+        let (mut app, [source, target, synapse]) = network();
+
+        let first = run_trial(&mut app, [source, target], 0.0);
+        assert!(!first[1].is_empty());
+        // the trial ends with spikes in transit
+        assert!(!app.world().resource::<DelayBuffer>().is_empty());
+
+        let end = app.world().resource::<Clock>().time;
+        app.world_mut().send_event(ResetNetworkEvent::default());
+        let second = run_trial(&mut app, [source, target], 0.0);
+
+        // the clock starts over with the trial
+        assert_eq!(second, first);
+        assert_eq!(app.world().resource::<Clock>().time, end);
+        let synapse = app.world().get::<SimpleSynapse>(synapse).unwrap();
+        assert_eq!(synapse.weight, 10.0);
+    }
+
+    #[test]
+    fn test_reset_restores_short_term_plasticity() {
+        let (mut app, [source, target, synapse]) = network_with(|source, target| StpSynapse {
+            delay: 400,
+            ..StpSynapse::new(
+                source,
+                target,
+                20.0,
+                SynapseType::Excitatory,
+                0.5,
+                800.0,
+                0.0,
+            )
+        });
+
+        // only the first spike is strong enough to make the target fire, the synapse is depleted
+        // for the rest of the trial
+        let first = run_trial(&mut app, [source, target], 0.0);
+        assert_eq!(first[1].len(), 1);
+        assert!(first[0].len() > 1);
+        assert!(app.world().get::<StpSynapse>(synapse).unwrap().x < 0.5);
+
+        app.world_mut().send_event(ResetNetworkEvent::default());
+        let second = run_trial(&mut app, [source, target], 0.0);
+        assert_eq!(second, first);
+    }
+
+    #[test]
+    fn test_reset_simulation_restarts_clock() {
+        let (mut app, [source, target, synapse]) = network();
+        let first = run_trial(&mut app, [source, target], 0.0);
+        let end = app.world().resource::<Clock>().time;
+
+        app.world_mut()
+            .get_mut::<ValueRecorder>(synapse)
+            .unwrap()
+            .push(end, 10.0);
+        app.world_mut().send_event(ResetSimulation);
+        let second = run_trial(&mut app, [source, target], 0.0);
+
+        assert_eq!(second, first);
+        assert_eq!(app.world().resource::<Clock>().time, end);
+        assert!(app
+            .world()
+            .get::<ValueRecorder>(synapse)
+            .unwrap()
+            .values
+            .is_empty());
+    }
+
+    #[test]
+    fn test_reset_weights() {
+        let (mut app, [source, target, synapse]) = network();
+        let first = run_trial(&mut app, [source, target], 0.0);
+
+        // a weight this low doesn't make the target fire
+        app.world_mut()
+            .get_mut::<SimpleSynapse>(synapse)
+            .unwrap()
+            .weight = 1.0;
+        app.world_mut().send_event(ResetNetworkEvent {
+            reset_weights: true,
+            ..Default::default()
+        });
+        let second = run_trial(&mut app, [source, target], 0.0);
+
+        assert_eq!(second, first);
+        let synapse = app.world().get::<SimpleSynapse>(synapse).unwrap();
+        assert_eq!(synapse.weight, 10.0);
+    }
+
+    #[test]
+    fn test_reset_can_keep_recordings() {
+        let (mut app, [source, target, synapse]) = network();
+        let first = run_trial(&mut app, [source, target], 0.0);
+        let start = app.world().resource::<Clock>().time;
+        app.world_mut()
+            .get_mut::<ValueRecorder>(target)
+            .unwrap()
+            .push(start, -60.0);
+
+        app.world_mut().send_event(ResetNetworkEvent {
+            keep_recordings: true,
+            ..Default::default()
+        });
+        let both = run_trial(&mut app, [source, target], 0.0);
+
+        // the network starts over, but the spikes of the first trial are still recorded
+        let offset = (start / 0.025).round() as u64;
+        for (both, first) in both.iter().zip(&first) {
+            let second = both
+                .iter()
+                .filter(|tick| **tick >= offset)
+                .map(|tick| tick - offset)
+                .collect::<Vec<_>>();
+            assert_eq!(both[..first.len()], first[..]);
+            assert_eq!(&second, first);
+        }
+        assert_eq!(
+            app.world().get::<ValueRecorder>(target).unwrap().values,
+            vec![(start, -60.0)]
+        );
+        assert_eq!(
+            app.world().get::<SimpleSynapse>(synapse).unwrap().weight,
+            10.0
+        );
+    }
+}
@@ -0,0 +1,279 @@
+use bevy::{
+    app::{App, Plugin},
+    prelude::{
+        Commands, Entity, EventReader, IntoSystemConfigs, Local, Query, Res, Resource, Transform,
+    },
+    reflect::Reflect,
+    utils::HashMap,
+};
+use silicon_core::{Clock, SimulationStep};
+use synapses::{
+    stdp::{StdpParams, StdpRule, StdpSpikeType, StdpState, StdpSynapse},
+    SynapseType,
+};
+
+use crate::{emit_spikes, synapse_index::SynapseIndex, SpikeEvent};
+
+/// Grows new synapses between nearby neurons that fire together, the counterpart of pruning the
+/// weak ones. Needs the `SimulationPlugin`.
+pub struct StructuralPlasticityPlugin;
+
+impl Plugin for StructuralPlasticityPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<StructuralPlasticitySettings>()
+            .init_resource::<StructuralPlasticitySettings>()
+            .add_systems(SimulationStep, grow_synapses.after(emit_spikes));
+    }
+}
+
+#[derive(Debug, Reflect, Resource)]
+pub struct StructuralPlasticitySettings {
+    /// neurons further apart than this never grow a synapse between them
+    pub radius: f32,
+    /// both neurons must have fired within this many ms before the scan
+    pub coincidence_window: f64,
+    /// look for new connections every this many ms
+    pub growth_interval: f64,
+    /// the weight of a new synapse
+    pub w_init: f64,
+    /// neurons with this many incoming and outgoing synapses don't grow new ones
+    pub max_synapses_per_neuron: usize,
+}
+
+impl Default for StructuralPlasticitySettings {
+    fn default() -> Self {
+        StructuralPlasticitySettings {
+            radius: 2.0,
+            coincidence_window: 10.0,
+            growth_interval: 100.0,
+            w_init: 0.1,
+            max_synapses_per_neuron: 50,
+        }
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct GrowthState {
+    /// the time of the last spike of every neuron that fired
+    last_spikes: HashMap<Entity, f64>,
+    last_growth: Option<f64>,
+}
+
+/// Connects every pair of neurons within `radius` of each other that both fired within the
+/// `coincidence_window` and aren't connected in either direction yet. The neuron that fired first
+/// becomes the presynaptic one.
+pub(crate) fn grow_synapses(
+    neurons: Query<&Transform>,
+    mut spike_reader: EventReader<SpikeEvent>,
+    index: Res<SynapseIndex>,
+    settings: Res<StructuralPlasticitySettings>,
+    clock: Res<Clock>,
+    mut state: Local<GrowthState>,
+    mut commands: Commands,
+) {
+    for spike_event in spike_reader.read() {
+        state
+            .last_spikes
+            .insert(spike_event.neuron, spike_event.time);
+    }
+
+    // the clock runs backwards after the simulation was reset
+    let due = state.last_growth.map_or(true, |last_growth| {
+        clock.time < last_growth || clock.time - last_growth >= settings.growth_interval
+    });
+    if !due {
+        return;
+    }
+    state.last_growth = Some(clock.time);
+
+    let mut recent = state
+        .last_spikes
+        .iter()
+        .filter(|(_, time)| {
+            **time <= clock.time && clock.time - **time <= settings.coincidence_window
+        })
+        .filter_map(|(neuron, time)| {
+            let position = neurons.get(*neuron).ok()?.translation;
+            Some((*neuron, *time, position))
+        })
+        .collect::<Vec<_>>();
+    // the order of a hash map isn't stable, the synapses should grow the same way every run
+    recent.sort_by(|(a, a_time, _), (b, b_time, _)| {
+        a_time
+            .total_cmp(b_time)
+            .then_with(|| (a.index(), a.generation()).cmp(&(b.index(), b.generation())))
+    });
+
+    let synapse_count =
+        |neuron: Entity| index.outgoing(neuron).len() + index.incoming(neuron).len();
+    let mut grown = HashMap::<Entity, usize>::new();
+    for (i, (pre, _, pre_position)) in recent.iter().enumerate() {
+        for (post, _, post_position) in recent[i + 1..].iter() {
+            if pre_position.distance(*post_position) > settings.radius
+                || index.connects(*pre, *post)
+                || index.connects(*post, *pre)
+            {
+                continue;
+            }
+
+            let full = [pre, post].iter().any(|neuron| {
+                synapse_count(**neuron) + grown.get(*neuron).copied().unwrap_or(0)
+                    >= settings.max_synapses_per_neuron
+            });
+            if full {
+                continue;
+            }
+
+            commands.spawn(StdpSynapse {
+                stdp_params: StdpParams {
+                    a_plus: 0.01,
+                    a_minus: -0.01,
+                    tau_plus: 0.2,
+                    tau_minus: 0.2,
+                    w_max: 1.0,
+                    w_min: 0.0,
+                    soft_bound: false,
+                    tau_eligibility: 1000.0,
+                },
+                stdp_state: StdpState {
+                    a: 0.0,
+                    spike_type: StdpSpikeType::PreSpike,
+                    eligibility: 0.0,
+                },
+                source: *pre,
+                target: *post,
+                weight: settings.w_init,
+                delay: 1,
+                synapse_type: SynapseType::Excitatory,
+                rule: StdpRule::Asymmetric,
+            });
+            *grown.entry(*pre).or_default() += 1;
+            *grown.entry(*post).or_default() += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{app::Update, prelude::Vec3};
+    use bevy_trait_query::RegisterExt;
+    use synapses::{DeferredStdpEvent, Synapse};
+
+    use super::*;
+    use crate::{synapse_index::index_synapses, time::update_clock, FiredNeurons};
+
+    fn app(settings: StructuralPlasticitySettings) -> App {
+        let mut app = App::new();
+        app.insert_resource(Clock {
+            time_to_simulate: f64::MAX,
+            tau: 1.0,
+            ..Default::default()
+        })
+        .insert_resource(settings)
+        .init_resource::<FiredNeurons>()
+        .init_resource::<SynapseIndex>()
+        .add_event::<SpikeEvent>()
+        .add_event::<DeferredStdpEvent>()
+        .register_component_as::<dyn Synapse, StdpSynapse>()
+        .add_systems(
+            Update,
+            (update_clock, index_synapses, emit_spikes, grow_synapses).chain(),
+        );
+        app
+    }
+
+    fn neuron(app: &mut App, x: f32) -> Entity {
+        app.world_mut()
+            .spawn(Transform::from_translation(Vec3::new(x, 0.0, 0.0)))
+            .id()
+    }
+
+    /// Runs `ticks` updates, every neuron in `firing` fires every 20 ms.
+    fn run(app: &mut App, firing: &[Entity], ticks: usize) {
+        for tick in 0..ticks {
+            if tick % 20 == 0 {
+                let time = app.world().resource::<Clock>().time;
+                app.world_mut().resource_mut::<FiredNeurons>().spikes =
+                    firing.iter().map(|neuron| (*neuron, time)).collect();
+            }
+            app.update();
+        }
+    }
+
+    fn synapses(app: &mut App) -> Vec<(Entity, Entity, f64)> {
+        app.world_mut()
+            .query::<&StdpSynapse>()
+            .iter(app.world())
+            .map(|synapse| (synapse.source, synapse.target, synapse.weight))
+            .collect()
+    }
+
+    #[test]
+    fn test_coincident_neurons_in_range_connect() {
+        let mut app = app(StructuralPlasticitySettings {
+            w_init: 0.2,
+            ..Default::default()
+        });
+        let a = neuron(&mut app, 0.0);
+        let b = neuron(&mut app, 1.5);
+        // fires together with the others but is out of range
+        let far = neuron(&mut app, 10.0);
+        // in range but never fires
+        let silent = neuron(&mut app, -1.0);
+
+        run(&mut app, &[a, b, far], 1000);
+
+        // one synapse despite ten scans, in a single direction
+        let synapses = synapses(&mut app);
+        assert_eq!(synapses.len(), 1);
+        let (source, target, weight) = synapses[0];
+        assert!([(a, b), (b, a)].contains(&(source, target)));
+        assert_eq!(weight, 0.2);
+        assert!(!synapses
+            .iter()
+            .any(|(source, target, _)| [far, silent].contains(source)
+                || [far, silent].contains(target)));
+    }
+
+    #[test]
+    fn test_synapse_limit_per_neuron() {
+        let mut app = app(StructuralPlasticitySettings {
+            max_synapses_per_neuron: 2,
+            ..Default::default()
+        });
+        let neurons = [0.0, 0.5, 1.0, 1.5].map(|x| neuron(&mut app, x));
+
+        run(&mut app, &neurons, 1000);
+
+        let synapses = synapses(&mut app);
+        assert!(!synapses.is_empty());
+        for neuron in neurons {
+            let count = synapses
+                .iter()
+                .filter(|(source, target, _)| *source == neuron || *target == neuron)
+                .count();
+            assert!(count <= 2);
+        }
+    }
+
+    #[test]
+    fn test_neurons_that_fire_apart_stay_unconnected() {
+        let mut app = app(StructuralPlasticitySettings::default());
+        let a = neuron(&mut app, 0.0);
+        let b = neuron(&mut app, 1.0);
+
+        // b fires 30 ms after a, outside the 10 ms window
+        for tick in 0..1000 {
+            let time = app.world().resource::<Clock>().time;
+            let firing = match tick % 60 {
+                0 => vec![(a, time)],
+                30 => vec![(b, time)],
+                _ => vec![],
+            };
+            app.world_mut().resource_mut::<FiredNeurons>().spikes = firing;
+            app.update();
+        }
+
+        assert!(synapses(&mut app).is_empty());
+    }
+}
@@ -0,0 +1,262 @@
+use bevy::{
+    prelude::{Commands, Component, Entity, Query, RemovedComponents, ResMut, Resource},
+    utils::HashMap,
+};
+use bevy_trait_query::{One, OneAdded};
+use smallvec::SmallVec;
+use synapses::Synapse;
+
+/// Maps every neuron to its outgoing and incoming synapses, so a spike only has to visit the
+/// synapses it actually reaches and the plasticity rules only the synapses of the neurons that
+/// fired.
+///
+/// New synapses are picked up by `index_synapses`, `prune_synapses` removes the synapses it
+/// despawns right away. Synapses despawned elsewhere are dropped by `unindex_despawned_synapses`
+/// on the next update.
+#[derive(Resource, Debug, Default)]
+pub struct SynapseIndex {
+    outgoing: HashMap<Entity, SmallVec<[Entity; 8]>>,
+    incoming: HashMap<Entity, SmallVec<[Entity; 8]>>,
+    /// the presynaptic and postsynaptic neuron of every indexed synapse
+    synapses: HashMap<Entity, (Entity, Entity)>,
+}
+
+impl SynapseIndex {
+    pub fn insert(&mut self, synapse: Entity, presynaptic: Entity, postsynaptic: Entity) {
+        if self.synapses.contains_key(&synapse) {
+            return;
+        }
+
+        self.synapses.insert(synapse, (presynaptic, postsynaptic));
+        self.outgoing.entry(presynaptic).or_default().push(synapse);
+        self.incoming.entry(postsynaptic).or_default().push(synapse);
+    }
+
+    pub fn remove(&mut self, synapse: Entity) {
+        let Some((presynaptic, postsynaptic)) = self.synapses.remove(&synapse) else {
+            return;
+        };
+
+        for (map, neuron) in [
+            (&mut self.outgoing, presynaptic),
+            (&mut self.incoming, postsynaptic),
+        ] {
+            if let Some(synapses) = map.get_mut(&neuron) {
+                synapses.retain(|entity| *entity != synapse);
+                if synapses.is_empty() {
+                    map.remove(&neuron);
+                }
+            }
+        }
+    }
+
+    /// The synapses that have `presynaptic` as their source.
+    pub fn outgoing(&self, presynaptic: Entity) -> &[Entity] {
+        self.outgoing
+            .get(&presynaptic)
+            .map(|synapses| synapses.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// The synapses that have `postsynaptic` as their target.
+    pub fn incoming(&self, postsynaptic: Entity) -> &[Entity] {
+        self.incoming
+            .get(&postsynaptic)
+            .map(|synapses| synapses.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Whether there is a synapse from `presynaptic` to `postsynaptic`.
+    pub fn connects(&self, presynaptic: Entity, postsynaptic: Entity) -> bool {
+        self.outgoing(presynaptic).iter().any(|synapse| {
+            self.synapses
+                .get(synapse)
+                .is_some_and(|(_, target)| *target == postsynaptic)
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.synapses.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.synapses.is_empty()
+    }
+}
+
+/// Marks the synapses in the `SynapseIndex`. Removals of a trait can't be observed, removals of
+/// this component cover every synapse type.
+#[derive(Component, Debug)]
+pub struct IndexedSynapse;
+
+pub fn index_synapses(
+    synapses: Query<(Entity, One<&dyn Synapse>), OneAdded<dyn Synapse>>,
+    mut index: ResMut<SynapseIndex>,
+    mut commands: Commands,
+) {
+    for (entity, synapse) in synapses.iter() {
+        index.insert(
+            entity,
+            synapse.get_presynaptic(),
+            synapse.get_postsynaptic(),
+        );
+        commands.entity(entity).try_insert(IndexedSynapse);
+    }
+}
+
+/// Drops the despawned synapses from the `SynapseIndex`.
+pub fn unindex_despawned_synapses(
+    mut removed: RemovedComponents<IndexedSynapse>,
+    mut index: ResMut<SynapseIndex>,
+) {
+    for synapse in removed.read() {
+        index.remove(synapse);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        app::{App, Update},
+        hierarchy::DespawnRecursiveExt,
+        prelude::IntoSystemConfigs,
+    };
+    use bevy_trait_query::RegisterExt;
+    use silicon_core::Clock;
+    use synapses::{simple::SimpleSynapse, SynapseType};
+
+    use super::*;
+    use crate::{delay::DelayBuffer, update_synapses_for_spikes, SpikeEvent};
+
+    #[test]
+    fn test_spike_only_visits_outgoing_synapses() {
+        let mut app = App::new();
+        app.insert_resource(Clock::default())
+            .init_resource::<SynapseIndex>()
+            .init_resource::<DelayBuffer>()
+            .add_event::<SpikeEvent>()
+            .register_component_as::<dyn Synapse, SimpleSynapse>()
+            .add_systems(
+                Update,
+                (
+                    unindex_despawned_synapses,
+                    index_synapses,
+                    update_synapses_for_spikes,
+                )
+                    .chain(),
+            );
+
+        let neurons = (0..500)
+            .map(|_| app.world_mut().spawn_empty().id())
+            .collect::<Vec<_>>();
+        // 5000 synapses, every neuron has 10 outgoing synapses
+        for (i, source) in neurons.iter().enumerate() {
+            for j in 1..=10 {
+                app.world_mut().spawn(SimpleSynapse {
+                    weight: 1.0,
+                    delay: 1,
+                    source: *source,
+                    target: neurons[(i + j) % neurons.len()],
+                    synapse_type: SynapseType::Excitatory,
+                });
+            }
+        }
+
+        app.update();
+        assert_eq!(app.world().resource::<SynapseIndex>().len(), 5000);
+
+        app.world_mut().send_event(SpikeEvent {
+            time: 0.0,
+            neuron: neurons[42],
+        });
+        app.update();
+
+        let index = app.world().resource::<SynapseIndex>();
+        assert_eq!(index.outgoing(neurons[42]).len(), 10);
+        assert_eq!(index.incoming(neurons[42]).len(), 10);
+        assert_eq!(app.world().resource::<DelayBuffer>().len(), 10);
+    }
+
+    #[test]
+    fn test_despawned_synapses_are_dropped() {
+        let mut app = App::new();
+        app.insert_resource(Clock::default())
+            .init_resource::<SynapseIndex>()
+            .init_resource::<DelayBuffer>()
+            .add_event::<SpikeEvent>()
+            .register_component_as::<dyn Synapse, SimpleSynapse>()
+            .add_systems(
+                Update,
+                (
+                    unindex_despawned_synapses,
+                    index_synapses,
+                    update_synapses_for_spikes,
+                )
+                    .chain(),
+            );
+
+        let source = app.world_mut().spawn_empty().id();
+        let target = app.world_mut().spawn_empty().id();
+        let synapses = [0, 1].map(|_| {
+            app.world_mut()
+                .spawn(SimpleSynapse {
+                    weight: 1.0,
+                    delay: 1,
+                    source,
+                    target,
+                    synapse_type: SynapseType::Excitatory,
+                })
+                .id()
+        });
+        app.update();
+
+        // dropped without waiting for a spike of the presynaptic neuron
+        app.world_mut().despawn(synapses[0]);
+        app.update();
+
+        let index = app.world().resource::<SynapseIndex>();
+        assert_eq!(index.len(), 1);
+        assert_eq!(index.outgoing(source), [synapses[1]]);
+        assert_eq!(index.incoming(target), [synapses[1]]);
+
+        app.world_mut().send_event(SpikeEvent {
+            time: 0.0,
+            neuron: source,
+        });
+        app.update();
+        assert_eq!(app.world().resource::<DelayBuffer>().len(), 1);
+    }
+
+    #[test]
+    fn test_index_follows_despawns_outside_pruning() {
+        let mut app = App::new();
+        app.init_resource::<SynapseIndex>()
+            .register_component_as::<dyn Synapse, SimpleSynapse>()
+            .add_systems(Update, (unindex_despawned_synapses, index_synapses).chain());
+
+        let pre = app.world_mut().spawn_empty().id();
+        let post = app.world_mut().spawn_empty().id();
+        let synapse = app
+            .world_mut()
+            .spawn(SimpleSynapse {
+                weight: 1.0,
+                delay: 1,
+                source: pre,
+                target: post,
+                synapse_type: SynapseType::Excitatory,
+            })
+            .id();
+        app.update();
+        assert!(app.world().resource::<SynapseIndex>().connects(pre, post));
+
+        // like the UI and the network builders do, without a spike or `prune_synapses`
+        app.world_mut().entity_mut(synapse).despawn_recursive();
+        app.update();
+
+        let index = app.world().resource::<SynapseIndex>();
+        assert!(!index.connects(pre, post));
+        assert!(index.incoming(post).is_empty());
+        assert!(index.outgoing(pre).is_empty());
+        assert!(index.is_empty());
+    }
+}
@@ -1,8 +1,32 @@
-use bevy::prelude::ResMut;
-use silicon_core::Clock;
+use bevy::{
+    prelude::{ResMut, Resource, World},
+    reflect::Reflect,
+    time::Time,
+};
+use silicon_core::{Clock, SimulationStep};
 
+/// Configures the real time mode of the `Clock`.
+#[derive(Debug, Clone, Reflect, Resource)]
+pub struct SimulationSpeed {
+    /// simulated ms per real ms
+    pub speed_factor: f64,
+    /// the most simulated time in ms a single frame adds, so a stalled frame doesn't have to
+    /// catch up on thousands of steps at once
+    pub max_frame_advance: f64,
+}
+
+impl Default for SimulationSpeed {
+    fn default() -> Self {
+        SimulationSpeed {
+            speed_factor: 1.0,
+            max_frame_advance: 100.0,
+        }
+    }
+}
+
+/// Advances the clock by one time step while there is time left to simulate.
 pub(crate) fn update_clock(mut clock: ResMut<Clock>) {
-    if clock.run_indefinitely && clock.time_to_simulate <= 0.1 {
+    if clock.run_indefinitely && !clock.real_time && clock.time_to_simulate <= 0.1 {
         clock.time_to_simulate += 0.1;
     }
 
@@ -13,3 +37,111 @@ pub(crate) fn update_clock(mut clock: ResMut<Clock>) {
     clock.time += clock.tau;
     clock.time_to_simulate -= clock.tau;
 }
+
+/// Runs the `SimulationStep` schedule once per frame. In real time mode the real duration of the
+/// frame times the `speed_factor` is added to the time left to simulate instead, and the schedule
+/// runs as many steps of `tau` as fit in it. What's left over carries over to the next frame.
+pub(crate) fn run_simulation_steps(world: &mut World) {
+    if !world.resource::<Clock>().real_time {
+        world.run_schedule(SimulationStep);
+        return;
+    }
+
+    let speed = world
+        .get_resource::<SimulationSpeed>()
+        .cloned()
+        .unwrap_or_default();
+    let frame = world
+        .get_resource::<Time>()
+        .map_or(0.0, |time| time.delta_seconds_f64() * 1000.0);
+    world.resource_mut::<Clock>().time_to_simulate +=
+        (frame * speed.speed_factor).clamp(0.0, speed.max_frame_advance);
+
+    // the systems that run after the clock only check that there is time left, so a step needs
+    // more than a full tick left to run all of them
+    loop {
+        let clock = world.resource::<Clock>();
+        if !clock.real_time || clock.tau <= 0.0 || clock.time_to_simulate <= clock.tau {
+            break;
+        }
+        world.run_schedule(SimulationStep);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::{
+        app::{App, Update},
+        prelude::{IntoSystemConfigs, Res},
+    };
+
+    use super::*;
+
+    #[derive(Resource, Default)]
+    struct Steps {
+        count: usize,
+        without_time_left: usize,
+    }
+
+    fn count_steps(clock: Res<Clock>, mut steps: ResMut<Steps>) {
+        steps.count += 1;
+        if clock.time_to_simulate <= 0.0 {
+            steps.without_time_left += 1;
+        }
+    }
+
+    fn advance_frame(app: &mut App, millis: u64) {
+        app.world_mut()
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_millis(millis));
+        app.update();
+    }
+
+    #[test]
+    fn test_real_time_follows_frame_duration() {
+        let mut app = App::new();
+        app.insert_resource(Clock {
+            real_time: true,
+            ..Default::default()
+        })
+        .insert_resource(SimulationSpeed {
+            speed_factor: 0.5,
+            max_frame_advance: 20.0,
+        })
+        .init_resource::<Time>()
+        .init_resource::<Steps>()
+        .add_systems(Update, run_simulation_steps)
+        .add_systems(SimulationStep, (update_clock, count_steps).chain());
+
+        // the time step stays fixed, the frame runs as many of them as fit
+        advance_frame(&mut app, 16);
+        let clock = app.world().resource::<Clock>();
+        assert_eq!(clock.tau, 0.025);
+        assert!((clock.time + clock.time_to_simulate - 8.0).abs() < 1e-9);
+        assert!(clock.time_to_simulate <= clock.tau);
+        assert!(app.world().resource::<Steps>().count >= 319);
+
+        // a stalled frame is clamped
+        advance_frame(&mut app, 10_000);
+        let clock = app.world().resource::<Clock>();
+        assert!((clock.time + clock.time_to_simulate - 28.0).abs() < 1e-9);
+        assert!(app.world().resource::<Steps>().count >= 1119);
+        assert_eq!(app.world().resource::<Steps>().without_time_left, 0);
+
+        // back to the budgeted mode, a single step per frame
+        let mut clock = app.world_mut().resource_mut::<Clock>();
+        clock.real_time = false;
+        clock.time_to_simulate = 0.0;
+        let time = clock.time;
+        advance_frame(&mut app, 16);
+        assert_eq!(app.world().resource::<Clock>().time, time);
+
+        app.world_mut().resource_mut::<Clock>().time_to_simulate = 0.04;
+        for _ in 0..4 {
+            advance_frame(&mut app, 16);
+        }
+        assert!((app.world().resource::<Clock>().time - time - 0.05).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,224 @@
+use std::{
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+use bevy::prelude::{Component, Entity, Query, Res};
+use bevy_trait_query::One;
+use silicon_core::{Clock, Neuron};
+use tracing::warn;
+
+const MAGIC: &[u8; 4] = b"SITR";
+
+/// Streams the membrane potential of a neuron to a binary file on every simulation tick.
+/// Unlike the `ValueRecorder` nothing is deduplicated or dropped, which makes it suitable for
+/// detailed offline analysis.
+///
+/// The file starts with a header containing the magic bytes `SITR`, the neuron id as a `u64`,
+/// the time step and the start time as `f64`, followed by one `f32` per tick. All values are
+/// little endian. When a reset sets the clock back the ticks of the next trial are appended, the
+/// file keeps counting time from the start time.
+#[derive(Component)]
+pub struct BinaryTraceRecorder {
+    writer: BufWriter<File>,
+    last_time: f64,
+}
+
+impl BinaryTraceRecorder {
+    pub fn create(
+        path: impl AsRef<Path>,
+        neuron: Entity,
+        tau: f64,
+        start_time: f64,
+    ) -> io::Result<Self> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writer.write_all(MAGIC)?;
+        writer.write_all(&neuron.to_bits().to_le_bytes())?;
+        writer.write_all(&tau.to_le_bytes())?;
+        writer.write_all(&start_time.to_le_bytes())?;
+
+        Ok(BinaryTraceRecorder {
+            writer,
+            last_time: start_time,
+        })
+    }
+
+    pub fn push(&mut self, value: f64) -> io::Result<()> {
+        self.writer.write_all(&(value as f32).to_le_bytes())
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// Record the ticks after `time` again, for when the clock was set back.
+    pub(crate) fn rewind(&mut self, time: f64) {
+        self.last_time = time;
+    }
+}
+
+/// A membrane trace loaded from a file written by `BinaryTraceRecorder`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BinaryTrace {
+    pub neuron: Entity,
+    pub tau: f64,
+    pub start_time: f64,
+    pub values: Vec<f32>,
+}
+
+impl BinaryTrace {
+    /// Returns a time & value tuple for every recorded tick.
+    pub fn samples(&self) -> impl Iterator<Item = (f64, f32)> + '_ {
+        self.values
+            .iter()
+            .enumerate()
+            .map(|(i, value)| (self.start_time + (i + 1) as f64 * self.tau, *value))
+    }
+}
+
+pub fn load_binary_trace(path: impl AsRef<Path>) -> io::Result<BinaryTrace> {
+    let mut reader = BufReader::new(File::open(path)?);
+
+    let mut magic = [0; 4];
+    reader.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "not a binary trace file",
+        ));
+    }
+
+    let mut u64_bytes = [0; 8];
+    reader.read_exact(&mut u64_bytes)?;
+    let neuron = Entity::try_from_bits(u64::from_le_bytes(u64_bytes))
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "invalid neuron id"))?;
+    reader.read_exact(&mut u64_bytes)?;
+    let tau = f64::from_le_bytes(u64_bytes);
+    reader.read_exact(&mut u64_bytes)?;
+    let start_time = f64::from_le_bytes(u64_bytes);
+
+    let mut data = vec![];
+    reader.read_to_end(&mut data)?;
+    if data.len() % 4 != 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "truncated trace value",
+        ));
+    }
+
+    let values = data
+        .chunks_exact(4)
+        .map(|bytes| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .collect();
+
+    Ok(BinaryTrace {
+        neuron,
+        tau,
+        start_time,
+        values,
+    })
+}
+
+pub(crate) fn record_binary_trace(
+    mut recorders: Query<(Entity, One<&dyn Neuron>, &mut BinaryTraceRecorder)>,
+    clock: Res<Clock>,
+) {
+    for (entity, neuron, mut recorder) in recorders.iter_mut() {
+        if clock.time <= recorder.last_time {
+            continue;
+        }
+
+        recorder.last_time = clock.time;
+        if let Err(err) = recorder.push(neuron.get_membrane_potential()) {
+            warn!("Failed to write trace for {:?}: {}", entity, err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use neurons::leaky::LifNeuron;
+
+    use super::*;
+    use crate::{
+        current::CurrentSource, headless::HeadlessSimulation, reset::ResetNetworkEvent,
+        SimulationPlugin,
+    };
+
+    /// A LIF neuron driven by a constant current whose trace is written to `path`.
+    fn traced_simulation(path: &Path) -> (HeadlessSimulation, Entity) {
+        let mut simulation = HeadlessSimulation::new(SimulationPlugin::with_seed(1));
+        let neuron = simulation.add_neuron(LifNeuron::default());
+        let recorder = BinaryTraceRecorder::create(path, neuron, 0.025, 0.0).unwrap();
+        simulation
+            .world_mut()
+            .entity_mut(neuron)
+            .insert((CurrentSource::Constant { amplitude: 2.0 }, recorder));
+        (simulation, neuron)
+    }
+
+    fn load(simulation: &mut HeadlessSimulation, neuron: Entity, path: &Path) -> BinaryTrace {
+        simulation
+            .world_mut()
+            .get_mut::<BinaryTraceRecorder>(neuron)
+            .unwrap()
+            .flush()
+            .unwrap();
+        let trace = load_binary_trace(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        trace
+    }
+
+    #[test]
+    fn test_records_potential_after_update() {
+        let path = std::env::temp_dir().join("silicon_binary_trace_after_update.bin");
+        let (mut simulation, neuron) = traced_simulation(&path);
+        simulation.step();
+
+        // the first sample is the potential at the end of the first tick, not the resting potential
+        let trace = load(&mut simulation, neuron, &path);
+        let expected = -70.0 + 10.0 * 2.0 / 10.0 * 0.025;
+        assert_eq!(trace.values, [expected as f32]);
+    }
+
+    #[test]
+    fn test_reset_continues_trace() {
+        let path = std::env::temp_dir().join("silicon_binary_trace_reset.bin");
+        let (mut simulation, neuron) = traced_simulation(&path);
+        simulation.run_for(0.01);
+        simulation
+            .world_mut()
+            .send_event(ResetNetworkEvent::default());
+        simulation.run_for(0.01);
+
+        // the second trial is recorded even though the clock went back to zero
+        let trace = load(&mut simulation, neuron, &path);
+        assert_eq!(trace.values.len(), 800);
+        assert_eq!(trace.values[400..], trace.values[..400]);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let path = std::env::temp_dir().join("silicon_binary_trace_round_trip.bin");
+        let neuron = Entity::from_raw(42);
+        let values = (0..1000)
+            .map(|i| -70.0 + (i as f32 * 0.37).sin() * 20.0)
+            .collect::<Vec<f32>>();
+
+        let mut recorder = BinaryTraceRecorder::create(&path, neuron, 0.025, 1.5).unwrap();
+        for value in values.iter() {
+            recorder.push(*value as f64).unwrap();
+        }
+        recorder.flush().unwrap();
+        drop(recorder);
+
+        let trace = load_binary_trace(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(trace.neuron, neuron);
+        assert_eq!(trace.tau, 0.025);
+        assert_eq!(trace.start_time, 1.5);
+        assert_eq!(trace.values, values);
+    }
+}
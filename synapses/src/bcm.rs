@@ -0,0 +1,169 @@
+use bevy::{
+    prelude::{Component, Entity, Query, Res, Without},
+    reflect::Reflect,
+};
+use silicon_core::Clock;
+
+use crate::{FrozenPlasticity, Synapse, SynapseType};
+
+/// Synapse with the Bienenstock-Cooper-Munro learning rule
+/// `dw/dt = learning_rate * (phi(v_post) * v_pre - epsilon * w)` with `phi(v) = v * (v - theta_m)`.
+/// The activities are firing rate estimates in Hz, tracked from the spikes of both neurons. The
+/// modification threshold slides with the postsynaptic activity,
+/// `d_theta_m/dt = (v_post^2 - theta_m) / tau_theta`.
+#[derive(Debug, Component, Reflect)]
+pub struct BcmSynapse {
+    pub weight: f64,
+    pub delay: u32,
+    pub source: Entity,
+    pub target: Entity,
+    pub synapse_type: SynapseType,
+    /// sliding modification threshold
+    pub theta_m: f64,
+    /// time constant of the sliding threshold in ms
+    pub tau_theta: f64,
+    pub learning_rate: f64,
+    /// passive weight decay
+    pub epsilon: f64,
+    /// time constant of the activity estimates in ms
+    pub tau_activity: f64,
+    /// presynaptic firing rate estimate in Hz
+    pub pre_activity: f64,
+    /// postsynaptic firing rate estimate in Hz
+    pub post_activity: f64,
+}
+
+impl BcmSynapse {
+    pub fn new(source: Entity, target: Entity, weight: f64, synapse_type: SynapseType) -> Self {
+        BcmSynapse {
+            weight,
+            delay: 1,
+            source,
+            target,
+            synapse_type,
+            theta_m: 10.0,
+            tau_theta: 1000.0,
+            learning_rate: 1e-6,
+            epsilon: 0.0,
+            tau_activity: 100.0,
+            pre_activity: 0.0,
+            post_activity: 0.0,
+        }
+    }
+
+    pub fn register_pre_spike(&mut self) {
+        self.pre_activity += 1000.0 / self.tau_activity;
+    }
+
+    pub fn register_post_spike(&mut self) {
+        self.post_activity += 1000.0 / self.tau_activity;
+    }
+
+    /// The BCM nonlinearity, positive above the threshold and negative below it.
+    pub fn phi(&self, post_activity: f64) -> f64 {
+        post_activity * (post_activity - self.theta_m)
+    }
+
+    /// Apply the learning rule for a time step of `tau` ms.
+    pub fn update_weight(&mut self, tau: f64) {
+        let delta_w = self.phi(self.post_activity) * self.pre_activity - self.epsilon * self.weight;
+        self.weight += self.learning_rate * delta_w * tau;
+        self.theta_m += (self.post_activity.powi(2) - self.theta_m) / self.tau_theta * tau;
+
+        let decay = (-tau / self.tau_activity).exp();
+        self.pre_activity *= decay;
+        self.post_activity *= decay;
+    }
+}
+
+impl Synapse for BcmSynapse {
+    fn update(&mut self, _tau: f64) {}
+
+    fn get_weight(&self) -> f64 {
+        self.weight
+    }
+
+    fn set_weight(&mut self, weight: f64) {
+        self.weight = weight;
+    }
+
+    fn get_presynaptic(&self) -> Entity {
+        self.source
+    }
+
+    fn get_postsynaptic(&self) -> Entity {
+        self.target
+    }
+
+    fn get_type(&self) -> SynapseType {
+        self.synapse_type
+    }
+
+    fn get_delay(&self) -> u32 {
+        self.delay
+    }
+
+    /// The activities are reset, the sliding threshold `theta_m` is learned and kept.
+    fn reset_state(&mut self) {
+        self.pre_activity = 0.0;
+        self.post_activity = 0.0;
+    }
+}
+
+pub(crate) fn update_bcm_synapses(
+    mut synapses: Query<&mut BcmSynapse, Without<FrozenPlasticity>>,
+    clock: Res<Clock>,
+) {
+    if clock.time_to_simulate <= 0.0 {
+        return;
+    }
+
+    for mut synapse in synapses.iter_mut() {
+        synapse.update_weight(clock.tau);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn synapse(pre_activity: f64, post_activity: f64) -> BcmSynapse {
+        BcmSynapse {
+            pre_activity,
+            post_activity,
+            theta_m: 10.0,
+            ..BcmSynapse::new(
+                Entity::from_raw(0),
+                Entity::from_raw(1),
+                0.5,
+                SynapseType::Excitatory,
+            )
+        }
+    }
+
+    #[test]
+    fn test_potentiation_above_threshold() {
+        let mut synapse = synapse(10.0, 20.0);
+        synapse.update_weight(0.025);
+
+        assert!(synapse.weight > 0.5);
+        // the threshold slides towards the squared postsynaptic activity
+        assert!(synapse.theta_m > 10.0);
+    }
+
+    #[test]
+    fn test_depression_below_threshold() {
+        let mut synapse = synapse(10.0, 5.0);
+        synapse.update_weight(0.025);
+
+        assert!(synapse.weight < 0.5);
+    }
+
+    #[test]
+    fn test_no_change_without_presynaptic_activity() {
+        let mut synapse = synapse(0.0, 20.0);
+        synapse.update_weight(0.025);
+
+        assert_eq!(synapse.weight, 0.5);
+    }
+}
@@ -0,0 +1,376 @@
+use bevy::{
+    prelude::{Component, Entity, Query, Res},
+    reflect::Reflect,
+};
+use bevy_trait_query::One;
+use silicon_core::{Clock, Neuron};
+
+use crate::{Synapse, SynapseType, TransmissionMode};
+
+/// Current based synapse with an exponentially decaying synaptic current. Every presynaptic spike
+/// increases the current by `weight`, in between spikes it decays with `tau_syn`. The current is
+/// injected into the postsynaptic neuron every tick, so spikes that arrive close together sum up
+/// instead of each causing an instantaneous jump of the membrane potential.
+///
+/// The current starts on the tick after the presynaptic spike, the synapse has no delay of its
+/// own.
+#[derive(Component, Debug, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExponentialSynapse {
+    pub weight: f64,
+    pub source: Entity,
+    pub target: Entity,
+    pub synapse_type: SynapseType,
+    /// decay time constant of the synaptic current in ms
+    pub tau_syn: f64,
+    /// the synaptic current, inhibitory synapses inject it with a negative sign
+    pub current: f64,
+}
+
+impl ExponentialSynapse {
+    pub fn new(
+        source: Entity,
+        target: Entity,
+        weight: f64,
+        synapse_type: SynapseType,
+        tau_syn: f64,
+    ) -> Self {
+        ExponentialSynapse {
+            weight,
+            source,
+            target,
+            synapse_type,
+            tau_syn,
+            current: 0.0,
+        }
+    }
+
+    /// The current flowing into the postsynaptic neuron.
+    pub fn postsynaptic_current(&self) -> f64 {
+        match self.synapse_type {
+            SynapseType::Inhibitory => -self.current,
+            _ => self.current,
+        }
+    }
+}
+
+impl Synapse for ExponentialSynapse {
+    fn update(&mut self, tau: f64) {
+        self.current *= (-tau / self.tau_syn).exp();
+    }
+
+    fn get_weight(&self) -> f64 {
+        self.weight
+    }
+
+    fn set_weight(&mut self, weight: f64) {
+        self.weight = weight;
+    }
+
+    fn get_presynaptic(&self) -> Entity {
+        self.source
+    }
+
+    fn get_postsynaptic(&self) -> Entity {
+        self.target
+    }
+
+    fn get_type(&self) -> SynapseType {
+        self.synapse_type
+    }
+
+    fn get_delay(&self) -> u32 {
+        0
+    }
+
+    fn on_pre_spike(&mut self, _time: f64) {
+        self.current += self.weight;
+    }
+
+    fn reset_state(&mut self) {
+        self.current = 0.0;
+    }
+
+    /// The current is injected by `inject_synaptic_currents`.
+    fn transmission_mode(&self) -> TransmissionMode {
+        TransmissionMode::Continuous
+    }
+}
+
+/// Injects the current of every `ExponentialSynapse` into its postsynaptic neuron.
+pub fn inject_synaptic_currents(
+    synapses: Query<&ExponentialSynapse>,
+    mut neurons: Query<One<&mut dyn Neuron>>,
+    clock: Res<Clock>,
+) {
+    if clock.time_to_simulate <= 0.0 {
+        return;
+    }
+
+    for synapse in synapses.iter() {
+        if synapse.current == 0.0 {
+            continue;
+        }
+
+        if let Ok(mut neuron) = neurons.get_mut(synapse.target) {
+            neuron.insert_current(synapse.postsynaptic_current());
+        }
+    }
+}
+
+/// Conductance based synapse with an exponentially decaying conductance. Every presynaptic spike
+/// opens the conductance by `weight`, in between spikes it decays with `tau_syn`. Every tick the
+/// postsynaptic neuron receives `g * (reversal_potential - V)`, so the postsynaptic potential
+/// rises and falls smoothly instead of jumping on arrival of the spike.
+///
+/// Like `ExponentialSynapse` the conductance opens on the tick after the presynaptic spike.
+#[derive(Component, Debug, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ConductanceExpSynapse {
+    pub weight: f64,
+    pub source: Entity,
+    pub target: Entity,
+    pub synapse_type: SynapseType,
+    /// decay time constant of the conductance in ms
+    pub tau_syn: f64,
+    /// reversal potential in mV the synaptic current drives the membrane towards
+    pub reversal_potential: f64,
+    /// the synaptic conductance
+    pub conductance: f64,
+}
+
+impl ConductanceExpSynapse {
+    /// A `ConductanceExpSynapse` with the reversal potential of `synapse_type`.
+    pub fn new(
+        source: Entity,
+        target: Entity,
+        weight: f64,
+        synapse_type: SynapseType,
+        tau_syn: f64,
+    ) -> Self {
+        ConductanceExpSynapse {
+            weight,
+            source,
+            target,
+            synapse_type,
+            tau_syn,
+            reversal_potential: synapse_type.reversal_potential().unwrap_or_default(),
+            conductance: 0.0,
+        }
+    }
+}
+
+impl Synapse for ConductanceExpSynapse {
+    fn update(&mut self, tau: f64) {
+        self.conductance -= self.conductance * tau / self.tau_syn;
+    }
+
+    fn get_weight(&self) -> f64 {
+        self.weight
+    }
+
+    fn set_weight(&mut self, weight: f64) {
+        self.weight = weight;
+    }
+
+    fn get_presynaptic(&self) -> Entity {
+        self.source
+    }
+
+    fn get_postsynaptic(&self) -> Entity {
+        self.target
+    }
+
+    fn get_type(&self) -> SynapseType {
+        self.synapse_type
+    }
+
+    fn get_delay(&self) -> u32 {
+        0
+    }
+
+    fn on_pre_spike(&mut self, _time: f64) {
+        self.conductance += self.weight;
+    }
+
+    fn reset_state(&mut self) {
+        self.conductance = 0.0;
+    }
+
+    /// The conductance is opened by `apply_synaptic_conductances`.
+    fn transmission_mode(&self) -> TransmissionMode {
+        TransmissionMode::Continuous
+    }
+}
+
+/// Opens the conductance of every `ConductanceExpSynapse` on its postsynaptic neuron for the next
+/// update.
+pub fn apply_synaptic_conductances(
+    synapses: Query<&ConductanceExpSynapse>,
+    mut neurons: Query<One<&mut dyn Neuron>>,
+    clock: Res<Clock>,
+) {
+    if clock.time_to_simulate <= 0.0 {
+        return;
+    }
+
+    for synapse in synapses.iter() {
+        if synapse.conductance == 0.0 {
+            continue;
+        }
+
+        if let Ok(mut neuron) = neurons.get_mut(synapse.target) {
+            neuron.add_conductance(synapse.conductance, synapse.reversal_potential);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        app::{App, Update},
+        prelude::IntoSystemConfigs,
+    };
+    use bevy_trait_query::RegisterExt;
+
+    use super::*;
+    use crate::test_utils::TestNeuron;
+
+    const TAU: f64 = 0.025;
+
+    fn update(mut neurons: Query<&mut TestNeuron>, mut synapses: Query<&mut ExponentialSynapse>) {
+        for mut neuron in neurons.iter_mut() {
+            neuron.update(TAU);
+        }
+        for mut synapse in synapses.iter_mut() {
+            synapse.update(TAU);
+        }
+    }
+
+    /// The peak synaptic current for presynaptic spikes at the given ticks.
+    fn peak_current(spike_ticks: &[usize]) -> f64 {
+        let mut synapse = ExponentialSynapse::new(
+            Entity::PLACEHOLDER,
+            Entity::PLACEHOLDER,
+            1.0,
+            SynapseType::Excitatory,
+            5.0,
+        );
+
+        (0..1000)
+            .map(|tick| {
+                if spike_ticks.contains(&tick) {
+                    synapse.on_pre_spike(0.0);
+                }
+                let current = synapse.current;
+                synapse.update(TAU);
+                current
+            })
+            .fold(0.0, f64::max)
+    }
+
+    #[test]
+    fn test_spikes_summate() {
+        let single = peak_current(&[0]);
+        // 2ms apart
+        let double = peak_current(&[0, 80]);
+
+        assert_eq!(single, 1.0);
+        assert!((double - (1.0 + (-2.0f64 / 5.0).exp())).abs() < 1e-9);
+        assert!(double > 1.5 * single);
+    }
+
+    fn clock() -> Clock {
+        Clock {
+            time_to_simulate: f64::MAX,
+            tau: TAU,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_injected_charge() {
+        let mut app = App::new();
+        app.insert_resource(clock())
+            .register_component_as::<dyn Neuron, TestNeuron>()
+            .add_systems(Update, (inject_synaptic_currents, update).chain());
+
+        let neuron = app
+            .world_mut()
+            // a perfect integrator, the membrane potential is the injected charge
+            .spawn(TestNeuron::at(0.0))
+            .id();
+        let mut synapse =
+            ExponentialSynapse::new(neuron, neuron, 2.0, SynapseType::Excitatory, 5.0);
+        synapse.on_pre_spike(0.0);
+        app.world_mut().spawn(synapse);
+
+        // 20 time constants
+        for _ in 0..4000 {
+            app.update();
+        }
+
+        let charge = app.world().get::<TestNeuron>(neuron).unwrap().v;
+        // forward summation of the decaying current overestimates the integral by about tau / 2
+        assert!((charge - 2.0 * 5.0).abs() < 0.05, "charge {charge}");
+
+        // nothing is injected while the simulation is paused
+        app.world_mut().resource_mut::<Clock>().time_to_simulate = 0.0;
+        let mut synapses = app.world_mut().query::<&mut ExponentialSynapse>();
+        synapses.single_mut(app.world_mut()).on_pre_spike(0.0);
+        app.update();
+        assert_eq!(app.world().get::<TestNeuron>(neuron).unwrap().v, charge);
+    }
+
+    fn update_leaky(
+        mut neurons: Query<&mut TestNeuron>,
+        mut synapses: Query<&mut ConductanceExpSynapse>,
+    ) {
+        for mut neuron in neurons.iter_mut() {
+            neuron.update(TAU);
+        }
+        for mut synapse in synapses.iter_mut() {
+            synapse.update(TAU);
+        }
+    }
+
+    #[test]
+    fn test_single_spike_psp() {
+        let mut app = App::new();
+        app.insert_resource(clock())
+            .register_component_as::<dyn Neuron, TestNeuron>()
+            .add_systems(Update, (apply_synaptic_conductances, update_leaky).chain());
+
+        let neuron = app
+            .world_mut()
+            // the membrane potential is the postsynaptic potential
+            .spawn(TestNeuron::leaky(10.0))
+            .id();
+        let mut synapse =
+            ConductanceExpSynapse::new(neuron, neuron, 0.01, SynapseType::Excitatory, 5.0);
+        synapse.reversal_potential = 70.0;
+        synapse.on_pre_spike(0.0);
+        app.world_mut().spawn(synapse);
+
+        // 100ms
+        let psp = (0..4000)
+            .map(|_| {
+                app.update();
+                app.world().get::<TestNeuron>(neuron).unwrap().v
+            })
+            .collect::<Vec<_>>();
+
+        let (peak_tick, peak) = psp
+            .iter()
+            .copied()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .unwrap();
+        // the potential rises over a few ms instead of jumping
+        assert!(psp[0] < peak / 20.0);
+        assert!(peak_tick as f64 * TAU > 2.0);
+        assert!(psp[..peak_tick].windows(2).all(|pair| pair[1] > pair[0]));
+        assert!(psp[peak_tick..].windows(2).all(|pair| pair[1] <= pair[0]));
+        assert!(*psp.last().unwrap() < peak / 100.0);
+    }
+}
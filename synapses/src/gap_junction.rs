@@ -0,0 +1,168 @@
+use bevy::{
+    app::{App, Plugin},
+    prelude::{Component, Entity, IntoSystemConfigs, Query, Res},
+    reflect::Reflect,
+};
+use bevy_trait_query::{One, RegisterExt};
+use silicon_core::{Clock, Neuron, SimulationStep};
+
+use crate::{Synapse, SynapseType, SynapticInputSet};
+
+/// Electrical synapse that continuously passes a current proportional to the voltage difference
+/// between both neurons, `I = weight * (V_pre - V_post)`. The coupling is symmetric, the
+/// presynaptic neuron receives the opposite current.
+#[derive(Component, Debug, Reflect)]
+pub struct GapJunctionSynapse {
+    pub weight: f64,
+    pub source: Entity,
+    pub target: Entity,
+}
+
+impl GapJunctionSynapse {
+    /// The current flowing from the presynaptic into the postsynaptic neuron.
+    pub fn current(&self, v_pre: f64, v_post: f64) -> f64 {
+        self.weight * (v_pre - v_post)
+    }
+}
+
+impl Synapse for GapJunctionSynapse {
+    fn update(&mut self, _tau: f64) {}
+
+    fn get_weight(&self) -> f64 {
+        self.weight
+    }
+
+    fn set_weight(&mut self, weight: f64) {
+        self.weight = weight;
+    }
+
+    fn get_presynaptic(&self) -> Entity {
+        self.source
+    }
+
+    fn get_postsynaptic(&self) -> Entity {
+        self.target
+    }
+
+    fn get_type(&self) -> SynapseType {
+        SynapseType::Electrical
+    }
+
+    fn get_delay(&self) -> u32 {
+        0
+    }
+}
+
+/// Applies the gap junction currents to the connected neurons every tick.
+pub struct GapJunctionPlugin;
+
+impl Plugin for GapJunctionPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_component_as::<dyn Synapse, GapJunctionSynapse>()
+            .register_type::<GapJunctionSynapse>()
+            .add_systems(
+                SimulationStep,
+                couple_gap_junctions.in_set(SynapticInputSet),
+            );
+    }
+}
+
+pub fn couple_gap_junctions(
+    junctions: Query<&GapJunctionSynapse>,
+    mut neurons: Query<One<&mut dyn Neuron>>,
+    clock: Res<Clock>,
+) {
+    if clock.time_to_simulate <= 0.0 {
+        return;
+    }
+
+    // all currents are computed before any is applied so the result doesn't depend on the
+    // iteration order of the junctions
+    let currents = junctions
+        .iter()
+        .filter_map(|junction| {
+            let [pre, post] = neurons.get_many([junction.source, junction.target]).ok()?;
+            let current =
+                junction.current(pre.get_membrane_potential(), post.get_membrane_potential());
+
+            Some((junction.source, junction.target, current))
+        })
+        .collect::<Vec<_>>();
+
+    for (source, target, current) in currents {
+        if let Ok([mut pre, mut post]) = neurons.get_many_mut([source, target]) {
+            pre.insert_current(-current);
+            post.insert_current(current);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{app::Update, prelude::IntoSystemConfigs};
+
+    use super::*;
+    use crate::test_utils::TestNeuron;
+
+    fn update_neurons(mut neurons: Query<&mut TestNeuron>) {
+        for mut neuron in neurons.iter_mut() {
+            neuron.update(1.0);
+        }
+    }
+
+    /// Couples a neuron at -70 mV with one at -50 mV for 100 ticks and returns both potentials. The
+    /// neurons are perfect integrators so only the gap junction moves their membrane potentials.
+    fn coupled_potentials(weight: f64, time_to_simulate: f64) -> (f64, f64) {
+        let mut app = App::new();
+        app.insert_resource(Clock {
+            time_to_simulate,
+            tau: 1.0,
+            ..Default::default()
+        })
+        .register_component_as::<dyn Neuron, TestNeuron>()
+        .add_systems(
+            Update,
+            (
+                couple_gap_junctions,
+                update_neurons.after(couple_gap_junctions),
+            ),
+        );
+
+        let pre = app.world_mut().spawn(TestNeuron::at(-70.0)).id();
+        let post = app.world_mut().spawn(TestNeuron::at(-50.0)).id();
+        app.world_mut().spawn(GapJunctionSynapse {
+            weight,
+            source: pre,
+            target: post,
+        });
+
+        for _ in 0..100 {
+            app.update();
+        }
+
+        let v_pre = app.world().get::<TestNeuron>(pre).unwrap().v;
+        let v_post = app.world().get::<TestNeuron>(post).unwrap().v;
+        (v_pre, v_post)
+    }
+
+    #[test]
+    fn test_neurons_synchronize() {
+        let (v_pre, v_post) = coupled_potentials(0.1, f64::MAX);
+        assert!((v_pre - v_post).abs() < 1e-3);
+        assert!((v_pre - -60.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_weak_coupling_leaves_neurons_nearly_independent() {
+        let (v_pre, v_post) = coupled_potentials(1e-4, f64::MAX);
+        // the difference shrinks by a factor of 1 - 2 * weight per tick
+        assert!((v_post - v_pre - 20.0 * 0.9998f64.powi(100)).abs() < 1e-9);
+        assert!((v_pre - -70.0).abs() < 0.5);
+        assert!((v_post - -50.0).abs() < 0.5);
+    }
+
+    #[test]
+    fn test_paused_simulation_passes_no_current() {
+        assert_eq!(coupled_potentials(0.1, 0.0), (-70.0, -50.0));
+    }
+}
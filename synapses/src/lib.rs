@@ -1,15 +1,42 @@
+use bcm::{update_bcm_synapses, BcmSynapse};
 use bevy::{
-    app::{App, Plugin, Update},
-    prelude::{Component, Entity, Event, Events, Query, Res, ResMut, Resource},
+    app::{App, Plugin},
+    prelude::{
+        Component, Entity, Event, Events, IntoSystemConfigs, Query, Res, ResMut, Resource,
+        SystemSet,
+    },
     reflect::Reflect,
 };
 use bevy_trait_query::{One, RegisterExt};
-use silicon_core::Clock;
+use exponential::{
+    apply_synaptic_conductances, inject_synaptic_currents, ConductanceExpSynapse,
+    ExponentialSynapse,
+};
+use gap_junction::GapJunctionPlugin;
+#[cfg(feature = "serde")]
+use silicon_core::checkpoint::CheckpointExt;
+use silicon_core::{
+    Clock, SimulationStep, EXCITATORY_REVERSAL_POTENTIAL, INHIBITORY_REVERSAL_POTENTIAL,
+};
 use simple::SimpleSynapse;
 use stdp::StdpSynapse;
+use stp::StpSynapse;
+use triplet_stdp::TripletStdpSynapse;
 
+pub mod bcm;
+pub mod exponential;
+pub mod gap_junction;
 pub mod simple;
 pub mod stdp;
+pub mod stp;
+#[cfg(test)]
+mod test_utils;
+pub mod triplet_stdp;
+
+/// The systems that pass the input of continuously acting synapses to their neurons every tick.
+/// The simulator runs them before the neurons are updated.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SynapticInputSet;
 
 /// A component that allows a neuron to receive synapses.
 #[derive(Component, Debug, Reflect)]
@@ -26,13 +53,87 @@ pub trait Synapse {
     fn get_postsynaptic(&self) -> Entity;
 
     fn get_type(&self) -> SynapseType;
+
+    /// The transmission delay in simulation ticks between a presynaptic spike and its arrival at
+    /// the postsynaptic neuron.
+    fn get_delay(&self) -> u32;
+
+    /// Called once for every presynaptic spike, returns the weight that is transmitted to the
+    /// postsynaptic neuron. Synapses with short-term dynamics transmit a different amount than
+    /// their static weight.
+    fn effective_weight_on_spike(&mut self) -> f64 {
+        self.get_weight()
+    }
+
+    /// The `(min, max)` range the learning rule of the synapse keeps its weight in, `None` for
+    /// synapses with unbounded weights.
+    fn weight_bounds(&self) -> Option<(f64, f64)> {
+        None
+    }
+
+    /// Called once for every spike of the presynaptic neuron at `time` ms, before
+    /// `effective_weight_on_spike` for synapses that transmit `OnSpike`.
+    fn on_pre_spike(&mut self, _time: f64) {}
+
+    /// Called once for every spike of the postsynaptic neuron at `time` ms.
+    fn on_post_spike(&mut self, _time: f64) {}
+
+    /// Return the short-term state of the synapse, its traces, resources and currents, to the
+    /// values it starts with. The weight and everything else the synapse learned are kept.
+    fn reset_state(&mut self) {}
+
+    /// How the input of the synapse reaches the postsynaptic neuron.
+    fn transmission_mode(&self) -> TransmissionMode {
+        TransmissionMode::OnSpike
+    }
+}
+
+/// How a synapse delivers its input to the postsynaptic neuron.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default, Reflect)]
+pub enum TransmissionMode {
+    /// The simulator opens a conductance of `effective_weight_on_spike` on the postsynaptic
+    /// neuron once the delay of a presynaptic spike has passed.
+    #[default]
+    OnSpike,
+    /// The synapse drives the postsynaptic neuron itself every tick, a presynaptic spike only
+    /// reaches it through `on_pre_spike`.
+    Continuous,
+}
+
+/// Marks a synapse whose weight the learning rules leave alone, so it can be tuned by hand. Spikes
+/// still pass through it.
+#[derive(Component, Debug, Default, Reflect)]
+pub struct FrozenPlasticity;
+
+/// Selects the compartment of the postsynaptic neuron a synapse delivers its input to.
+/// Synapses without this component target the soma.
+#[derive(Component, Debug, PartialEq, Eq, Copy, Clone, Default, Reflect)]
+pub enum CompartmentTarget {
+    #[default]
+    Soma,
+    Dendrite,
 }
 
 #[derive(Debug, PartialEq, Copy, Clone, Default, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SynapseType {
     #[default]
     Excitatory,
     Inhibitory,
+    /// Gap junctions, these pass current continuously instead of on presynaptic spikes.
+    Electrical,
+}
+
+impl SynapseType {
+    /// The reversal potential in mV used when the synapse opens a conductance on its target.
+    /// Electrical synapses don't open a conductance and have no reversal potential.
+    pub fn reversal_potential(&self) -> Option<f64> {
+        match self {
+            SynapseType::Excitatory => Some(EXCITATORY_REVERSAL_POTENTIAL),
+            SynapseType::Inhibitory => Some(INHIBITORY_REVERSAL_POTENTIAL),
+            SynapseType::Electrical => None,
+        }
+    }
 }
 
 /// The primary purpose of this event is to allow for reward modulated STDP. By deferring the
@@ -49,7 +150,8 @@ pub struct DeferredStdpEvent {
 
 /// A resource that configures the decay of synapses.
 /// Add this resource to the App to enable synapse decay.
-/// substracts the amount from the weight of all synapses at the interval.
+/// substracts the amount from the weight of all synapses at the interval, down to the lower bound
+/// of the synapse or 0 for synapses without bounds.
 #[derive(Debug, Clone, Reflect, Resource)]
 pub struct SynapseDecay {
     pub interval: f64,
@@ -67,8 +169,11 @@ fn decay_synapses(
         if time >= decay.next_decay {
             decay.next_decay = time + decay.interval;
             for mut synapse in synapses.iter_mut() {
+                let min = synapse.weight_bounds().map_or(0.0, |(min, _)| min);
                 let weight = synapse.get_weight();
-                synapse.set_weight(weight - decay.amount);
+                if weight > min {
+                    synapse.set_weight((weight - decay.amount).max(min));
+                }
             }
         }
     }
@@ -80,9 +185,34 @@ impl Plugin for SynapsePlugin {
     fn build(&self, app: &mut App) {
         app.register_component_as::<dyn Synapse, SimpleSynapse>()
             .register_component_as::<dyn Synapse, StdpSynapse>()
+            .register_component_as::<dyn Synapse, StpSynapse>()
+            .register_component_as::<dyn Synapse, BcmSynapse>()
+            .register_component_as::<dyn Synapse, TripletStdpSynapse>()
+            .register_component_as::<dyn Synapse, ExponentialSynapse>()
+            .register_component_as::<dyn Synapse, ConductanceExpSynapse>()
             .register_type::<SimpleSynapse>()
             .register_type::<StdpSynapse>()
+            .register_type::<StpSynapse>()
+            .register_type::<BcmSynapse>()
+            .register_type::<TripletStdpSynapse>()
+            .register_type::<ExponentialSynapse>()
+            .register_type::<ConductanceExpSynapse>()
+            .register_type::<CompartmentTarget>()
+            .register_type::<FrozenPlasticity>()
+            .add_plugins(GapJunctionPlugin)
             .init_resource::<Events<DeferredStdpEvent>>()
-            .add_systems(Update, decay_synapses);
+            .add_systems(
+                SimulationStep,
+                (
+                    decay_synapses,
+                    update_bcm_synapses,
+                    (inject_synaptic_currents, apply_synaptic_conductances)
+                        .in_set(SynapticInputSet),
+                ),
+            );
+
+        #[cfg(feature = "serde")]
+        app.register_checkpoint::<SimpleSynapse>()
+            .register_checkpoint::<StdpSynapse>();
     }
 }
@@ -6,6 +6,7 @@ use bevy::{
 use crate::{Synapse, SynapseType};
 
 #[derive(Component, Debug, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SimpleSynapse {
     pub weight: f64,
     pub delay: u32,
@@ -36,4 +37,8 @@ impl Synapse for SimpleSynapse {
     fn get_type(&self) -> SynapseType {
         self.synapse_type
     }
+
+    fn get_delay(&self) -> u32 {
+        self.delay
+    }
 }
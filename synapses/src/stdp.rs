@@ -14,6 +14,7 @@ pub struct StdpSettings {
 }
 
 #[derive(Debug, Component, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct StdpSynapse {
     pub weight: f64,
     pub delay: u32,
@@ -22,21 +23,76 @@ pub struct StdpSynapse {
     pub synapse_type: SynapseType,
     pub stdp_params: StdpParams,
     pub stdp_state: StdpState,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub rule: StdpRule,
+}
+
+/// The learning rule of an `StdpSynapse`.
+#[derive(Debug, Clone, Default, Reflect, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StdpRule {
+    /// pre before post potentiates and post before pre depresses, the weight changes are deferred
+    /// until the reward is known
+    #[default]
+    Asymmetric,
+    /// the symmetric rule for inhibitory synapses from Vogels et al. (2011)
+    Inhibitory(InhibitoryStdp),
+}
+
+/// Inhibitory STDP from Vogels et al. (2011). Near coincident pre and post spikes potentiate the
+/// inhibition in either order, every presynaptic spike depresses it by `learning_rate * alpha`.
+/// On average the weight grows while the postsynaptic neuron fires faster than `target_rate` and
+/// shrinks while it fires slower, which balances the excitation a neuron receives.
+#[derive(Debug, Clone, Reflect, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InhibitoryStdp {
+    /// eta, scales every weight change
+    pub learning_rate: f64,
+    /// rho_0, the postsynaptic firing rate in Hz the inhibition steers toward
+    pub target_rate: f64,
+    /// decay time constant in ms of both traces
+    pub tau: f64,
+    pub pre_trace: f64,
+    pub post_trace: f64,
+}
+
+impl InhibitoryStdp {
+    pub fn new(learning_rate: f64, target_rate: f64) -> Self {
+        InhibitoryStdp {
+            learning_rate,
+            target_rate,
+            tau: 20.0,
+            pre_trace: 0.0,
+            post_trace: 0.0,
+        }
+    }
+
+    /// alpha = 2 * rho_0 * tau, with the rate converted to spikes per ms.
+    pub fn alpha(&self) -> f64 {
+        2.0 * self.target_rate / 1000.0 * self.tau
+    }
 }
 
 #[derive(Debug, Clone, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct StdpState {
     pub a: f64,
     pub spike_type: StdpSpikeType,
+    /// the sum of the recent weight changes of the asymmetric rule, decaying with
+    /// `tau_eligibility`. Dopamine turns it into an actual weight change
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub eligibility: f64,
 }
 
 #[derive(Debug, Clone, Reflect, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum StdpSpikeType {
     PreSpike,
     PostSpike,
 }
 
 #[derive(Debug, Clone, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct StdpParams {
     /// the maximum value of a positive weight change
     pub a_plus: f64,
@@ -50,35 +106,84 @@ pub struct StdpParams {
     pub w_max: f64,
     /// the minimum value of the weight
     pub w_min: f64,
+    /// scale potentiation by `w_max - w` and depression by `w - w_min`, so weights approach the
+    /// bounds ever more slowly instead of piling up against them
+    pub soft_bound: bool,
+    /// the decay time constant in ms of the eligibility trace
+    #[cfg_attr(feature = "serde", serde(default = "default_tau_eligibility"))]
+    pub tau_eligibility: f64,
+}
+
+#[cfg(feature = "serde")]
+fn default_tau_eligibility() -> f64 {
+    1000.0
 }
 
 impl StdpSynapse {
+    /// Returns the weight change to apply once the reward is known, or `None` when there is none.
+    /// The change is also added to the eligibility trace. The inhibitory rule isn't modulated by reward, it applies its weight changes directly and
+    /// always returns `None`.
     pub fn register_pre_spike(&mut self) -> Option<f64> {
+        if let StdpRule::Inhibitory(rule) = &mut self.rule {
+            let delta_w = rule.learning_rate * (rule.post_trace - rule.alpha());
+            rule.pre_trace += 1.0;
+            self.apply(delta_w);
+            return None;
+        }
+
         let mut delta_w: Option<f64> = None;
 
         if self.stdp_state.a.abs() > f64::EPSILON
             && self.stdp_state.spike_type == StdpSpikeType::PostSpike
         {
-            delta_w = Some(self.stdp_state.a);
+            delta_w = Some(self.weight_dependent(self.stdp_state.a));
         }
 
+        self.stdp_state.eligibility += delta_w.unwrap_or(0.0);
         self.stdp_state.spike_type = StdpSpikeType::PreSpike;
         self.stdp_state.a = self.stdp_params.a_plus;
         delta_w
     }
 
+    /// See `register_pre_spike`.
     pub fn register_post_spike(&mut self) -> Option<f64> {
+        if let StdpRule::Inhibitory(rule) = &mut self.rule {
+            let delta_w = rule.learning_rate * rule.pre_trace;
+            rule.post_trace += 1.0;
+            self.apply(delta_w);
+            return None;
+        }
+
         let mut delta_w = None;
         if self.stdp_state.a.abs() > f64::EPSILON
             && self.stdp_state.spike_type == StdpSpikeType::PreSpike
         {
-            delta_w = Some(self.stdp_state.a);
+            delta_w = Some(self.weight_dependent(self.stdp_state.a));
         }
 
+        self.stdp_state.eligibility += delta_w.unwrap_or(0.0);
         self.stdp_state.spike_type = StdpSpikeType::PostSpike;
         self.stdp_state.a = self.stdp_params.a_minus;
         delta_w
     }
+
+    fn apply(&mut self, delta_w: f64) {
+        self.weight = (self.weight + self.weight_dependent(delta_w))
+            .clamp(self.stdp_params.w_min, self.stdp_params.w_max);
+    }
+
+    /// Applies the soft bounds to a weight change when they are enabled.
+    fn weight_dependent(&self, delta_w: f64) -> f64 {
+        if !self.stdp_params.soft_bound {
+            return delta_w;
+        }
+
+        if delta_w > 0.0 {
+            delta_w * (self.stdp_params.w_max - self.weight)
+        } else {
+            delta_w * (self.weight - self.stdp_params.w_min)
+        }
+    }
 }
 
 impl Synapse for StdpSynapse {
@@ -89,6 +194,12 @@ impl Synapse for StdpSynapse {
         };
 
         self.stdp_state.a += delta_a;
+        self.stdp_state.eligibility *= (-tau / self.stdp_params.tau_eligibility).exp();
+
+        if let StdpRule::Inhibitory(rule) = &mut self.rule {
+            rule.pre_trace *= (-tau / rule.tau).exp();
+            rule.post_trace *= (-tau / rule.tau).exp();
+        }
     }
 
     fn get_weight(&self) -> f64 {
@@ -110,4 +221,214 @@ impl Synapse for StdpSynapse {
     fn get_type(&self) -> SynapseType {
         self.synapse_type
     }
+
+    fn get_delay(&self) -> u32 {
+        self.delay
+    }
+
+    fn weight_bounds(&self) -> Option<(f64, f64)> {
+        Some((self.stdp_params.w_min, self.stdp_params.w_max))
+    }
+
+    fn reset_state(&mut self) {
+        self.stdp_state.a = 0.0;
+        self.stdp_state.eligibility = 0.0;
+        if let StdpRule::Inhibitory(rule) = &mut self.rule {
+            rule.pre_trace = 0.0;
+            rule.post_trace = 0.0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Repeats pre-post pairings, each followed by a post-pre pairing, and applies the weight
+    /// changes the same way the reward modulated STDP does. Returns the final weight.
+    fn pairing_protocol(soft_bound: bool, weight: f64) -> f64 {
+        let mut synapse = StdpSynapse {
+            weight,
+            delay: 1,
+            source: Entity::from_raw(0),
+            target: Entity::from_raw(1),
+            synapse_type: SynapseType::Excitatory,
+            stdp_params: StdpParams {
+                a_plus: 0.02,
+                a_minus: -0.01,
+                tau_plus: 0.2,
+                tau_minus: 0.2,
+                w_max: 1.0,
+                w_min: 0.0,
+                soft_bound,
+                tau_eligibility: 1000.0,
+            },
+            stdp_state: StdpState {
+                a: 0.0,
+                spike_type: StdpSpikeType::PreSpike,
+                eligibility: 0.0,
+            },
+            rule: StdpRule::Asymmetric,
+        };
+
+        fn apply(synapse: &mut StdpSynapse, delta_w: Option<f64>) {
+            if let Some(delta_w) = delta_w {
+                synapse.weight = (synapse.weight + delta_w)
+                    .clamp(synapse.stdp_params.w_min, synapse.stdp_params.w_max);
+            }
+        }
+
+        for _ in 0..2000 {
+            let delta_w = synapse.register_pre_spike();
+            apply(&mut synapse, delta_w);
+            synapse.update(0.025);
+            let delta_w = synapse.register_post_spike();
+            apply(&mut synapse, delta_w);
+            synapse.update(0.025);
+        }
+
+        synapse.weight
+    }
+
+    #[test]
+    fn test_hard_bound_saturates() {
+        assert_eq!(pairing_protocol(false, 0.5), 1.0);
+    }
+
+    #[test]
+    fn test_soft_bound_settles_at_equilibrium() {
+        // potentiation 0.02 * (1 - w) balances depression 0.01 * w at w = 2/3
+        let from_below = pairing_protocol(true, 0.1);
+        let from_above = pairing_protocol(true, 0.9);
+
+        assert!(
+            (from_below - 2.0 / 3.0).abs() < 0.01,
+            "weight was {from_below}"
+        );
+        assert!(
+            (from_above - 2.0 / 3.0).abs() < 0.01,
+            "weight was {from_above}"
+        );
+    }
+
+    /// A leaky integrate and fire neuron with a constant drive that alone makes it fire at over
+    /// 100 Hz, inhibited through a plastic synapse from a neuron firing at about 100 Hz. Returns the rate
+    /// of the postsynaptic neuron in Hz over the last 20 s of 100 s.
+    fn driven_neuron_rate(weight: f64) -> f64 {
+        const DT: f64 = 0.1;
+        const TAU_M: f64 = 20.0;
+        const TAU_SYN: f64 = 10.0;
+        const DRIVE: f64 = 3.0;
+        const INHIBITION: f64 = 5.0;
+
+        let mut synapse = StdpSynapse {
+            weight,
+            delay: 1,
+            source: Entity::from_raw(0),
+            target: Entity::from_raw(1),
+            synapse_type: SynapseType::Inhibitory,
+            stdp_params: StdpParams {
+                a_plus: 0.01,
+                a_minus: -0.01,
+                tau_plus: 0.2,
+                tau_minus: 0.2,
+                w_max: 1.0,
+                w_min: 0.0,
+                soft_bound: false,
+                tau_eligibility: 1000.0,
+            },
+            stdp_state: StdpState {
+                a: 0.0,
+                spike_type: StdpSpikeType::PreSpike,
+                eligibility: 0.0,
+            },
+            rule: StdpRule::Inhibitory(InhibitoryStdp::new(0.001, 10.0)),
+        };
+
+        let (mut v, mut conductance) = (0.0, 0.0);
+        let mut post_spikes = 0;
+        let steps = (100_000.0 / DT) as usize;
+        for step in 0..steps {
+            // every 10 ms with a slowly drifting phase, so the spikes don't lock to each other
+            if step % 100 == 0 || step % 997 == 0 {
+                assert_eq!(synapse.register_pre_spike(), None);
+                conductance += 1.0;
+            }
+
+            v += (-v + DRIVE - INHIBITION * synapse.weight * conductance) / TAU_M * DT;
+            conductance -= conductance / TAU_SYN * DT;
+            if v >= 1.0 {
+                v = 0.0;
+                assert_eq!(synapse.register_post_spike(), None);
+                if step >= steps * 4 / 5 {
+                    post_spikes += 1;
+                }
+            }
+
+            synapse.update(DT);
+        }
+
+        post_spikes as f64 / 20.0
+    }
+
+    #[test]
+    fn test_inhibitory_rule_pulls_rate_to_target() {
+        // without inhibition the neuron fires at about 120 Hz, at full strength it's silent
+        let from_above = driven_neuron_rate(0.0);
+        let from_below = driven_neuron_rate(1.0);
+
+        assert!((from_above - 10.0).abs() < 3.0, "rate was {from_above} Hz");
+        assert!((from_below - 10.0).abs() < 3.0, "rate was {from_below} Hz");
+    }
+
+    #[test]
+    fn test_inhibitory_rule_is_symmetric() {
+        let rule = InhibitoryStdp::new(0.1, 10.0);
+        // 2 * 0.01 spikes per ms * 20 ms
+        assert!((rule.alpha() - 0.4).abs() < 1e-12);
+
+        let synapse = |rule: InhibitoryStdp| StdpSynapse {
+            weight: 0.5,
+            delay: 1,
+            source: Entity::from_raw(0),
+            target: Entity::from_raw(1),
+            synapse_type: SynapseType::Inhibitory,
+            stdp_params: StdpParams {
+                a_plus: 0.01,
+                a_minus: -0.01,
+                tau_plus: 0.2,
+                tau_minus: 0.2,
+                w_max: 1.0,
+                w_min: 0.0,
+                soft_bound: false,
+                tau_eligibility: 1000.0,
+            },
+            stdp_state: StdpState {
+                a: 0.0,
+                spike_type: StdpSpikeType::PreSpike,
+                eligibility: 0.0,
+            },
+            rule: StdpRule::Inhibitory(rule),
+        };
+
+        // pre then post and post then pre, 1 ms apart, potentiate by the same amount
+        let mut pre_post = synapse(rule.clone());
+        pre_post.register_pre_spike();
+        pre_post.update(1.0);
+        pre_post.register_post_spike();
+
+        let mut post_pre = synapse(rule.clone());
+        post_pre.register_post_spike();
+        post_pre.update(1.0);
+        post_pre.register_pre_spike();
+
+        let potentiation = 0.1 * (-1.0f64 / 20.0).exp();
+        assert!((pre_post.weight - (0.5 - 0.04 + potentiation)).abs() < 1e-12);
+        assert!((post_pre.weight - (0.5 - 0.04 + potentiation)).abs() < 1e-12);
+
+        // a lone presynaptic spike depresses by learning_rate * alpha
+        let mut lone = synapse(rule);
+        lone.register_pre_spike();
+        assert!((lone.weight - 0.46).abs() < 1e-12);
+    }
 }
@@ -0,0 +1,251 @@
+use bevy::{
+    prelude::{Component, Entity},
+    reflect::Reflect,
+};
+
+use crate::{Synapse, SynapseType};
+
+/// Synapse with Tsodyks-Markram short-term plasticity. Every presynaptic spike releases a
+/// fraction `u` of the available resources `x`, the released resources are active for a short
+/// while (`y`) and then slowly recover. Depending on the parameters repeated stimulation leads to
+/// depression or facilitation of the released amount.
+#[derive(Debug, Component, Reflect)]
+pub struct StpSynapse {
+    pub weight: f64,
+    pub delay: u32,
+    pub source: Entity,
+    pub target: Entity,
+    pub synapse_type: SynapseType,
+    /// utilization of synaptic efficacy, the fraction of resources released by the first spike
+    pub u_se: f64,
+    /// recovery time constant of the resources
+    pub tau_rec: f64,
+    /// facilitation time constant, 0.0 disables facilitation
+    pub tau_facil: f64,
+    /// inactivation time constant of the released resources
+    pub tau_inact: f64,
+    /// running utilization
+    pub u: f64,
+    /// fraction of available resources
+    pub x: f64,
+    /// fraction of active resources
+    pub y: f64,
+}
+
+/// The name this synapse is commonly known by.
+pub type TsodyksMarkramSynapse = StpSynapse;
+
+impl StpSynapse {
+    pub fn new(
+        source: Entity,
+        target: Entity,
+        weight: f64,
+        synapse_type: SynapseType,
+        u_se: f64,
+        tau_rec: f64,
+        tau_facil: f64,
+    ) -> Self {
+        StpSynapse {
+            weight,
+            delay: 1,
+            source,
+            target,
+            synapse_type,
+            u_se,
+            tau_rec,
+            tau_facil,
+            tau_inact: 3.0,
+            u: 0.0,
+            x: 1.0,
+            y: 0.0,
+        }
+    }
+
+    /// Register a presynaptic spike, returns the fraction of resources that got released.
+    pub fn register_spike(&mut self) -> f64 {
+        if self.tau_facil > 0.0 {
+            self.u += self.u_se * (1.0 - self.u);
+        } else {
+            self.u = self.u_se;
+        }
+
+        let released = self.u * self.x;
+        self.x -= released;
+        self.y += released;
+        released
+    }
+}
+
+impl Synapse for StpSynapse {
+    fn update(&mut self, tau: f64) {
+        let z = 1.0 - self.x - self.y;
+        let inactivated = self.y / self.tau_inact * tau;
+
+        self.y -= inactivated;
+        self.x += z / self.tau_rec * tau;
+
+        if self.tau_facil > 0.0 {
+            self.u -= self.u / self.tau_facil * tau;
+        }
+    }
+
+    fn get_weight(&self) -> f64 {
+        self.weight
+    }
+
+    fn set_weight(&mut self, weight: f64) {
+        self.weight = weight;
+    }
+
+    fn get_presynaptic(&self) -> Entity {
+        self.source
+    }
+
+    fn get_postsynaptic(&self) -> Entity {
+        self.target
+    }
+
+    fn get_type(&self) -> SynapseType {
+        self.synapse_type
+    }
+
+    fn get_delay(&self) -> u32 {
+        self.delay
+    }
+
+    /// `weight * u * x`, with `u` and `x` updated for this spike.
+    fn effective_weight_on_spike(&mut self) -> f64 {
+        self.weight * self.register_spike()
+    }
+
+    fn reset_state(&mut self) {
+        self.u = 0.0;
+        self.x = 1.0;
+        self.y = 0.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Stimulate the synapse with a regular spike train and return the released fraction per spike.
+    fn stimulate(synapse: &mut StpSynapse, spikes: usize, interval: f64, tau: f64) -> Vec<f64> {
+        (0..spikes)
+            .map(|_| {
+                let released = synapse.register_spike();
+                for _ in 0..(interval / tau) as usize {
+                    synapse.update(tau);
+                }
+                released
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_depression() {
+        let mut synapse = StpSynapse::new(
+            Entity::from_raw(0),
+            Entity::from_raw(1),
+            1.0,
+            SynapseType::Excitatory,
+            0.5,
+            800.0,
+            0.0,
+        );
+
+        let released = stimulate(&mut synapse, 10, 20.0, 0.025);
+        for pair in released.windows(2) {
+            assert!(pair[1] < pair[0]);
+        }
+        assert!(released[9] < released[0] * 0.2);
+    }
+
+    #[test]
+    fn test_effective_weight_depresses_under_high_frequency() {
+        let mut synapse = TsodyksMarkramSynapse::new(
+            Entity::from_raw(0),
+            Entity::from_raw(1),
+            2.0,
+            SynapseType::Excitatory,
+            0.5,
+            800.0,
+            0.0,
+        );
+
+        // 100Hz presynaptic train
+        let weights = (0..10)
+            .map(|_| {
+                let weight = synapse.effective_weight_on_spike();
+                for _ in 0..(10.0 / 0.025) as usize {
+                    synapse.update(0.025);
+                }
+                weight
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(weights[0], 2.0 * 0.5);
+        for pair in weights.windows(2) {
+            assert!(pair[1] < pair[0]);
+        }
+        assert!(weights[9] < weights[0] * 0.2);
+        assert_eq!(synapse.get_weight(), 2.0);
+    }
+
+    #[test]
+    fn test_facilitation() {
+        let mut synapse = StpSynapse::new(
+            Entity::from_raw(0),
+            Entity::from_raw(1),
+            1.0,
+            SynapseType::Excitatory,
+            0.1,
+            50.0,
+            1000.0,
+        );
+
+        let released = stimulate(&mut synapse, 5, 20.0, 0.025);
+        for pair in released.windows(2) {
+            assert!(pair[1] > pair[0]);
+        }
+    }
+
+    #[test]
+    fn test_recovery() {
+        let mut synapse = StpSynapse::new(
+            Entity::from_raw(0),
+            Entity::from_raw(1),
+            1.0,
+            SynapseType::Excitatory,
+            0.5,
+            100.0,
+            0.0,
+        );
+
+        let first = synapse.register_spike();
+        for _ in 0..(2000.0 / 0.025) as usize {
+            synapse.update(0.025);
+        }
+
+        assert!((synapse.register_spike() - first).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_reset_state_restores_resources() {
+        let mut synapse = StpSynapse::new(
+            Entity::from_raw(0),
+            Entity::from_raw(1),
+            1.0,
+            SynapseType::Excitatory,
+            0.2,
+            800.0,
+            50.0,
+        );
+
+        let first = synapse.register_spike();
+        stimulate(&mut synapse, 5, 20.0, 0.025);
+        synapse.reset_state();
+
+        assert_eq!(synapse.register_spike(), first);
+    }
+}
@@ -0,0 +1,53 @@
+//! Fixtures shared by the tests of this crate.
+
+use bevy::prelude::Component;
+use silicon_core::Neuron;
+
+/// A neuron that never spikes, the membrane potential is its integrated input. With a `tau_m` it
+/// leaks back to 0mV, without one it's a perfect integrator.
+#[derive(Component, Default)]
+pub(crate) struct TestNeuron {
+    pub v: f64,
+    pub input_current: f64,
+    pub tau_m: Option<f64>,
+}
+
+impl TestNeuron {
+    /// A perfect integrator starting at `v`.
+    pub fn at(v: f64) -> Self {
+        TestNeuron {
+            v,
+            ..Default::default()
+        }
+    }
+
+    /// A leaky neuron at rest at 0mV.
+    pub fn leaky(tau_m: f64) -> Self {
+        TestNeuron {
+            tau_m: Some(tau_m),
+            ..Default::default()
+        }
+    }
+}
+
+impl Neuron for TestNeuron {
+    fn update(&mut self, tau: f64) -> bool {
+        let leak = self.tau_m.map_or(0.0, |tau_m| -self.v / tau_m);
+        self.v += (leak + self.input_current) * tau;
+        self.input_current = 0.0;
+        false
+    }
+
+    fn get_membrane_potential(&self) -> f64 {
+        self.v
+    }
+
+    fn insert_current(&mut self, current: f64) -> f64 {
+        self.input_current += current;
+        self.v
+    }
+
+    fn add_conductance(&mut self, g: f64, reversal_potential: f64) {
+        self.input_current += g * (reversal_potential - self.v);
+    }
+}
@@ -0,0 +1,231 @@
+use bevy::{
+    prelude::{Component, Entity},
+    reflect::Reflect,
+};
+
+use crate::{Synapse, SynapseType};
+
+/// Parameters of the minimal triplet model, the defaults are the all-to-all visual cortex fit
+/// from Pfister & Gerstner (2006).
+#[derive(Debug, Clone, Reflect)]
+pub struct TripletStdpParams {
+    pub a2_plus: f64,
+    pub a3_plus: f64,
+    pub a2_minus: f64,
+    /// decay time constant of the fast presynaptic trace `r1`
+    pub tau_plus: f64,
+    /// decay time constant of the slow presynaptic trace `r2`
+    pub tau_x: f64,
+    /// decay time constant of the fast postsynaptic trace `o1`
+    pub tau_minus: f64,
+    /// decay time constant of the slow postsynaptic trace `o2`
+    pub tau_y: f64,
+    pub w_max: f64,
+    pub w_min: f64,
+}
+
+impl Default for TripletStdpParams {
+    fn default() -> Self {
+        TripletStdpParams {
+            a2_plus: 5e-10,
+            a3_plus: 6.2e-3,
+            a2_minus: 7e-3,
+            tau_plus: 16.8,
+            tau_x: 101.0,
+            tau_minus: 33.7,
+            tau_y: 125.0,
+            w_max: 1.0,
+            w_min: 0.0,
+        }
+    }
+}
+
+/// STDP synapse with the triplet rule. Unlike `StdpSynapse` every spike contributes to the traces,
+/// which makes the weight change depend on the firing frequency and not only on spike pairs.
+#[derive(Debug, Component, Reflect)]
+pub struct TripletStdpSynapse {
+    pub weight: f64,
+    pub delay: u32,
+    pub source: Entity,
+    pub target: Entity,
+    pub synapse_type: SynapseType,
+    pub params: TripletStdpParams,
+    pub r1: f64,
+    pub r2: f64,
+    pub o1: f64,
+    pub o2: f64,
+}
+
+impl TripletStdpSynapse {
+    pub fn new(source: Entity, target: Entity, weight: f64, synapse_type: SynapseType) -> Self {
+        TripletStdpSynapse {
+            weight,
+            delay: 1,
+            source,
+            target,
+            synapse_type,
+            params: TripletStdpParams::default(),
+            r1: 0.0,
+            r2: 0.0,
+            o1: 0.0,
+            o2: 0.0,
+        }
+    }
+
+    /// The depression `-A2_minus * o1` caused by a presynaptic spike, or `None` when there is
+    /// none. Like `StdpSynapse` the weight itself isn't changed, the change is deferred.
+    pub fn register_pre_spike(&mut self) -> Option<f64> {
+        let delta_w = -self.params.a2_minus * self.o1;
+        self.r1 += 1.0;
+        self.r2 += 1.0;
+        (delta_w != 0.0).then_some(delta_w)
+    }
+
+    /// The potentiation `A2_plus * r1 + A3_plus * r1 * o2` caused by a postsynaptic spike, or
+    /// `None` when there is none. The slow trace `o2` is read before it's incremented by this
+    /// spike.
+    pub fn register_post_spike(&mut self) -> Option<f64> {
+        let delta_w = self.r1 * (self.params.a2_plus + self.params.a3_plus * self.o2);
+        self.o1 += 1.0;
+        self.o2 += 1.0;
+        (delta_w != 0.0).then_some(delta_w)
+    }
+}
+
+impl Synapse for TripletStdpSynapse {
+    fn update(&mut self, tau: f64) {
+        self.r1 *= (-tau / self.params.tau_plus).exp();
+        self.r2 *= (-tau / self.params.tau_x).exp();
+        self.o1 *= (-tau / self.params.tau_minus).exp();
+        self.o2 *= (-tau / self.params.tau_y).exp();
+    }
+
+    fn get_weight(&self) -> f64 {
+        self.weight
+    }
+
+    fn set_weight(&mut self, weight: f64) {
+        self.weight = weight;
+    }
+
+    fn get_presynaptic(&self) -> Entity {
+        self.source
+    }
+
+    fn get_postsynaptic(&self) -> Entity {
+        self.target
+    }
+
+    fn get_type(&self) -> SynapseType {
+        self.synapse_type
+    }
+
+    fn get_delay(&self) -> u32 {
+        self.delay
+    }
+
+    fn weight_bounds(&self) -> Option<(f64, f64)> {
+        Some((self.params.w_min, self.params.w_max))
+    }
+
+    fn reset_state(&mut self) {
+        self.r1 = 0.0;
+        self.r2 = 0.0;
+        self.o1 = 0.0;
+        self.o2 = 0.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 60 spike pairs at `frequency` Hz, the postsynaptic spike follows the presynaptic one by
+    /// `delta_t` ms. Returns the total weight change.
+    fn pairing_protocol(frequency: f64, delta_t: f64) -> f64 {
+        let mut synapse = TripletStdpSynapse {
+            params: TripletStdpParams {
+                w_max: 10.0,
+                ..Default::default()
+            },
+            ..TripletStdpSynapse::new(
+                Entity::from_raw(0),
+                Entity::from_raw(1),
+                1.0,
+                SynapseType::Excitatory,
+            )
+        };
+
+        let tau = 0.1;
+        let period = (1000.0 / frequency / tau).round() as usize;
+        let offset = (delta_t.abs() / tau).round() as usize;
+        let (pre_offset, post_offset) = if delta_t > 0.0 {
+            (0, offset)
+        } else {
+            (offset, 0)
+        };
+
+        fn apply(synapse: &mut TripletStdpSynapse, delta_w: Option<f64>) {
+            if let Some(delta_w) = delta_w {
+                synapse.weight =
+                    (synapse.weight + delta_w).clamp(synapse.params.w_min, synapse.params.w_max);
+            }
+        }
+
+        for step in 0..(60 * period) {
+            if step % period == pre_offset {
+                let delta_w = synapse.register_pre_spike();
+                apply(&mut synapse, delta_w);
+            }
+            if step % period == post_offset {
+                let delta_w = synapse.register_post_spike();
+                apply(&mut synapse, delta_w);
+            }
+            synapse.update(tau);
+        }
+
+        synapse.weight - 1.0
+    }
+
+    #[test]
+    fn test_potentiation_grows_with_frequency() {
+        let low = pairing_protocol(1.0, 10.0);
+        let medium = pairing_protocol(20.0, 10.0);
+        let high = pairing_protocol(50.0, 10.0);
+
+        assert!(low < medium);
+        assert!(medium < high);
+    }
+
+    #[test]
+    fn test_potentiation_at_10hz_and_50hz() {
+        let low = pairing_protocol(10.0, 10.0);
+        let high = pairing_protocol(50.0, 10.0);
+
+        assert!(low > 0.0);
+        assert!(high > 2.0 * low);
+    }
+
+    #[test]
+    fn test_post_pre_pairing_depends_on_frequency() {
+        // post-before-pre pairs depress at low frequencies, but potentiate at high frequencies
+        assert!(pairing_protocol(1.0, -10.0) < 0.0);
+        assert!(pairing_protocol(50.0, -10.0) > 0.0);
+    }
+
+    #[test]
+    fn test_weight_change_is_deferred() {
+        let mut synapse = TripletStdpSynapse::new(
+            Entity::from_raw(0),
+            Entity::from_raw(1),
+            1.0,
+            SynapseType::Excitatory,
+        );
+        // no traces yet
+        assert_eq!(synapse.register_pre_spike(), None);
+        let delta_w = synapse.register_post_spike().unwrap();
+        assert_eq!(delta_w, synapse.params.a2_plus);
+
+        assert_eq!(synapse.weight, 1.0);
+    }
+}
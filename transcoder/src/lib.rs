@@ -1,2 +1,6 @@
 pub mod nlp;
 pub mod population;
+pub mod rate;
+#[cfg(test)]
+mod test_utils;
+pub mod ttfs;
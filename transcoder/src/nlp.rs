@@ -20,14 +20,86 @@ pub fn char_to_spike_train(c: char, time_frame: f64) -> Vec<f64> {
     spike_train
 }
 
+/// Every character gets an equal share of the time frame, one after the other.
 pub fn string_to_spike_train(s: &str, time_frame: f64) -> Vec<f64> {
+    let char_time_frame = time_frame / s.len() as f64;
     let mut spike_train = Vec::new();
     let mut index = 0;
     for c in s.chars() {
-        let mut char_spike_train = char_to_spike_train(c, time_frame / s.len() as f64);
-        char_spike_train.iter_mut().for_each(|t| *t += index as f64);
+        let mut char_spike_train = char_to_spike_train(c, char_time_frame);
+        char_spike_train
+            .iter_mut()
+            .for_each(|t| *t += index as f64 * char_time_frame);
         index += 1;
         spike_train.append(&mut char_spike_train);
     }
     spike_train
 }
+
+/// Spike times are computed as multiples of the bin width, this keeps spikes that should land
+/// exactly on the start of a bin from falling into the previous one.
+const BIN_EPSILON: f64 = 1e-9;
+
+/// The inverse of `char_to_spike_train`, the time frame is divided into 8 bins, the first bin holds
+/// the most significant bit. A bin without a spike is a 0, spikes outside the time frame are ignored.
+pub fn spike_train_to_char(spikes: &[f64], time_frame: f64) -> char {
+    spike_train_to_string(spikes, time_frame, 1)
+        .chars()
+        .next()
+        .unwrap_or('\0')
+}
+
+/// The inverse of `string_to_spike_train` for a string of `char_count` characters.
+pub fn spike_train_to_string(spikes: &[f64], time_frame: f64, char_count: usize) -> String {
+    let mut bytes = vec![0u8; char_count];
+    let bin_width = time_frame / (char_count * 8) as f64;
+
+    for spike in spikes {
+        let bin = (spike / bin_width + BIN_EPSILON).floor();
+        if bin < 0.0 || bin >= (char_count * 8) as f64 {
+            continue;
+        }
+
+        let bin = bin as usize;
+        bytes[bin / 8] |= 1 << (7 - bin % 8);
+    }
+
+    bytes.into_iter().map(char::from).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+
+    #[test]
+    fn test_string_round_trip() {
+        let mut rng = StdRng::seed_from_u64(5);
+        for _ in 0..500 {
+            let length = rng.gen_range(1..20);
+            let string = (0..length)
+                .map(|_| char::from(rng.gen_range(0..128u8)))
+                .collect::<String>();
+            let time_frame = rng.gen_range(0.1..100.0);
+
+            let spikes = string_to_spike_train(&string, time_frame);
+            assert_eq!(spike_train_to_string(&spikes, time_frame, length), string);
+        }
+    }
+
+    #[test]
+    fn test_char_edge_cases() {
+        // spikes on the start of the first and the last bin
+        assert_eq!(
+            spike_train_to_char(&[0.0, 7.0], 8.0),
+            char::from(0b1000_0001)
+        );
+        // spikes anywhere within a bin count, spikes outside the time frame are ignored
+        assert_eq!(
+            spike_train_to_char(&[1.9, -0.5, 8.0, 12.0], 8.0),
+            char::from(0b0100_0000)
+        );
+        assert_eq!(spike_train_to_char(&[], 8.0), '\0');
+    }
+}
@@ -1,4 +1,6 @@
-use bevy::{prelude::Entity, reflect::Reflect};
+use bevy::{prelude::Entity, reflect::Reflect, utils::HashMap};
+use rand::Rng;
+use silicon_core::{Clock, SpikeRecorder};
 
 #[derive(Debug, Clone, Reflect)]
 pub struct PopulationEncoder {
@@ -7,11 +9,11 @@ pub struct PopulationEncoder {
 
 impl PopulationEncoder {
     /// sample_rate is a value between 0.0 and 1.0 that determines the percentage of neurons to include in the population
-    pub fn from_sample_rate(neurons: &Vec<Entity>, sample_rate: f64) -> Self {
+    pub fn from_sample_rate(neurons: &Vec<Entity>, sample_rate: f64, rng: &mut impl Rng) -> Self {
         let selected_neurons = neurons
             .iter()
             .enumerate()
-            .filter(|(_, _)| rand::random::<f64>() < sample_rate)
+            .filter(|(_, _)| rng.gen::<f64>() < sample_rate)
             .map(|(_, neuron)| *neuron)
             .collect();
 
@@ -20,3 +22,265 @@ impl PopulationEncoder {
         }
     }
 }
+
+/// Population coding with Gaussian tuning curves. Every neuron fires fastest for its preferred
+/// stimulus `mu` and slower the further the stimulus is from it, `sigma` sets how broadly it is
+/// tuned.
+#[derive(Debug, Clone, Reflect)]
+pub struct GaussianPopulationEncoder {
+    pub neurons: Vec<Entity>,
+    /// the preferred stimulus of every neuron in `neurons`
+    pub mu: Vec<f64>,
+    /// the tuning width of every neuron in `neurons`
+    pub sigma: Vec<f64>,
+    /// firing rate in Hz of a neuron at its preferred stimulus
+    pub r_max: f64,
+}
+
+impl GaussianPopulationEncoder {
+    /// Spread the preferred stimuli evenly over `[min, max]`, every neuron is tuned as wide as
+    /// the spacing between two preferred stimuli.
+    pub fn new(neurons: Vec<Entity>, min: f64, max: f64, r_max: f64) -> Self {
+        let n = neurons.len();
+        let spacing = if n > 1 {
+            (max - min) / (n - 1) as f64
+        } else {
+            max - min
+        };
+        let mu = (0..n)
+            .map(|i| {
+                if n > 1 {
+                    min + spacing * i as f64
+                } else {
+                    (min + max) / 2.0
+                }
+            })
+            .collect();
+
+        GaussianPopulationEncoder {
+            neurons,
+            mu,
+            sigma: vec![spacing; n],
+            r_max,
+        }
+    }
+
+    /// The firing rate in Hz of every neuron for `value`.
+    pub fn encode(&self, value: f64) -> Vec<(Entity, f64)> {
+        self.neurons
+            .iter()
+            .zip(self.mu.iter().zip(&self.sigma))
+            .map(|(neuron, (mu, sigma))| {
+                let rate = self.r_max * (-(value - mu).powi(2) / (2.0 * sigma * sigma)).exp();
+                (*neuron, rate)
+            })
+            .collect()
+    }
+
+    /// Population vector decoding, the average of the preferred stimuli weighted by the activity
+    /// of the neurons, either their firing rates or spike counts. NaN when the population is
+    /// silent.
+    pub fn decode(&self, spikes: &[(Entity, f64)]) -> f64 {
+        let (weighted, total) = spikes
+            .iter()
+            .filter_map(|(neuron, activity)| {
+                let index = self.neurons.iter().position(|other| other == neuron)?;
+                Some((self.mu[index], *activity))
+            })
+            .fold((0.0, 0.0), |(weighted, total), (mu, activity)| {
+                (weighted + mu * activity, total + activity)
+            });
+
+        weighted / total
+    }
+}
+
+/// Reads out a class from the output of the network, every class is represented by its own set
+/// of neurons and the class whose population fires fastest wins.
+#[derive(Debug, Clone)]
+pub struct PopulationDecoder<C> {
+    pub populations: Vec<(C, Vec<Entity>)>,
+}
+
+impl<C> Default for PopulationDecoder<C> {
+    fn default() -> Self {
+        PopulationDecoder {
+            populations: vec![],
+        }
+    }
+}
+
+impl<C: Clone> PopulationDecoder<C> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the population of neurons that represents `class`.
+    pub fn with_population(mut self, class: C, neurons: Vec<Entity>) -> Self {
+        self.populations.push((class, neurons));
+        self
+    }
+
+    /// The number of spikes of every population in the last `window` ms. Neurons without a
+    /// recorder don't count.
+    pub fn spike_counts<'a>(
+        &self,
+        window: f64,
+        clock: &Clock,
+        recorders: impl IntoIterator<Item = (Entity, &'a dyn SpikeRecorder)>,
+    ) -> Vec<(C, usize)> {
+        let recorders = recorders.into_iter().collect::<HashMap<_, _>>();
+        self.populations
+            .iter()
+            .map(|(class, neurons)| {
+                let count = neurons
+                    .iter()
+                    .filter_map(|neuron| recorders.get(neuron))
+                    .map(|recorder| {
+                        recorder
+                            .get_spikes()
+                            .iter()
+                            .filter(|time| **time >= clock.time - window && **time <= clock.time)
+                            .count()
+                    })
+                    .sum();
+                (class.clone(), count)
+            })
+            .collect()
+    }
+
+    /// The average firing rate in Hz of the neurons of every population in the last `window` ms.
+    pub fn firing_rates<'a>(
+        &self,
+        window: f64,
+        clock: &Clock,
+        recorders: impl IntoIterator<Item = (Entity, &'a dyn SpikeRecorder)>,
+    ) -> Vec<(C, f64)> {
+        self.spike_counts(window, clock, recorders)
+            .into_iter()
+            .zip(self.populations.iter())
+            .map(|((class, count), (_, neurons))| {
+                if neurons.is_empty() || window <= 0.0 {
+                    return (class, 0.0);
+                }
+                (class, count as f64 / neurons.len() as f64 / window * 1000.0)
+            })
+            .collect()
+    }
+
+    /// The class whose population fired fastest in the last `window` ms, `None` when all
+    /// populations were silent or the fastest ones are tied.
+    pub fn decode<'a>(
+        &self,
+        window: f64,
+        clock: &Clock,
+        recorders: impl IntoIterator<Item = (Entity, &'a dyn SpikeRecorder)>,
+    ) -> Option<C> {
+        let rates = self.firing_rates(window, clock, recorders);
+        let max = rates.iter().map(|(_, rate)| *rate).fold(0.0, f64::max);
+        let mut winners = rates.into_iter().filter(|(_, rate)| *rate == max);
+
+        match (winners.next(), winners.next()) {
+            (Some((class, rate)), None) if rate > 0.0 => Some(class),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+    use crate::test_utils::Spikes;
+
+    #[test]
+    fn test_seeded_sampling_is_reproducible() {
+        let neurons = (0..100).map(Entity::from_raw).collect::<Vec<_>>();
+        let sample = |seed| {
+            PopulationEncoder::from_sample_rate(&neurons, 0.5, &mut StdRng::seed_from_u64(seed))
+                .neurons
+        };
+
+        assert_eq!(sample(1), sample(1));
+        assert_ne!(sample(1), sample(2));
+    }
+
+    #[test]
+    fn test_gaussian_round_trip() {
+        let neurons = (0..10).map(Entity::from_raw).collect::<Vec<_>>();
+        let encoder = GaussianPopulationEncoder::new(neurons, 0.0, 1.0, 100.0);
+
+        for value in (0..=20).map(|i| i as f64 / 20.0) {
+            let decoded = encoder.decode(&encoder.encode(value));
+            assert!(
+                (decoded - value).abs() < encoder.sigma[0],
+                "{value} decoded as {decoded}"
+            );
+        }
+        assert!(encoder.decode(&[]).is_nan());
+    }
+
+    #[test]
+    fn test_preferred_neuron_fires_fastest() {
+        let neurons = (0..10).map(Entity::from_raw).collect::<Vec<_>>();
+        let encoder = GaussianPopulationEncoder::new(neurons, 0.0, 9.0, 100.0);
+
+        let rates = encoder.encode(4.0);
+        assert_eq!(rates[4].1, 100.0);
+        assert!(rates[..4].windows(2).all(|pair| pair[0].1 < pair[1].1));
+        assert!(rates[4..].windows(2).all(|pair| pair[0].1 > pair[1].1));
+    }
+
+    #[test]
+    fn test_decoder_picks_fastest_population() {
+        let neurons = (0..6).map(Entity::from_raw).collect::<Vec<_>>();
+        let decoder = PopulationDecoder::new()
+            .with_population('a', neurons[..3].to_vec())
+            .with_population('b', neurons[3..].to_vec());
+        let clock = Clock {
+            time: 100.0,
+            tau: 0.1,
+            ..Default::default()
+        };
+
+        let decode = |spikes: [Vec<f64>; 6]| {
+            let recorders = spikes.map(Spikes);
+            let recorders = || {
+                neurons.iter().copied().zip(
+                    recorders
+                        .iter()
+                        .map(|recorder| recorder as &dyn SpikeRecorder),
+                )
+            };
+            (
+                decoder.decode(50.0, &clock, recorders()),
+                decoder.firing_rates(50.0, &clock, recorders()),
+            )
+        };
+
+        // population b fires more often, but most of its spikes are older than the window
+        let (class, rates) = decode([
+            vec![60.0, 80.0],
+            vec![90.0],
+            vec![],
+            vec![10.0, 20.0, 30.0, 70.0],
+            vec![10.0, 20.0],
+            vec![],
+        ]);
+        assert_eq!(class, Some('a'));
+        assert_eq!(
+            rates,
+            vec![
+                ('a', 3.0 / 3.0 / 50.0 * 1000.0),
+                ('b', 1.0 / 3.0 / 50.0 * 1000.0)
+            ]
+        );
+
+        let (class, _) = decode([vec![60.0], vec![], vec![], vec![], vec![70.0], vec![]]);
+        assert_eq!(class, None);
+        let (class, rates) = decode(Default::default());
+        assert_eq!(class, None);
+        assert_eq!(rates, vec![('a', 0.0), ('b', 0.0)]);
+    }
+}
@@ -0,0 +1,84 @@
+use rand::Rng;
+
+/// Rate coding, a value is represented by the firing rate of a Poisson spike train. Spike times
+/// are in ms, rates in Hz.
+pub struct RateCoder;
+
+impl RateCoder {
+    /// A Poisson spike train over `duration` ms whose rate is proportional to where `value` lies
+    /// in `[min, max]`, from 0Hz at `min` to `target_rate_hz` at `max`. Values outside the range
+    /// are clamped.
+    pub fn encode(
+        value: f64,
+        min: f64,
+        max: f64,
+        duration: f64,
+        target_rate_hz: f64,
+        rng: &mut impl Rng,
+    ) -> Vec<f64> {
+        let position = if max > min {
+            ((value - min) / (max - min)).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        let rate = position * target_rate_hz / 1000.0;
+        if rate <= 0.0 {
+            return vec![];
+        }
+
+        let mut spikes = vec![];
+        let mut time = 0.0;
+        loop {
+            // exponentially distributed inter spike intervals
+            time += -(1.0 - rng.gen::<f64>()).ln() / rate;
+            if time >= duration {
+                return spikes;
+            }
+            spikes.push(time);
+        }
+    }
+
+    /// The firing rate in Hz of the spikes in the first `window` ms of the train, divide by the
+    /// `target_rate_hz` of `encode` to get the position of the value in its range.
+    pub fn decode(spikes: &[f64], window: f64) -> f64 {
+        if window <= 0.0 {
+            return 0.0;
+        }
+
+        let count = spikes.iter().filter(|time| **time < window).count();
+        count as f64 / (window / 1000.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let mut rng = StdRng::seed_from_u64(3);
+        for value in [2.0, 5.0, 7.5, 10.0] {
+            // 30 seconds at up to 100Hz
+            let spikes = RateCoder::encode(value, 0.0, 10.0, 30000.0, 100.0, &mut rng);
+            let decoded = RateCoder::decode(&spikes, 30000.0) / 100.0 * 10.0;
+
+            assert!(
+                (decoded - value).abs() <= 0.15 * value,
+                "{value} decoded as {decoded}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_range_edges() {
+        let mut rng = StdRng::seed_from_u64(3);
+
+        assert!(RateCoder::encode(-1.0, 0.0, 1.0, 1000.0, 100.0, &mut rng).is_empty());
+        assert!(RateCoder::encode(0.5, 1.0, 1.0, 1000.0, 100.0, &mut rng).is_empty());
+        let spikes = RateCoder::encode(2.0, 0.0, 1.0, 1000.0, 100.0, &mut rng);
+        assert!(spikes.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(spikes.iter().all(|time| (0.0..1000.0).contains(time)));
+    }
+}
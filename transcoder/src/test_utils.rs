@@ -0,0 +1,26 @@
+//! Fixtures shared by the tests of this crate.
+
+use bevy::prelude::Component;
+use silicon_core::SpikeRecorder;
+
+/// A recorder whose spike times can be set directly.
+#[derive(Component, Default)]
+pub(crate) struct Spikes(pub Vec<f64>);
+
+impl SpikeRecorder for Spikes {
+    fn record_spike(&mut self, time: f64) {
+        self.0.push(time);
+    }
+
+    fn get_spikes(&self) -> Vec<f64> {
+        self.0.clone()
+    }
+
+    fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    fn clear_before(&mut self, time: f64) {
+        self.0.retain(|spike| *spike >= time);
+    }
+}
@@ -0,0 +1,127 @@
+use bevy::{prelude::Entity, reflect::Reflect};
+
+/// Time-to-first-spike coding, a value is represented by the time of a single spike. Smaller
+/// values fire earlier. Times are in ms.
+pub struct TtfsCoder;
+
+impl TtfsCoder {
+    /// The spike time in `[0, window]` for `value`, `min` fires at 0 and `max` at `window`.
+    /// Values outside the range are clamped.
+    pub fn encode(value: f64, min: f64, max: f64, window: f64) -> f64 {
+        if max <= min {
+            return 0.0;
+        }
+
+        ((value - min) / (max - min)).clamp(0.0, 1.0) * window
+    }
+
+    /// The position in `[0, 1]` of the encoded value in its range, scale it by the range passed
+    /// to `encode` to recover the value.
+    pub fn decode(spike_time: f64, window: f64) -> f64 {
+        if window <= 0.0 {
+            return 0.0;
+        }
+
+        (spike_time / window).clamp(0.0, 1.0)
+    }
+}
+
+/// Encodes a value into the first spike times of a population. Every neuron prefers a value,
+/// spaced evenly over `[min, max]`, and fires the earlier the closer the input is to it, so the
+/// neuron tuned to the input fires first.
+#[derive(Debug, Clone, Reflect)]
+pub struct TtfsPopulationEncoder {
+    pub neurons: Vec<Entity>,
+    /// the preferred value of every neuron in `neurons`
+    pub preferred: Vec<f64>,
+    pub min: f64,
+    pub max: f64,
+    /// the spike of the neuron furthest from the input arrives `window` ms after the input
+    pub window: f64,
+}
+
+impl TtfsPopulationEncoder {
+    pub fn new(neurons: Vec<Entity>, min: f64, max: f64, window: f64) -> Self {
+        let preferred = match neurons.len() {
+            0 => vec![],
+            1 => vec![(min + max) / 2.0],
+            n => (0..n)
+                .map(|i| min + (max - min) * i as f64 / (n - 1) as f64)
+                .collect(),
+        };
+
+        TtfsPopulationEncoder {
+            neurons,
+            preferred,
+            min,
+            max,
+            window,
+        }
+    }
+
+    /// The first spike time of every neuron for `value`.
+    pub fn encode(&self, value: f64) -> Vec<(Entity, f64)> {
+        self.neurons
+            .iter()
+            .zip(&self.preferred)
+            .map(|(neuron, preferred)| {
+                let distance = (value - preferred).abs();
+                (
+                    *neuron,
+                    TtfsCoder::encode(distance, 0.0, self.max - self.min, self.window),
+                )
+            })
+            .collect()
+    }
+
+    /// The preferred value of the neuron that fired first, `None` when none of the spikes is
+    /// from a neuron of the population.
+    pub fn decode(&self, spikes: &[(Entity, f64)]) -> Option<f64> {
+        spikes
+            .iter()
+            .filter_map(|(neuron, time)| {
+                let index = self.neurons.iter().position(|other| other == neuron)?;
+                Some((self.preferred[index], *time))
+            })
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(preferred, _)| preferred)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encoding_is_monotonic() {
+        let times = (0..=10)
+            .map(|value| TtfsCoder::encode(value as f64, 0.0, 10.0, 20.0))
+            .collect::<Vec<_>>();
+
+        assert_eq!(times[0], 0.0);
+        assert_eq!(times[10], 20.0);
+        assert!(times.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn test_decode_inverts_encode() {
+        for value in [-3.0, 0.0, 1.5, 4.25, 7.0] {
+            let time = TtfsCoder::encode(value, -3.0, 7.0, 20.0);
+            let decoded = -3.0 + TtfsCoder::decode(time, 20.0) * 10.0;
+            assert!((decoded - value).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_population_closest_neuron_fires_first() {
+        let neurons = (0..11).map(Entity::from_raw).collect::<Vec<_>>();
+        let encoder = TtfsPopulationEncoder::new(neurons, 0.0, 1.0, 20.0);
+
+        let spikes = encoder.encode(0.32);
+        assert_eq!(encoder.decode(&spikes), Some(encoder.preferred[3]));
+        // the spike times grow with the distance to the input
+        assert!(spikes[3..].windows(2).all(|pair| pair[0].1 < pair[1].1));
+        assert!(spikes[..=3].windows(2).all(|pair| pair[0].1 > pair[1].1));
+        assert_eq!(encoder.decode(&[]), None);
+    }
+}